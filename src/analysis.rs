@@ -0,0 +1,874 @@
+/*!
+Derived metrics computed over the sections of a game's timeline
+
+The timeline is the sequence of sections found in a file's chunks, timed with their absolute in-game time.
+Keyframes are ignored as they hold snapshots of data already present in chunks.
+
+Sections are only available if the lib was compiled with the `payload` feature.
+
+# Examples
+
+Implement a custom metric:
+
+```rust
+use lolrofl::analysis::Metric;
+use lolrofl::model::section::GenericSection;
+
+/// The in-game time of the last section
+struct LastTime(f32);
+
+impl Metric for LastTime {
+    fn name(&self) -> String { "last-time".to_string() }
+    fn observe(&mut self, time: f32, _section: &GenericSection) { self.0 = time; }
+    fn value(&self) -> f64 { self.0 as f64 }
+}
+
+# let content = lolrofl::test::sample_full_file_0();
+// let content = std::fs::read("game.rofl").unwrap();
+let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+
+let mut last_time = LastTime(0.);
+lolrofl::analysis::evaluate(&game, &mut [&mut last_time]).unwrap();
+println!("{}: {}", last_time.name(), last_time.value());
+# #[cfg(feature = "payload")]
+# assert_eq!(last_time.value(), 30.02f32 as f64);
+```
+*/
+
+use crate::{Errors, Rofl, iter::PayloadIterOptions, model::section::{GenericSection, SectionAddress, SectionPosition}};
+
+/// A value derived from the sections of a game's timeline
+pub trait Metric {
+    /// The metric's name
+    fn name(&self) -> String;
+    /// Feed a section and its absolute in-game time in seconds to the metric
+    fn observe(&mut self, time: f32, section: &GenericSection);
+    /// The metric's value after all sections were observed
+    fn value(&self) -> f64;
+}
+
+/// The number of sections per in-game minute
+#[derive(Clone, Debug, Default)]
+pub struct SectionRate {
+    /// Number of observed sections
+    count: usize,
+    /// Time of the first and last observed sections
+    bounds: Option<(f32, f32)>,
+}
+
+impl SectionRate {
+    /// Build a new section rate metric
+    pub fn new() -> SectionRate { SectionRate::default() }
+}
+
+impl Metric for SectionRate {
+    fn name(&self) -> String { "section-rate".to_string() }
+    fn observe(&mut self, time: f32, _section: &GenericSection) {
+        self.count += 1;
+        self.bounds = Some(self.bounds.map_or((time, time), |(start, _)| (start, time)));
+    }
+    fn value(&self) -> f64 {
+        match self.bounds {
+            Some((start, end)) if end > start => self.count as f64 * 60. / (end - start) as f64,
+            _ => 0.,
+        }
+    }
+}
+
+/// The number of sections of a given type
+#[derive(Clone, Debug)]
+pub struct TypeCount {
+    /// The counted type
+    data_type: u32,
+    /// Number of sections of the counted type
+    count: usize,
+}
+
+impl TypeCount {
+    /// Build a new metric that counts the sections of a type
+    pub fn new(data_type: u32) -> TypeCount { TypeCount { data_type, count: 0 } }
+}
+
+impl Metric for TypeCount {
+    fn name(&self) -> String { format!("type-count-{}", self.data_type) }
+    fn observe(&mut self, _time: f32, section: &GenericSection) {
+        if section.data_type() == self.data_type {
+            self.count += 1;
+        }
+    }
+    fn value(&self) -> f64 { self.count as f64 }
+}
+
+/// Feed all sections of a game's timeline to a function along with their absolute in-game time in seconds
+pub(crate) fn walk_timeline<F>(rofl: &Rofl, mut f: F) -> Result<(), Errors>
+where F: FnMut(f32, &GenericSection) {
+    // Without decryption support, no chunk is loaded and the timeline is empty
+    let mut iterator = rofl.segment_iter(&PayloadIterOptions::new().load_data(cfg!(feature="payload")))?;
+    let mut time = 0.;
+    for segment in iterator.by_ref().filter(|s| s.is_chunk() && s.is_loaded()) {
+        let mut sections = segment.section_iter()?;
+        for section in sections.by_ref() {
+            time = section.time().to_absolute(time);
+            f(time, &section);
+        }
+        if !sections.is_valid() {
+            return Err(sections.to_error());
+        }
+    }
+    if iterator.is_valid() { Ok(()) } else { Err(iterator.to_error()) }
+}
+
+/// Evaluate metrics over a game's timeline
+///
+/// # Examples
+///
+/// ```rust
+/// use lolrofl::analysis::{Metric, SectionRate, TypeCount};
+///
+/// # let content = lolrofl::test::sample_full_file_0();
+/// // let content = std::fs::read("game.rofl").unwrap();
+/// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+///
+/// let mut rate = SectionRate::new();
+/// let mut count = TypeCount::new(256);
+/// lolrofl::analysis::evaluate(&game, &mut [&mut rate, &mut count]).unwrap();
+/// # #[cfg(feature = "payload")]
+/// assert_eq!(count.value(), 4.);
+/// ```
+pub fn evaluate(rofl: &Rofl, metrics: &mut [&mut dyn Metric]) -> Result<(), Errors> {
+    evaluate_with_pauses(rofl, &Pauses::new(), metrics)
+}
+
+/// Evaluate metrics over a game's timeline with pause-adjusted section times
+pub fn evaluate_with_pauses(rofl: &Rofl, pauses: &Pauses, metrics: &mut [&mut dyn Metric]) -> Result<(), Errors> {
+    walk_timeline(rofl, |time, section| {
+        let time = pauses.adjust(time);
+        for metric in metrics.iter_mut() {
+            metric.observe(time, section);
+        }
+    })
+}
+
+/// A period of a game during which it was paused
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Pause {
+    /// The raw in-game time in seconds at which the game was paused
+    start: f32,
+    /// The raw in-game time in seconds at which the game was resumed
+    end: f32,
+}
+
+impl Pause {
+    /// The raw in-game time in seconds at which the game was paused
+    pub fn start(&self) -> f32 { self.start }
+    /// The raw in-game time in seconds at which the game was resumed
+    pub fn end(&self) -> f32 { self.end }
+    /// The pause's duration in seconds
+    pub fn duration(&self) -> f32 { self.end - self.start }
+}
+
+/// The pauses of a game, used to convert raw section times to pause-adjusted times
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Pauses {
+    /// The pauses, sorted by start time
+    pauses: Vec<Pause>,
+}
+
+impl Pauses {
+    /// Build an empty set of pauses, adjusted times match raw times
+    pub fn new() -> Pauses { Pauses::default() }
+    /// Add a pause, pauses that end before they start are ignored
+    pub fn push(&mut self, start: f32, end: f32) {
+        if end >= start {
+            let index = self.pauses.partition_point(|p| p.start <= start);
+            self.pauses.insert(index, Pause { start, end });
+        }
+    }
+    /// The number of pauses
+    pub fn len(&self) -> usize { self.pauses.len() }
+    /// Whether the game was never paused
+    pub fn is_empty(&self) -> bool { self.pauses.is_empty() }
+    /// Iterate over the pauses
+    pub fn iter(&self) -> std::slice::Iter<'_, Pause> { self.pauses.iter() }
+    /// The total paused time in seconds
+    pub fn total(&self) -> f32 { self.pauses.iter().map(Pause::duration).sum() }
+    /// Convert a raw in-game time to the time elapsed without pauses
+    ///
+    /// Times within a pause are converted to the pause's adjusted start time
+    pub fn adjust(&self, time: f32) -> f32 {
+        let paused: f32 = self.pauses.iter()
+            .take_while(|p| p.start < time)
+            .map(|p| p.end.min(time) - p.start)
+            .sum();
+        time - paused
+    }
+}
+
+/// Find the pauses of a game from its pause and resume sections
+///
+/// A pause starts with a section whose type is in `pause_types` and ends with the next section
+/// whose type is in `resume_types`, or with the last section of the game if it was never resumed.
+///
+/// # Examples
+///
+/// ```rust
+/// # let content = lolrofl::test::sample_full_file_0();
+/// // let content = std::fs::read("game.rofl").unwrap();
+/// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+///
+/// let pauses = lolrofl::analysis::detect_pauses(&game, &[512], &[256]).unwrap();
+/// println!("Paused {} times for {:.1} seconds", pauses.len(), pauses.total());
+/// # #[cfg(feature = "payload")] {
+/// # assert_eq!(pauses.len(), 1);
+/// # assert_eq!(pauses.adjust(30.02), 30.02 - (30. - 0.515));
+/// # }
+/// ```
+pub fn detect_pauses(rofl: &Rofl, pause_types: &[u32], resume_types: &[u32]) -> Result<Pauses, Errors> {
+    let mut pauses = Pauses::new();
+    let mut start = None;
+    let mut last_time = 0.;
+    walk_timeline(rofl, |time, section| {
+        last_time = time;
+        match start {
+            None if pause_types.contains(&section.data_type()) => start = Some(time),
+            Some(s) if resume_types.contains(&section.data_type()) => {
+                pauses.push(s, time);
+                start = None;
+            },
+            _ => {},
+        }
+    })?;
+    if let Some(s) = start {
+        pauses.push(s, last_time);
+    }
+    Ok(pauses)
+}
+
+/// Evaluate a metric over sliding windows of a game's timeline
+///
+/// A copy of `metric` observes the sections of each window of `window` seconds, windows start
+/// every `step` seconds from the start of the game. Returns each window's start time in seconds
+/// along with the metric's value over the window, windows that start after the last section or
+/// after the payload's duration are not returned. Sections whose time is not finite are ignored.
+///
+/// # Errors
+///
+/// If `window` or `step` is not a finite, strictly positive number or if the file's chunks could not be read
+///
+/// # Examples
+///
+/// ```rust
+/// use lolrofl::analysis::TypeCount;
+///
+/// # let content = lolrofl::test::sample_full_file_0();
+/// // let content = std::fs::read("game.rofl").unwrap();
+/// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+///
+/// // The number of sections of type 256 over 20 seconds, every 10 seconds
+/// for (start, value) in lolrofl::analysis::rolling(&game, &TypeCount::new(256), 20., 10.).unwrap() {
+///     println!("{:.0}s: {}", start, value);
+/// }
+/// # #[cfg(feature = "payload")]
+/// # assert_eq!(lolrofl::analysis::rolling(&game, &TypeCount::new(256), 20., 10.).unwrap(), [(0., 2.), (10., 0.), (20., 2.), (30., 2.)]);
+/// # assert!(matches!(lolrofl::analysis::rolling(&game, &TypeCount::new(256), 20., f32::NAN), Err(lolrofl::Errors::InvalidArgument { .. })));
+/// # assert!(matches!(lolrofl::analysis::rolling(&game, &TypeCount::new(256), f32::INFINITY, 10.), Err(lolrofl::Errors::InvalidArgument { .. })));
+/// ```
+pub fn rolling<M: Metric + Clone>(rofl: &Rofl, metric: &M, window: f32, step: f32) -> Result<Vec<(f32, f64)>, Errors> {
+    if !window.is_finite() || window <= 0. {
+        return Err(Errors::InvalidArgument { name: "window", reason: "must be finite and strictly positive" });
+    }
+    if !step.is_finite() || step <= 0. {
+        return Err(Errors::InvalidArgument { name: "step", reason: "must be finite and strictly positive" });
+    }
+    // The number of windows that start within the game, section times come from the file and may be out of its bounds
+    let count = ((rofl.payload()?.duration() as f32 / 1000. / step) as usize).saturating_add(1);
+    let mut series = Vec::new();
+    // Windows that may still receive sections, by start order
+    let mut open = std::collections::VecDeque::<(f32, M)>::new();
+    let mut next = 0usize;
+    walk_timeline(rofl, |time, section| {
+        if !time.is_finite() {
+            return;
+        }
+        while next < count && next as f32 * step <= time {
+            open.push_back((next as f32 * step, metric.clone()));
+            next += 1;
+        }
+        while open.front().is_some_and(|(start, _)| start + window <= time) {
+            let (start, metric) = open.pop_front().unwrap();
+            series.push((start, metric.value()));
+        }
+        for (_, metric) in open.iter_mut() {
+            metric.observe(time, section);
+        }
+    })?;
+    series.extend(open.into_iter().map(|(start, metric)| (start, metric.value())));
+    Ok(series)
+}
+
+/// Feed all sections of a game's timeline to a function along with their raw and pause-adjusted in-game times in seconds
+///
+/// # Examples
+///
+/// ```rust
+/// use lolrofl::analysis::Pauses;
+///
+/// # let content = lolrofl::test::sample_full_file_0();
+/// // let content = std::fs::read("game.rofl").unwrap();
+/// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+///
+/// let mut pauses = Pauses::new();
+/// pauses.push(10., 20.);
+/// lolrofl::analysis::timeline(&game, &pauses, |raw, adjusted, section| {
+///     println!("{} at {:.3}s ({:.3}s without pauses)", section.data_type(), raw, adjusted);
+/// }).unwrap();
+/// # assert_eq!(pauses.adjust(30.), 20.);
+/// # assert_eq!(pauses.adjust(15.), 10.);
+/// ```
+pub fn timeline<F>(rofl: &Rofl, pauses: &Pauses, mut f: F) -> Result<(), Errors>
+where F: FnMut(f32, f32, &GenericSection) {
+    walk_timeline(rofl, |time, section| f(time, pauses.adjust(time), section))
+}
+
+/// The action rate of an entity over a game
+#[derive(Clone, Debug, PartialEq)]
+pub struct ActionRate {
+    /// The ID of the entity that sent the actions
+    entity: u32,
+    /// The number of actions sent by the entity
+    total: usize,
+    /// The number of actions sent by the entity in each time window
+    windows: Vec<usize>,
+    /// The duration of each time window in seconds
+    window: f32,
+    /// The game's duration in seconds
+    duration: f32,
+}
+
+impl ActionRate {
+    /// The ID of the entity that sent the actions, as found in the sections' parameters
+    pub fn entity(&self) -> u32 { self.entity }
+    /// The number of actions sent by the entity
+    pub fn total(&self) -> usize { self.total }
+    /// The average number of actions per minute over the game
+    pub fn apm(&self) -> f64 {
+        if self.duration > 0. { self.total as f64 * 60. / self.duration as f64 } else { 0. }
+    }
+    /// The number of actions per minute in each time window, along with the window's start time in seconds
+    pub fn series(&self) -> Vec<(f32, f64)> {
+        self.windows.iter().enumerate()
+            .map(|(i, count)| (i as f32 * self.window, *count as f64 * 60. / self.window as f64))
+            .collect()
+    }
+}
+
+/// Estimate the action rate of each entity of a game over time
+///
+/// Actions are the sections whose type is in `action_types` (e.g. spell casts and movement orders)
+/// and are attributed to the entity ID found in the sections' parameters.
+/// Rates are computed over the whole game and over consecutive windows of `window` seconds.
+///
+/// # Examples
+///
+/// ```rust
+/// # let content = lolrofl::test::sample_full_file_0();
+/// // let content = std::fs::read("game.rofl").unwrap();
+/// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+///
+/// for rate in lolrofl::analysis::apm(&game, &[256], 60.).unwrap() {
+///     println!("Entity {}: {:.1} APM", rate.entity(), rate.apm());
+/// }
+/// # #[cfg(feature = "payload")]
+/// # assert_eq!(lolrofl::analysis::apm(&game, &[256], 60.).unwrap().len(), 4);
+/// # assert!(matches!(lolrofl::analysis::apm(&game, &[256], f32::NAN), Err(lolrofl::Errors::InvalidArgument { .. })));
+/// # assert!(matches!(lolrofl::analysis::apm(&game, &[256], f32::INFINITY), Err(lolrofl::Errors::InvalidArgument { .. })));
+/// ```
+pub fn apm(rofl: &Rofl, action_types: &[u32], window: f32) -> Result<Vec<ActionRate>, Errors> {
+    apm_with_pauses(rofl, &Pauses::new(), action_types, window)
+}
+
+/// Estimate the action rate of each entity of a game over time, ignoring the time spent paused
+///
+/// Windows are built over pause-adjusted times and the game's duration excludes pauses.
+/// Actions whose time is not finite or is past the payload's duration are ignored.
+///
+/// # Errors
+///
+/// If `window` is not a finite, strictly positive number or if the file's chunks could not be read
+pub fn apm_with_pauses(rofl: &Rofl, pauses: &Pauses, action_types: &[u32], window: f32) -> Result<Vec<ActionRate>, Errors> {
+    if !window.is_finite() || window <= 0. {
+        return Err(Errors::InvalidArgument { name: "window", reason: "must be finite and strictly positive" });
+    }
+    let duration = (rofl.payload()?.duration() as f32 / 1000. - pauses.total()).max(0.);
+    let mut rates = std::collections::BTreeMap::<u32, ActionRate>::new();
+    walk_timeline(rofl, |time, section| {
+        if !action_types.contains(&section.data_type()) {
+            return;
+        }
+        let time = pauses.adjust(time);
+        // Section times come from the file and may be out of the game's bounds
+        if !time.is_finite() || time > duration {
+            return;
+        }
+        let entity = section.params().iter().rev().fold(0, |id, b| (id << 8) | *b as u32);
+        let rate = rates.entry(entity).or_insert_with(|| ActionRate { entity, total: 0, windows: Vec::new(), window, duration });
+        let index = ((time.max(0.) / window) as usize).min((duration / window) as usize);
+        if rate.windows.len() <= index {
+            rate.windows.resize(index + 1, 0);
+        }
+        rate.total += 1;
+        rate.windows[index] += 1;
+    })?;
+    Ok(rates.into_values().collect())
+}
+
+/// A phase of a game, see [`chapters`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Phase {
+    /// The early game, during which players mostly stay in their lane
+    Laning,
+    /// The middle of the game, once teams start grouping to fight over objectives
+    MidGame,
+    /// The end of the game, once fights follow each other and decide the game
+    LateGame,
+}
+
+impl Phase {
+    /// All phases, in the order they occur
+    pub const ALL: [Phase; 3] = [Phase::Laning, Phase::MidGame, Phase::LateGame];
+    /// The phase's name, as parsed by [`Phase::from_str`](std::str::FromStr::from_str)
+    pub fn name(&self) -> &'static str {
+        match self {
+            Phase::Laning => "laning",
+            Phase::MidGame => "mid-game",
+            Phase::LateGame => "late-game",
+        }
+    }
+}
+
+impl std::fmt::Display for Phase {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.pad(self.name())
+    }
+}
+
+impl std::str::FromStr for Phase {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Phase::ALL.into_iter()
+            .find(|p| p.name().eq_ignore_ascii_case(s))
+            .ok_or_else(|| format!("Unknown phase {:?}, expected laning, mid-game or late-game", s))
+    }
+}
+
+/// How a game's timeline is split in chapters, see [`chapters`]
+///
+/// Events are the sections whose type is in `event_types`, chosen by the caller: the sections of
+/// kills and objectives have not been identified, so the lib does not detect them and phases follow
+/// the density of the given section types. A fight is a time span of `fight_window` seconds that
+/// holds at least `fight_events` events.
+/// The mid-game starts at the first fight or at `laning_end`, whichever comes first.
+/// The late game starts at the first fight of the mid-game that holds twice as many events or at
+/// `late_game_start`, whichever comes first.
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub struct ChapterRules {
+    /// The section types counted as events
+    pub event_types: Vec<u32>,
+    /// The in-game time in seconds at which the laning phase ends if no fight occurs before
+    pub laning_end: f32,
+    /// The in-game time in seconds at which the late game starts if no large fight occurs before
+    pub late_game_start: f32,
+    /// The duration in seconds of the time span events are counted over to detect fights
+    pub fight_window: f32,
+    /// The number of events in a time span that make up a fight
+    pub fight_events: usize,
+}
+
+impl Default for ChapterRules {
+    fn default() -> Self {
+        ChapterRules { event_types: Vec::new(), laning_end: 840., late_game_start: 1800., fight_window: 30., fight_events: 3 }
+    }
+}
+
+impl ChapterRules {
+    /// Rules with the usual phase times that count the sections of the given types as events
+    pub fn new(event_types: &[u32]) -> ChapterRules {
+        ChapterRules { event_types: event_types.to_vec(), ..ChapterRules::default() }
+    }
+    /// Set the in-game time in seconds at which the laning phase ends if no fight occurs before
+    pub fn laning_end(mut self, time: f32) -> ChapterRules {
+        self.laning_end = time;
+        self
+    }
+    /// Set the in-game time in seconds at which the late game starts if no large fight occurs before
+    pub fn late_game_start(mut self, time: f32) -> ChapterRules {
+        self.late_game_start = time;
+        self
+    }
+    /// Set the number of events within a time span of `window` seconds that make up a fight
+    pub fn fight(mut self, window: f32, events: usize) -> ChapterRules {
+        self.fight_window = window;
+        self.fight_events = events;
+        self
+    }
+}
+
+/// A part of a game's timeline spent in a phase
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Chapter {
+    /// The game's phase during the chapter
+    phase: Phase,
+    /// The chapter's start time in seconds
+    start: f32,
+    /// The chapter's end time in seconds
+    end: f32,
+    /// The number of events during the chapter
+    events: usize,
+    /// Whether the chapter ends the game
+    last: bool,
+}
+
+impl Chapter {
+    /// The game's phase during the chapter
+    pub fn phase(&self) -> Phase { self.phase }
+    /// The chapter's start time in seconds, usable as a marker to seek to
+    pub fn start(&self) -> f32 { self.start }
+    /// The chapter's end time in seconds
+    pub fn end(&self) -> f32 { self.end }
+    /// The chapter's duration in seconds
+    pub fn duration(&self) -> f32 { self.end - self.start }
+    /// The number of events during the chapter
+    pub fn events(&self) -> usize { self.events }
+    /// Whether the chapter ends the game
+    pub fn is_last(&self) -> bool { self.last }
+    /// Whether an in-game time in seconds is within the chapter
+    ///
+    /// The chapter's end time is excluded unless the chapter ends the game
+    pub fn contains(&self, time: f32) -> bool {
+        self.start <= time && (time < self.end || (self.last && time == self.end))
+    }
+}
+
+/// The start time of the first span of `window` seconds that holds at least `count` of the sorted
+/// event times and starts at or after `after`
+fn first_fight(times: &[f32], after: f32, window: f32, count: usize) -> Option<f32> {
+    let times = &times[times.partition_point(|t| *t < after)..];
+    if count == 0 {
+        return Some(after);
+    }
+    times.windows(count).find(|w| w[count - 1] - w[0] <= window).map(|w| w[0])
+}
+
+/// Split a game's timeline in laning, mid-game and late-game chapters based on time and the density of sections of given types
+///
+/// See [`ChapterRules`] for how phases are detected. Phases that are skipped, such as the late game
+/// of a game that ends early, have no chapter. The game ends with its payload's duration or its last
+/// section, whichever comes last.
+///
+/// # Errors
+///
+/// If the fight window is not strictly positive or if the file's chunks could not be read
+///
+/// # Examples
+///
+/// ```rust
+/// use lolrofl::analysis::{ChapterRules, Phase};
+///
+/// # let content = lolrofl::test::sample_full_file_0();
+/// // let content = std::fs::read("game.rofl").unwrap();
+/// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+///
+/// // A short game: the laning phase ends after 10 seconds, the late game starts after 20 seconds
+/// // and fights are made of 3 events within a second
+/// let rules = ChapterRules::new(&[256]).laning_end(10.).late_game_start(20.).fight(1., 3);
+/// for chapter in lolrofl::analysis::chapters(&game, &rules).unwrap() {
+///     println!("{} from {:.0}s to {:.0}s: {} events", chapter.phase(), chapter.start(), chapter.end(), chapter.events());
+/// }
+/// # #[cfg(feature = "payload")] {
+/// # let chapters = lolrofl::analysis::chapters(&game, &rules).unwrap();
+/// # assert_eq!(chapters.iter().map(|c| (c.phase(), c.start(), c.events())).collect::<Vec<_>>(), [(Phase::Laning, 0., 2), (Phase::MidGame, 10., 0), (Phase::LateGame, 20., 2)]);
+/// # // Two events within a second start the mid-game
+/// # let chapters = lolrofl::analysis::chapters(&game, &rules.fight(1., 2)).unwrap();
+/// # assert_eq!(chapters[1].start(), 0.5);
+/// # }
+/// ```
+pub fn chapters(rofl: &Rofl, rules: &ChapterRules) -> Result<Vec<Chapter>, Errors> {
+    if rules.fight_window <= 0. {
+        return Err(Errors::InvalidArgument { name: "fight_window", reason: "must be strictly positive" });
+    }
+    let mut duration = rofl.payload()?.duration() as f32 / 1000.;
+    let mut times = Vec::new();
+    walk_timeline(rofl, |time, section| {
+        duration = duration.max(time);
+        if rules.event_types.contains(&section.data_type()) {
+            times.push(time);
+        }
+    })?;
+    times.sort_by(f32::total_cmp);
+    let mid_game = first_fight(&times, 0., rules.fight_window, rules.fight_events)
+        .map_or(rules.laning_end, |fight| fight.min(rules.laning_end))
+        .max(0.);
+    let late_game = first_fight(&times, mid_game, rules.fight_window, rules.fight_events.saturating_mul(2))
+        .map_or(rules.late_game_start, |fight| fight.min(rules.late_game_start))
+        .max(mid_game);
+    let bounds = [0., mid_game, late_game, duration];
+    let mut chapters: Vec<Chapter> = Phase::ALL.into_iter().enumerate()
+        .map(|(i, phase)| Chapter { phase, start: bounds[i].min(duration), end: bounds[i + 1].min(duration), events: 0, last: false })
+        .filter(|c| c.end > c.start)
+        .collect();
+    if let Some(last) = chapters.last_mut() {
+        last.last = true;
+    }
+    for time in times {
+        if let Some(chapter) = chapters.iter_mut().find(|c| c.contains(time)) {
+            chapter.events += 1;
+        }
+    }
+    Ok(chapters)
+}
+
+/// The number of sections and bytes of a section type
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct TypeCoverage {
+    /// Number of sections
+    sections: usize,
+    /// Number of bytes, section headers included
+    bytes: usize,
+    /// Whether the type is decoded by a typed decoder
+    decoded: bool,
+}
+
+impl TypeCoverage {
+    /// Number of sections
+    pub fn sections(&self) -> usize { self.sections }
+    /// Number of bytes, section headers included
+    pub fn bytes(&self) -> usize { self.bytes }
+    /// Whether the type is decoded by a typed decoder, else it is only available as a [`GenericSection`]
+    pub fn is_decoded(&self) -> bool { self.decoded }
+}
+
+/// How much of a file's sections are decoded by typed decoders
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Coverage {
+    /// Coverage of each section type, by type
+    types: std::collections::BTreeMap<u32, TypeCoverage>,
+}
+
+impl Coverage {
+    /// Coverage of each section type, by type
+    pub fn types(&self) -> &std::collections::BTreeMap<u32, TypeCoverage> { &self.types }
+    /// Total number of sections
+    pub fn sections(&self) -> usize { self.types.values().map(TypeCoverage::sections).sum() }
+    /// Total number of bytes
+    pub fn bytes(&self) -> usize { self.types.values().map(TypeCoverage::bytes).sum() }
+    /// Number of sections decoded by typed decoders
+    pub fn decoded_sections(&self) -> usize { self.types.values().filter(|t| t.decoded).map(TypeCoverage::sections).sum() }
+    /// Number of bytes decoded by typed decoders
+    pub fn decoded_bytes(&self) -> usize { self.types.values().filter(|t| t.decoded).map(TypeCoverage::bytes).sum() }
+    /// Fraction of bytes decoded by typed decoders, between 0 and 1
+    pub fn byte_ratio(&self) -> f64 {
+        match self.bytes() {
+            0 => 0.,
+            bytes => self.decoded_bytes() as f64 / bytes as f64,
+        }
+    }
+}
+
+/// Measure how much of the sections of a file's chunks and keyframes are decoded by typed decoders
+///
+/// Section types without a decoder in [`known_kinds`](crate::model::section::known_kinds) fall
+/// back to [`GenericSection`]
+///
+/// # Errors
+///
+/// If the file's segments could not be decrypted or split into sections
+///
+/// # Examples
+///
+/// ```rust
+/// # let content = lolrofl::test::sample_full_file_0();
+/// // let content = std::fs::read("game.rofl").unwrap();
+/// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+///
+/// let coverage = lolrofl::analysis::coverage(&game).unwrap();
+/// println!("{:.1}% of bytes decoded", coverage.byte_ratio() * 100.);
+/// for (data_type, stats) in coverage.types() {
+///     println!("{}: {} sections, {} bytes", data_type, stats.sections(), stats.bytes());
+/// }
+/// # assert_eq!(coverage.decoded_bytes(), 0);
+/// # #[cfg(feature = "payload")]
+/// # assert_eq!(coverage.types()[&256].sections(), 4);
+/// ```
+pub fn coverage(rofl: &Rofl) -> Result<Coverage, Errors> {
+    use crate::model::section::{SectionCore, kind_info};
+
+    let mut coverage = Coverage::default();
+    let mut iterator = rofl.segment_iter(&PayloadIterOptions::new().load_data(cfg!(feature="payload")))?;
+    for segment in iterator.by_ref().filter(|s| s.is_loaded()) {
+        let mut sections = segment.section_iter()?;
+        for section in sections.by_ref() {
+            let data_type = section.data_type();
+            let stats = coverage.types.entry(data_type).or_insert_with(|| TypeCoverage {
+                decoded: kind_info(data_type).is_some_and(|k| k.has_decoder()),
+                ..TypeCoverage::default()
+            });
+            stats.sections += 1;
+            stats.bytes += section.len();
+        }
+        if !sections.is_valid() {
+            return Err(sections.to_error());
+        }
+    }
+    if iterator.is_valid() { Ok(coverage) } else { Err(iterator.to_error()) }
+}
+
+/// A difference between two recordings of the same game
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum Divergence {
+    /// A chunk is only found in one of the recordings
+    MissingChunk {
+        /// The chunk's ID
+        id: u32,
+        /// Whether the chunk is missing from the first recording, else it is missing from the second
+        from_first: bool,
+    },
+    /// The sections of a chunk differ, only the first differing section of each chunk is reported
+    Section {
+        /// The address of the section in the first recording, if the chunk holds enough sections
+        first: Option<SectionAddress>,
+        /// The address of the section in the second recording, if the chunk holds enough sections
+        second: Option<SectionAddress>,
+        /// The section's in-game time in seconds, in the first recording if it holds the section
+        time: f32,
+    },
+}
+
+impl std::fmt::Display for Divergence {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Divergence::MissingChunk { id, from_first } => write!(f, "Chunk {} is missing from the {} file", id, if *from_first {"first"} else {"second"}),
+            Divergence::Section { first, second, time } => {
+                let display = |address: &Option<SectionAddress>| address.map_or("(none)".to_string(), |a| a.to_string());
+                write!(f, "Sections differ at {}: {} and {}", crate::model::section::format_game_time(*time), display(first), display(second))
+            },
+        }
+    }
+}
+
+/// How two recordings of the same game line up
+#[derive(Clone, Debug, PartialEq)]
+pub struct Alignment {
+    /// The game IDs of the first and second recordings
+    match_ids: (u64, u64),
+    /// The number of chunks found in both recordings with the same sections
+    matched: usize,
+    /// The differences between the recordings, by chunk ID
+    divergences: Vec<Divergence>,
+}
+
+impl Alignment {
+    /// The game IDs of the first and second recordings
+    pub fn match_ids(&self) -> (u64, u64) { self.match_ids }
+    /// Whether both recordings hold the same game
+    pub fn is_same_game(&self) -> bool { self.match_ids.0 == self.match_ids.1 }
+    /// The number of chunks found in both recordings with the same sections
+    pub fn matched_chunks(&self) -> usize { self.matched }
+    /// The differences between the recordings, by chunk ID
+    pub fn divergences(&self) -> &[Divergence] { &self.divergences }
+}
+
+/// The digest, in-game time and offset of a section
+type SectionDigest = (u64, f32, usize);
+
+/// The digest, time and offset of each section of a file's chunks, by chunk ID
+fn section_digests(rofl: &Rofl) -> Result<std::collections::BTreeMap<u32, Vec<SectionDigest>>, Errors> {
+    use crate::model::section::SectionCore;
+
+    let mut chunks = std::collections::BTreeMap::new();
+    let mut iterator = rofl.segment_iter(&PayloadIterOptions::new().load_data(cfg!(feature="payload")))?;
+    let mut time = 0.;
+    for segment in iterator.by_ref().filter(|s| s.is_chunk() && s.is_loaded()) {
+        let (mut digests, mut offset) = (Vec::new(), 0);
+        let mut sections = segment.section_iter()?;
+        for section in sections.by_ref() {
+            time = section.time().to_absolute(time);
+            // Times are left out as the same time may be encoded differently
+            let mut digest: u64 = 0xcbf29ce484222325;
+            let bytes = section.data_type().to_le_bytes();
+            for byte in bytes.iter().chain(section.params()).chain(section.raw_data().unwrap_or_default()) {
+                digest ^= *byte as u64;
+                digest = digest.wrapping_mul(0x100000001b3);
+            }
+            digests.push((digest, time, offset));
+            offset += section.len();
+        }
+        if !sections.is_valid() {
+            return Err(sections.to_error());
+        }
+        chunks.insert(segment.id(), digests);
+    }
+    if iterator.is_valid() { Ok(chunks) } else { Err(iterator.to_error()) }
+}
+
+/// Align two recordings of the same game, such as an observer's and a player's, and list their differences
+///
+/// Chunks are matched by ID and their sections are compared in order by type, parameters and data.
+/// Keyframes are ignored as they hold snapshots of data already present in chunks.
+///
+/// # Errors
+///
+/// If either file's payload header or chunks could not be read
+///
+/// # Examples
+///
+/// ```rust
+/// use lolrofl::analysis::Divergence;
+///
+/// # let content = lolrofl::test::sample_full_file_0();
+/// // let content = std::fs::read("observer.rofl").unwrap();
+/// let observer = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+/// # let mut builder = lolrofl::write::RoflBuilder::from_rofl(&observer).unwrap();
+/// # let other = builder.retain_segments(|s| s.is_keyframe() || s.id() != 2).build();
+/// // let other = std::fs::read("player.rofl").unwrap();
+/// let player = lolrofl::Rofl::from_slice(&other[..]).unwrap();
+///
+/// let alignment = lolrofl::analysis::align(&observer, &player).unwrap();
+/// assert_eq!(alignment.is_same_game(), true);
+/// for divergence in alignment.divergences() {
+///     println!("{}", divergence);
+/// }
+/// # #[cfg(feature = "payload")] {
+/// # assert_eq!(alignment.matched_chunks(), 1);
+/// # assert_eq!(alignment.divergences(), [Divergence::MissingChunk { id: 2, from_first: false }]);
+/// # assert_eq!(lolrofl::analysis::align(&observer, &observer).unwrap().divergences(), []);
+/// # }
+/// ```
+pub fn align(a: &Rofl, b: &Rofl) -> Result<Alignment, Errors> {
+    let match_ids = (a.payload()?.id(), b.payload()?.id());
+    let (first, second) = (section_digests(a)?, section_digests(b)?);
+    let mut alignment = Alignment { match_ids, matched: 0, divergences: Vec::new() };
+    let ids: std::collections::BTreeSet<u32> = first.keys().chain(second.keys()).copied().collect();
+    for id in ids {
+        let (first_sections, second_sections) = match (first.get(&id), second.get(&id)) {
+            (Some(f), Some(s)) => (f, s),
+            (f, _) => {
+                alignment.divergences.push(Divergence::MissingChunk { id, from_first: f.is_none() });
+                continue;
+            },
+        };
+        let address = |sections: &[SectionDigest], index: usize| sections.get(index)
+            .map(|(_, _, offset)| SectionAddress::chunk(id, SectionPosition::Offset(*offset)));
+        let length = first_sections.len().max(second_sections.len());
+        match (0..length).find(|i| first_sections.get(*i).map(|s| s.0) != second_sections.get(*i).map(|s| s.0)) {
+            None => alignment.matched += 1,
+            Some(index) => alignment.divergences.push(Divergence::Section {
+                first: address(first_sections, index),
+                second: address(second_sections, index),
+                time: first_sections.get(index).or_else(|| second_sections.get(index)).map_or(0., |s| s.1),
+            }),
+        }
+    }
+    Ok(alignment)
+}
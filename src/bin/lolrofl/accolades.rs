@@ -0,0 +1,61 @@
+//! Awards derived from the players' stats of a game
+
+use lolrofl::model::{Metadata, PlayerStats};
+
+/// An award given to a player
+#[derive(Debug)]
+pub struct Accolade {
+    /// The award's title
+    pub title: &'static str,
+    /// The awarded player's name and champion
+    pub player: String,
+    /// The value that earned the award
+    pub value: String,
+}
+
+/// The name and champion of a player
+fn describe(player: &PlayerStats) -> String {
+    format!("{} ({})", player.identity(), player.champion().unwrap_or("?"))
+}
+
+/// Find the player with the highest value of a stat, players without the stat are ignored
+fn best<F>(players: &[PlayerStats], value: F) -> Option<(&PlayerStats, f64)>
+where F: Fn(&PlayerStats) -> Option<f64> {
+    players.iter()
+        .filter_map(|p| value(p).map(|v| (p, v)))
+        .fold(None, |best: Option<(&PlayerStats, f64)>, (p, v)| match best {
+            Some((_, b)) if b >= v => best,
+            _ => Some((p, v)),
+        })
+}
+
+/// Compute the awards of a game, awards whose stats are missing from the metadata are skipped
+pub fn compute(metadata: &Metadata) -> Vec<Accolade> {
+    let players = metadata.players();
+    let teams = metadata.team_stats();
+    let mut accolades = Vec::new();
+    let stat = |key: &'static str| move |p: &PlayerStats| p.get_number(key).map(|v| v as f64);
+    if let Some((player, value)) = best(players, stat("TOTAL_DAMAGE_DEALT_TO_CHAMPIONS")) {
+        accolades.push(Accolade { title: "Most damage", player: describe(player), value: format!("{} damage to champions", value) });
+    }
+    if let Some((player, value)) = best(players, PlayerStats::kda) {
+        accolades.push(Accolade { title: "Best KDA", player: describe(player), value: format!("{:.2} KDA", value) });
+    }
+    if let Some((player, value)) = best(players, stat("VISION_SCORE")) {
+        accolades.push(Accolade { title: "Most vision", player: describe(player), value: format!("{} vision score", value) });
+    }
+    // Gold lead contribution: the player's gold above the average gold of the enemy team's players
+    let enemy_average = |player: &PlayerStats| -> Option<f64> {
+        let team = player.team()?;
+        let enemies: Vec<_> = teams.iter().filter(|t| t.team() != team && t.players() > 0).collect();
+        let players: usize = enemies.iter().map(|t| t.players()).sum();
+        if players == 0 {
+            return None;
+        }
+        Some(enemies.iter().map(|t| t.gold()).sum::<i64>() as f64 / players as f64)
+    };
+    if let Some((player, value)) = best(players, |p| Some(p.get_number("GOLD_EARNED")? as f64 - enemy_average(p)?)) {
+        accolades.push(Accolade { title: "Largest gold lead", player: describe(player), value: format!("{:+.0} gold over the enemy average", value) });
+    }
+    accolades
+}
@@ -0,0 +1,127 @@
+//! On-disk cache of the quick-scan results of ROFL files
+
+use std::collections::HashMap;
+use std::path::{Path, PathBuf};
+use lolrofl::{Rofl, model::Metadata};
+
+/// The high-level information gathered on a file without reading its payload
+#[derive(Clone, Debug)]
+pub struct ScanEntry {
+    /// The file's modification time in nanoseconds since the epoch
+    pub mtime: u128,
+    /// The game's ID
+    pub id: u64,
+    /// The game's duration in milliseconds
+    pub duration: u32,
+    /// The game's version as reported in the metadata
+    pub version: String,
+    /// The game's creation time in milliseconds since the epoch, if the metadata holds it
+    pub created: Option<u64>,
+}
+
+impl ScanEntry {
+    /// When the game was played: its creation time, or the file's modification time if it is unknown
+    pub fn date(&self) -> std::time::SystemTime {
+        match self.created {
+            Some(ms) => std::time::UNIX_EPOCH + std::time::Duration::from_millis(ms),
+            None => std::time::UNIX_EPOCH + std::time::Duration::from_nanos(self.mtime as u64),
+        }
+    }
+}
+
+/// A cache of scan results keyed by file path and invalidated when a file's mtime changes
+pub struct ScanCache {
+    /// Where the cache is saved, `None` if the cache is disabled
+    path: Option<PathBuf>,
+    /// The cached scan results
+    entries: HashMap<String, ScanEntry>,
+    /// Whether entries were added since the cache was loaded
+    dirty: bool,
+}
+
+impl ScanCache {
+    /// The default location of the cache: `$XDG_CACHE_HOME/lolrofl/index.json` or `~/.cache/lolrofl/index.json`
+    pub fn default_path() -> Option<PathBuf> {
+        std::env::var_os("XDG_CACHE_HOME").map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".cache")))
+            .map(|p| p.join("lolrofl").join("index.json"))
+    }
+    /// Build a cache that neither loads nor saves anything
+    pub fn disabled() -> ScanCache {
+        ScanCache { path: None, entries: HashMap::new(), dirty: false }
+    }
+    /// Load the cache at a path, previous entries are ignored if `refresh` is set
+    pub fn load(path: PathBuf, refresh: bool) -> ScanCache {
+        let mut entries = HashMap::new();
+        let content = if refresh { None } else { std::fs::read_to_string(&path).ok() };
+        if let Some(index) = content.and_then(|c| json::parse(&c).ok()) {
+            for (key, value) in index.entries() {
+                let entry = ScanEntry {
+                    mtime: value["mtime"].as_str().and_then(|m| m.parse().ok()).unwrap_or(0),
+                    id: value["id"].as_u64().unwrap_or(0),
+                    duration: value["duration"].as_u32().unwrap_or(0),
+                    version: value["version"].as_str().unwrap_or("").to_string(),
+                    created: value["created"].as_u64(),
+                };
+                entries.insert(key.to_string(), entry);
+            }
+        }
+        ScanCache { path: Some(path), entries, dirty: refresh }
+    }
+    /// Get the scan results of a file, from the cache if it did not change since it was cached
+    pub fn scan(&mut self, file: &Path) -> Option<ScanEntry> {
+        let key = file.canonicalize().ok()?.to_string_lossy().to_string();
+        let mtime = std::fs::metadata(file).ok()?
+            .modified().ok()?
+            .duration_since(std::time::UNIX_EPOCH).ok()?
+            .as_nanos();
+        if let Some(entry) = self.entries.get(&key) {
+            if entry.mtime == mtime {
+                return Some(entry.clone());
+            }
+        }
+        let entry = quick_scan(file, mtime)?;
+        if self.path.is_some() {
+            self.entries.insert(key, entry.clone());
+            self.dirty = true;
+        }
+        Some(entry)
+    }
+    /// Write the cache to disk if it changed
+    pub fn save(&self) -> std::io::Result<()> {
+        let path = match &self.path {
+            Some(path) if self.dirty => path,
+            _ => return Ok(()),
+        };
+        let mut index = json::JsonValue::new_object();
+        for (key, entry) in &self.entries {
+            index[key.as_str()] = json::object!{
+                mtime: entry.mtime.to_string(),
+                id: entry.id,
+                duration: entry.duration,
+                version: entry.version.as_str(),
+                created: entry.created,
+            };
+        }
+        if let Some(parent) = path.parent() {
+            std::fs::create_dir_all(parent)?;
+        }
+        std::fs::write(path, index.dump())
+    }
+}
+
+/// Read a file's header, metadata and payload header without loading its payload
+fn quick_scan(file: &Path, mtime: u128) -> Option<ScanEntry> {
+    let data = Rofl::headers_from_reader(std::fs::File::open(file).ok()?).ok()?;
+    let payload = data.payload().ok()?;
+    let metadata = Metadata::from_json(data.metadata().ok()?).ok()?;
+    Some(ScanEntry {
+        mtime,
+        id: payload.id(),
+        duration: payload.duration(),
+        version: metadata.game_version().unwrap_or("").to_string(),
+        created: metadata.game_creation()
+            .and_then(|c| c.duration_since(std::time::UNIX_EPOCH).ok())
+            .map(|c| c.as_millis() as u64),
+    })
+}
@@ -0,0 +1,92 @@
+//! Human-readable reports of the errors that stop a command
+
+use lolrofl::{Errors, Rofl, model::BinHeader};
+
+/// Get hints on what may have caused an error and how to work around it
+///
+/// `content` is the content of the file being processed, if it was read
+pub fn hints(error: &Errors, content: Option<&[u8]>) -> Vec<String> {
+    let mut hints = Vec::new();
+    let error = error.root();
+    match error {
+        Errors::Io(e) if e.kind() == std::io::ErrorKind::NotFound => hints.push("the file does not exist, check its path".to_string()),
+        Errors::Io(e) if e.kind() == std::io::ErrorKind::PermissionDenied => hints.push("the file can't be read by the current user, check its permissions".to_string()),
+        Errors::FeatureDisabled { feature } => hints.push(format!("this build does not support the operation, rebuild lolrofl with `--features {}`", feature)),
+        Errors::MalformedHeader { field } => hints.push(format!(
+            "the file header's {} field points outside of the file, the header is probably corrupted", field,
+        )),
+        Errors::MalformedPayloadHeader { field } => hints.push(format!(
+            "the payload header's {} field is not valid, the file may have been written by an unsupported game version", field,
+        )),
+        #[cfg(feature = "payload")]
+        Errors::InvalidBase64(_) => hints.push("the payload's encryption key is not valid base64, the payload header is probably corrupted".to_string()),
+        Errors::InvalidJson(_) => hints.push("the metadata is not valid JSON, print it with `lolrofl FILE get metadata` to inspect it".to_string()),
+        Errors::GameMismatch { expected, actual } => hints.push(format!(
+            "the files hold games {} and {}, only copies of the same game can be merged", expected, actual,
+        )),
+        Errors::VerificationFailed { report } => hints.extend(report.segments().iter().filter(|s| !s.is_valid()).map(|s| format!("{} failed its checks", s))),
+        _ => {},
+    }
+    let generic = hints.len();
+    if let Some(content) = content {
+        if !content.starts_with(&Rofl::MAGIC) {
+            hints.push("not a ROFL file: it does not start with the \"RIOT\" magic bytes".to_string());
+        } else if let Some(kind) = lolrofl::kind::detect(content).filter(|k| !k.is_supported()) {
+            hints.push(format!("the file's format ({:?}) is not supported, it was probably written by a more recent game client", kind));
+        } else if content.len() < BinHeader::LENGTH {
+            hints.push(format!("file truncated: a file's header holds {} bytes, found {} bytes", BinHeader::LENGTH, content.len()));
+        } else if let Ok(rofl) = Rofl::from_slice(content) {
+            let expected = rofl.head().file_len();
+            if expected > content.len() {
+                hints.push(format!(
+                    "file truncated: expected {}, found {}, the download or copy was probably interrupted",
+                    format_size(expected), format_size(content.len()),
+                ));
+            }
+        }
+    }
+    // Files that are not obviously damaged may have been written by an unsupported game version
+    if hints.len() == generic && matches!(error, Errors::InvalidBuffer | Errors::BufferTooSmall { .. } | Errors::NoData | Errors::MalformedHeader { .. } | Errors::MalformedPayloadHeader { .. }) {
+        hints.push("if the file was written by the game, build a sample to attach to a bug report with `lolrofl FILE minimize -o sample.rofl`".to_string());
+    }
+    hints
+}
+
+/// Format a number of bytes with a readable unit
+fn format_size(bytes: usize) -> String {
+    match bytes {
+        b if b >= 1 << 20 => format!("{:.1}MB ({} bytes)", b as f64 / (1 << 20) as f64, b),
+        b if b >= 1 << 10 => format!("{:.1}kB ({} bytes)", b as f64 / (1 << 10) as f64, b),
+        b => format!("{} bytes", b),
+    }
+}
+
+/// Displays an error followed by the errors that caused it, such as `Segment 3: section at offset 0xc: ...`
+pub struct Chain<'a>(pub &'a Errors);
+
+impl std::fmt::Display for Chain<'_> {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        use std::error::Error;
+        write!(f, "{}", self.0)?;
+        let mut source = self.0.source();
+        while let Some(error) = source {
+            write!(f, ": {}", error)?;
+            source = error.source();
+        }
+        Ok(())
+    }
+}
+
+/// Print an error, along with hints if `explain` is set, then exit
+pub fn fail(context: &str, error: &Errors, content: Option<&[u8]>, explain: bool) -> ! {
+    eprintln!("{}: {}", context, Chain(error));
+    let hints = hints(error, content);
+    if explain {
+        for hint in hints {
+            eprintln!("hint: {}", hint);
+        }
+    } else if !hints.is_empty() {
+        eprintln!("Run the command again with --explain for hints");
+    }
+    std::process::exit(1)
+}
@@ -0,0 +1,96 @@
+//! High-level information on a game printed by `get info`
+//!
+//! The information is read from the file header and the metadata first, the payload header is
+//! optional so that the metadata of files with a malformed payload header can still be read.
+
+use lolrofl::{Errors, model::{BinHeader, GameMode, Metadata, PayloadHeader, Summary, TeamStats}};
+
+/// What `get info` prints
+pub struct GameInfo<'a> {
+    /// The file's header
+    pub head: &'a BinHeader,
+    /// The file's payload header, or why it could not be read
+    pub payload: Result<PayloadHeader, Errors>,
+    /// The game's parsed metadata
+    pub metadata: Metadata,
+    /// The game's mode
+    pub mode: GameMode,
+}
+
+impl GameInfo<'_> {
+    /// The game's duration in milliseconds, from the payload header or else from the metadata
+    fn duration(&self) -> Option<u64> {
+        self.payload.as_ref().ok().map(|p| p.duration() as u64).or_else(|| self.metadata.game_length())
+    }
+    /// The team that won the game, if any
+    fn winner(&self) -> Option<u32> {
+        self.metadata.team_stats().iter().find(|t| t.win() == Some(true)).map(TeamStats::team)
+    }
+    /// The players of a team as `(name, champion)` pairs
+    fn team_players(&self, team: u32) -> Vec<(String, &str)> {
+        self.metadata.players().iter()
+            .filter(|p| p.team() == Some(team))
+            .map(|p| (p.identity().display_name().unwrap_or_else(|| "?".to_string()), p.champion().unwrap_or("?")))
+            .collect()
+    }
+}
+
+/// Render a game's information as human-readable lines
+pub fn render(info: &GameInfo) -> String {
+    let mut lines = Vec::new();
+    match &info.payload {
+        Ok(payload) => lines.push(format!("{:<10}{}", "Game:", payload.id())),
+        Err(e) => lines.push(format!("{:<10}unknown (could not read the payload header: {})", "Game:", e)),
+    }
+    lines.push(format!("{:<10}{}", "Version:", info.metadata.game_version().unwrap_or("unknown")));
+    lines.push(format!("{:<10}{}", "Mode:", info.mode));
+    lines.push(format!(
+        "{:<10}{}", "Duration:",
+        info.duration().map_or("unknown".to_string(), |d| format!("{}:{:02}", d / 60_000, d / 1000 % 60)),
+    ));
+    match &info.payload {
+        Ok(payload) => lines.push(format!(
+            "{:<10}{} bytes, {} chunks, {} keyframes", "File:", info.head.file_len(), payload.chunk_count(), payload.keyframe_count(),
+        )),
+        Err(_) => lines.push(format!("{:<10}{} bytes", "File:", info.head.file_len())),
+    }
+    lines.push(format!("{:<10}{}", "End:", info.metadata.end_reason()));
+    lines.push(format!("{:<10}{}", "Winner:", info.winner().map_or("unknown".to_string(), Summary::team_name)));
+    for team in info.metadata.team_stats() {
+        let players: Vec<String> = info.team_players(team.team()).iter()
+            .map(|(name, champion)| format!("{} ({})", name, champion))
+            .collect();
+        lines.push(format!("{:<10}{}", format!("{}:", Summary::team_name(team.team())), players.join(", ")));
+    }
+    lines.join("\n")
+}
+
+/// Describe a game's information as JSON, the payload header's values are `null` if it could not be read
+pub fn to_json(info: &GameInfo) -> json::JsonValue {
+    let teams: Vec<json::JsonValue> = info.metadata.team_stats().iter().map(|team| {
+        let players: Vec<json::JsonValue> = info.team_players(team.team()).into_iter()
+            .map(|(name, champion)| json::object!{ name: name, champion: champion })
+            .collect();
+        json::object!{
+            team: team.team(),
+            name: Summary::team_name(team.team()),
+            win: team.win(),
+            kills: team.kills(),
+            players: players,
+        }
+    }).collect();
+    let payload = info.payload.as_ref().ok();
+    json::object!{
+        match_id: payload.map(PayloadHeader::id),
+        version: info.metadata.game_version(),
+        mode: info.mode.to_string(),
+        duration: info.duration(),
+        file_len: info.head.file_len(),
+        chunks: payload.map(PayloadHeader::chunk_count),
+        keyframes: payload.map(PayloadHeader::keyframe_count),
+        end_reason: info.metadata.end_reason().to_string(),
+        winner: info.winner(),
+        teams: teams,
+        payload_error: info.payload.as_ref().err().map(|e| crate::explain::Chain(e).to_string()),
+    }
+}
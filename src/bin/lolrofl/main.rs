@@ -0,0 +1,2068 @@
+mod accolades;
+mod cache;
+mod explain;
+mod info;
+mod output;
+mod play;
+mod schema;
+mod selftest;
+mod share;
+mod sink;
+mod stats;
+mod template;
+
+use clap::{Args, ArgEnum, Parser, Subcommand};
+use json::parse;
+use lolrofl::{Rofl, fmt::{ColorTable, Style}, iter::PayloadIterOptions, model::{Metadata, Segment, section::{format_game_time, GenericSection, SectionAddress, SectionCore, SectionPosition}}};
+
+/// A program to extract information from LoL replay files
+#[derive(Parser, Debug)]
+#[clap(author = "Ayowel", version, about, long_about = None)]
+struct Cli {
+    #[clap(subcommand)]
+    command: CliCommands,
+
+    /// Path to the ROFL file to open (or directory to scan)
+    #[clap(global=true)]
+    file: Option<std::path::PathBuf>,
+
+    /// Verbose mode
+    #[clap(short, long, global=true)]
+    verbose: bool,
+
+    /// Print hints on the cause of errors and how to work around them
+    #[clap(long, global=true)]
+    explain: bool,
+
+    /// Format of the output of `get payload`, `analyze --mode stats`, `schema dump` and export manifests
+    #[clap(long, arg_enum, global=true, default_value="text")]
+    format: output::OutputFormat,
+
+    /// Color the text output of `get`, `analyze` and `list`
+    #[clap(long, arg_enum, global=true, default_value="auto")]
+    color: output::ColorChoice,
+}
+
+#[derive(Debug, Subcommand)]
+enum CliCommands {
+    #[clap(about = "Get information on the file")]
+    Get(InspectCommand),
+    #[clap(about = "Get information on the file")]
+    Analyze(AnalyzeCommand),
+    #[clap(about = "Export chunk or keyframe data to a file")]
+    Export(ExportCommand),
+    #[clap(about = "Print the file's sections as JSON lines, optionally filtered by an expression")]
+    Events(EventsCommand),
+    #[clap(about = "Print the game's decoded events, only interaction pings for now, in chronological order")]
+    Timeline,
+    #[clap(about = "Compare the file with another recording of the same game and list their differences")]
+    Align(AlignCommand),
+    #[clap(about = "Build the smallest file that reproduces a parsing failure")]
+    Minimize(MinimizeCommand),
+    #[clap(about = "Rewrite the file with its segments in canonical order and compact data offsets")]
+    Normalize(NormalizeCommand),
+    #[clap(about = "Rebuild a truncated or partially corrupted file from its whole segments")]
+    Repair(RepairCommand),
+    #[clap(about = "Combine partial copies of the same game into its most complete file")]
+    Merge(MergeCommand),
+    #[clap(about = "List the ROFL files of a directory")]
+    List(ListCommand),
+    #[clap(about = "Rename the ROFL files of a directory based on their metadata")]
+    Rename(RenameCommand),
+    #[clap(about = "Find the ROFL files of a directory that hold the same game")]
+    Dedupe(DedupeCommand),
+    #[clap(about = "Print statistics aggregated over the ROFL files of a directory")]
+    Stats(StatsCommand),
+    #[clap(about = "Compare the section types of sets of ROFL files")]
+    Schema(SchemaCommand),
+    #[clap(about = "Check that this build can parse a file, the built-in sample is used if no file is provided")]
+    Selftest,
+    #[clap(about = "List the personally identifying information held by the file and where it is found")]
+    Privacy,
+    #[clap(about = "Launch the local League of Legends client in replay mode on the file")]
+    Play(PlayCommand),
+    #[cfg(feature = "http")]
+    #[clap(about = "Post a summary of the game to a Discord webhook")]
+    Notify(NotifyCommand),
+    #[cfg(feature = "http")]
+    #[clap(about = "Download a game from a spectator server and write it as a ROFL file")]
+    Fetch(FetchCommand),
+}
+
+#[derive(Debug, Args)]
+struct InspectCommand {
+    #[clap(subcommand)]
+    command: SubInspectCommands,
+}
+
+#[derive(Debug, Subcommand)]
+enum SubInspectCommands {
+    #[clap(alias = "i", about = "Print simple/high-level info on the file and the game")]
+    Info(InfoInspectCommand),
+    #[clap(alias = "m", about = "Print the game's metadata")]
+    Metadata(MetadataInspectCommand),
+    #[clap(alias = "p", about = "Print technical information on the file")]
+    Payload(PayloadInspectCommand),
+    #[clap(alias = "h", about = "Print the file's header and check it against the actual file")]
+    Header(HeaderInspectCommand),
+    #[clap(alias = "s", about = "Print the payload's segment headers without decrypting them")]
+    Segments(SegmentsInspectCommand),
+    #[clap(about = "Print awards computed from the players' stats (most damage, best KDA, ...)")]
+    Accolades(AccoladesInspectCommand),
+    #[clap(about = "Print a short summary of the game to paste in a chat application")]
+    Share(ShareInspectCommand),
+    #[clap(alias = "r", about = "NOT IMPLEMENTED - Print details on exported payload data")]
+    RawData(RawDataInspectCommand),
+}
+
+#[derive(Debug, Args)]
+struct InfoInspectCommand {
+    #[clap(long, help("Print internal file signature"))]
+    signature: bool,
+
+    #[clap(long, help("Print the game's region, as found in the file's metadata or name"))]
+    region: bool,
+
+    #[clap(long, help("Print the game's mode, as found in the metadata"))]
+    mode: bool,
+
+    #[clap(long, help("Print how the game ended (surrender, remake, nexus destroyed), as found in the metadata"))]
+    end_reason: bool,
+
+    #[clap(long, help("Print when the game was created, as found in the metadata"))]
+    date: bool,
+
+    #[clap(long, conflicts_with_all(&["signature", "region", "mode", "end-reason", "date"]), help("Print the game's summary as JSON"))]
+    json: bool,
+}
+
+#[derive(Debug, Args)] #[clap(about)]
+struct MetadataInspectCommand {
+    #[clap(long, help("Print only the \"statsJson\" key's content as a JSON"))]
+    stats: bool,
+
+    #[clap(long, help("Re-serialize the metadata with sorted keys and normalized numbers so that it can be diffed"))]
+    canonical: bool,
+
+    #[clap(long, help("NOT IMPLEMENTED - Print only the values corresponding to a specific key"))]
+    key: Option<String>,
+}
+
+
+#[derive(Debug, Args)]
+struct PayloadInspectCommand {
+    #[clap(long, help("Print the game's ID"))]
+    id: bool,
+
+    #[clap(long, help("Print the game's duration in seconds"))]
+    duration: bool,
+
+    #[clap(long, arg_enum, multiple_values(true), help("Print the total number of chunks or keyframes"))]
+    count: Vec<SegmentType>,
+
+    #[clap(long, help("Print the ID of the last loading chunk for the game"))]
+    loadid: bool,
+
+    #[clap(long, help("Print the ID of the first chunk after the game's start"))]
+    startid: bool,
+
+    #[clap(long, help("Print keyframe interval in seconds"))]
+    interval: bool,
+
+    #[clap(long, help("Print the file's primary encryption key"))]
+    key: bool,
+}
+
+#[derive(Debug, Args)]
+struct HeaderInspectCommand {
+    #[clap(long, help("Print the header as a JSON object"))]
+    json: bool,
+}
+
+#[derive(Debug, Args)]
+struct SegmentsInspectCommand {
+    #[clap(long, help("Print the segment headers as a JSON array"))]
+    json: bool,
+
+    #[clap(long, help("Decrypt the segments to print the time range they cover"))]
+    times: bool,
+}
+
+#[derive(Debug, Args)]
+struct RawDataInspectCommand {
+}
+
+#[derive(Debug, Args)]
+struct AccoladesInspectCommand {
+}
+
+#[derive(Debug, Args)]
+struct ShareInspectCommand {
+    #[clap(long, arg_enum, default_value="discord", help("Style of the summary"))]
+    style: share::ShareFormat,
+}
+
+#[derive(Debug, Args)]
+struct ExportCommand {
+    #[clap(subcommand)]
+    command: SubExportCommands,
+
+    #[clap(short, long, global=true, default_value=".", help("Data export output directory"))]
+    directory: std::path::PathBuf,
+
+    #[clap(long, global=true, default_value="{matchid}-{id}-{type}.bin", help("Template of the exported files' names, supports {matchid}, {version}, {id} and {type}"))]
+    name: String,
+
+    #[cfg(feature = "encrypt")]
+    #[clap(long, global=true, help("Encrypt the exported files with a password, \".enc\" is appended to their names"))]
+    encrypt_with: Option<String>,
+}
+
+#[derive(Debug, Subcommand)]
+enum SubExportCommands {
+    #[clap(alias = "c", about = "Export chunks")]
+    Chunk(SegmentExportCommand),
+
+    #[clap(alias = "k", about = "Export keyframes")]
+    Keyframe(SegmentExportCommand),
+
+    #[clap(alias = "a", about = "Export everything")]
+    All(FullSegmentExportCommand),
+
+    #[clap(alias = "s", about = "Export a sample of the sections of the whole game as text")]
+    Sections(SectionExportCommand),
+
+    #[cfg(feature = "npy")]
+    #[clap(alias = "t", about = "Export the sections sent by each entity over time as a NumPy tensor with a JSON manifest")]
+    Tensor(TensorExportCommand),
+}
+
+#[derive(Debug, Args)]
+struct SegmentExportCommand {
+
+    #[clap(long, conflicts_with("id"), help("Used to export all chunks or keyframes in the file - default to true if no chunk is configured"))]
+    all: bool,
+
+    #[clap(short, long, global = true, help("Chunk/keyframe IDs to export"))]
+    id: Vec<u32>,
+}
+
+#[derive(Debug, Args)]
+struct FullSegmentExportCommand {
+    #[clap(long, help("Skip segments that can't be decrypted instead of stopping the export, failures are listed in the manifest"))]
+    keep_going: bool,
+
+    #[clap(long, help("Also write a summary.json file that describes the game, the payload and the export's verification report"))]
+    sidecar: bool,
+}
+
+#[derive(Debug, Args)]
+struct SectionExportCommand {
+    #[clap(long, default_value="1/1", help("Share of the sections to export as KEEP/EVERY, such as 1/100 to export one section in 100"))]
+    sample: Sampling,
+
+    #[clap(long("type"), help("Only export the sections of these types"))]
+    types: Vec<u32>,
+}
+
+#[cfg(feature = "npy")]
+#[derive(Debug, Args)]
+struct TensorExportCommand {
+    #[clap(long("type"), required(true), help("The section types counted as features"))]
+    types: Vec<u32>,
+
+    #[clap(long, default_value="60", help("The duration in seconds of the tensor's time steps"))]
+    every: f32,
+}
+
+/// A share of items to keep, such as 1 item in 100
+#[derive(Clone, Copy, Debug)]
+struct Sampling {
+    keep: u64,
+    every: u64,
+}
+
+impl Sampling {
+    /// Whether the item at an index among the candidate items is kept
+    fn keeps(&self, index: u64) -> bool { index % self.every < self.keep }
+}
+
+impl std::str::FromStr for Sampling {
+    type Err = String;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let (keep, every) = s.split_once('/').ok_or_else(|| format!("Expected KEEP/EVERY, got {:?}", s))?;
+        let keep: u64 = keep.trim().parse().map_err(|e| format!("Invalid sample size {:?} ({})", keep, e))?;
+        let every: u64 = every.trim().parse().map_err(|e| format!("Invalid sample period {:?} ({})", every, e))?;
+        if every == 0 || keep > every {
+            return Err(format!("The sample size must be at most its period, got {}", s));
+        }
+        Ok(Sampling { keep, every })
+    }
+}
+
+#[derive(Debug, Args)]
+struct EventsCommand {
+    #[clap(long("where"), help("Only print the sections that match an expression such as \"type = 256 && time < 600\""))]
+    filter: Option<lolrofl::filter::Filter>,
+
+    #[clap(long, parse(try_from_str = parse_entity), help("Only print the sections caused by or targeting an entity, designated by its ID such as 0x40000001"))]
+    pov: Option<u32>,
+
+    #[clap(long, requires("event-types"), help("Only print the sections of a phase of the game: laning, mid-game or late-game"))]
+    chapter: Option<lolrofl::analysis::Phase>,
+
+    #[clap(long("event-type"), help("With --chapter, the section types whose density marks fights, kill and objective sections are not identified"))]
+    event_types: Vec<u32>,
+}
+
+#[derive(Debug, Args)]
+struct AlignCommand {
+    #[clap(help("Path to the other recording of the game"))]
+    other: std::path::PathBuf,
+}
+
+#[derive(Debug, Args)]
+struct MinimizeCommand {
+    #[clap(short, long, help("Path of the minimized file to write"))]
+    output: std::path::PathBuf,
+}
+
+#[derive(Debug, Args)]
+struct NormalizeCommand {
+    #[clap(short, long, help("Path of the normalized file to write"))]
+    output: std::path::PathBuf,
+}
+
+#[derive(Debug, Args)]
+struct RepairCommand {
+    #[clap(short, long, help("Path of the repaired file to write"))]
+    output: std::path::PathBuf,
+}
+
+#[derive(Debug, Args)]
+struct MergeCommand {
+    #[clap(long("part"), required(true), help("Path to another copy of the game, may be repeated, segments are taken from the first copy that holds them whole"))]
+    parts: Vec<std::path::PathBuf>,
+
+    #[clap(short, long, help("Path of the merged file to write"))]
+    output: std::path::PathBuf,
+}
+
+#[derive(Debug, Args)]
+struct ListCommand {
+    #[clap(long, help("Only list the games played on this version or a more recent one, such as 14.10"))]
+    min_version: Option<lolrofl::model::GameVersion>,
+
+    #[clap(long, conflicts_with("refresh"), help("Neither read nor update the scan cache"))]
+    no_cache: bool,
+
+    #[clap(long, help("Ignore cached scan results and rebuild the cache"))]
+    refresh: bool,
+}
+
+#[derive(Debug, Args)]
+struct RenameCommand {
+    #[clap(short, long, default_value="{date}_{matchid}.rofl", help("Template of the new file names, supports {date}, {matchid}, {version}, {duration}, and {champion} and {result} when --player is set"))]
+    template: String,
+
+    #[clap(long, help("Name, Riot ID or PUUID of the player whose champion and result are used in the template"))]
+    player: Option<String>,
+
+    #[clap(long, help("Print the new names without renaming the files"))]
+    dry_run: bool,
+}
+
+#[derive(Debug, Args)]
+struct DedupeCommand {
+    #[clap(long, conflicts_with("link"), help("Delete duplicates that hold the same bytes as the first file of their group (by path), which is kept"))]
+    delete: bool,
+
+    #[clap(long, help("Replace duplicates that hold the same bytes as the first file of their group (by path) with hard links to it"))]
+    link: bool,
+}
+
+#[derive(Debug, Args)]
+struct StatsCommand {
+    #[clap(short, long, help("Number of files to process in parallel - defaults to the number of CPUs"))]
+    jobs: Option<usize>,
+
+    #[cfg(feature = "metrics")]
+    #[clap(long, help("Write parsing metrics in the OpenMetrics text format to this file"))]
+    metrics: Option<std::path::PathBuf>,
+}
+
+#[derive(Debug, Args)]
+struct SchemaCommand {
+    #[clap(subcommand)]
+    command: SubSchemaCommands,
+}
+
+#[derive(Debug, Subcommand)]
+enum SubSchemaCommands {
+    #[clap(about = "List the section types that appeared, disappeared or changed length between the ROFL files of two directories, such as two patches")]
+    Drift(DriftCommand),
+    #[clap(about = "Print the reference of the section types known to the lib and of their decoders, as Markdown with --format text or md, or as JSON")]
+    Dump,
+}
+
+#[derive(Debug, Args)]
+struct DriftCommand {
+    #[clap(help("Directory of the files of the old patch"))]
+    old: std::path::PathBuf,
+
+    #[clap(help("Directory of the files of the new patch"))]
+    new: std::path::PathBuf,
+
+    #[clap(long, default_value="0.25", help("The change of a variable-length type's average length, as a ratio, above which it is reported as resized"))]
+    tolerance: f64,
+}
+
+#[derive(Debug, Args)]
+struct PlayCommand {
+    #[clap(long, help("League of Legends install directory - defaults to LOLROFL_LEAGUE_DIR or the platform's default install directory"))]
+    install: Option<std::path::PathBuf>,
+}
+
+#[cfg(feature = "http")]
+#[derive(Debug, Args)]
+struct NotifyCommand {
+    #[clap(long, help("URL of the Discord webhook to post to"))]
+    webhook: String,
+}
+
+#[cfg(feature = "http")]
+#[derive(Debug, Args)]
+struct FetchCommand {
+    #[clap(long, help("Region or platform ID of the game (e.g. euw or EUW1)"))]
+    region: String,
+
+    #[clap(long, help("ID of the game to download"))]
+    game: u64,
+
+    #[clap(long, help("Encryption key of the game - read from the game's metadata if not set"))]
+    key: Option<String>,
+
+    #[clap(long, help("Base URL of the spectator server - defaults to the region's public server"))]
+    server: Option<String>,
+
+    #[clap(long, help("Directory where downloaded segments are cached to resume interrupted downloads - defaults to ~/.cache/lolrofl/spectator"))]
+    cache: Option<std::path::PathBuf>,
+
+    #[clap(short, long, help("File to write the game to"))]
+    output: std::path::PathBuf,
+}
+
+#[derive(Debug, Args)]
+struct AnalyzeCommand {
+    #[clap(short, long, help("Which segment IDs to analyze"))]
+    id: Vec<u32>,
+
+    #[clap(long, arg_enum, default_value="stats", help("What information to look for/display"))]
+    mode: AnalyzeCommandMode,
+
+    #[clap(long, arg_enum, help("Which segment type to analyze"))]
+    only: Option<SegmentType>,
+
+    #[clap(long("type"), help("In stats mode, a specific type whose length stats should be calculated - in metrics mode, a type to count"))]
+    typed: Option<usize>,
+
+    #[clap(long("start-time"), help("Only process blocks after this in-game time in seconds"))]
+    start_time: Option<f32>,
+
+    #[clap(long("end-time"), help("Only process blocks before this in-game time in seconds"))]
+    end_time: Option<f32>,
+
+    #[clap(long, help("Inspect the section at an address such as chunk:42/offset:0x1A3 or kf:7/idx:15"))]
+    at: Option<SectionAddress>,
+
+    #[clap(long("action-type"), help("In apm mode, the section types that are counted as actions"))]
+    action_types: Vec<u32>,
+
+    #[clap(long("event-type"), help("In chapters mode, the section types whose density marks fights, kill and objective sections are not identified"))]
+    event_types: Vec<u32>,
+
+    #[clap(long("pause-type"), requires("resume-types"), help("In apm mode, the section types that pause the game - paused time is ignored"))]
+    pause_types: Vec<u32>,
+
+    #[clap(long("resume-type"), help("In apm mode, the section types that resume the game"))]
+    resume_types: Vec<u32>,
+
+    #[clap(long, default_value="60", help("In apm and metrics modes, the duration in seconds of the windows rates and metrics are computed over"))]
+    window: f32,
+
+    #[clap(long, help("In metrics mode, the time in seconds between the start of consecutive windows - defaults to the window's duration"))]
+    step: Option<f32>,
+
+    #[clap(long, parse(try_from_str = parse_offset), help("In layout mode, a file offset such as 0x1A3 to locate in the file's areas"))]
+    offset: Option<usize>,
+
+    #[clap(long, default_value="0", help("In detail and bytes modes, the number of records to skip before printing any"))]
+    skip: usize,
+
+    #[clap(long, help("In detail and bytes modes, the maximum number of records to print"))]
+    limit: Option<usize>,
+
+    #[clap(short('H'), long("human-readable"), help("Improve display for reading by a human"))]
+    human: bool,
+}
+
+#[derive(ArgEnum, Clone, Debug)]
+enum AnalyzeCommandMode {
+    Bytes,
+    Apm,
+    Chapters,
+    Coverage,
+    Detail,
+    Layout,
+    Metrics,
+    Stats,
+    Verify,
+}
+
+#[derive(ArgEnum, Clone, Debug, PartialEq, Eq)]
+enum SegmentType {
+    Chunk,
+    Keyframe,
+}
+
+/// Parse a decimal or `0x`-prefixed hexadecimal file offset
+fn parse_offset(value: &str) -> Result<usize, std::num::ParseIntError> {
+    match value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")) {
+        Some(hex) => usize::from_str_radix(hex, 16),
+        None => value.parse(),
+    }
+}
+
+/// Parse a decimal or `0x`-prefixed hexadecimal entity ID
+fn parse_entity(value: &str) -> Result<u32, std::num::ParseIntError> {
+    match value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")) {
+        Some(hex) => u32::from_str_radix(hex, 16),
+        None => value.parse(),
+    }
+}
+
+/// The number of columns of the map printed by `analyze --mode layout`
+const LAYOUT_MAP_WIDTH: usize = 64;
+
+/// The character an area is drawn with in the map printed by `analyze --mode layout`
+fn layout_symbol(area: lolrofl::model::Area) -> char {
+    use lolrofl::model::{Area, SegmentKind};
+    match area {
+        Area::Header => 'H',
+        Area::Metadata => 'M',
+        Area::PayloadHeader => 'P',
+        Area::SegmentHeaders => 'D',
+        Area::Segment { kind: Some(SegmentKind::Chunk), .. } => 'c',
+        Area::Segment { kind: Some(SegmentKind::Keyframe), .. } => 'k',
+        Area::Segment { .. } => 's',
+        _ => '.',
+    }
+}
+
+/// The name of a command if it needs to decrypt the payload's segments, which requires the `payload` feature
+fn decrypting_command(command: &CliCommands) -> Option<&'static str> {
+    match command {
+        CliCommands::Get(InspectCommand { command: SubInspectCommands::Segments(segments_args) }) if segments_args.times => Some("get segments --times"),
+        CliCommands::Analyze(analyze_args) if !matches!(analyze_args.mode, AnalyzeCommandMode::Layout) => Some("analyze"),
+        CliCommands::Export(_) => Some("export"),
+        CliCommands::Events(_) => Some("events"),
+        CliCommands::Timeline => Some("timeline"),
+        CliCommands::Align(_) => Some("align"),
+        CliCommands::Minimize(_) => Some("minimize"),
+        CliCommands::Normalize(_) => Some("normalize"),
+        CliCommands::Repair(_) => Some("repair"),
+        CliCommands::Merge(_) => Some("merge"),
+        CliCommands::Privacy => Some("privacy"),
+        CliCommands::Selftest => Some("selftest"),
+        CliCommands::Schema(SchemaCommand { command: SubSchemaCommands::Drift(_) }) => Some("schema drift"),
+        _ => None,
+    }
+}
+
+/// Exit with a report if output could not be written
+fn write_output(result: std::io::Result<()>) {
+    if let Err(e) = result {
+        eprintln!("Could not write the output: {}", e);
+        std::process::exit(1);
+    }
+}
+
+/// Format bytes as a lowercase hexadecimal string
+fn hex(bytes: &[u8]) -> String {
+    bytes.iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// The fields of a section in the records of `analyze --mode detail`
+fn section_fields(address: SectionAddress, time: f32, section: &GenericSection) -> [(&'static str, json::JsonValue); 5] {
+    [
+        ("address", address.to_string().into()),
+        ("time", time.into()),
+        ("type", section.data_type().into()),
+        ("params", hex(section.params()).into()),
+        ("data", hex(section.raw_data().unwrap_or_default()).into()),
+    ]
+}
+
+/// Read the source file, exit with a report on failure
+fn read_source(path: &std::path::Path, explain: bool) -> Vec<u8> {
+    std::fs::read(path).unwrap_or_else(|e| explain::fail("Could not read the file", &e.into(), None, explain))
+}
+
+/// Open the source file's content, exit with a report on failure
+fn open_source(content: &[u8], explain: bool) -> Rofl<'_> {
+    Rofl::from_slice(content).unwrap_or_else(|e| explain::fail("Could not open the file", &e, Some(content), explain))
+}
+
+/// Get an operation's result, exit with a report on failure
+fn check<T>(result: Result<T, lolrofl::Errors>, context: &str, content: &[u8], explain: bool) -> T {
+    result.unwrap_or_else(|e| explain::fail(context, &e, Some(content), explain))
+}
+
+/// List the ROFL files of a directory, sorted by path
+fn rofl_files(directory: &std::path::Path) -> Vec<std::path::PathBuf> {
+    if !directory.is_dir() {
+        eprintln!("Source is not a directory: {}", directory.display());
+        std::process::exit(1);
+    }
+    let mut files: Vec<_> = std::fs::read_dir(directory).unwrap()
+        .filter_map(|e| e.ok().map(|e| e.path()))
+        .filter(|p| p.is_file() && p.extension().map(|e| e.eq_ignore_ascii_case("rofl")).unwrap_or(false))
+        .collect();
+    files.sort();
+    files
+}
+
+/// Whether two files hold the same bytes
+fn same_content(a: &std::path::Path, b: &std::path::Path) -> std::io::Result<bool> {
+    if std::fs::metadata(a)?.len() != std::fs::metadata(b)?.len() {
+        return Ok(false);
+    }
+    Ok(std::fs::read(a)? == std::fs::read(b)?)
+}
+
+/// Replace a file with a hard link to another, the file is kept if the link can't be created
+fn replace_with_link(original: &std::path::Path, file: &std::path::Path) -> std::io::Result<()> {
+    let mut name = std::ffi::OsString::from(".");
+    name.push(file.file_name().unwrap_or_default());
+    name.push(".link");
+    let temporary = file.with_file_name(name);
+    std::fs::hard_link(original, &temporary)?;
+    std::fs::rename(&temporary, file).inspect_err(|_| {
+        let _ = std::fs::remove_file(&temporary);
+    })
+}
+
+/// Get the template values of a file, player-specific values are only set if a player is provided
+fn file_template_values(path: &std::path::Path, data: &Rofl, player: Option<&str>) -> Result<template::Values, String> {
+    let payload = data.payload().map_err(|e| explain::Chain(&e).to_string())?;
+    // Only the player's values require the metadata, files whose metadata is malformed may still be exported
+    let metadata = data.metadata().and_then(Metadata::from_json);
+    let mut values = template::Values::new();
+    values.insert("matchid", payload.id().to_string());
+    values.insert("version", metadata.as_ref().ok().and_then(Metadata::game_version).unwrap_or("unknown").to_string());
+    values.insert("duration", format!("{}m{:02}s", payload.duration() / 60000, payload.duration() / 1000 % 60));
+    // The file's modification time changes when it is copied, it is only used when the metadata has no creation time
+    if let Some(created) = metadata.as_ref().ok().and_then(Metadata::game_creation).or_else(|| std::fs::metadata(path).and_then(|m| m.modified()).ok()) {
+        values.insert("date", template::format_date(created));
+    }
+    if let Some(player) = player {
+        let metadata = metadata.map_err(|e| explain::Chain(&e).to_string())?;
+        let entry = metadata.players().iter()
+            .find(|p| p.identity().matches(player))
+            .ok_or_else(|| format!("Player {:?} did not play in the game", player))?;
+        values.insert("champion", entry.champion().unwrap_or("unknown").to_string());
+        values.insert("result", match entry.win() { Some(true) => "Win", Some(false) => "Fail", None => "unknown" }.to_string());
+    }
+    Ok(values)
+}
+
+/// Build the sink exported files are written to
+fn export_sink(export_args: &ExportCommand) -> Result<Box<dyn sink::Sink>, String> {
+    #[cfg(feature = "encrypt")]
+    if let Some(password) = &export_args.encrypt_with {
+        return Ok(Box::new(sink::EncryptedSink::new(sink::FileSink, password)?));
+    }
+    let _ = export_args;
+    Ok(Box::new(sink::FileSink))
+}
+
+/// How far in milliseconds a keyframe's first section may be from its expected time
+const KEYFRAME_TIME_TOLERANCE: u64 = 1000;
+
+/// An exported segment's type, ID and exported file or error
+type ExportedSegment = (&'static str, Option<u32>, Result<std::path::PathBuf, String>);
+
+/// Describe a file's metadata, payload header and export results as JSON
+fn export_summary(data: &Rofl, actual_len: usize, report: &[ExportedSegment]) -> Result<json::JsonValue, lolrofl::Errors> {
+    let payload = data.payload()?;
+    let metadata = Metadata::from_json(data.metadata()?)?;
+    let players: Vec<json::JsonValue> = metadata.players().iter()
+        .map(|p| p.stats().iter().map(|(k, v)| (k.as_str(), v.as_str())).collect::<std::collections::BTreeMap<_, _>>().into())
+        .collect();
+    let segments: Vec<json::JsonValue> = report.iter().map(|(kind, id, result)| json::object!{
+        type: *kind,
+        id: *id,
+        status: if result.is_ok() {"ok"} else {"failed"},
+        detail: match result {
+            Ok(path) => path.display().to_string(),
+            Err(e) => e.clone(),
+        },
+    }).collect();
+    Ok(json::object!{
+        metadata: {
+            game_length: metadata.game_length(),
+            game_version: metadata.game_version(),
+            last_game_chunk_id: metadata.last_game_chunk_id(),
+            last_key_frame_id: metadata.last_key_frame_id(),
+            end_reason: metadata.end_reason().to_string(),
+            players: players,
+        },
+        payload: {
+            id: payload.id(),
+            duration: payload.duration(),
+            chunk_count: payload.chunk_count(),
+            keyframe_count: payload.keyframe_count(),
+            load_end_chunk: payload.load_end_chunk(),
+            game_start_chunk: payload.game_start_chunk(),
+            keyframe_interval: payload.keyframe_interval(),
+        },
+        verification: {
+            file_length: data.head().file_len(),
+            actual_file_length: actual_len,
+            warnings: data.warnings().into_iter()
+                .chain(data.keyframe_warnings(KEYFRAME_TIME_TOLERANCE).unwrap_or_default())
+                .map(|w| w.to_string())
+                .collect::<Vec<_>>(),
+            segments: segments,
+        },
+    })
+}
+
+/// Get the name of a segment's type
+fn segment_type_name(segment: &Segment) -> &'static str {
+    if segment.is_chunk() {"Chunk"} else {"Keyframe"}
+}
+
+/// Get the name of an exported segment's file
+fn segment_file_name(name_template: &str, values: &template::Values, segment: &Segment) -> String {
+    let mut values = values.clone();
+    values.insert("id", segment.id().to_string());
+    values.insert("type", segment_type_name(segment).to_string());
+    template::render(name_template, &values).unwrap_or_else(|e| {
+        eprintln!("{}", e);
+        std::process::exit(1)
+    })
+}
+
+/// Run the self-test on a file or on the built-in sample and exit with its status
+fn run_selftest(file: Option<&std::path::Path>) -> ! {
+    let content = match file {
+        Some(path) => std::fs::read(path).unwrap_or_else(|e| {
+            println!("Could not read {}: {}", path.display(), e);
+            std::process::exit(1);
+        }),
+        #[cfg(feature = "test")]
+        None => lolrofl::test::sample_full_file_0(),
+        #[cfg(not(feature = "test"))]
+        None => {
+            println!("This build has no built-in sample, a path to a source file MUST be provided");
+            std::process::exit(1);
+        },
+    };
+    let checks = selftest::run(&content[..]);
+    for check in &checks {
+        println!("{}", check);
+    }
+    std::process::exit(if checks.iter().any(|c| c.failed()) { 1 } else { 0 })
+}
+
+/// Download a game from a spectator server and exit with its status
+#[cfg(feature = "http")]
+fn run_fetch(fetch_args: &FetchCommand) -> ! {
+    use lolrofl::spectator::{Download, SpectatorClient};
+
+    let platform_id = match fetch_args.region.parse::<lolrofl::model::Region>() {
+        Ok(region) => region.platform_id(),
+        Err(_) => {
+            eprintln!("Unknown region: {}", fetch_args.region);
+            std::process::exit(1);
+        },
+    };
+    let mut client = match &fetch_args.server {
+        Some(server) => SpectatorClient::new(server),
+        None => SpectatorClient::for_platform(platform_id),
+    };
+    let cache_dir = fetch_args.cache.clone()
+        .or_else(|| std::env::var_os("XDG_CACHE_HOME").map(std::path::PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|h| std::path::PathBuf::from(h).join(".cache")))
+            .map(|p| p.join("lolrofl").join("spectator")))
+        .unwrap_or_else(|| std::path::PathBuf::from("."));
+    let download = Download::new(platform_id, fetch_args.game, &cache_dir);
+    let result = download.run(&mut client, fetch_args.key.as_deref())
+        .and_then(|builder| Ok(std::fs::write(&fetch_args.output, builder.build())?));
+    if let Err(e) = result {
+        eprintln!("Could not download game {}: {}", fetch_args.game, explain::Chain(&e));
+        eprintln!("Downloaded segments are kept in {}, run the command again to resume", download.cache_dir().display());
+        std::process::exit(1);
+    }
+    std::process::exit(0)
+}
+
+/// Run the schema dump command, which does not read a source file
+fn run_schema_dump(format: output::OutputFormat) -> ! {
+    let types = schema::reference();
+    let result = match format {
+        output::OutputFormat::Text => {
+            print!("{}", schema::reference_markdown(&types));
+            Ok(())
+        },
+        output::OutputFormat::Json => {
+            let types: Vec<json::JsonValue> = types.iter().map(|known| {
+                let mut object = json::JsonValue::new_object();
+                for (name, value) in known.fields() {
+                    object[name] = value;
+                }
+                object
+            }).collect();
+            println!("{}", json::object! { version: env!("CARGO_PKG_VERSION"), types: types }.pretty(2));
+            Ok(())
+        },
+        output::OutputFormat::Csv => {
+            let mut out = output::OutputSink::stdout(format);
+            types.iter().try_for_each(|known| out.record(&known.name, &known.fields())).and_then(|_| out.flush())
+        },
+    };
+    write_output(result);
+    std::process::exit(0)
+}
+
+/// Run the schema drift command, which compares two directories instead of reading a source file
+fn run_schema_drift(drift_args: &DriftCommand, verbose: bool) -> ! {
+    let old = schema::Schema::collect(&rofl_files(&drift_args.old));
+    let new = schema::Schema::collect(&rofl_files(&drift_args.new));
+    for (name, schema) in [("Old", &old), ("New", &new)] {
+        println!("{} files: {} ({} failed), {} section types", name, schema.files, schema.failures.len(), schema.types.len());
+        if verbose {
+            for (file, error) in &schema.failures {
+                eprintln!("Could not process {}: {}", file.display(), error);
+            }
+        }
+    }
+    let drifts = schema::drift(&old, &new, drift_args.tolerance);
+    for drift in &drifts {
+        println!("{}", drift);
+    }
+    std::process::exit(if drifts.is_empty() { 0 } else { 1 })
+}
+
+fn main() {
+    let args = Cli::parse();
+    if let Some(command) = decrypting_command(&args.command).filter(|_| !lolrofl::capabilities().payload) {
+        eprintln!("`lolrofl {}` decrypts the payload's segments, which this build does not support", command);
+        eprintln!("Rebuild lolrofl with `--features payload` to use it, `get` commands other than `get segments --times` work without it");
+        std::process::exit(1);
+    }
+
+    if let CliCommands::Schema(SchemaCommand { command: SubSchemaCommands::Drift(drift_args) }) = &args.command {
+        run_schema_drift(drift_args, args.verbose);
+    }
+    if let CliCommands::Schema(SchemaCommand { command: SubSchemaCommands::Dump }) = &args.command {
+        run_schema_dump(args.format);
+    }
+    if let CliCommands::Selftest = args.command {
+        run_selftest(args.file.as_deref());
+    }
+    #[cfg(feature = "http")]
+    if let CliCommands::Fetch(fetch_args) = &args.command {
+        run_fetch(fetch_args);
+    }
+    if args.file.is_none() {
+        println!("A path to a source file MUST be provided");
+        std::process::exit(1);
+    }
+    let explain = args.explain;
+    let format = args.format;
+    let colors = args.color.colors(format);
+    let source_file = args.file.unwrap();
+    if !source_file.exists() {
+        println!("Source file does not exist: {}", source_file.display());
+        std::process::exit(1);
+    }
+
+    if args.verbose && source_file.is_file() {
+        if let Ok(content) = std::fs::read(&source_file) {
+            if let Ok(data) = Rofl::from_slice(&content[..]) {
+                for warning in data.warnings() {
+                    eprintln!("WARNING: {}", warning);
+                }
+            }
+        }
+    }
+
+    match args.command {
+        CliCommands::Get(inspect_args) => {
+            match inspect_args.command {
+                SubInspectCommands::Info(info_args) => {
+                    let content = read_source(&source_file, explain);
+                    let data = open_source(&content, explain);
+                    if !(info_args.signature || info_args.region || info_args.mode || info_args.end_reason || info_args.date) {
+                        let metadata_json = check(data.metadata(), "Could not read the game's metadata", &content, explain);
+                        let game_info = info::GameInfo {
+                            head: data.head(),
+                            payload: data.payload(),
+                            metadata: check(Metadata::from_json(metadata_json), "Could not parse the game's metadata", &content, explain),
+                            mode: lolrofl::model::GameMode::from_metadata(metadata_json),
+                        };
+                        if info_args.json {
+                            println!("{}", info::to_json(&game_info).pretty(2));
+                        } else {
+                            println!("{}", info::render(&game_info));
+                        }
+                        return;
+                    }
+                    if info_args.signature {
+                        println!("{:?}", data.head().signature());
+                    }
+                    if info_args.region {
+                        let region = data.metadata().ok().and_then(|m| Metadata::from_json(m).ok()).and_then(|m| m.region())
+                            .or_else(|| source_file.file_name().and_then(|n| lolrofl::model::Region::from_file_name(&n.to_string_lossy())));
+                        match region {
+                            Some(region) => println!("Region: {} ({})", region, region.platform_id()),
+                            None => println!("Region: Unknown"),
+                        }
+                    }
+                    if info_args.mode {
+                        println!("Mode: {}", lolrofl::model::GameMode::from_metadata(check(data.metadata(), "Could not read the game's metadata", &content, explain)));
+                    }
+                    if info_args.end_reason {
+                        match Metadata::from_json(check(data.metadata(), "Could not read the game's metadata", &content, explain)) {
+                            Ok(metadata) => println!("End: {}", metadata.end_reason()),
+                            Err(e) => println!("End: Unknown ({})", explain::Chain(&e)),
+                        }
+                    }
+                    if info_args.date {
+                        match Metadata::from_json(check(data.metadata(), "Could not read the game's metadata", &content, explain)) {
+                            Ok(metadata) => match metadata.game_creation() {
+                                Some(created) => println!("Date: {}", template::format_date_time(created)),
+                                None => println!("Date: Unknown (the metadata does not hold the game's creation time)"),
+                            },
+                            Err(e) => println!("Date: Unknown ({})", explain::Chain(&e)),
+                        }
+                    }
+                },
+                SubInspectCommands::Metadata(meta_args) => {
+                    let content = read_source(&source_file, explain);
+                    let data = open_source(&content, explain);
+                    let mut json_metadata_string = check(data.metadata(), "Could not read the game's metadata", &content, explain).to_string();
+                    if meta_args.canonical {
+                        json_metadata_string = match Metadata::canonicalize(&json_metadata_string) {
+                            Ok(canonical) => canonical,
+                            Err(e) => {
+                                eprintln!("Could not parse the game's metadata: {}", explain::Chain(&e));
+                                std::process::exit(1)
+                            },
+                        };
+                    }
+                    if !meta_args.stats {
+                        println!("{}", json_metadata_string);
+                    } else {
+                        let metadata = parse(&json_metadata_string).unwrap();
+                        println!("{}", metadata["statsJson"].as_str().unwrap_or("[]"));
+                    }
+                },
+                SubInspectCommands::Payload(payload_args) => {
+                    let content = read_source(&source_file, explain);
+                    let data = open_source(&content, explain);
+                    let payload = check(data.payload(), "Could not read the payload header", &content, explain);
+                    let all = !(payload_args.id || payload_args.duration || !payload_args.count.is_empty()
+                        || payload_args.loadid || payload_args.startid || payload_args.interval || payload_args.key);
+                    let mut lines = Vec::new();
+                    let mut fields = Vec::new();
+                    if payload_args.id || all {
+                        lines.push(format!("ID: {}", payload.id()));
+                        fields.push(("id", payload.id().into()));
+                    }
+                    if payload_args.duration || all {
+                        lines.push(format!("Duration: {} ms", payload.duration()));
+                        fields.push(("duration", payload.duration().into()));
+                    }
+                    let counts = if all { vec![SegmentType::Chunk, SegmentType::Keyframe] } else { payload_args.count };
+                    for segment_type in counts {
+                        match segment_type {
+                            SegmentType::Chunk => {
+                                lines.push(format!("ChunkCount: {}", payload.chunk_count()));
+                                fields.push(("chunk_count", payload.chunk_count().into()));
+                            },
+                            SegmentType::Keyframe => {
+                                lines.push(format!("KeyframeCount: {}", payload.keyframe_count()));
+                                fields.push(("keyframe_count", payload.keyframe_count().into()));
+                            },
+                        }
+                    }
+                    if payload_args.loadid || all {
+                        lines.push(format!("LoadEndChunk: {}", payload.load_end_chunk()));
+                        fields.push(("load_end_chunk", payload.load_end_chunk().into()));
+                    }
+                    if payload_args.startid || all {
+                        lines.push(format!("StartChunk: {}", payload.game_start_chunk()));
+                        fields.push(("start_chunk", payload.game_start_chunk().into()));
+                    }
+                    if payload_args.interval || all {
+                        lines.push(format!("KeyframeInterval: {}", payload.keyframe_interval()));
+                        fields.push(("keyframe_interval", payload.keyframe_interval().into()));
+                    }
+                    if payload_args.key || all {
+                        lines.push(format!("EncryptionKey: {}", payload.encryption_key()));
+                        fields.push(("encryption_key", payload.encryption_key().into()));
+                    }
+                    let mut sink = output::OutputSink::stdout(format);
+                    write_output(sink.record(&lines.join("\n"), &fields).and_then(|_| sink.flush()));
+                },
+                SubInspectCommands::Header(header_args) => {
+                    let content = read_source(&source_file, explain);
+                    let data = open_source(&content, explain);
+                    let head = data.head();
+                    let signature = head.signature().iter().map(|b| format!("{:02x}", b)).collect::<String>();
+                    let warnings: Vec<String> = data.warnings().iter().map(|w| w.to_string()).collect();
+                    if header_args.json {
+                        let output = json::object!{
+                            header_length: head.header_len(),
+                            file_length: head.file_len(),
+                            metadata_offset: head.metadata_offset(),
+                            metadata_length: head.metadata_len(),
+                            payload_header_offset: head.payload_header_offset(),
+                            payload_header_length: head.payload_header_len(),
+                            payload_offset: head.payload_offset(),
+                            signature: signature,
+                            format: data.format_version().to_string(),
+                            actual_file_length: content.len(),
+                            warnings: warnings,
+                        };
+                        println!("{}", output.pretty(2));
+                    } else {
+                        println!("{:#}", head);
+                        println!("{:<16}{}", "Signature:", signature);
+                        println!("{:<16}{}", "Format:", data.format_version());
+                        println!("{:<16}{} bytes", "Actual length:", content.len());
+                        for warning in warnings {
+                            println!("WARNING: {}", warning);
+                        }
+                    }
+                },
+                SubInspectCommands::Segments(segments_args) => {
+                    let content = read_source(&source_file, explain);
+                    let data = open_source(&content, explain);
+                    let options = PayloadIterOptions::new().load_data(segments_args.times);
+                    let mut iterator = check(data.segment_iter(&options), "Could not read the segments", &content, explain);
+                    let segments: Vec<Segment> = iterator.by_ref().collect();
+                    if segments_args.json {
+                        let mut output = json::JsonValue::new_array();
+                        for segment in &segments {
+                            output.push(json::object!{
+                                id: segment.id(),
+                                type: if segment.is_chunk() {"Chunk"} else if segment.is_keyframe() {"Keyframe"} else {"Segment"},
+                                length: segment.len(),
+                                next_chunk_id: segment.next_chunk_id(),
+                                offset: segment.offset(),
+                                start_time: segment.time_bounds().ok().map(|b| b.0),
+                                end_time: segment.time_bounds().ok().map(|b| b.1),
+                            }).unwrap();
+                        }
+                        println!("{}", output.pretty(2));
+                    } else {
+                        print!("{}", ColorTable(&segments, colors));
+                    }
+                    if !iterator.is_valid() {
+                        eprintln!("Segment headers could not be read past segment #{}: {}", iterator.internal_index(), iterator.to_error());
+                        std::process::exit(1);
+                    }
+                },
+                SubInspectCommands::Accolades(_) => {
+                    let content = read_source(&source_file, explain);
+                    let data = open_source(&content, explain);
+                    let metadata = match Metadata::from_json(check(data.metadata(), "Could not read the game's metadata", &content, explain)) {
+                        Ok(metadata) => metadata,
+                        Err(e) => {
+                            eprintln!("Could not parse the game's metadata: {}", explain::Chain(&e));
+                            std::process::exit(1);
+                        },
+                    };
+                    let accolades = accolades::compute(&metadata);
+                    if accolades.is_empty() {
+                        println!("The metadata holds no stats to compute awards from");
+                    }
+                    for accolade in accolades {
+                        println!("{:<20}{}: {}", format!("{}:", accolade.title), accolade.player, accolade.value);
+                    }
+                },
+                SubInspectCommands::Share(share_args) => {
+                    let content = read_source(&source_file, explain);
+                    let data = open_source(&content, explain);
+                    match lolrofl::model::Summary::from_rofl(&data) {
+                        Ok(summary) => println!("{}", share::render(&summary, share_args.style)),
+                        Err(e) => {
+                            eprintln!("Could not summarize the game: {}", explain::Chain(&e));
+                            std::process::exit(1);
+                        },
+                    }
+                },
+                SubInspectCommands::RawData(_) => {
+                    eprintln!("Exported payload data inspection is not supported yet");
+                    std::process::exit(1);
+                },
+            }
+        },
+        CliCommands::Export(export_args) => {
+            let is_dir_valid = std::fs::metadata(&export_args.directory)
+                .ok()
+                .and_then(|f| if f.is_dir() {Some(())} else {None})
+                .or_else(|| std::fs::create_dir(&export_args.directory).ok());
+            if is_dir_valid.is_none() {
+                eprintln!("Could not access nor create directory at {:?}", &export_args.directory);
+                std::process::exit(1)
+            }
+            let sink: Box<dyn sink::Sink> = match export_sink(&export_args) {
+                Ok(sink) => sink,
+                Err(e) => {
+                    eprintln!("Could not prepare the export ({})", e);
+                    std::process::exit(1)
+                },
+            };
+            match export_args.command {
+                SubExportCommands::Chunk(chunk_args) => {
+                    let content = read_source(&source_file, explain);
+                    let data = open_source(&content, explain);
+                    let values = match file_template_values(&source_file, &data, None) {
+                        Ok(values) => values,
+                        Err(e) => {
+                            eprintln!("Could not name the exported files ({})", e);
+                            std::process::exit(1)
+                        },
+                    };
+                    for segment in check(data.segment_iter(&PayloadIterOptions::with_data()), "Could not read the segments", &content, explain) {
+                        if segment.is_chunk() && (chunk_args.all || chunk_args.id.is_empty() || chunk_args.id.contains(&segment.id())){
+                            let output_file = export_args.directory.join(segment_file_name(&export_args.name, &values, &segment));
+                            let write_success = sink.write(&output_file, segment.data());
+                            if let Err(e) = write_success {
+                                eprintln!("An error occured while writing to {:?} ({})", &output_file, e);
+                                std::process::exit(1)
+                            }
+                        }
+                    }
+                },
+                SubExportCommands::Keyframe(keyframe_args) => {
+                    let content = read_source(&source_file, explain);
+                    let data = open_source(&content, explain);
+                    let values = match file_template_values(&source_file, &data, None) {
+                        Ok(values) => values,
+                        Err(e) => {
+                            eprintln!("Could not name the exported files ({})", e);
+                            std::process::exit(1)
+                        },
+                    };
+                    for segment in check(data.segment_iter(&PayloadIterOptions::with_data()), "Could not read the segments", &content, explain) {
+                        if segment.is_keyframe() && (keyframe_args.all || keyframe_args.id.is_empty() || keyframe_args.id.contains(&segment.id())){
+                            let output_file = export_args.directory.join(segment_file_name(&export_args.name, &values, &segment));
+                            let write_success = sink.write(&output_file, segment.data());
+                            if let Err(e) = write_success {
+                                eprintln!("An error occured while writing to {:?} ({})", &output_file, e);
+                                std::process::exit(1)
+                            }
+                        }
+                    }
+                },
+                SubExportCommands::All(all_args) => {
+                    let content = read_source(&source_file, explain);
+                    let data = open_source(&content, explain);
+                    let values = match file_template_values(&source_file, &data, None) {
+                        Ok(values) => values,
+                        Err(e) => {
+                            eprintln!("Could not name the exported files ({})", e);
+                            std::process::exit(1)
+                        },
+                    };
+                    let mut report: Vec<ExportedSegment> = Vec::new();
+                    let mut stopped = None;
+                    let mut iterator = check(data.segment_iter(&PayloadIterOptions::with_data()), "Could not read the segments", &content, explain);
+                    loop {
+                        for segment in iterator.by_ref() {
+                            let output_file = export_args.directory.join(segment_file_name(&export_args.name, &values, &segment));
+                            match sink.write(&output_file, segment.data()) {
+                                Ok(path) => report.push((segment_type_name(&segment), Some(segment.id()), Ok(path))),
+                                Err(e) => {
+                                    eprintln!("An error occured while writing to {:?} ({})", &output_file, e);
+                                    std::process::exit(1)
+                                },
+                            }
+                        }
+                        match iterator.skip_failed() {
+                            Some((segment, error)) => {
+                                let (kind, id) = segment.as_ref().map_or(("Unknown", None), |s| (segment_type_name(s), Some(s.id())));
+                                report.push((kind, id, Err(explain::Chain(&error).to_string())));
+                                if !all_args.keep_going {
+                                    stopped = Some(error);
+                                    break;
+                                }
+                                eprintln!("Skipped {} {}: {}", kind, id.map_or("?".to_string(), |id| id.to_string()), explain::Chain(&error));
+                            },
+                            None => break,
+                        }
+                    }
+                    let match_id = check(data.payload(), "Could not read the payload header", &content, explain).id();
+                    let mut manifest = output::OutputSink::new(Vec::new(), format);
+                    let _ = manifest.text("type\tid\tstatus\tdetail");
+                    for (kind, id, result) in &report {
+                        let (status, detail) = match result {
+                            Ok(path) => ("ok", path.display().to_string()),
+                            Err(e) => ("failed", e.to_string()),
+                        };
+                        let text = format!("{}\t{}\t{}\t{}", kind, id.map_or("?".to_string(), |id| id.to_string()), status, detail);
+                        let _ = manifest.record(&text, &[("type", (*kind).into()), ("id", (*id).into()), ("status", status.into()), ("detail", detail.into())]);
+                    }
+                    let extension = match format {
+                        output::OutputFormat::Text => "tsv",
+                        output::OutputFormat::Json => "jsonl",
+                        output::OutputFormat::Csv => "csv",
+                    };
+                    let manifest_file = export_args.directory.join(format!("{}-manifest.{}", match_id, extension));
+                    if let Err(e) = std::fs::write(&manifest_file, manifest.into_inner()) {
+                        eprintln!("An error occured while writing to {:?} ({})", &manifest_file, e);
+                        std::process::exit(1)
+                    }
+                    if all_args.sidecar {
+                        let summary = check(export_summary(&data, content.len(), &report), "Could not summarize the game", &content, explain);
+                        let summary_file = export_args.directory.join("summary.json");
+                        if let Err(e) = sink.write(&summary_file, summary.pretty(2).as_bytes()) {
+                            eprintln!("An error occured while writing to {:?} ({})", &summary_file, e);
+                            std::process::exit(1)
+                        }
+                    }
+                    if let Some(error) = stopped {
+                        eprintln!("The export stopped on a segment that could not be read ({})", explain::Chain(&error));
+                        std::process::exit(1)
+                    }
+                },
+                SubExportCommands::Sections(section_args) => {
+                    let content = read_source(&source_file, explain);
+                    let data = open_source(&content, explain);
+                    let mut out = String::new();
+                    let (mut time, mut candidates) = (0., 0);
+                    let mut iterator = check(data.segment_iter(&PayloadIterOptions::with_data()), "Could not read the segments", &content, explain);
+                    for segment in iterator.by_ref() {
+                        let mut offset = 0;
+                        let mut sections = check(segment.section_iter(), "Could not read the segment's sections", &content, explain);
+                        for section in sections.by_ref() {
+                            time = section.time().to_absolute(time);
+                            let address = if segment.is_chunk() {
+                                SectionAddress::chunk(segment.id(), SectionPosition::Offset(offset))
+                            } else {
+                                SectionAddress::keyframe(segment.id(), SectionPosition::Offset(offset))
+                            };
+                            offset += section.len();
+                            if !section_args.types.is_empty() && !section_args.types.contains(&section.data_type()) {
+                                continue;
+                            }
+                            if section_args.sample.keeps(candidates) {
+                                out.push_str(&format!("{}\n", lolrofl::fmt::Event { address, time, section: &section }));
+                            }
+                            candidates += 1;
+                        }
+                        if !sections.is_valid() {
+                            explain::fail("Could not read the segment's sections", &sections.to_error(), Some(&content), explain);
+                        }
+                    }
+                    if !iterator.is_valid() {
+                        explain::fail("Could not read the segments", &iterator.to_error(), Some(&content), explain);
+                    }
+                    let output_file = export_args.directory.join(format!("{}-sections.txt", check(data.payload(), "Could not read the payload header", &content, explain).id()));
+                    if let Err(e) = sink.write(&output_file, out.as_bytes()) {
+                        eprintln!("An error occured while writing to {:?} ({})", &output_file, e);
+                        std::process::exit(1)
+                    }
+                },
+                #[cfg(feature = "npy")]
+                SubExportCommands::Tensor(tensor_args) => {
+                    let content = read_source(&source_file, explain);
+                    let data = open_source(&content, explain);
+                    let match_id = check(data.payload(), "Could not read the payload header", &content, explain).id();
+                    let tensor = check(lolrofl::tensor::activity(&data, &tensor_args.types, tensor_args.every), "Could not read the game's timeline", &content, explain);
+                    let mut npy = Vec::new();
+                    write_output(tensor.write_npy(&mut npy));
+                    for (output_file, out) in [
+                        (export_args.directory.join(format!("{}-tensor.npy", match_id)), npy),
+                        (export_args.directory.join(format!("{}-tensor.json", match_id)), tensor.manifest().into_bytes()),
+                    ] {
+                        if let Err(e) = sink.write(&output_file, &out) {
+                            eprintln!("An error occured while writing to {:?} ({})", &output_file, e);
+                            std::process::exit(1)
+                        }
+                    }
+                },
+            }
+        },
+        CliCommands::Events(events_args) => {
+            let content = read_source(&source_file, explain);
+            let data = open_source(&content, explain);
+            let mut filter = lolrofl::filter::EventFilter::new();
+            if let Some(expression) = events_args.filter.clone() {
+                filter = filter.expression(expression);
+            }
+            if let Some(entity) = events_args.pov {
+                filter = filter.participant(entity);
+            }
+            let chapter = events_args.chapter.map(|phase| {
+                let rules = lolrofl::analysis::ChapterRules::new(&events_args.event_types);
+                let chapters = check(lolrofl::analysis::chapters(&data, &rules), "Could not read the game's timeline", &content, explain);
+                chapters.into_iter().find(|c| c.phase() == phase).unwrap_or_else(|| {
+                    eprintln!("The game has no {} phase", phase);
+                    std::process::exit(1);
+                })
+            });
+            let mut time = 0.;
+            let mut iterator = check(data.segment_iter(&PayloadIterOptions::with_data()), "Could not read the segments", &content, explain);
+            for segment in iterator.by_ref() {
+                let mut offset = 0;
+                let mut sections = check(segment.section_iter(), "Could not read the segment's sections", &content, explain);
+                for section in sections.by_ref() {
+                    time = section.time().to_absolute(time);
+                    let address = if segment.is_chunk() {
+                        SectionAddress::chunk(segment.id(), SectionPosition::Offset(offset))
+                    } else {
+                        SectionAddress::keyframe(segment.id(), SectionPosition::Offset(offset))
+                    };
+                    offset += section.len();
+                    let event = lolrofl::fmt::Event { address, time, section: &section };
+                    if filter.matches(&event) && chapter.is_none_or(|c| c.contains(time)) {
+                        println!("{}", json::stringify(json::object!{
+                            address: address.to_string(),
+                            time: time,
+                            type: section.data_type(),
+                            params: section.params(),
+                            len: section.data_len(),
+                        }));
+                    }
+                }
+                if !sections.is_valid() {
+                    explain::fail("Could not read the segment's sections", &sections.to_error(), Some(&content), explain);
+                }
+            }
+            if !iterator.is_valid() {
+                explain::fail("Could not read the segments", &iterator.to_error(), Some(&content), explain);
+            }
+        },
+        CliCommands::Timeline => {
+            let content = read_source(&source_file, explain);
+            let data = open_source(&content, explain);
+            let timeline = check(lolrofl::timeline::events(&data), "Could not read the game's timeline", &content, explain);
+            for event in timeline.events() {
+                println!("{}", event);
+            }
+            for (address, error) in timeline.malformed() {
+                eprintln!("Could not decode the section {}: {}", address, explain::Chain(error));
+            }
+        },
+        CliCommands::Align(align_args) => {
+            let content = read_source(&source_file, explain);
+            let data = open_source(&content, explain);
+            let other_content = read_source(&align_args.other, explain);
+            let other = open_source(&other_content, explain);
+            let alignment = check(lolrofl::analysis::align(&data, &other), "Could not compare the files", &content, explain);
+            if !alignment.is_same_game() {
+                println!("The files hold different games ({} and {})", alignment.match_ids().0, alignment.match_ids().1);
+            }
+            println!("{} matching chunks, {} differences", alignment.matched_chunks(), alignment.divergences().len());
+            for divergence in alignment.divergences() {
+                println!("{}", divergence);
+            }
+            if !alignment.divergences().is_empty() {
+                std::process::exit(1);
+            }
+        },
+        CliCommands::Minimize(minimize_args) => {
+            let content = read_source(&source_file, explain);
+            match lolrofl::minimize::diagnose(&content[..]) {
+                None => println!("No parsing failure was found"),
+                Some(failure) => {
+                    println!("Failure: {}", failure);
+                    let minimized = lolrofl::minimize::minimize(&content[..]).unwrap();
+                    if let Err(e) = std::fs::write(&minimize_args.output, &minimized) {
+                        eprintln!("An error occured while writing to {:?} ({})", &minimize_args.output, e);
+                        std::process::exit(1)
+                    }
+                    println!("Wrote a {} bytes sample (from {} bytes) to {:?}", minimized.len(), content.len(), &minimize_args.output);
+                },
+            }
+        },
+        CliCommands::Normalize(normalize_args) => {
+            let content = read_source(&source_file, explain);
+            let normalized = Rofl::from_slice(&content[..])
+                .and_then(|data| lolrofl::write::RoflBuilder::from_rofl(&data))
+                .map(|mut builder| builder.normalize_order().build());
+            match normalized {
+                Ok(normalized) => if let Err(e) = std::fs::write(&normalize_args.output, &normalized) {
+                    eprintln!("An error occured while writing to {:?} ({})", &normalize_args.output, e);
+                    std::process::exit(1)
+                },
+                Err(e) => {
+                    eprintln!("Could not read the file's segments ({})", explain::Chain(&e));
+                    std::process::exit(1)
+                },
+            }
+        },
+        CliCommands::Repair(repair_args) => {
+            let content = read_source(&source_file, explain);
+            let (repaired, report) = check(lolrofl::repair::repair(&content[..]), "Could not repair the file", &content, explain).into_parts();
+            if report.declared_len() != report.actual_len() {
+                println!("Rebuilt the file's length (declared {} bytes, actual {} bytes)", report.declared_len(), report.actual_len());
+            }
+            if report.is_metadata_replaced() {
+                println!("Replaced the unreadable metadata with an empty JSON object");
+            }
+            if let Some(e) = report.segment_headers() {
+                println!("Lost the segments whose header could not be read ({})", explain::Chain(e));
+            }
+            for dropped in report.dropped() {
+                println!("Dropped {}", dropped);
+            }
+            if !lolrofl::capabilities().payload {
+                println!("Segment data was not checked as the tool was built without the payload feature");
+            }
+            if let Err(e) = std::fs::write(&repair_args.output, &repaired) {
+                eprintln!("An error occured while writing to {:?} ({})", &repair_args.output, e);
+                std::process::exit(1)
+            }
+            println!("Wrote {} segments ({} bytes) to {:?}", report.kept(), repaired.len(), &repair_args.output);
+        },
+        CliCommands::Merge(merge_args) => {
+            let content = read_source(&source_file, explain);
+            let others: Vec<Vec<u8>> = merge_args.parts.iter().map(|path| read_source(path, explain)).collect();
+            let mut parts = vec![open_source(&content, explain)];
+            parts.extend(others.iter().map(|other| open_source(other, explain)));
+            let builder = check(lolrofl::write::RoflBuilder::merge(&parts), "Could not merge the files", &content, explain);
+            let merged = builder.build();
+            if !lolrofl::capabilities().payload {
+                println!("Segment data was not checked as the tool was built without the payload feature");
+            }
+            if let Err(e) = std::fs::write(&merge_args.output, &merged) {
+                eprintln!("An error occured while writing to {:?} ({})", &merge_args.output, e);
+                std::process::exit(1)
+            }
+            println!("Wrote {} segments ({} bytes) to {:?}", builder.segments().len(), merged.len(), &merge_args.output);
+        },
+        CliCommands::List(list_args) => {
+            let mut scan_cache = match cache::ScanCache::default_path() {
+                Some(path) if !list_args.no_cache => cache::ScanCache::load(path, list_args.refresh),
+                _ => cache::ScanCache::disabled(),
+            };
+            let files = rofl_files(&source_file);
+            println!("{}", colors.paint(format!("{:<12} {:<10} {:>10} {:<16} File", "ID", "Date", "Duration", "Version"), Style::Title));
+            for file in files {
+                match scan_cache.scan(&file) {
+                    Some(entry) if list_args.min_version.is_some_and(|min| entry.version.parse().map_or(true, |v: lolrofl::model::GameVersion| v < min)) => {},
+                    Some(entry) => println!(
+                        "{:<12} {:<10} {:>10} {:<16} {}",
+                        entry.id, template::format_date(entry.date()), format_game_time(entry.duration as f32 / 1000.), entry.version, file.display(),
+                    ),
+                    None => eprintln!("Could not read {}", file.display()),
+                }
+            }
+            if let Err(e) = scan_cache.save() {
+                eprintln!("Could not save the scan cache ({})", e);
+            }
+        },
+        CliCommands::Rename(rename_args) => {
+            let needs_player = template::placeholders(&rename_args.template)
+                .unwrap_or_else(|e| {
+                    eprintln!("{}", e);
+                    std::process::exit(1)
+                })
+                .iter().any(|p| ["champion", "result"].contains(p));
+            if needs_player && rename_args.player.is_none() {
+                eprintln!("The {{champion}} and {{result}} placeholders require a --player");
+                std::process::exit(1);
+            }
+            let files = rofl_files(&source_file);
+            let mut failed = false;
+            for file in files {
+                let new_name = std::fs::read(&file).map_err(|e| e.to_string())
+                    .and_then(|content| {
+                        let data = Rofl::from_slice(&content[..]).map_err(|e| explain::Chain(&e).to_string())?;
+                        let values = file_template_values(&file, &data, rename_args.player.as_deref())?;
+                        template::render(&rename_args.template, &values)
+                    });
+                let target = match new_name {
+                    Ok(name) => file.with_file_name(name),
+                    Err(e) => {
+                        eprintln!("Could not rename {}: {}", file.display(), e);
+                        failed = true;
+                        continue;
+                    },
+                };
+                if target == file {
+                    continue;
+                }
+                if target.exists() {
+                    eprintln!("Could not rename {}: {} already exists", file.display(), target.display());
+                    failed = true;
+                    continue;
+                }
+                println!("{} -> {}", file.display(), target.display());
+                if !rename_args.dry_run {
+                    if let Err(e) = std::fs::rename(&file, &target) {
+                        eprintln!("Could not rename {}: {}", file.display(), e);
+                        failed = true;
+                    }
+                }
+            }
+            if failed {
+                std::process::exit(1);
+            }
+        },
+        CliCommands::Dedupe(dedupe_args) => {
+            let mut groups = std::collections::BTreeMap::<lolrofl::fingerprint::Fingerprint, Vec<std::path::PathBuf>>::new();
+            for file in rofl_files(&source_file) {
+                let fingerprint = std::fs::read(&file).map_err(lolrofl::Errors::from)
+                    .and_then(|content| Rofl::from_slice(&content[..])?.fingerprint());
+                match fingerprint {
+                    Ok(fingerprint) => groups.entry(fingerprint).or_default().push(file),
+                    Err(e) => eprintln!("Could not read {}: {}", file.display(), explain::Chain(&e)),
+                }
+            }
+            let mut failed = false;
+            for (fingerprint, files) in groups.iter().filter(|(_, files)| files.len() > 1) {
+                println!("{}:", fingerprint);
+                println!("  {} (kept)", files[0].display());
+                for duplicate in &files[1..] {
+                    println!("  {}", duplicate.display());
+                    if !dedupe_args.delete && !dedupe_args.link {
+                        continue;
+                    }
+                    // Files of the same game may still differ, such as partial copies
+                    match same_content(&files[0], duplicate) {
+                        Ok(true) => {},
+                        Ok(false) => {
+                            eprintln!("Skipped {}: its content differs from {}", duplicate.display(), files[0].display());
+                            continue;
+                        },
+                        Err(e) => {
+                            eprintln!("Could not deduplicate {}: {}", duplicate.display(), e);
+                            failed = true;
+                            continue;
+                        },
+                    }
+                    let result = if dedupe_args.delete {
+                        std::fs::remove_file(duplicate)
+                    } else {
+                        replace_with_link(&files[0], duplicate)
+                    };
+                    if let Err(e) = result {
+                        eprintln!("Could not deduplicate {}: {}", duplicate.display(), e);
+                        failed = true;
+                    }
+                }
+            }
+            if failed {
+                std::process::exit(1);
+            }
+        },
+        CliCommands::Stats(stats_args) => {
+            let files = rofl_files(&source_file);
+            let jobs = stats_args.jobs.unwrap_or_else(|| std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1));
+            let stats = stats::collect(&files, jobs);
+            println!("{:<16}{} ({} failed)", "Files:", stats.files, stats.failures.len());
+            println!("{:<16}{} bytes", "Size:", stats.bytes);
+            println!("{:<16}{}", "Game time:", format_game_time(stats.duration as f32 / 1000.));
+            if stats.files > 0 {
+                println!("{:<16}{}", "Average game:", format_game_time(stats.duration as f32 / 1000. / stats.files as f32));
+            }
+            println!("{:<16}{} chunks, {} keyframes", "Segments:", stats.chunks, stats.keyframes);
+            if lolrofl::capabilities().payload {
+                println!("{:<16}{}", "Sections:", stats.sections);
+            } else {
+                println!("{:<16}unknown (this build does not decrypt segments)", "Sections:");
+            }
+            println!("Versions:");
+            let mut versions: Vec<_> = stats.versions.iter().collect();
+            versions.sort_by_key(|(version, _)| version.parse::<lolrofl::model::GameVersion>().ok());
+            for (version, count) in versions {
+                println!("  {:<20}{}", version, count);
+            }
+            if args.verbose {
+                for (file, error) in &stats.failures {
+                    eprintln!("Could not process {}: {}", file.display(), error);
+                }
+            }
+            #[cfg(feature = "metrics")]
+            if let Some(path) = &stats_args.metrics {
+                if let Err(e) = std::fs::write(path, stats.metrics.render()) {
+                    eprintln!("Could not write metrics to {}: {}", path.display(), e);
+                    std::process::exit(1);
+                }
+            }
+        },
+        CliCommands::Selftest | CliCommands::Schema(_) => unreachable!(),
+        CliCommands::Privacy => {
+            let content = read_source(&source_file, explain);
+            let data = open_source(&content, explain);
+            match lolrofl::privacy::scan(&data) {
+                Ok(findings) => {
+                    println!("Location\tField\tValue");
+                    for finding in &findings {
+                        println!("{}", finding);
+                    }
+                    if args.verbose {
+                        eprintln!("{} identifying values found, chat messages are only found when they hold a player's name", findings.len());
+                    }
+                },
+                Err(e) => {
+                    eprintln!("Could not scan the file: {}", explain::Chain(&e));
+                    std::process::exit(1);
+                },
+            }
+        },
+        CliCommands::Play(play_args) => {
+            let content = read_source(&source_file, explain);
+            let data = open_source(&content, explain);
+            if let Some(version) = data.metadata().ok().and_then(|m| Metadata::from_json(m).ok()).and_then(|m| m.game_version().map(str::to_string)) {
+                println!("The replay was recorded with version {}, the client can only play replays of its own version", version);
+            }
+            let result = play::locate(play_args.install.as_deref())
+                .and_then(|client| play::launch(&client, &source_file).map_err(|e| format!("Could not start {}: {}", client.display(), e)));
+            if let Err(e) = result {
+                eprintln!("{}", e);
+                std::process::exit(1);
+            }
+        },
+        #[cfg(feature = "http")]
+        CliCommands::Fetch(_) => unreachable!(),
+        #[cfg(feature = "http")]
+        CliCommands::Notify(notify_args) => {
+            let content = read_source(&source_file, explain);
+            let data = open_source(&content, explain);
+            let result = lolrofl::model::Summary::from_rofl(&data)
+                .and_then(|summary| lolrofl::notify::discord(&notify_args.webhook, &summary));
+            if let Err(e) = result {
+                eprintln!("Could not post the game's summary: {}", explain::Chain(&e));
+                std::process::exit(1);
+            }
+        },
+        CliCommands::Analyze(analyze_args) => {
+            let content = read_source(&source_file, explain);
+            let data = open_source(&content, explain);
+            if let Some(address) = analyze_args.at {
+                let segment = data.segment_iter(&PayloadIterOptions::new()).ok().and_then(|mut i| i.find(|s| address.is_in(s)));
+                let mut segment = match segment {
+                    Some(segment) => segment,
+                    None => {
+                        eprintln!("No segment matches {}", address);
+                        std::process::exit(1);
+                    },
+                };
+                if let Err(e) = data.load_segment(&mut segment) {
+                    eprintln!("Could not decrypt the segment of {}: {}", address, explain::Chain(&e));
+                    std::process::exit(1);
+                }
+                let mut sections = Vec::new();
+                let mut iterator = check(segment.section_iter(), "Could not read the segment's sections", &content, explain);
+                let mut offset = 0;
+                for (index, section) in iterator.by_ref().enumerate() {
+                    let section_offset = offset;
+                    offset += section.len();
+                    sections.push((index, section_offset, section));
+                }
+                let target = match sections.iter().position(|(index, offset, _)| address.matches(*index, *offset)) {
+                    Some(target) => target,
+                    None => {
+                        eprintln!("No section matches {}", address);
+                        if !iterator.is_valid() {
+                            eprintln!("Sections could not be read past offset {:#X}: {}", iterator.internal_index(), iterator.error());
+                        }
+                        std::process::exit(1);
+                    },
+                };
+                let section_address = |index: usize| {
+                    let position = SectionPosition::Offset(sections[index].1);
+                    if segment.is_chunk() { SectionAddress::chunk(segment.id(), position) } else { SectionAddress::keyframe(segment.id(), position) }
+                };
+                let (index, _, section) = &sections[target];
+                println!("{} (index {})", section_address(target), index);
+                println!("{:#}", section);
+                println!("  {:<12}{}", "Raw bytes:", section.bytes().iter().map(|b| format!("{:02x}", b)).collect::<Vec<_>>().join(" "));
+                let registry = lolrofl::model::section::decode::Registry::default();
+                match (registry.get(section.data_type()), registry.decode(section)) {
+                    (Some(decoder), Some(Ok(decoded))) => println!("  {:<12}{}: {}", "Decoder:", decoder.name(), decoded),
+                    (Some(decoder), Some(Err(e))) => println!("  {:<12}{}: could not decode the section ({})", "Decoder:", decoder.name(), explain::Chain(&e)),
+                    _ => println!("  {:<12}none", "Decoder:"),
+                }
+                println!("Neighbors:");
+                let first = target.saturating_sub(2);
+                for (neighbor, (_, _, section)) in sections.iter().enumerate().skip(first).take(target + 3 - first) {
+                    println!(
+                        "{} [{}] {}",
+                        if neighbor == target {">"} else {" "}, section_address(neighbor), lolrofl::fmt::Compact(section),
+                    );
+                }
+                return;
+            }
+            if let AnalyzeCommandMode::Layout = analyze_args.mode {
+                let layout = check(data.layout(), "Could not read the file's layout", &content, explain);
+                let total = layout.iter().map(|e| e.end()).max().unwrap_or(0).max(1);
+                let column_len = total.div_ceil(LAYOUT_MAP_WIDTH);
+                // Each column is drawn with the area that holds its first byte
+                let map: String = (0..total.div_ceil(column_len))
+                    .map(|column| layout.iter().rev().find(|e| e.contains(column * column_len)).map_or('.', |e| layout_symbol(e.area())))
+                    .collect();
+                println!("|{}| {} bytes, {} bytes per column", map, total, column_len);
+                if let Some(offset) = analyze_args.offset.filter(|o| *o < total) {
+                    println!(" {:>width$}", "^", width = offset / column_len + 1);
+                }
+                println!("H: header, M: metadata, P: payload header, D: segment headers, c: chunk, k: keyframe, .: unused");
+                println!();
+                println!("{}", colors.paint(format!("{:>10} {:>10} {:>10} {:>7} Area", "Start", "End", "Length", "Share"), Style::Title));
+                for extent in layout.iter() {
+                    println!(
+                        "{:>#10x} {:>#10x} {:>10} {:>6.1}% {}{}",
+                        extent.offset(), extent.end(), extent.len(), extent.len() as f64 * 100. / total as f64, extent.area(),
+                        if analyze_args.offset.is_some_and(|o| extent.contains(o)) {" <"} else {""},
+                    );
+                }
+                if content.len() < total {
+                    println!("The file ends at {:#x}, {} bytes are missing", content.len(), total - content.len());
+                }
+                if let Some(offset) = analyze_args.offset {
+                    let areas: Vec<String> = layout.iter().filter(|e| e.contains(offset)).map(|e| e.area().to_string()).collect();
+                    if areas.is_empty() {
+                        println!("Offset {:#x} is past the end of the file", offset);
+                    } else {
+                        println!("Offset {:#x} is in: {}", offset, areas.join(", "));
+                    }
+                }
+                return;
+            }
+            if let AnalyzeCommandMode::Apm = analyze_args.mode {
+                if analyze_args.action_types.is_empty() {
+                    eprintln!("At least one --action-type is required in apm mode");
+                    std::process::exit(1);
+                }
+                let pauses = match lolrofl::analysis::detect_pauses(&data, &analyze_args.pause_types, &analyze_args.resume_types) {
+                    Ok(pauses) => pauses,
+                    Err(e) => {
+                        eprintln!("Could not read the game's timeline: {}", explain::Chain(&e));
+                        std::process::exit(1);
+                    },
+                };
+                if args.verbose {
+                    for pause in pauses.iter() {
+                        println!("Paused from {} to {}", format_game_time(pause.start()), format_game_time(pause.end()));
+                    }
+                }
+                let rates = match lolrofl::analysis::apm_with_pauses(&data, &pauses, &analyze_args.action_types, analyze_args.window) {
+                    Ok(rates) => rates,
+                    Err(e) => {
+                        eprintln!("Could not read the game's timeline: {}", explain::Chain(&e));
+                        std::process::exit(1);
+                    },
+                };
+                for rate in rates {
+                    print!("Entity {}: {} actions, {:.1} APM", rate.entity(), rate.total(), rate.apm());
+                    if args.verbose {
+                        for (start, apm) in rate.series() {
+                            print!(" [{} {:.1}]", format_game_time(start), apm);
+                        }
+                    }
+                    println!();
+                }
+                return;
+            }
+            if let AnalyzeCommandMode::Chapters = analyze_args.mode {
+                if analyze_args.event_types.is_empty() {
+                    eprintln!("At least one --event-type is required in chapters mode");
+                    std::process::exit(1);
+                }
+                let rules = lolrofl::analysis::ChapterRules::new(&analyze_args.event_types);
+                let chapters = check(lolrofl::analysis::chapters(&data, &rules), "Could not read the game's timeline", &content, explain);
+                for chapter in chapters {
+                    println!(
+                        "{:<10} {} to {} ({} events)",
+                        chapter.phase(), format_game_time(chapter.start()), format_game_time(chapter.end()), chapter.events(),
+                    );
+                }
+                return;
+            }
+            if let AnalyzeCommandMode::Coverage = analyze_args.mode {
+                let coverage = match lolrofl::analysis::coverage(&data) {
+                    Ok(coverage) => coverage,
+                    Err(e) => {
+                        eprintln!("Could not read the game's sections: {}", explain::Chain(&e));
+                        std::process::exit(1);
+                    },
+                };
+                let percent = |part: usize, total: usize| if total == 0 { 0. } else { part as f64 * 100. / total as f64 };
+                println!(
+                    "Decoded: {}/{} sections ({:.1}%), {}/{} bytes ({:.1}%)",
+                    coverage.decoded_sections(), coverage.sections(), percent(coverage.decoded_sections(), coverage.sections()),
+                    coverage.decoded_bytes(), coverage.bytes(), coverage.byte_ratio() * 100.,
+                );
+                println!("{:>8} {:>10} {:>12} {:>8} Decoder", "Type", "Sections", "Bytes", "Bytes%");
+                for (data_type, stats) in coverage.types() {
+                    println!(
+                        "{:>8} {:>10} {:>12} {:>7.1}% {}",
+                        data_type, stats.sections(), stats.bytes(), percent(stats.bytes(), coverage.bytes()),
+                        if stats.is_decoded() {"typed"} else {"generic"},
+                    );
+                }
+                return;
+            }
+            if let AnalyzeCommandMode::Metrics = analyze_args.mode {
+                let mut rate = lolrofl::analysis::SectionRate::new();
+                let mut count = analyze_args.typed.map(|t| lolrofl::analysis::TypeCount::new(t as u32));
+                let mut metrics: Vec<&mut dyn lolrofl::analysis::Metric> = vec![&mut rate];
+                if let Some(count) = count.as_mut() {
+                    metrics.push(count);
+                }
+                if let Err(e) = lolrofl::analysis::evaluate(&data, &mut metrics[..]) {
+                    eprintln!("Could not read the game's timeline: {}", explain::Chain(&e));
+                    std::process::exit(1);
+                }
+                for metric in metrics {
+                    println!("{}: {}", metric.name(), metric.value());
+                }
+                if args.verbose {
+                    use lolrofl::analysis::Metric;
+                    let step = analyze_args.step.unwrap_or(analyze_args.window);
+                    let rate = lolrofl::analysis::SectionRate::new();
+                    let mut series = vec![(rate.name(), lolrofl::analysis::rolling(&data, &rate, analyze_args.window, step))];
+                    if let Some(t) = analyze_args.typed {
+                        let count = lolrofl::analysis::TypeCount::new(t as u32);
+                        series.push((count.name(), lolrofl::analysis::rolling(&data, &count, analyze_args.window, step)));
+                    }
+                    for (name, values) in series {
+                        let values = check(values, "Could not read the game's timeline", &content, explain);
+                        print!("{}:", name);
+                        for (start, value) in values {
+                            print!(" [{} {:.1}]", format_game_time(start), value);
+                        }
+                        println!();
+                    }
+                }
+                return;
+            }
+            // The labels of the verify mode's checks
+            let success = colors.paint("SUCCESS", Style::Success);
+            let failure = colors.paint("FAIL", Style::Failure);
+            let warned = colors.paint("WARNING", Style::Warning);
+            // The file's integrity is reported before the segments are parsed, which fails on corrupted files
+            if let AnalyzeCommandMode::Verify = analyze_args.mode {
+                let report = data.verify();
+                if report.is_length_valid() {
+                    println!("{} File length", success);
+                } else {
+                    println!("{} File length (declared {}, actual {})", failure, report.declared_len(), report.actual_len());
+                }
+                for (name, result) in [
+                    ("Metadata", report.metadata()),
+                    ("Payload header", report.payload_header()),
+                    ("Segment headers", report.segment_headers()),
+                ] {
+                    match result {
+                        Ok(()) => println!("{} {}", success, name),
+                        Err(e) => println!("{} {} ({})", failure, name, explain::Chain(e)),
+                    }
+                }
+                for segment in report.segments() {
+                    if !segment.in_bounds() {
+                        println!("{} {} bounds (ends at {}, after the end of the file)", failure, segment, segment.offset() + segment.len());
+                    }
+                    match segment.data() {
+                        Some(Ok(())) => println!("{} {} data", success, segment),
+                        Some(Err(e)) => println!("{} {} data ({})", failure, segment, explain::Chain(e)),
+                        None => {},
+                    }
+                }
+            }
+            let mut time: f32 = 0.;
+            let (mut decoded_size, mut largest_segment) = (0, 0);
+            let mut sink = output::OutputSink::stdout(format);
+            // Detail mode pages through sections, bytes mode through segments
+            let mut page = lolrofl::iter::Pagination::new(analyze_args.skip, analyze_args.limit);
+            for segment in check(data.segment_iter(&PayloadIterOptions::with_data()), "Could not read the segments", &content, explain) {
+                if page.is_done() {
+                    break;
+                }
+                decoded_size += segment.approx_size();
+                largest_segment = std::cmp::max(largest_segment, segment.approx_size());
+                let is_analyzed = 
+                    ( // No filter is applied
+                        analyze_args.id.is_empty() && analyze_args.only.is_none()
+                    ) || ( // A filter is applied and the segment is a chunk
+                        segment.is_chunk()
+                        && (analyze_args.id.contains(&segment.id()) || analyze_args.id.is_empty())
+                        && analyze_args.only != Some(SegmentType::Keyframe)
+                    ) || ( // A filter is applied and the segment is a keyframe
+                        segment.is_keyframe()
+                        && (analyze_args.id.contains(&segment.id()) || analyze_args.id.is_empty())
+                        && analyze_args.only != Some(SegmentType::Chunk)
+                    );
+                // Skip the segments that are entirely out of the selected time range
+                if let Ok((start, end)) = segment.time_bounds() {
+                    let out_of_range = analyze_args.start_time.is_some_and(|t| end < t) || analyze_args.end_time.is_some_and(|t| start > t);
+                    if out_of_range {
+                        time = end;
+                        continue;
+                    }
+                }
+                if is_analyzed { // TODO: cleanup this code
+                    let mut iterator = check(segment.section_iter(), "Could not read the segment's sections", &content, explain);
+                    let mut last_segment: Option<GenericSection> = None;
+                    let mut inventory_count = std::collections::HashMap::<usize, usize>::new();
+                    let mut total_subdata = 0;
+                    let mut next_offset = 0;
+                    if analyze_args.human && matches!(analyze_args.mode, AnalyzeCommandMode::Detail) {
+                        write_output(sink.text(&format!("{} {}:", if segment.is_chunk() {"Chunk"} else {"Keyframe"}, segment.id())));
+                    }
+                    // Sections are written as soon as they are read so that large segments are not held in memory
+                    for g in iterator.by_ref() {
+                        time = g.time().to_absolute(time);
+                        let offset = next_offset;
+                        next_offset += g.len();
+                        let time_condition =
+                            (analyze_args.start_time.is_some() && time < *analyze_args.start_time.as_ref().unwrap())
+                            || (analyze_args.end_time.is_some() && time > *analyze_args.end_time.as_ref().unwrap());
+                        if time_condition {continue;}
+                        let address = if segment.is_chunk() {
+                            SectionAddress::chunk(segment.id(), SectionPosition::Offset(offset))
+                        } else {
+                            SectionAddress::keyframe(segment.id(), SectionPosition::Offset(offset))
+                        };
+                        let inventory_key = match analyze_args.typed {
+                            None => Some(g.data_type() as usize),
+                            Some(typed) if typed == g.data_type() as usize => Some(g.data_len()),
+                            Some(_) => None,
+                        };
+                        if let Some(key) = inventory_key {
+                            total_subdata += 1;
+                            *inventory_count.entry(key).or_insert(0) += 1;
+                            if matches!(analyze_args.mode, AnalyzeCommandMode::Detail) && page.admit() {
+                                let text = if analyze_args.human {
+                                    lolrofl::fmt::Event { address, time, section: &g }.to_string()
+                                } else {
+                                    format!("{}{}: ({}, {:?})", if segment.is_chunk() {"C"} else {"K"}, segment.id(), time, g.bytes())
+                                };
+                                write_output(sink.record(&text, &section_fields(address, time, &g)));
+                            }
+                        }
+                        last_segment = Some(g);
+                        if matches!(analyze_args.mode, AnalyzeCommandMode::Detail) && page.is_done() {
+                            break;
+                        }
+                    }
+                    match analyze_args.mode {
+                        AnalyzeCommandMode::Bytes => if page.admit() { write_output(sink.record(
+                            &format!("{} {}: {:?}", if segment.is_chunk() {"Chunk"} else {"Keyframe"}, segment.id(), segment.data()),
+                            &[
+                                ("type", if segment.is_chunk() {"Chunk"} else {"Keyframe"}.into()),
+                                ("id", segment.id().into()),
+                                ("data", hex(segment.data()).into()),
+                            ],
+                        )) },
+                        AnalyzeCommandMode::Detail => {
+                            if !iterator.is_valid() {
+                                eprintln!(
+                                    "BROKE at index {} of {} {}, next bytes: {:?}",
+                                    iterator.internal_index(),
+                                    if segment.is_chunk() {"Chunk"} else {"Keyframe"},
+                                    segment.id(),
+                                    &iterator.internal_slice()[iterator.internal_index()..std::cmp::min(iterator.internal_index()+20, iterator.internal_slice().len())],
+                                );
+                                if analyze_args.human && args.verbose {
+                                    write_output(sink.text(&format!(
+                                        "{:?},",
+                                        &iterator.internal_slice()[iterator.internal_index()..iterator.internal_slice().len()],
+                                    )));
+                                }
+                            }
+                        },
+                        AnalyzeCommandMode::Apm | AnalyzeCommandMode::Chapters | AnalyzeCommandMode::Coverage | AnalyzeCommandMode::Layout | AnalyzeCommandMode::Metrics => {},
+                        AnalyzeCommandMode::Stats => {
+                            if !iterator.is_valid() {
+                                eprintln!(
+                                    "BROKE at index {} of {} {} with error {}, next bytes: {:?}",
+                                    iterator.internal_index(),
+                                    if segment.is_chunk() {"Chunk"} else {"Keyframe"},
+                                    segment.id(),
+                                    iterator.error(),
+                                    &iterator.internal_slice()[iterator.internal_index()..std::cmp::min(iterator.internal_index()+20, iterator.internal_slice().len())],
+                                );
+                            }
+                            let stats = lolrofl::fmt::SegmentStats::new(&segment, total_subdata, inventory_count.into_iter().collect());
+                            let mut inventory = json::JsonValue::new_object();
+                            for (key, count) in stats.inventory() {
+                                inventory[key.to_string().as_str()] = (*count).into();
+                            }
+                            let text = if args.verbose { format!("{:#}", stats) } else { stats.to_string() };
+                            let fields = [
+                                ("type", stats.kind().into()),
+                                ("id", stats.id().into()),
+                                ("data_len", stats.data_len().into()),
+                                ("sections", stats.sections().into()),
+                                ("inventory", inventory),
+                            ];
+                            write_output(sink.record(&text, &fields));
+                        }
+                        AnalyzeCommandMode::Verify => {
+                            if args.verbose && !iterator.is_valid() {
+                                eprint!(
+                                    "BROKE at index {} of {} {}",
+                                    iterator.internal_index(),
+                                    if segment.is_chunk() {"Chunk"} else {"Keyframe"},
+                                    segment.id(),
+                                );
+                                if let Some(g) = last_segment {
+                                    eprint!(", last dataset type: {} ({} bytes)",g.kind(), g.len());
+                                }
+                                eprintln!(
+                                    ", next bytes: {:?}",
+                                    &iterator.internal_slice()[iterator.internal_index()..std::cmp::min(iterator.internal_index()+20, iterator.internal_slice().len())],    
+                                );
+                            }
+                            println!(
+                                "{} {} {}",
+                                if iterator.is_valid() {success} else {failure},
+                                if segment.is_chunk() {"Chunk"} else {"Keyframe"},
+                                segment.id(),
+                            )
+                        },
+                    }
+                }
+            }
+            write_output(sink.flush());
+            if args.verbose {
+                eprintln!(
+                    "Memory: {} bytes held by the parser, {} bytes of decoded segments ({} bytes at most at once)",
+                    data.memory_footprint(),
+                    decoded_size,
+                    largest_segment,
+                );
+            }
+            match analyze_args.mode {
+                AnalyzeCommandMode::Bytes => {},
+                AnalyzeCommandMode::Detail => {},
+                AnalyzeCommandMode::Apm => {},
+                AnalyzeCommandMode::Chapters => {},
+                AnalyzeCommandMode::Coverage => {},
+                AnalyzeCommandMode::Layout => {},
+                AnalyzeCommandMode::Metrics => {},
+                AnalyzeCommandMode::Stats => {},
+                AnalyzeCommandMode::Verify => {
+                    match data.keyframe_warnings(KEYFRAME_TIME_TOLERANCE) {
+                        Ok(warnings) => for warning in warnings {
+                            println!("{} {}", warned, warning);
+                        },
+                        Err(e) => println!("{} Keyframe times ({})", failure, explain::Chain(&e)),
+                    }
+                },
+            }
+
+        }
+    }
+}
@@ -0,0 +1,121 @@
+//! Output of records in the format selected with `--format`
+
+use std::io::Write;
+use clap::ArgEnum;
+
+/// The format of the records printed by commands
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum OutputFormat {
+    /// Human-readable text, or Markdown for documents
+    #[clap(alias = "md")]
+    Text,
+    /// One JSON object per record and per line
+    Json,
+    /// Comma-separated values, with a header line
+    Csv,
+}
+
+/// When text output is colored
+#[derive(ArgEnum, Clone, Copy, Debug, PartialEq, Eq)]
+pub enum ColorChoice {
+    /// If the standard output is a terminal and `NO_COLOR` is not set
+    Auto,
+    /// Always
+    Always,
+    /// Never
+    Never,
+}
+
+impl ColorChoice {
+    /// The colors of the text written to the standard output in an output format
+    pub fn colors(self, format: OutputFormat) -> lolrofl::fmt::Colors {
+        use std::io::IsTerminal;
+        lolrofl::fmt::Colors::new(format == OutputFormat::Text && match self {
+            ColorChoice::Auto => std::io::stdout().is_terminal() && std::env::var_os("NO_COLOR").is_none_or(|v| v.is_empty()),
+            ColorChoice::Always => true,
+            ColorChoice::Never => false,
+        })
+    }
+}
+
+/// Writes records, made of named values and of their human-readable text, in an output format
+///
+/// Records are written as soon as they are received so that large outputs are not held in memory
+pub struct OutputSink<W: Write> {
+    /// Where records are written
+    out: W,
+    /// The format records are written in
+    format: OutputFormat,
+    /// The columns of the CSV header, set when the first record is written
+    columns: Option<Vec<&'static str>>,
+}
+
+impl OutputSink<std::io::BufWriter<std::io::Stdout>> {
+    /// Build a sink that writes to the standard output
+    pub fn stdout(format: OutputFormat) -> Self {
+        OutputSink::new(std::io::BufWriter::new(std::io::stdout()), format)
+    }
+}
+
+impl<W: Write> OutputSink<W> {
+    /// Build a sink that writes records in a format
+    pub fn new(out: W, format: OutputFormat) -> Self {
+        OutputSink { out, format, columns: None }
+    }
+    /// Write a line in text format only, such as a table's header
+    pub fn text(&mut self, line: &str) -> std::io::Result<()> {
+        if self.format == OutputFormat::Text {
+            writeln!(self.out, "{}", line)?;
+        }
+        Ok(())
+    }
+    /// Write a record, `text` is only written in text format
+    ///
+    /// In CSV format, the first record's fields are the columns of the header, the fields of
+    /// the following records are matched to columns by name
+    pub fn record(&mut self, text: &dyn std::fmt::Display, fields: &[(&'static str, json::JsonValue)]) -> std::io::Result<()> {
+        match self.format {
+            OutputFormat::Text => writeln!(self.out, "{}", text),
+            OutputFormat::Json => {
+                let mut object = json::JsonValue::new_object();
+                for (name, value) in fields {
+                    object[*name] = value.clone();
+                }
+                writeln!(self.out, "{}", object.dump())
+            },
+            OutputFormat::Csv => {
+                if self.columns.is_none() {
+                    let columns: Vec<&'static str> = fields.iter().map(|(name, _)| *name).collect();
+                    writeln!(self.out, "{}", columns.join(","))?;
+                    self.columns = Some(columns);
+                }
+                let row: Vec<String> = self.columns.iter().flatten()
+                    .map(|column| fields.iter().find(|(name, _)| name == column).map_or(String::new(), |(_, value)| csv_value(value)))
+                    .collect();
+                writeln!(self.out, "{}", row.join(","))
+            },
+        }
+    }
+    /// Flush the written records
+    pub fn flush(&mut self) -> std::io::Result<()> {
+        self.out.flush()
+    }
+    /// Get the destination records were written to
+    pub fn into_inner(self) -> W {
+        self.out
+    }
+}
+
+/// Format a value as a CSV field, quoted if needed
+fn csv_value(value: &json::JsonValue) -> String {
+    let raw = match value {
+        json::JsonValue::Null => String::new(),
+        json::JsonValue::Short(_) | json::JsonValue::String(_) => value.as_str().unwrap_or_default().to_string(),
+        _ => value.dump(),
+    };
+    if raw.contains([',', '"', '\n', '\r']) {
+        format!("\"{}\"", raw.replace('"', "\"\""))
+    } else {
+        raw
+    }
+}
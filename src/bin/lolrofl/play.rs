@@ -0,0 +1,63 @@
+//! Launch the local League of Legends client in replay mode
+
+use std::path::{Path, PathBuf};
+use std::process::{Child, Command};
+
+/// The game client's executable, relative to the install directory
+#[cfg(target_os = "windows")]
+const CLIENT: &str = "Game/League of Legends.exe";
+#[cfg(target_os = "macos")]
+const CLIENT: &str = "Contents/LoL/Game/LeagueofLegends.app/Contents/MacOS/LeagueofLegends";
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+const CLIENT: &str = "Game/League of Legends.exe";
+
+/// The directories League of Legends is installed to by default
+#[cfg(target_os = "windows")]
+const DEFAULT_INSTALLS: &[&str] = &["C:/Riot Games/League of Legends", "D:/Riot Games/League of Legends"];
+#[cfg(target_os = "macos")]
+const DEFAULT_INSTALLS: &[&str] = &["/Applications/League of Legends.app"];
+#[cfg(not(any(target_os = "windows", target_os = "macos")))]
+const DEFAULT_INSTALLS: &[&str] = &[];
+
+/// Find the game client's executable
+///
+/// The install directory is, in order: `install`, the `LOLROFL_LEAGUE_DIR` environment variable,
+/// then the platform's default install directories
+pub fn locate(install: Option<&Path>) -> Result<PathBuf, String> {
+    let candidates: Vec<PathBuf> = match install {
+        Some(install) => vec![install.to_path_buf()],
+        None => std::env::var_os("LOLROFL_LEAGUE_DIR").map(PathBuf::from).into_iter()
+            .chain(DEFAULT_INSTALLS.iter().map(PathBuf::from))
+            .collect(),
+    };
+    if candidates.is_empty() {
+        return Err("No default install directory on this platform, use --install or LOLROFL_LEAGUE_DIR".to_string());
+    }
+    candidates.iter()
+        .map(|dir| dir.join(CLIENT))
+        .find(|client| client.is_file())
+        .ok_or_else(|| format!(
+            "Could not find the game client in {}",
+            candidates.iter().map(|d| d.display().to_string()).collect::<Vec<String>>().join(", "),
+        ))
+}
+
+/// Start the game client in replay mode on a file
+///
+/// The client only plays replays recorded with its own version
+pub fn launch(client: &Path, file: &Path) -> std::io::Result<Child> {
+    let file = file.canonicalize()?;
+    let mut command = if cfg!(any(target_os = "windows", target_os = "macos")) {
+        Command::new(client)
+    } else {
+        // Other platforms run the Windows client through Wine
+        let mut wine = Command::new("wine");
+        wine.arg(client);
+        wine
+    };
+    // The client resolves its data files relative to its working directory
+    if let Some(dir) = client.parent() {
+        command.current_dir(dir);
+    }
+    command.arg(file).spawn()
+}
@@ -0,0 +1,209 @@
+//! Section type statistics compared between two sets of files to detect format changes, and
+//! the reference of the section types known to the lib
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use lolrofl::{Errors, Rofl, iter::PayloadIterOptions, model::section::{SectionCore, decode::Registry, kind_info}};
+
+/// The lengths of a section type's data across a set of files
+#[derive(Clone, Copy, Debug)]
+pub struct TypeProfile {
+    /// Number of sections of the type
+    pub sections: u64,
+    /// Number of files the type was found in
+    pub files: usize,
+    /// Smallest data length
+    pub min_len: usize,
+    /// Largest data length
+    pub max_len: usize,
+    /// Total data length
+    pub total_len: u64,
+}
+
+impl TypeProfile {
+    /// The average data length of the type's sections
+    pub fn avg_len(&self) -> f64 { self.total_len as f64 / self.sections as f64 }
+    /// Whether all sections of the type have the same data length
+    pub fn is_fixed(&self) -> bool { self.min_len == self.max_len }
+}
+
+impl std::fmt::Display for TypeProfile {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        if self.is_fixed() {
+            write!(f, "{} bytes", self.min_len)
+        } else {
+            write!(f, "{}-{} bytes, {:.1} on average", self.min_len, self.max_len, self.avg_len())
+        }
+    }
+}
+
+/// The section type profiles of a set of files
+#[derive(Debug, Default)]
+pub struct Schema {
+    /// Number of successfully processed files
+    pub files: usize,
+    /// Files that could not be processed and why
+    pub failures: Vec<(PathBuf, String)>,
+    /// The profile of each section type found in the files
+    pub types: BTreeMap<u32, TypeProfile>,
+}
+
+impl Schema {
+    /// Profile the section types of the files, keyframes included
+    pub fn collect(files: &[PathBuf]) -> Schema {
+        let mut schema = Schema::default();
+        for file in files {
+            if let Err(e) = schema.add_file(file) {
+                schema.failures.push((file.clone(), crate::explain::Chain(&e).to_string()));
+            }
+        }
+        schema
+    }
+    /// Add the section types of a file
+    fn add_file(&mut self, path: &Path) -> Result<(), Errors> {
+        let content = std::fs::read(path)?;
+        let data = Rofl::from_slice(&content[..])?;
+        let mut types = BTreeMap::<u32, TypeProfile>::new();
+        let mut iterator = data.segment_iter(&PayloadIterOptions::with_data())?;
+        for segment in iterator.by_ref() {
+            let mut sections = segment.section_iter()?;
+            for section in sections.by_ref() {
+                let len = section.data_len();
+                let profile = types.entry(section.data_type())
+                    .or_insert(TypeProfile { sections: 0, files: 1, min_len: len, max_len: len, total_len: 0 });
+                profile.sections += 1;
+                profile.min_len = profile.min_len.min(len);
+                profile.max_len = profile.max_len.max(len);
+                profile.total_len += len as u64;
+            }
+            if !sections.is_valid() {
+                return Err(sections.to_error());
+            }
+        }
+        if !iterator.is_valid() {
+            return Err(iterator.to_error());
+        }
+        self.files += 1;
+        for (data_type, profile) in types {
+            self.types.entry(data_type)
+                .and_modify(|p| {
+                    p.sections += profile.sections;
+                    p.files += 1;
+                    p.min_len = p.min_len.min(profile.min_len);
+                    p.max_len = p.max_len.max(profile.max_len);
+                    p.total_len += profile.total_len;
+                })
+                .or_insert(profile);
+        }
+        Ok(())
+    }
+}
+
+/// A change of a section type between two sets of files
+#[derive(Clone, Copy, Debug)]
+pub enum Drift {
+    /// The type only appears in the new files
+    New(u32, TypeProfile),
+    /// The type only appears in the old files
+    Disappeared(u32, TypeProfile),
+    /// The type's data length changed
+    Resized(u32, TypeProfile, TypeProfile),
+}
+
+impl std::fmt::Display for Drift {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Drift::New(data_type, profile) => write!(f, "NEW         {:>6} {} sections ({})", data_type, profile.sections, profile),
+            Drift::Disappeared(data_type, profile) => write!(f, "DISAPPEARED {:>6} {} sections ({})", data_type, profile.sections, profile),
+            Drift::Resized(data_type, old, new) => write!(f, "RESIZED     {:>6} {} -> {}", data_type, old, new),
+        }
+    }
+}
+
+/// List the section types that appeared, disappeared or changed length between two schemas
+///
+/// A type is resized if it had a fixed length in both schemas and that length changed, or if
+/// its average length changed by more than `tolerance` (a ratio, such as 0.25 for 25%)
+pub fn drift(old: &Schema, new: &Schema, tolerance: f64) -> Vec<Drift> {
+    let mut drifts = Vec::new();
+    for (data_type, profile) in &old.types {
+        match new.types.get(data_type) {
+            None => drifts.push(Drift::Disappeared(*data_type, *profile)),
+            Some(other) => {
+                let resized = if profile.is_fixed() && other.is_fixed() {
+                    profile.min_len != other.min_len
+                } else {
+                    (other.avg_len() - profile.avg_len()).abs() > profile.avg_len().max(1.) * tolerance
+                };
+                if resized {
+                    drifts.push(Drift::Resized(*data_type, *profile, *other));
+                }
+            },
+        }
+    }
+    drifts.extend(new.types.iter().filter(|(t, _)| !old.types.contains_key(t)).map(|(t, p)| Drift::New(*t, *p)));
+    drifts
+}
+
+/// A section type of the reference, known from the lib's documented kinds or decoders
+#[derive(Clone, Copy, Debug)]
+pub struct KnownType {
+    /// The section's data type
+    pub data_type: u32,
+    /// The section's human-readable name
+    pub name: &'static str,
+    /// The first game version the data type is known to be used in, if known
+    pub min_version: Option<&'static str>,
+    /// The name of the lib's decoder of the data type, if any
+    pub decoder: Option<&'static str>,
+}
+
+impl KnownType {
+    /// The type's fields as named values
+    pub fn fields(&self) -> [(&'static str, json::JsonValue); 4] {
+        [
+            ("data_type", self.data_type.into()),
+            ("name", self.name.into()),
+            ("min_version", self.min_version.into()),
+            ("decoder", self.decoder.into()),
+        ]
+    }
+}
+
+/// List the section types known to the lib, sorted by data type
+///
+/// Types are listed if they are documented, see [`lolrofl::model::section::known_kinds`], or
+/// if the default [`Registry`] has a decoder for them.
+pub fn reference() -> Vec<KnownType> {
+    let registry = Registry::default();
+    let mut data_types: Vec<u32> = lolrofl::model::section::known_kinds().iter().map(|k| k.id())
+        .chain(registry.data_types())
+        .collect();
+    data_types.sort_unstable();
+    data_types.dedup();
+    data_types.into_iter().map(|data_type| {
+        let info = kind_info(data_type);
+        let decoder = registry.get(data_type).map(|d| d.name());
+        KnownType {
+            data_type,
+            name: info.map(|k| k.name()).or(decoder).unwrap_or("?"),
+            min_version: info.and_then(|k| k.min_version()),
+            decoder,
+        }
+    }).collect()
+}
+
+/// Render the section types known to the lib as a Markdown document
+pub fn reference_markdown(types: &[KnownType]) -> String {
+    let mut text = String::from("# ROFL section types\n\n");
+    text.push_str(&format!("Generated by lolrofl {}, see `ROFLFormat.adoc` for the layouts.\n\n", env!("CARGO_PKG_VERSION")));
+    text.push_str("| Data type | Name | First game version | Decoder |\n");
+    text.push_str("|-----------|------|--------------------|---------|\n");
+    for known in types {
+        text.push_str(&format!(
+            "| {} | {} | {} | {} |\n",
+            known.data_type, known.name, known.min_version.unwrap_or("-"), known.decoder.unwrap_or("-"),
+        ));
+    }
+    text
+}
@@ -0,0 +1,115 @@
+//! Run every parsing step on a file and report which ones this build supports
+
+use lolrofl::{Rofl, iter::PayloadIterOptions, model::Metadata};
+
+/// The outcome of a parsing step
+#[derive(Debug)]
+pub enum Outcome {
+    /// The step succeeded, with a short description of what was found
+    Passed(String),
+    /// The step failed, with the reason of the failure
+    Failed(String),
+    /// The step could not be run, with the reason why
+    Skipped(String),
+}
+
+/// A parsing step and its outcome
+#[derive(Debug)]
+pub struct Check {
+    /// The step's name
+    pub name: &'static str,
+    /// The step's outcome
+    pub outcome: Outcome,
+}
+
+impl Check {
+    /// Whether the step failed
+    pub fn failed(&self) -> bool { matches!(self.outcome, Outcome::Failed(_)) }
+}
+
+impl std::fmt::Display for Check {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let (status, detail) = match &self.outcome {
+            Outcome::Passed(d) => ("OK", d),
+            Outcome::Failed(d) => ("FAILED", d),
+            Outcome::Skipped(d) => ("SKIPPED", d),
+        };
+        write!(f, "{:<8}{:<16}{}", status, self.name, detail)
+    }
+}
+
+/// Run all parsing steps on a file's content
+///
+/// Steps that depend on a failed step are skipped
+pub fn run(content: &[u8]) -> Vec<Check> {
+    let mut checks = vec![Check { name: "features", outcome: Outcome::Passed(lolrofl::capabilities().enabled().join(", ")) }];
+    let data = match Rofl::from_slice(content) {
+        Ok(data) => {
+            let head = data.head();
+            checks.push(Check { name: "header", outcome: Outcome::Passed(format!("{} bytes, payload at {:#x}", head.file_len(), head.payload_offset())) });
+            data
+        },
+        Err(e) => {
+            checks.push(Check { name: "header", outcome: Outcome::Failed(crate::explain::Chain(&e).to_string()) });
+            return checks;
+        },
+    };
+    let warnings = data.warnings();
+    checks.push(Check { name: "consistency", outcome: if warnings.is_empty() {
+        Outcome::Passed("no inconsistency found".to_string())
+    } else {
+        Outcome::Failed(warnings.iter().map(|w| w.to_string()).collect::<Vec<String>>().join("; "))
+    }});
+    checks.push(Check { name: "metadata", outcome: match data.metadata().map(Metadata::from_json) {
+        Ok(Ok(metadata)) => Outcome::Passed(format!("version {}, {} players",
+            metadata.game_version().unwrap_or("unknown"), metadata.players().len())),
+        Ok(Err(e)) | Err(e) => Outcome::Failed(crate::explain::Chain(&e).to_string()),
+    }});
+    let payload = match data.payload() {
+        Ok(payload) => {
+            checks.push(Check { name: "payload header", outcome: Outcome::Passed(format!("{} chunks, {} keyframes", payload.chunk_count(), payload.keyframe_count())) });
+            payload
+        },
+        Err(e) => {
+            checks.push(Check { name: "payload header", outcome: Outcome::Failed(crate::explain::Chain(&e).to_string()) });
+            return checks;
+        },
+    };
+    let mut segments = match data.segment_iter(&PayloadIterOptions::with_data()) {
+        Ok(segments) => segments,
+        Err(e) => {
+            checks.push(Check { name: "decryption", outcome: Outcome::Failed(crate::explain::Chain(&e).to_string()) });
+            return checks;
+        },
+    };
+    let loaded: Vec<_> = segments.by_ref().collect();
+    let expected = (payload.chunk_count() + payload.keyframe_count()) as usize;
+    if !segments.is_valid() {
+        checks.push(Check { name: "decryption", outcome: Outcome::Failed(format!("segment {} of {}: {}", loaded.len() + 1, expected, segments.to_error())) });
+        return checks;
+    }
+    checks.push(Check { name: "decryption", outcome: Outcome::Passed(format!("{} segments", loaded.len())) });
+    let mut section_count = 0;
+    let mut failure = None;
+    for segment in &loaded {
+        match segment.section_iter() {
+            Ok(mut sections) => {
+                section_count += sections.by_ref().count();
+                if !sections.is_valid() {
+                    failure = Some(format!("segment {}: {}", segment.id(), sections.to_error()));
+                    break;
+                }
+            },
+            Err(e) => {
+                failure = Some(format!("segment {}: {}", segment.id(), e));
+                break;
+            },
+        }
+    }
+    checks.push(Check { name: "sections", outcome: match failure {
+        Some(reason) => Outcome::Failed(reason),
+        None => Outcome::Passed(format!("{} sections", section_count)),
+    }});
+    checks.push(Check { name: "decoders", outcome: Outcome::Skipped("no section decoder is available in this build".to_string()) });
+    checks
+}
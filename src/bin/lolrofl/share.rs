@@ -0,0 +1,48 @@
+//! Short game summaries to paste in chat applications
+
+use clap::ArgEnum;
+use lolrofl::model::Summary;
+
+/// The style of a share text
+#[derive(ArgEnum, Clone, Copy, Debug)]
+pub enum ShareFormat {
+    /// Multi-line text with Discord markdown
+    Discord,
+    /// Single-line plain text
+    Plain,
+}
+
+/// Render a game's summary as a share text
+pub fn render(summary: &Summary, format: ShareFormat) -> String {
+    let teams = summary.teams();
+    let score = teams.iter().map(|t| t.kills().to_string()).collect::<Vec<String>>().join(" - ");
+    let names = teams.iter().map(|t| Summary::team_name(t.team())).collect::<Vec<String>>();
+    let duration = summary.format_duration();
+    let version = summary.game_version().unwrap_or("unknown patch");
+    let result = match summary.winner() {
+        Some(team) => format!("{} victory", Summary::team_name(team)),
+        None => "No result".to_string(),
+    };
+    match format {
+        ShareFormat::Plain => {
+            let champions = teams.iter().map(|t| summary.champions(t.team()).join(", ")).collect::<Vec<String>>().join(" vs ");
+            let mut parts = vec![result];
+            if !teams.is_empty() {
+                parts.push(format!("{} {} {}", names.join("/"), score, champions));
+            }
+            parts.push(duration);
+            parts.push(version.to_string());
+            parts.join(" | ")
+        },
+        ShareFormat::Discord => {
+            let mut lines = vec![format!("**{}** ({}) · {} · {}", result, summary.end_reason(), duration, version)];
+            if !teams.is_empty() {
+                lines.push(format!("Score: **{}**", score));
+            }
+            for (team, name) in teams.iter().zip(names) {
+                lines.push(format!("**{}**: {}", name, summary.champions(team.team()).join(", ")));
+            }
+            lines.join("\n")
+        },
+    }
+}
@@ -0,0 +1,46 @@
+//! Destinations of exported data
+
+use std::path::{Path, PathBuf};
+
+/// A destination exported data is written to
+pub trait Sink {
+    /// Write data to a path, returns the path that was actually written
+    fn write(&self, path: &Path, data: &[u8]) -> Result<PathBuf, String>;
+}
+
+/// Write data to plain files
+pub struct FileSink;
+
+impl Sink for FileSink {
+    fn write(&self, path: &Path, data: &[u8]) -> Result<PathBuf, String> {
+        std::fs::write(path, data).map_err(|e| e.to_string())?;
+        Ok(path.to_path_buf())
+    }
+}
+
+/// Encrypt data with a password before passing it to another sink, `.enc` is appended to file names
+#[cfg(feature = "encrypt")]
+pub struct EncryptedSink<S: Sink> {
+    /// The sink encrypted data is written to
+    inner: S,
+    /// Encrypts the data
+    sealer: lolrofl::encrypt::Sealer,
+}
+
+#[cfg(feature = "encrypt")]
+impl<S: Sink> EncryptedSink<S> {
+    pub fn new(inner: S, password: &str) -> Result<Self, String> {
+        let sealer = lolrofl::encrypt::Sealer::new(password).map_err(|e| crate::explain::Chain(&e).to_string())?;
+        Ok(EncryptedSink { inner, sealer })
+    }
+}
+
+#[cfg(feature = "encrypt")]
+impl<S: Sink> Sink for EncryptedSink<S> {
+    fn write(&self, path: &Path, data: &[u8]) -> Result<PathBuf, String> {
+        let sealed = self.sealer.seal(data).map_err(|e| crate::explain::Chain(&e).to_string())?;
+        let mut name = path.as_os_str().to_os_string();
+        name.push(".enc");
+        self.inner.write(Path::new(&name), &sealed)
+    }
+}
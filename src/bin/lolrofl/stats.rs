@@ -0,0 +1,116 @@
+//! Aggregated statistics over the ROFL files of a directory
+
+use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicUsize, Ordering};
+use lolrofl::{Errors, Rofl, iter::PayloadIterOptions, model::Metadata};
+
+/// Statistics aggregated over a set of files
+#[derive(Debug, Default)]
+pub struct DirStats {
+    /// Number of successfully processed files
+    pub files: usize,
+    /// Files that could not be processed and why
+    pub failures: Vec<(PathBuf, String)>,
+    /// Total size in bytes of the processed files
+    pub bytes: u64,
+    /// Total duration in milliseconds of the processed games
+    pub duration: u64,
+    /// Total number of chunks
+    pub chunks: u64,
+    /// Total number of keyframes
+    pub keyframes: u64,
+    /// Total number of sections in chunks
+    pub sections: u64,
+    /// Number of games per game version
+    pub versions: BTreeMap<String, usize>,
+    /// Parsing metrics to export
+    #[cfg(feature = "metrics")]
+    pub metrics: lolrofl::metrics::ParseMetrics,
+}
+
+impl DirStats {
+    /// Add the statistics of another set of files
+    fn merge(&mut self, other: DirStats) {
+        self.files += other.files;
+        self.failures.extend(other.failures);
+        self.bytes += other.bytes;
+        self.duration += other.duration;
+        self.chunks += other.chunks;
+        self.keyframes += other.keyframes;
+        self.sections += other.sections;
+        for (version, count) in other.versions {
+            *self.versions.entry(version).or_default() += count;
+        }
+        #[cfg(feature = "metrics")]
+        self.metrics.merge(&other.metrics);
+    }
+    /// Add the statistics of a file and return the file's size
+    fn add_file(&mut self, path: &Path) -> Result<usize, Errors> {
+        #[cfg(feature = "mmap")]
+        let data = Rofl::open(path)?;
+        #[cfg(not(feature = "mmap"))]
+        let content = std::fs::read(path)?;
+        #[cfg(not(feature = "mmap"))]
+        let data = Rofl::from_slice(&content[..])?;
+        let size = std::fs::metadata(path)?.len() as usize;
+        let payload = data.payload()?;
+        let metadata = data.metadata().ok().and_then(|m| Metadata::from_json(m).ok()).unwrap_or_default();
+        let mut sections = 0;
+        // Sections are only counted if segments can be decrypted
+        let mut iterator = data.segment_iter(&PayloadIterOptions::new().load_data(cfg!(feature = "payload")))?;
+        for segment in iterator.by_ref().filter(|s| s.is_chunk() && s.is_loaded()) {
+            let mut section_iterator = segment.section_iter()?;
+            sections += section_iterator.by_ref().count() as u64;
+            if !section_iterator.is_valid() {
+                return Err(section_iterator.to_error());
+            }
+        }
+        if !iterator.is_valid() {
+            return Err(iterator.to_error());
+        }
+        self.files += 1;
+        self.bytes += size as u64;
+        self.duration += payload.duration() as u64;
+        self.chunks += payload.chunk_count() as u64;
+        self.keyframes += payload.keyframe_count() as u64;
+        self.sections += sections;
+        *self.versions.entry(metadata.game_version().unwrap_or("unknown").to_string()).or_default() += 1;
+        Ok(size)
+    }
+}
+
+/// Aggregate the statistics of files with a pool of `jobs` worker threads
+///
+/// Workers claim files one at a time from a shared index so busy workers never hold back
+/// idle ones, and only keep one file in memory at a time.
+pub fn collect(files: &[PathBuf], jobs: usize) -> DirStats {
+    let next = AtomicUsize::new(0);
+    let mut stats = DirStats::default();
+    std::thread::scope(|scope| {
+        let workers: Vec<_> = (0..jobs.max(1)).map(|_| scope.spawn(|| {
+            let mut local = DirStats::default();
+            loop {
+                let index = next.fetch_add(1, Ordering::Relaxed);
+                let file = match files.get(index) {
+                    Some(file) => file,
+                    None => break,
+                };
+                #[cfg(feature = "metrics")]
+                let start = std::time::Instant::now();
+                let result = local.add_file(file);
+                #[cfg(feature = "metrics")]
+                local.metrics.record(result.as_ref().map(|_| ()), start.elapsed(), *result.as_ref().unwrap_or(&0));
+                if let Err(e) = result {
+                    local.failures.push((file.clone(), crate::explain::Chain(&e).to_string()));
+                }
+            }
+            local
+        })).collect();
+        for worker in workers {
+            stats.merge(worker.join().unwrap());
+        }
+    });
+    stats.failures.sort();
+    stats
+}
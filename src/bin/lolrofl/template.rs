@@ -0,0 +1,56 @@
+//! File name templates with `{placeholder}` substitution
+
+use std::collections::HashMap;
+
+/// Values available to a template, by placeholder name
+pub type Values = HashMap<&'static str, String>;
+
+/// List the placeholders used by a template
+pub fn placeholders(template: &str) -> Result<Vec<&str>, String> {
+    let mut names = Vec::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..].find('}').ok_or_else(|| format!("Unclosed placeholder in template {:?}", template))?;
+        names.push(&rest[start + 1..start + end]);
+        rest = &rest[start + end + 1..];
+    }
+    Ok(names)
+}
+
+/// Render a template, placeholder values are sanitized to be usable in a file name
+pub fn render(template: &str, values: &Values) -> Result<String, String> {
+    let mut output = String::new();
+    let mut rest = template;
+    while let Some(start) = rest.find('{') {
+        let end = rest[start..].find('}').ok_or_else(|| format!("Unclosed placeholder in template {:?}", template))?;
+        let name = &rest[start + 1..start + end];
+        let value = values.get(name).ok_or_else(|| format!("Unknown or unavailable placeholder {{{}}}", name))?;
+        output.push_str(&rest[..start]);
+        output.extend(value.chars().map(|c| if c.is_alphanumeric() || "-_.".contains(c) {c} else {'_'}));
+        rest = &rest[start + end + 1..];
+    }
+    output.push_str(rest);
+    Ok(output)
+}
+
+/// Format a time as a `YYYY-MM-DD` date (UTC)
+pub fn format_date(time: std::time::SystemTime) -> String {
+    let days = time.duration_since(std::time::UNIX_EPOCH).map(|d| d.as_secs() / 86400).unwrap_or(0) as i64;
+    // Convert days since the epoch to a civil date
+    let z = days + 719468;
+    let era = z.div_euclid(146097);
+    let doe = z - era * 146097;
+    let yoe = (doe - doe / 1460 + doe / 36524 - doe / 146096) / 365;
+    let doy = doe - (365 * yoe + yoe / 4 - yoe / 100);
+    let mp = (5 * doy + 2) / 153;
+    let day = doy - (153 * mp + 2) / 5 + 1;
+    let month = if mp < 10 { mp + 3 } else { mp - 9 };
+    let year = yoe + era * 400 + if month <= 2 { 1 } else { 0 };
+    format!("{:04}-{:02}-{:02}", year, month, day)
+}
+
+/// Format a time as a `YYYY-MM-DD HH:MM:SS UTC` date and time
+pub fn format_date_time(time: std::time::SystemTime) -> String {
+    let seconds = time.duration_since(std::time::UNIX_EPOCH).map(|d| d.as_secs()).unwrap_or(0);
+    format!("{} {:02}:{:02}:{:02} UTC", format_date(time), seconds / 3600 % 24, seconds / 60 % 60, seconds % 60)
+}
@@ -0,0 +1,53 @@
+//! Describes which optional features the library was compiled with
+
+/// The optional features the library was compiled with
+///
+/// New fields are added as new optional features become available
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct Capabilities {
+    /// Whether segment data may be decrypted (`payload` feature)
+    pub payload: bool,
+    /// Whether metadata may be parsed into typed structs (`json` feature)
+    pub json: bool,
+    /// Whether metadata values may be extracted without parsing the whole JSON document (`json-stream` feature)
+    pub json_stream: bool,
+    /// Whether test fixtures are available (`test` feature)
+    pub test: bool,
+}
+
+impl Capabilities {
+    /// The names of the enabled features
+    pub fn enabled(&self) -> Vec<&'static str> {
+        [
+            ("payload", self.payload),
+            ("json", self.json),
+            ("json-stream", self.json_stream),
+            ("test", self.test),
+        ].iter().filter(|(_, enabled)| *enabled).map(|(name, _)| *name).collect()
+    }
+}
+
+/// Get the optional features the library was compiled with
+///
+/// This allows applications to adapt to the library's build instead of hitting
+/// `Errors::FeatureDisabled` at runtime
+///
+/// # Examples
+///
+/// ```rust
+/// let capabilities = lolrofl::capabilities();
+/// if !capabilities.payload {
+///     println!("Segment data can not be decrypted with this build");
+/// }
+/// # assert_eq!(capabilities.payload, cfg!(feature = "payload"));
+/// # assert_eq!(capabilities.enabled().contains(&"json"), true);
+/// ```
+pub fn capabilities() -> Capabilities {
+    Capabilities {
+        payload: cfg!(feature = "payload"),
+        json: cfg!(feature = "json"),
+        json_stream: cfg!(feature = "json-stream"),
+        test: cfg!(feature = "test"),
+    }
+}
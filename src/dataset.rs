@@ -0,0 +1,206 @@
+/*!
+Deterministic selection of portions of replays to build datasets
+
+[`sample`] selects segments, events or time frames from each replay of a corpus with a seeded
+pseudo-random generator. The same seed and strategy always select the same portions of a
+replay, regardless of the other replays of the corpus and of their order, so that the exact
+data that fed a model can be listed and selected again.
+
+# Examples
+
+```rust
+use lolrofl::dataset::{Strategy, sample};
+
+# let content = lolrofl::test::sample_full_file_0();
+// let content = std::fs::read("game.rofl").unwrap();
+let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+
+let samples = sample(&[game], 42, &Strategy::Frames { count: 2, duration: 30. }).unwrap();
+for replay in &samples {
+    for pick in replay.picks() {
+        println!("{} {}", replay.match_id(), pick);
+    }
+}
+# let again = sample(&[lolrofl::Rofl::from_slice(&content[..]).unwrap()], 42, &Strategy::Frames { count: 2, duration: 30. }).unwrap();
+# assert_eq!(samples, again);
+# assert_eq!(samples[0].picks().len(), 2);
+# let segments = sample(&[lolrofl::Rofl::from_slice(&content[..]).unwrap()], 42, &Strategy::Segments(5)).unwrap();
+# assert_eq!(segments[0].picks().len(), 3);
+# #[cfg(feature = "payload")] {
+# let events = sample(&[lolrofl::Rofl::from_slice(&content[..]).unwrap()], 7, &Strategy::Events { count: 2, types: vec![256] }).unwrap();
+# assert_eq!(events[0].picks().len(), 2);
+# assert_eq!(events[0].picks().iter().all(|p| matches!(p, lolrofl::dataset::Pick::Event { .. })), true);
+# }
+```
+*/
+
+use crate::{Errors, Rofl, iter::PayloadIterOptions, model::{SegmentKind, section::{format_game_time, SectionAddress, SectionCore, SectionPosition}}};
+
+/// What to select from each replay, see [`sample`]
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum Strategy {
+    /// Select this number of segments, chunks and keyframes alike
+    Segments(usize),
+    /// Select `count` sections of the replay's chunks whose type is in `types`, of any type if `types` is empty
+    Events {
+        /// The number of sections to select
+        count: usize,
+        /// The section types to select from
+        types: Vec<u32>,
+    },
+    /// Select `count` time frames of `duration` seconds, frames start at multiples of `duration`
+    Frames {
+        /// The number of frames to select
+        count: usize,
+        /// The duration in seconds of a frame
+        duration: f32,
+    },
+}
+
+/// A portion of a replay selected by [`sample`]
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Pick {
+    /// A segment
+    Segment {
+        /// Whether the segment is a chunk or a keyframe, `None` if its type is unknown
+        kind: Option<SegmentKind>,
+        /// The segment's ID
+        id: u32,
+    },
+    /// A section of a chunk
+    Event {
+        /// The section's address
+        address: SectionAddress,
+        /// The section's in-game time in seconds
+        time: f32,
+    },
+    /// A time frame
+    Frame {
+        /// The frame's start time in seconds
+        start: f32,
+        /// The frame's end time in seconds
+        end: f32,
+    },
+}
+
+impl std::fmt::Display for Pick {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Pick::Segment { kind: Some(SegmentKind::Chunk), id } => write!(f, "chunk:{}", id),
+            Pick::Segment { kind: Some(SegmentKind::Keyframe), id } => write!(f, "kf:{}", id),
+            Pick::Segment { kind: None, id } => write!(f, "segment:{}", id),
+            Pick::Event { address, .. } => write!(f, "{}", address),
+            Pick::Frame { start, end } => write!(f, "{}-{}", format_game_time(*start), format_game_time(*end)),
+        }
+    }
+}
+
+/// The portions selected from a replay
+#[derive(Clone, Debug, PartialEq)]
+pub struct ReplaySample {
+    /// The replay's index in the sampled corpus
+    index: usize,
+    /// The replay's game ID
+    match_id: u64,
+    /// The selected portions, in the replay's order
+    picks: Vec<Pick>,
+}
+
+impl ReplaySample {
+    /// The replay's index in the sampled corpus
+    pub fn index(&self) -> usize { self.index }
+    /// The replay's game ID
+    pub fn match_id(&self) -> u64 { self.match_id }
+    /// The selected portions, in the replay's order
+    pub fn picks(&self) -> &[Pick] { &self.picks }
+}
+
+/// A SplitMix64 pseudo-random generator, whose output is stable across platforms and versions
+struct SplitMix64(u64);
+
+impl SplitMix64 {
+    /// Get the next pseudo-random number
+    fn next(&mut self) -> u64 {
+        self.0 = self.0.wrapping_add(0x9E37_79B9_7F4A_7C15);
+        let mut z = self.0;
+        z = (z ^ (z >> 30)).wrapping_mul(0xBF58_476D_1CE4_E5B9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94D0_49BB_1331_11EB);
+        z ^ (z >> 31)
+    }
+}
+
+/// Select `count` of the candidates, in their original order
+fn choose<T>(candidates: Vec<T>, count: usize, rng: &mut SplitMix64) -> Vec<T> {
+    let count = count.min(candidates.len());
+    let mut indexes: Vec<usize> = (0..candidates.len()).collect();
+    // Partial Fisher-Yates shuffle of the indexes
+    for i in 0..count {
+        let j = i + (rng.next() % (indexes.len() - i) as u64) as usize;
+        indexes.swap(i, j);
+    }
+    let mut chosen = vec![false; candidates.len()];
+    for i in &indexes[..count] {
+        chosen[*i] = true;
+    }
+    candidates.into_iter().zip(chosen).filter_map(|(candidate, chosen)| chosen.then_some(candidate)).collect()
+}
+
+/// The candidate portions of a replay for a strategy, in the replay's order
+fn candidates(rofl: &Rofl, strategy: &Strategy) -> Result<Vec<Pick>, Errors> {
+    match strategy {
+        Strategy::Segments(_) => {
+            let mut iterator = rofl.segment_iter(&PayloadIterOptions::new())?;
+            let picks = iterator.by_ref().map(|s| Pick::Segment { kind: s.kind(), id: s.id() }).collect();
+            if iterator.is_valid() { Ok(picks) } else { Err(iterator.to_error()) }
+        },
+        Strategy::Events { types, .. } => {
+            // Without decryption support, no chunk is loaded and there is no event to select
+            let mut iterator = rofl.segment_iter(&PayloadIterOptions::new().load_data(cfg!(feature="payload")))?;
+            let (mut picks, mut time) = (Vec::new(), 0.);
+            for segment in iterator.by_ref().filter(|s| s.is_chunk() && s.is_loaded()) {
+                let mut offset = 0;
+                let mut sections = segment.section_iter()?;
+                for section in sections.by_ref() {
+                    time = section.time().to_absolute(time);
+                    if types.is_empty() || types.contains(&section.data_type()) {
+                        picks.push(Pick::Event { address: SectionAddress::chunk(segment.id(), SectionPosition::Offset(offset)), time });
+                    }
+                    offset += section.len();
+                }
+                if !sections.is_valid() {
+                    return Err(sections.to_error());
+                }
+            }
+            if iterator.is_valid() { Ok(picks) } else { Err(iterator.to_error()) }
+        },
+        Strategy::Frames { duration, .. } => {
+            if *duration <= 0. {
+                return Err(Errors::InvalidArgument { name: "duration", reason: "must be strictly positive" });
+            }
+            let frames = (rofl.payload()?.duration() as f32 / 1000. / duration) as usize;
+            Ok((0..frames).map(|i| Pick::Frame { start: i as f32 * duration, end: (i + 1) as f32 * duration }).collect())
+        },
+    }
+}
+
+/// Deterministically select portions of each replay of a corpus
+///
+/// Each replay's selection only depends on `seed`, `strategy` and the replay's game ID. Replays
+/// with fewer candidate portions than requested are selected whole.
+///
+/// # Errors
+///
+/// If a replay's payload header or segments could not be read, or if a frame's duration is not
+/// strictly positive
+pub fn sample(replays: &[Rofl], seed: u64, strategy: &Strategy) -> Result<Vec<ReplaySample>, Errors> {
+    let count = match strategy {
+        Strategy::Segments(count) | Strategy::Events { count, .. } | Strategy::Frames { count, .. } => *count,
+    };
+    replays.iter().enumerate().map(|(index, rofl)| {
+        let match_id = rofl.payload()?.id();
+        let mut rng = SplitMix64(seed ^ SplitMix64(match_id).next());
+        let picks = choose(candidates(rofl, strategy)?, count, &mut rng);
+        Ok(ReplaySample { index, match_id, picks })
+    }).collect()
+}
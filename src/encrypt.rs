@@ -0,0 +1,136 @@
+/*!
+Password-based encryption of exported data
+
+Decrypted segments and exported events may hold private chat messages, this module
+encrypts them at rest with AES-256-GCM and a key derived from a password with
+PBKDF2-HMAC-SHA256.
+
+Encrypted data starts with [`MAGIC`], followed by the number of PBKDF2 iterations as a
+little endian `u32`, a 16-byte salt, a 12-byte nonce, then the ciphertext and its tag.
+
+This module requires the `encrypt` feature.
+
+# Examples
+
+```rust
+use lolrofl::encrypt::{open, Sealer};
+
+let sealer = Sealer::with_iterations("correct horse", 1000).unwrap();
+let sealed = sealer.seal(b"private chat").unwrap();
+
+assert_eq!(open("correct horse", &sealed).unwrap(), b"private chat");
+assert_eq!(open("wrong password", &sealed).is_err(), true);
+```
+*/
+
+use aes_gcm::{Aes256Gcm, KeyInit, Nonce, aead::Aead};
+use crate::Errors;
+
+/// Starting bytes of encrypted data
+pub const MAGIC: [u8; 8] = *b"LOLROFLE";
+/// Length of the salt used to derive keys
+const SALT_LEN: usize = 16;
+/// Length of the AES-GCM nonce
+const NONCE_LEN: usize = 12;
+/// Length of the header that precedes the ciphertext
+const HEADER_LEN: usize = MAGIC.len() + 4 + SALT_LEN + NONCE_LEN;
+
+/// Derive an encryption key from a password
+fn derive_key(password: &str, salt: &[u8], iterations: u32) -> Aes256Gcm {
+    let mut key = [0u8; 32];
+    pbkdf2::pbkdf2_hmac::<sha2::Sha256>(password.as_bytes(), salt, iterations, &mut key);
+    Aes256Gcm::new(&key.into())
+}
+
+/// Fill a buffer with random bytes
+fn random(buffer: &mut [u8]) -> Result<(), Errors> {
+    getrandom::getrandom(buffer).map_err(|e| Errors::Io(std::io::Error::other(e.to_string())))
+}
+
+/// Encrypts data with a password
+///
+/// The key is derived once, data encrypted by the same sealer shares its salt but each
+/// encryption uses a new random nonce.
+#[derive(Clone)]
+pub struct Sealer {
+    /// The key derived from the password
+    cipher: Aes256Gcm,
+    /// The salt the key was derived with
+    salt: [u8; SALT_LEN],
+    /// The number of PBKDF2 iterations
+    iterations: u32,
+}
+
+impl std::fmt::Debug for Sealer {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_struct("Sealer").field("iterations", &self.iterations).finish_non_exhaustive()
+    }
+}
+
+impl Sealer {
+    /// The default number of PBKDF2 iterations
+    pub const DEFAULT_ITERATIONS: u32 = 600_000;
+    /// Build a sealer that derives its key from a password with the default number of iterations
+    ///
+    /// # Errors
+    ///
+    /// If no random data is available
+    pub fn new(password: &str) -> Result<Sealer, Errors> {
+        Sealer::with_iterations(password, Sealer::DEFAULT_ITERATIONS)
+    }
+    /// Build a sealer that derives its key from a password with a number of PBKDF2 iterations,
+    /// more iterations make passwords harder to brute-force
+    ///
+    /// # Errors
+    ///
+    /// If no random data is available
+    pub fn with_iterations(password: &str, iterations: u32) -> Result<Sealer, Errors> {
+        let iterations = iterations.max(1);
+        let mut salt = [0u8; SALT_LEN];
+        random(&mut salt)?;
+        Ok(Sealer { cipher: derive_key(password, &salt, iterations), salt, iterations })
+    }
+    /// Encrypt data with a new random nonce
+    ///
+    /// # Errors
+    ///
+    /// If no random data is available
+    pub fn seal(&self, data: &[u8]) -> Result<Vec<u8>, Errors> {
+        let mut nonce = [0u8; NONCE_LEN];
+        random(&mut nonce)?;
+        let ciphertext = self.cipher.encrypt(Nonce::from_slice(&nonce), data).map_err(|_| Errors::InvalidBuffer)?;
+
+        let mut sealed = Vec::with_capacity(HEADER_LEN + ciphertext.len());
+        sealed.extend_from_slice(&MAGIC);
+        sealed.extend_from_slice(&self.iterations.to_le_bytes());
+        sealed.extend_from_slice(&self.salt);
+        sealed.extend_from_slice(&nonce);
+        sealed.extend_from_slice(&ciphertext);
+        Ok(sealed)
+    }
+}
+
+/// Whether data starts like encrypted data
+pub fn is_sealed(data: &[u8]) -> bool {
+    data.starts_with(&MAGIC)
+}
+
+/// Decrypt data encrypted by a [`Sealer`]
+///
+/// # Errors
+///
+/// If the data was not encrypted by a [`Sealer`], if the password is wrong or if the data was altered
+pub fn open(password: &str, data: &[u8]) -> Result<Vec<u8>, Errors> {
+    if data.len() < HEADER_LEN {
+        return Err(Errors::too_small(HEADER_LEN, data.len()));
+    }
+    if !is_sealed(data) {
+        return Err(Errors::InvalidBuffer);
+    }
+    let iterations = u32::from_le_bytes(data[MAGIC.len()..MAGIC.len() + 4].try_into().unwrap());
+    let salt = &data[MAGIC.len() + 4..MAGIC.len() + 4 + SALT_LEN];
+    let nonce = &data[HEADER_LEN - NONCE_LEN..HEADER_LEN];
+    derive_key(password, salt, iterations)
+        .decrypt(Nonce::from_slice(nonce), &data[HEADER_LEN..])
+        .map_err(|_| Errors::DecryptionFailed)
+}
@@ -1,23 +1,234 @@
-//! Defines the error data containers that may be used by this crate
-
-/// The errors that may be raised by this crate
-#[derive(Debug)]
-pub enum Errors {
-    /// No data was provided despite some being required
-    NoData,
-    /// The buffer used for an operation was too small
-    BufferTooSmall,
-    /// The buffer used for an operation was malformed or corrupted
-    /// and did not match the expected content constraints
-    InvalidBuffer,
-}
-
-impl std::fmt::Display for Errors {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        match self {
-            Errors::NoData => write!(f, "No data was loaded or provided"),
-            Errors::BufferTooSmall => write!(f, "The provided data buffer was too small to be used"),
-            Errors::InvalidBuffer => write!(f, "The provided data buffer did not provide usable data"),
-        }
-    }
-}
+//! Defines the error data containers that may be used by this crate
+
+/// The errors that may be raised by this crate
+///
+/// Errors that wrap another error, such as [`Errors::Segment`] or [`Errors::Io`], only display
+/// their own context, the wrapped error is their [`source`](std::error::Error::source).
+///
+/// # Examples
+///
+/// ```rust
+/// fn read_metadata(path: &std::path::Path) -> Result<String, lolrofl::Errors> {
+///     let content = std::fs::read(path)?; // io::Error is converted to Errors::Io
+///     let game = lolrofl::Rofl::from_slice(&content[..])?;
+///     Ok(game.metadata()?.to_string())
+/// }
+///
+/// let error = read_metadata(std::path::Path::new("does-not-exist.rofl")).unwrap_err();
+/// assert_eq!(matches!(error, lolrofl::Errors::Io(_)), true);
+/// ```
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum Errors {
+    /// No data was provided despite some being required
+    NoData,
+    /// The buffer used for an operation was too small
+    BufferTooSmall {
+        /// The number of bytes the operation required
+        expected: usize,
+        /// The number of bytes the buffer held
+        actual: usize,
+    },
+    /// The buffer used for an operation was malformed or corrupted
+    /// and did not match the expected content constraints
+    InvalidBuffer,
+    /// The file is a ROFL file in a format that this crate cannot parse, see [`crate::kind`]
+    UnsupportedFormat {
+        /// The format identified from the file's signature
+        kind: crate::kind::FileKind,
+    },
+    /// An argument provided by the caller, such as a parsed string, is invalid
+    InvalidArgument {
+        /// The name of the argument
+        name: &'static str,
+        /// Why the argument is invalid
+        reason: &'static str,
+    },
+    /// A field of the file's header did not match the header's constraints
+    MalformedHeader {
+        /// The name of the malformed field
+        field: &'static str,
+    },
+    /// A field of the payload header did not match the header's constraints
+    MalformedPayloadHeader {
+        /// The name of the malformed field
+        field: &'static str,
+    },
+    /// An operation requires a feature that was not enabled at compile-time
+    FeatureDisabled {
+        /// The name of the required feature
+        feature: &'static str,
+    },
+    /// The operation was cancelled through a cancellation token
+    Cancelled,
+    /// Encrypted data could not be decrypted, the password is wrong or the data was altered
+    DecryptionFailed,
+    /// A filter expression could not be parsed
+    InvalidFilter {
+        /// The offset in the expression at which parsing failed
+        position: usize,
+        /// What was expected at that offset
+        expected: &'static str,
+    },
+    /// Files that should hold the same game hold different games
+    GameMismatch {
+        /// The ID of the expected game
+        expected: u64,
+        /// The ID of the game the file holds
+        actual: u64,
+    },
+    /// A file built by the lib did not pass its verification
+    VerificationFailed {
+        /// The failed checks
+        report: Box<crate::verify::VerificationReport>,
+    },
+    /// An error occured while reading a segment's data or sections
+    Segment {
+        /// Whether the segment is a chunk or a keyframe, `None` if its type is unknown
+        kind: Option<crate::model::SegmentKind>,
+        /// The segment's ID
+        id: u32,
+        /// The error that occured
+        source: Box<Errors>,
+    },
+    /// An error occured while reading a section of a segment's decrypted data
+    Section {
+        /// The offset of the section in the segment's decrypted data
+        offset: usize,
+        /// The error that occured
+        source: Box<Errors>,
+    },
+    /// An I/O operation failed
+    Io(std::io::Error),
+    /// A string was not valid UTF-8
+    InvalidUtf8(std::str::Utf8Error),
+    /// A base64 string could not be decoded
+    #[cfg(feature="payload")]
+    InvalidBase64(base64::DecodeError),
+    /// A JSON string could not be parsed
+    #[cfg(feature="json")]
+    InvalidJson(json::Error),
+}
+
+impl Errors {
+    /// Build an error for a buffer of `actual` bytes that should hold at least `expected` bytes
+    pub(crate) fn too_small(expected: usize, actual: usize) -> Errors {
+        Errors::BufferTooSmall { expected, actual }
+    }
+    /// The error without the context of the segment and section it occured in
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use lolrofl::Errors;
+    ///
+    /// let error = Errors::Section { offset: 12, source: Box::new(Errors::InvalidBuffer) };
+    /// let error = Errors::Segment { kind: None, id: 3, source: Box::new(error) };
+    /// assert_eq!(matches!(error.root(), Errors::InvalidBuffer), true);
+    /// // The context is displayed alone, the error that caused it is its source
+    /// assert_eq!(error.to_string(), "Segment 3");
+    /// assert_eq!(std::error::Error::source(&error).unwrap().to_string(), "section at offset 0xc");
+    /// assert_eq!(error.root().to_string(), "The provided data buffer did not provide usable data");
+    /// ```
+    pub fn root(&self) -> &Errors {
+        match self {
+            Errors::Segment { source, .. } | Errors::Section { source, .. } => source.root(),
+            e => e,
+        }
+    }
+    /// A short stable name of the error's kind, for use in logs and metrics labels
+    ///
+    /// The context of the segment and section the error occured in is ignored
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// assert_eq!(lolrofl::Errors::BufferTooSmall { expected: 288, actual: 12 }.kind(), "buffer_too_small");
+    /// ```
+    pub fn kind(&self) -> &'static str {
+        match self {
+            Errors::NoData => "no_data",
+            Errors::BufferTooSmall { .. } => "buffer_too_small",
+            Errors::InvalidBuffer => "invalid_buffer",
+            Errors::UnsupportedFormat { .. } => "unsupported_format",
+            Errors::InvalidArgument { .. } => "invalid_argument",
+            Errors::MalformedHeader { .. } => "malformed_header",
+            Errors::MalformedPayloadHeader { .. } => "malformed_payload_header",
+            Errors::FeatureDisabled { .. } => "feature_disabled",
+            Errors::Cancelled => "cancelled",
+            Errors::DecryptionFailed => "decryption_failed",
+            Errors::InvalidFilter { .. } => "invalid_filter",
+            Errors::GameMismatch { .. } => "game_mismatch",
+            Errors::VerificationFailed { .. } => "verification_failed",
+            Errors::Segment { source, .. } | Errors::Section { source, .. } => source.kind(),
+            Errors::Io(_) => "io",
+            Errors::InvalidUtf8(_) => "invalid_utf8",
+            #[cfg(feature="payload")]
+            Errors::InvalidBase64(_) => "invalid_base64",
+            #[cfg(feature="json")]
+            Errors::InvalidJson(_) => "invalid_json",
+        }
+    }
+}
+
+impl std::fmt::Display for Errors {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Errors::NoData => write!(f, "No data was loaded or provided"),
+            Errors::BufferTooSmall { expected, actual } =>
+                write!(f, "The provided data buffer was too small to be used ({} bytes, {} bytes required)", actual, expected),
+            Errors::InvalidBuffer => write!(f, "The provided data buffer did not provide usable data"),
+            Errors::UnsupportedFormat { kind } => write!(f, "The file's format ({:?}) is not supported", kind),
+            Errors::InvalidArgument { name, reason } => write!(f, "The {} argument is invalid: {}", name, reason),
+            Errors::MalformedHeader { field } => write!(f, "The file header's {} field is malformed", field),
+            Errors::MalformedPayloadHeader { field } => write!(f, "The payload header's {} field is malformed", field),
+            Errors::FeatureDisabled { feature } => write!(f, "The operation requires the '{}' feature which was not enabled at compile-time", feature),
+            Errors::Cancelled => write!(f, "The operation was cancelled"),
+            Errors::DecryptionFailed => write!(f, "The data could not be decrypted, the password is wrong or the data was altered"),
+            Errors::InvalidFilter { position, expected } => write!(f, "The filter expression is invalid, expected {} at offset {}", expected, position),
+            Errors::GameMismatch { expected, actual } => write!(f, "The file holds game {} instead of game {}", actual, expected),
+            Errors::VerificationFailed { .. } => write!(f, "The built file did not pass its verification"),
+            Errors::Segment { kind, id, .. } => write!(f, "{}", crate::model::Area::Segment { kind: *kind, id: *id }),
+            Errors::Section { offset, .. } => write!(f, "section at offset {:#x}", offset),
+            Errors::Io(_) => write!(f, "An I/O operation failed"),
+            Errors::InvalidUtf8(_) => write!(f, "The provided data was not a valid UTF-8 string"),
+            #[cfg(feature="payload")]
+            Errors::InvalidBase64(_) => write!(f, "The provided data was not a valid base64 string"),
+            #[cfg(feature="json")]
+            Errors::InvalidJson(_) => write!(f, "The provided data was not a valid JSON string"),
+        }
+    }
+}
+
+impl std::error::Error for Errors {
+    fn source(&self) -> Option<&(dyn std::error::Error + 'static)> {
+        match self {
+            Errors::Segment { source, .. } | Errors::Section { source, .. } => Some(source.as_ref()),
+            Errors::Io(e) => Some(e),
+            Errors::InvalidUtf8(e) => Some(e),
+            #[cfg(feature="payload")]
+            Errors::InvalidBase64(e) => Some(e),
+            #[cfg(feature="json")]
+            Errors::InvalidJson(e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<std::io::Error> for Errors {
+    fn from(e: std::io::Error) -> Self { Errors::Io(e) }
+}
+
+impl From<std::str::Utf8Error> for Errors {
+    fn from(e: std::str::Utf8Error) -> Self { Errors::InvalidUtf8(e) }
+}
+
+#[cfg(feature="payload")]
+impl From<base64::DecodeError> for Errors {
+    fn from(e: base64::DecodeError) -> Self { Errors::InvalidBase64(e) }
+}
+
+#[cfg(feature="json")]
+impl From<json::Error> for Errors {
+    fn from(e: json::Error) -> Self { Errors::InvalidJson(e) }
+}
@@ -0,0 +1,92 @@
+/*!
+A minimal C-compatible API to extract a file's high-level information
+
+This is meant to back shell integrations built by other projects, such as a Windows property
+handler or an Explorer tooltip: only the beginning of the file up to the end of the payload
+header needs to be read, the payload itself is never decrypted.
+
+This module requires the `ffi` feature.
+*/
+
+use crate::{Errors, Rofl, model::Metadata};
+
+/// The information returned by [`lolrofl_quick_info`]
+#[repr(C)]
+#[derive(Clone, Copy, Debug)]
+pub struct LolRoflInfo {
+    /// The game's ID
+    pub match_id: u64,
+    /// The game's duration in milliseconds
+    pub duration_ms: u32,
+    /// The champions played in the game as a comma-separated, NUL-terminated UTF-8 string,
+    /// truncated if it does not fit
+    pub champions: [u8; 256],
+}
+
+impl Default for LolRoflInfo {
+    fn default() -> Self {
+        LolRoflInfo { match_id: 0, duration_ms: 0, champions: [0; 256] }
+    }
+}
+
+/// The call succeeded
+pub const LOLROFL_OK: i32 = 0;
+/// A pointer argument was null
+pub const LOLROFL_INVALID_ARGUMENT: i32 = -1;
+/// The data is not the beginning of a ROFL file or is too short
+pub const LOLROFL_PARSE_ERROR: i32 = -2;
+
+/// Extract a file's information, champions are left empty if the metadata can't be parsed
+fn quick_info(data: &[u8]) -> Result<LolRoflInfo, Errors> {
+    let rofl = Rofl::from_slice(data)?;
+    let payload = rofl.payload()?;
+    let mut info = LolRoflInfo { match_id: payload.id(), duration_ms: payload.duration(), ..LolRoflInfo::default() };
+    if let Ok(metadata) = rofl.metadata().and_then(Metadata::from_json) {
+        let champions = metadata.players().iter()
+            .map(|p| p.champion().unwrap_or("?"))
+            .collect::<Vec<&str>>()
+            .join(",");
+        // Keep room for the NUL terminator and don't cut a character in half
+        let mut len = champions.len().min(info.champions.len() - 1);
+        while !champions.is_char_boundary(len) {
+            len -= 1;
+        }
+        info.champions[..len].copy_from_slice(&champions.as_bytes()[..len]);
+    }
+    Ok(info)
+}
+
+/// Extract the match ID, duration and champions of a file from its first bytes
+///
+/// `data` must hold at least the file's headers, metadata and payload header.
+/// Returns [`LOLROFL_OK`] and fills `info` on success, or a negative error code.
+///
+/// # Safety
+///
+/// `data` must point to `len` readable bytes and `info` to a writable [`LolRoflInfo`]
+///
+/// # Examples
+///
+/// ```rust
+/// use lolrofl::ffi::{lolrofl_quick_info, LolRoflInfo, LOLROFL_OK};
+///
+/// # let content = lolrofl::test::sample_base_file_0();
+/// let mut info = LolRoflInfo::default();
+/// let status = unsafe { lolrofl_quick_info(content.as_ptr(), content.len(), &mut info) };
+/// assert_eq!(status, LOLROFL_OK);
+/// assert_eq!(info.duration_ms, 91722);
+/// assert_eq!(info.champions[0], 0);
+/// ```
+#[no_mangle]
+pub unsafe extern "system" fn lolrofl_quick_info(data: *const u8, len: usize, info: *mut LolRoflInfo) -> i32 {
+    if data.is_null() || info.is_null() {
+        return LOLROFL_INVALID_ARGUMENT;
+    }
+    match quick_info(std::slice::from_raw_parts(data, len)) {
+        Ok(result) => {
+            *info = result;
+            LOLROFL_OK
+        },
+        Err(_) => LOLROFL_PARSE_ERROR,
+    }
+}
@@ -0,0 +1,356 @@
+/*!
+A small expression language to select events
+
+Expressions compare the fields of an [`Event`] with values and combine comparisons with
+`&&`, `||`, `!` and parentheses. The available fields are:
+
+* `type`: the section's data type
+* `time`: the section's in-game time in seconds
+* `segment`: the type of segment the section is in, `chunk` or `keyframe`
+* `segment_id`: the ID of the segment the section is in
+* `param`: the section's parameters read as a little endian integer
+* `len`: the length of the section's data
+
+Fields are compared with `=`, `!=`, `<`, `<=`, `>` and `>=` to numbers or to strings
+quoted with `'` or `"`.
+
+# Examples
+
+```rust
+use lolrofl::{filter::Filter, fmt::Event, iter::PayloadIterOptions};
+use lolrofl::model::section::{SectionAddress, SectionCore, SectionPosition};
+
+let filter: Filter = "type = 256 && (time < 10 || param >= 7) && segment != 'keyframe'".parse().unwrap();
+
+# let content = lolrofl::test::sample_full_file_0();
+// let content = std::fs::read("game.rofl").unwrap();
+let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+# let mut matched: Vec<String> = Vec::new();
+# #[cfg(feature = "payload")]
+for segment in game.segment_iter(&PayloadIterOptions::with_data()).unwrap() {
+    let (mut time, mut offset) = (0., 0);
+    for section in segment.section_iter().unwrap() {
+        time = section.time().to_absolute(time);
+        let position = SectionPosition::Offset(offset);
+        let address = if segment.is_chunk() { SectionAddress::chunk(segment.id(), position) } else { SectionAddress::keyframe(segment.id(), position) };
+        offset += section.len();
+        let event = Event { address, time, section: &section };
+        if filter.matches(&event) {
+            println!("{}", event);
+#           matched.push(address.to_string());
+        }
+    }
+}
+# #[cfg(feature = "payload")]
+# assert_eq!(matched, ["chunk:1/offset:0x0", "chunk:1/offset:0xC", "chunk:2/offset:0xD"]);
+assert_eq!("type == 256".parse::<Filter>().is_err(), true);
+assert_eq!("kind = 256".parse::<Filter>().is_err(), true);
+```
+*/
+
+use crate::{Errors, fmt::Event, model::section::SectionCore};
+
+/// A field of an event
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Field {
+    Type,
+    Time,
+    Segment,
+    SegmentId,
+    Param,
+    Len,
+}
+
+impl Field {
+    /// Get a field from its name
+    fn from_name(name: &str) -> Option<Field> {
+        Some(match name {
+            "type" => Field::Type,
+            "time" => Field::Time,
+            "segment" => Field::Segment,
+            "segment_id" => Field::SegmentId,
+            "param" => Field::Param,
+            "len" => Field::Len,
+            _ => return None,
+        })
+    }
+    /// Get the field's value in an event
+    fn value(&self, event: &Event) -> Value {
+        match self {
+            Field::Type => Value::Number(event.section.data_type() as f64),
+            Field::Time => Value::Number(event.time as f64),
+            Field::Segment => Value::Text(if event.address.is_keyframe() {"keyframe"} else {"chunk"}.to_string()),
+            Field::SegmentId => Value::Number(event.address.segment_id() as f64),
+            Field::Param => Value::Number(event.section.params().iter().rev().fold(0u64, |p, b| (p << 8) | *b as u64) as f64),
+            Field::Len => Value::Number(event.section.data_len() as f64),
+        }
+    }
+}
+
+/// A value fields are compared to
+#[derive(Clone, Debug, PartialEq)]
+enum Value {
+    Number(f64),
+    Text(String),
+}
+
+/// A comparison operator
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+enum Operator {
+    Eq,
+    Ne,
+    Lt,
+    Le,
+    Gt,
+    Ge,
+}
+
+impl Operator {
+    /// Compare two values, values of different kinds are never equal nor ordered
+    fn compare(&self, left: &Value, right: &Value) -> bool {
+        let ordering = match (left, right) {
+            (Value::Number(l), Value::Number(r)) => l.partial_cmp(r),
+            (Value::Text(l), Value::Text(r)) => Some(l.cmp(r)),
+            _ => None,
+        };
+        match (self, ordering) {
+            (Operator::Ne, None) => true,
+            (_, None) => false,
+            (Operator::Eq, Some(o)) => o.is_eq(),
+            (Operator::Ne, Some(o)) => o.is_ne(),
+            (Operator::Lt, Some(o)) => o.is_lt(),
+            (Operator::Le, Some(o)) => o.is_le(),
+            (Operator::Gt, Some(o)) => o.is_gt(),
+            (Operator::Ge, Some(o)) => o.is_ge(),
+        }
+    }
+}
+
+/// A parsed filter expression
+#[derive(Clone, Debug, PartialEq)]
+pub struct Filter {
+    /// The expression's root node
+    root: Node,
+}
+
+/// A node of a filter expression
+#[derive(Clone, Debug, PartialEq)]
+enum Node {
+    Compare(Field, Operator, Value),
+    Not(Box<Node>),
+    And(Box<Node>, Box<Node>),
+    Or(Box<Node>, Box<Node>),
+}
+
+impl Node {
+    /// Whether an event matches the node
+    fn matches(&self, event: &Event) -> bool {
+        match self {
+            Node::Compare(field, operator, value) => operator.compare(&field.value(event), value),
+            Node::Not(node) => !node.matches(event),
+            Node::And(left, right) => left.matches(event) && right.matches(event),
+            Node::Or(left, right) => left.matches(event) || right.matches(event),
+        }
+    }
+}
+
+impl Filter {
+    /// Whether an event matches the filter
+    pub fn matches(&self, event: &Event) -> bool {
+        self.root.matches(event)
+    }
+}
+
+/// Selects the events that match an expression and that concern a participant of the game
+///
+/// An event concerns a participant if it is caused by the participant's entity, whose ID is
+/// the section's parameters (see the `param` field), or if it targets the entity, such as a
+/// ping on the participant's champion. What each participant could see is not recorded in the
+/// sections, so events are not filtered on visibility.
+///
+/// Participants are designated by their entity ID: the metadata does not hold the players'
+/// entity IDs and the sections that spawn champions are not decoded, so a player's name can not
+/// be resolved to an entity.
+///
+/// # Examples
+///
+/// ```rust
+/// use lolrofl::{filter::{EventFilter, Filter}, fmt::Event, iter::PayloadIterOptions};
+/// use lolrofl::model::section::{SectionAddress, SectionCore, SectionPosition};
+///
+/// let filter = EventFilter::by_participant(7).expression("type = 256".parse().unwrap());
+///
+/// # let content = lolrofl::test::sample_full_file_0();
+/// // let content = std::fs::read("game.rofl").unwrap();
+/// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+/// # let mut matched: Vec<String> = Vec::new();
+/// # #[cfg(feature = "payload")]
+/// for segment in game.segment_iter(&PayloadIterOptions::with_data()).unwrap() {
+///     let (mut time, mut offset) = (0., 0);
+///     for section in segment.section_iter().unwrap() {
+///         time = section.time().to_absolute(time);
+///         let position = SectionPosition::Offset(offset);
+///         let address = if segment.is_chunk() { SectionAddress::chunk(segment.id(), position) } else { SectionAddress::keyframe(segment.id(), position) };
+///         offset += section.len();
+///         let event = Event { address, time, section: &section };
+///         if filter.matches(&event) {
+///             println!("{}", event);
+/// #           matched.push(address.to_string());
+///         }
+///     }
+/// }
+/// # #[cfg(feature = "payload")]
+/// # assert_eq!(matched, ["chunk:2/offset:0xD"]);
+/// # assert_eq!(EventFilter::new(), EventFilter::default());
+/// ```
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct EventFilter {
+    /// The expression events must match
+    expression: Option<Filter>,
+    /// The entity ID of the participant events must concern
+    participant: Option<u32>,
+}
+
+impl EventFilter {
+    /// Build a filter that selects all events
+    pub fn new() -> EventFilter { EventFilter::default() }
+    /// Build a filter that selects the events caused by or targeting a participant's entity
+    pub fn by_participant(entity: u32) -> EventFilter {
+        EventFilter::new().participant(entity)
+    }
+    /// Only select the events that match an expression
+    pub fn expression(mut self, expression: Filter) -> Self {
+        self.expression = Some(expression);
+        self
+    }
+    /// Only select the events caused by or targeting a participant's entity
+    pub fn participant(mut self, entity: u32) -> Self {
+        self.participant = Some(entity);
+        self
+    }
+    /// Whether an event is selected by the filter
+    pub fn matches(&self, event: &Event) -> bool {
+        self.expression.as_ref().is_none_or(|expression| expression.matches(event))
+            && self.participant.is_none_or(|entity| concerns(event, entity))
+    }
+}
+
+/// Whether an event is caused by or targets an entity
+fn concerns(event: &Event, entity: u32) -> bool {
+    use crate::model::section::decode::{Decoded, PingDecoder, SectionDecoder};
+
+    let params = event.section.params();
+    if !params.is_empty() && params.iter().rev().fold(0u64, |p, b| (p << 8) | *b as u64) == entity as u64 {
+        return true;
+    }
+    let ping = PingDecoder;
+    event.section.data_type() == ping.data_type()
+        && matches!(ping.decode(event.section), Ok(Decoded::Ping(p)) if p.target() == Some(entity))
+}
+
+/// A recursive descent parser of filter expressions
+struct Parser<'a> {
+    /// The expression
+    source: &'a str,
+    /// The offset of the next character to read
+    position: usize,
+}
+
+impl<'a> Parser<'a> {
+    /// Build an error at the current position
+    fn error(&self, expected: &'static str) -> Errors {
+        Errors::InvalidFilter { position: self.position, expected }
+    }
+    /// The rest of the expression, after whitespace
+    fn rest(&mut self) -> &'a str {
+        let rest = &self.source[self.position..];
+        self.position += rest.len() - rest.trim_start().len();
+        &self.source[self.position..]
+    }
+    /// Consume a token if the expression continues with it
+    fn eat(&mut self, token: &str) -> bool {
+        if self.rest().starts_with(token) {
+            self.position += token.len();
+            true
+        } else {
+            false
+        }
+    }
+    /// Parse `or := and ("||" and)*`
+    fn or(&mut self) -> Result<Node, Errors> {
+        let mut node = self.and()?;
+        while self.eat("||") {
+            node = Node::Or(Box::new(node), Box::new(self.and()?));
+        }
+        Ok(node)
+    }
+    /// Parse `and := unary ("&&" unary)*`
+    fn and(&mut self) -> Result<Node, Errors> {
+        let mut node = self.unary()?;
+        while self.eat("&&") {
+            node = Node::And(Box::new(node), Box::new(self.unary()?));
+        }
+        Ok(node)
+    }
+    /// Parse `unary := "!" unary | "(" or ")" | comparison`
+    fn unary(&mut self) -> Result<Node, Errors> {
+        if self.rest().starts_with("!=") {
+            return Err(self.error("a field"));
+        }
+        if self.eat("!") {
+            return Ok(Node::Not(Box::new(self.unary()?)));
+        }
+        if self.eat("(") {
+            let node = self.or()?;
+            if !self.eat(")") {
+                return Err(self.error("')'"));
+            }
+            return Ok(node);
+        }
+        self.comparison()
+    }
+    /// Parse `comparison := field operator value`
+    fn comparison(&mut self) -> Result<Node, Errors> {
+        let rest = self.rest();
+        let len = rest.find(|c: char| !(c.is_ascii_alphanumeric() || c == '_')).unwrap_or(rest.len());
+        let field = Field::from_name(&rest[..len]).ok_or_else(|| self.error("a field"))?;
+        self.position += len;
+        // Two-character operators are checked first, "==" is rejected as ambiguous
+        let operator = [("!=", Operator::Ne), ("<=", Operator::Le), (">=", Operator::Ge), ("<", Operator::Lt), (">", Operator::Gt)]
+            .into_iter()
+            .find(|(token, _)| self.eat(token))
+            .map(|(_, operator)| operator);
+        let operator = match operator {
+            Some(operator) => operator,
+            None if !self.rest().starts_with("==") && self.eat("=") => Operator::Eq,
+            None => return Err(self.error("an operator")),
+        };
+        Ok(Node::Compare(field, operator, self.value()?))
+    }
+    /// Parse a number or a quoted string
+    fn value(&mut self) -> Result<Value, Errors> {
+        let rest = self.rest();
+        if let Some(quote) = rest.chars().next().filter(|c| *c == '\'' || *c == '"') {
+            let end = rest[1..].find(quote).ok_or_else(|| self.error("a closing quote"))?;
+            let text = rest[1..end + 1].to_string();
+            self.position += end + 2;
+            return Ok(Value::Text(text));
+        }
+        let len = rest.find(|c: char| !(c.is_ascii_digit() || c == '.' || c == '-')).unwrap_or(rest.len());
+        let number = rest[..len].parse().map_err(|_| self.error("a number or a quoted string"))?;
+        self.position += len;
+        Ok(Value::Number(number))
+    }
+}
+
+impl std::str::FromStr for Filter {
+    type Err = Errors;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parser = Parser { source: s, position: 0 };
+        let root = parser.or()?;
+        if !parser.rest().is_empty() {
+            return Err(parser.error("'&&', '||' or the end of the expression"));
+        }
+        Ok(Filter { root })
+    }
+}
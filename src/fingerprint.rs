@@ -0,0 +1,44 @@
+/*!
+Identify files that hold the same game
+
+Two files with the same fingerprint hold the same game data, even if their names,
+signatures or metadata differ (e.g. re-downloads of the same replay).
+*/
+
+use crate::{Errors, Rofl};
+
+/// The fingerprint of a ROFL file's game
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash, PartialOrd, Ord)]
+pub struct Fingerprint {
+    /// The game's ID
+    match_id: u64,
+    /// A FNV-1a digest of the file's payload
+    digest: u64,
+}
+
+impl Fingerprint {
+    /// The ID of the fingerprinted game
+    pub fn match_id(&self) -> u64 { self.match_id }
+    /// The digest of the fingerprinted payload
+    pub fn digest(&self) -> u64 { self.digest }
+    /// Compute the fingerprint of a file's game from its payload
+    pub(crate) fn from_rofl(rofl: &Rofl) -> Result<Fingerprint, Errors> {
+        let head = rofl.head();
+        let payload = rofl.payload()?;
+        if rofl.data.len() < head.file_len() || head.file_len() < head.payload_offset() {
+            return Err(Errors::too_small(head.file_len().max(head.payload_offset()), rofl.data.len()));
+        }
+        let mut digest: u64 = 0xcbf29ce484222325;
+        for byte in &rofl.data[head.payload_offset()..head.file_len()] {
+            digest ^= *byte as u64;
+            digest = digest.wrapping_mul(0x100000001b3);
+        }
+        Ok(Fingerprint { match_id: payload.id(), digest })
+    }
+}
+
+impl std::fmt::Display for Fingerprint {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}-{:016x}", self.match_id, self.digest)
+    }
+}
@@ -0,0 +1,466 @@
+/*!
+Renderers used to display the model's structs
+
+All model structs implement `Display`. The alternate flag (`{:#}`) renders a detailed
+and aligned multi-line view, [`Compact`] renders a value on a single line
+and [`Table`] renders a list of segments or sections as an aligned table.
+
+[`ColorTable`] renders the same tables for terminals, with the types of segments colored and
+the section types without a known layout dimmed. [`Colors`] paints any other value with the
+same styles, colors are only written when they are enabled so that output may be piped.
+
+# Examples
+
+```rust
+use lolrofl::fmt::{Compact, Table};
+
+# let content = lolrofl::test::sample_full_file_0();
+// let content = std::fs::read("game.rofl").unwrap();
+let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+let payload = game.payload().unwrap();
+
+println!("{:#}", payload); // Detailed view
+println!("{}", Compact(&payload)); // Single-line view
+# assert_eq!(format!("{}", Compact(&payload)), "Game 5900755914 (01:31.722, 2 chunks, 1 keyframes)");
+
+let segments: Vec<_> = game.segment_iter(&lolrofl::iter::PayloadIterOptions::new()).unwrap().collect();
+print!("{}", Table(&segments));
+# #[cfg(feature = "payload")] {
+# let segments: Vec<_> = game.segment_iter(&lolrofl::iter::PayloadIterOptions::with_data()).unwrap().collect();
+# assert_eq!(Table(&segments).to_string().lines().nth(1).unwrap().trim_end(), "       1 Chunk            48          0          0  00:00.500  00:00.515");
+# }
+```
+
+```rust
+use lolrofl::fmt::{Colors, ColorTable, Style, Table};
+
+# let content = lolrofl::test::sample_full_file_0();
+// let content = std::fs::read("game.rofl").unwrap();
+let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+let segments: Vec<_> = game.segment_iter(&lolrofl::iter::PayloadIterOptions::new()).unwrap().collect();
+
+let colors = Colors::new(true); // e.g. if the standard output is a terminal
+print!("{}", ColorTable(&segments, colors));
+println!("{}", colors.paint("SUCCESS", Style::Success));
+# assert_eq!(ColorTable(&segments, Colors::new(false)).to_string(), Table(&segments).to_string());
+# assert_eq!(format!("[{:<9}]", colors.paint("FAIL", Style::Failure)), "[\x1b[31mFAIL     \x1b[0m]");
+# assert_eq!(ColorTable(&segments, colors).to_string().contains("\x1b[35mKeyframe"), true);
+```
+*/
+
+use std::collections::BTreeMap;
+use std::fmt::{Display, Formatter, Result};
+use crate::model::{BinHeader, PayloadHeader, Segment, section::{format_game_time, GenericSection, SectionAddress, SectionCore}};
+
+/// Render a model struct on a single line
+pub struct Compact<'a, T>(pub &'a T);
+
+/// Render a list of model structs as an aligned table with a title row
+pub struct Table<'a, T>(pub &'a [T]);
+
+/// Render a list of model structs as [`Table`] does, styled with ANSI colors if they are enabled
+pub struct ColorTable<'a, T>(pub &'a [T], pub Colors);
+
+/// The styles [`Colors`] paints values with
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Style {
+    /// A chunk's type
+    Chunk,
+    /// A keyframe's type
+    Keyframe,
+    /// A value without a known meaning, such as a section type without a known layout
+    Unknown,
+    /// A table's title row
+    Title,
+    /// A check that passed
+    Success,
+    /// A check that failed
+    Failure,
+    /// A tolerated inconsistency
+    Warning,
+}
+
+impl Style {
+    /// The ANSI escape code that starts the style
+    fn code(&self) -> &'static str {
+        match self {
+            Style::Chunk => "\x1b[36m",
+            Style::Keyframe => "\x1b[35m",
+            Style::Unknown => "\x1b[2m",
+            Style::Title => "\x1b[1m",
+            Style::Success => "\x1b[32m",
+            Style::Failure => "\x1b[31m",
+            Style::Warning => "\x1b[33m",
+        }
+    }
+}
+
+/// Whether values are painted with ANSI colors
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Colors {
+    /// Whether colors are written
+    enabled: bool,
+}
+
+impl Colors {
+    /// Build a painter that writes colors only if `enabled` is set
+    pub fn new(enabled: bool) -> Colors { Colors { enabled } }
+    /// Whether colors are written
+    pub fn is_enabled(&self) -> bool { self.enabled }
+    /// Paint a value with a style
+    pub fn paint<T: Display>(&self, value: T, style: Style) -> Paint<T> {
+        Paint { value, style, enabled: self.enabled }
+    }
+    /// Paint the name of a segment's type
+    pub fn segment_kind(&self, segment: &Segment) -> Paint<&'static str> {
+        let style = if segment.is_chunk() { Style::Chunk } else if segment.is_keyframe() { Style::Keyframe } else { Style::Unknown };
+        self.paint(segment_kind(segment), style)
+    }
+    /// Paint a section's data type, dimmed if its layout is not known
+    pub fn section_type(&self, data_type: u32) -> Paint<u32> {
+        let known = crate::model::section::kind_info(data_type).is_some();
+        Paint { value: data_type, style: Style::Unknown, enabled: self.enabled && !known }
+    }
+}
+
+/// A value painted by [`Colors`]
+///
+/// The width and alignment of the format apply to the value only, so that painted values keep
+/// the columns of a table aligned.
+#[derive(Clone, Copy, Debug)]
+pub struct Paint<T> {
+    /// The painted value
+    value: T,
+    /// The value's style
+    style: Style,
+    /// Whether colors are written
+    enabled: bool,
+}
+
+impl<T: Display> Display for Paint<T> {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        if !self.enabled {
+            return f.pad(&self.value.to_string());
+        }
+        write!(f, "{}", self.style.code())?;
+        f.pad(&self.value.to_string())?;
+        write!(f, "\x1b[0m")
+    }
+}
+
+/// The number of sections of a segment, rendered as `analyze` does in stats mode
+///
+/// The alternate flag (`{:#}`) adds the inventory of sections
+///
+/// # Examples
+///
+/// ```rust
+/// use lolrofl::{fmt::SegmentStats, iter::PayloadIterOptions};
+///
+/// # let content = lolrofl::test::sample_full_file_0();
+/// // let content = std::fs::read("game.rofl").unwrap();
+/// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+/// # #[cfg(feature = "payload")] {
+/// let chunk = game.segment_iter(&PayloadIterOptions::with_data()).unwrap().next().unwrap();
+/// let stats = SegmentStats::from_segment(&chunk);
+/// assert_eq!(format!("{:#}", stats), "Chunk 001 (0000024): 3 {256: 2, 512: 1, }");
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct SegmentStats {
+    /// The segment's type name
+    kind: &'static str,
+    /// The segment's ID
+    id: u32,
+    /// The length of the segment's decrypted data
+    data_len: usize,
+    /// The number of counted sections
+    sections: usize,
+    /// The number of sections for each key, usually the sections' data type
+    inventory: BTreeMap<usize, usize>,
+}
+
+impl SegmentStats {
+    /// Build the stats of a segment from already counted sections
+    pub fn new(segment: &Segment, sections: usize, inventory: BTreeMap<usize, usize>) -> SegmentStats {
+        SegmentStats { kind: segment_kind(segment), id: segment.id(), data_len: segment.data().len(), sections, inventory }
+    }
+    /// Count the sections of a loaded segment by data type, counting stops at the first invalid section
+    pub fn from_segment(segment: &Segment) -> SegmentStats {
+        let mut inventory = BTreeMap::new();
+        let mut sections = 0;
+        if let Ok(iterator) = segment.section_iter() {
+            for section in iterator {
+                *inventory.entry(section.data_type() as usize).or_default() += 1;
+                sections += 1;
+            }
+        }
+        SegmentStats::new(segment, sections, inventory)
+    }
+    /// The segment's type name
+    pub fn kind(&self) -> &'static str { self.kind }
+    /// The segment's ID
+    pub fn id(&self) -> u32 { self.id }
+    /// The length of the segment's decrypted data
+    pub fn data_len(&self) -> usize { self.data_len }
+    /// The number of counted sections
+    pub fn sections(&self) -> usize { self.sections }
+    /// The number of sections for each key
+    pub fn inventory(&self) -> &BTreeMap<usize, usize> { &self.inventory }
+}
+
+impl Display for SegmentStats {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "{} {:#03} ({:#07}): {}", self.kind, self.id, self.data_len, self.sections)?;
+        if f.alternate() {
+            write!(f, " {{")?;
+            for (key, count) in &self.inventory {
+                write!(f, "{}: {}, ", key, count)?;
+            }
+            write!(f, "}}")?;
+        }
+        Ok(())
+    }
+}
+
+/// A section along with its address and absolute time, rendered as `analyze` does in human detail mode
+///
+/// # Examples
+///
+/// ```rust
+/// use lolrofl::{fmt::Event, iter::PayloadIterOptions};
+/// use lolrofl::model::section::{SectionAddress, SectionCore, SectionPosition};
+///
+/// # let content = lolrofl::test::sample_full_file_0();
+/// // let content = std::fs::read("game.rofl").unwrap();
+/// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+/// # #[cfg(feature = "payload")] {
+/// for segment in game.segment_iter(&PayloadIterOptions::with_data()).unwrap().filter(|s| s.is_chunk()) {
+///     let (mut time, mut offset) = (0., 0);
+///     for section in segment.section_iter().unwrap() {
+///         time = section.time().to_absolute(time);
+///         let address = SectionAddress::chunk(segment.id(), SectionPosition::Offset(offset));
+///         offset += section.len();
+///         println!("{}", Event { address, time, section: &section });
+///     }
+/// }
+/// # }
+/// ```
+#[derive(Clone, Debug)]
+pub struct Event<'a> {
+    /// The section's address
+    pub address: SectionAddress,
+    /// The section's absolute time in seconds
+    pub time: f32,
+    /// The section
+    pub section: &'a GenericSection<'a>,
+}
+
+impl Display for Event<'_> {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(
+            f, "[{}] {:#04}#{:#03} at {} ({:?}): {:?}",
+            self.address, self.section.data_type(), self.section.kind(), format_game_time(self.time),
+            self.section.params(), self.section.raw_data().unwrap_or(&[]),
+        )
+    }
+}
+
+/// Get the name of a segment's type
+pub(crate) fn segment_kind(segment: &Segment) -> &'static str {
+    if segment.is_chunk() {"Chunk"} else if segment.is_keyframe() {"Keyframe"} else {"Segment"}
+}
+
+impl Display for BinHeader {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        if f.alternate() {
+            writeln!(f, "{:<16}{} bytes", "Header length:", self.header_len())?;
+            writeln!(f, "{:<16}{} bytes", "File length:", self.file_len())?;
+            writeln!(f, "{:<16}{}..{} ({} bytes)", "Metadata:",
+                self.metadata_offset(), self.metadata_offset() + self.metadata_len(), self.metadata_len())?;
+            writeln!(f, "{:<16}{}..{} ({} bytes)", "Payload header:",
+                self.payload_header_offset(), self.payload_header_offset() + self.payload_header_len(), self.payload_header_len())?;
+            write!(f, "{:<16}{}..{}", "Payload:", self.payload_offset(), self.file_len())
+        } else {
+            write!(
+                f,
+                concat!(
+                    "Header size: {0}\n",
+                    "File size: {1}\n",
+                    "Metadata offset: {2}\n",
+                    "Metadata length: {3}\n",
+                    "Payload Header offset: {4}\n",
+                    "Payload Header Length: {5}\n",
+                    "Payload offset: {6}",
+                ),
+                self.header_len(),
+                self.file_len(),
+                self.metadata_offset(),
+                self.metadata_len(),
+                self.payload_header_offset(),
+                self.payload_header_len(),
+                self.payload_offset(),
+            )
+        }
+    }
+}
+
+impl Display for Compact<'_, BinHeader> {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(
+            f, "File of {} bytes (metadata: {}+{}, payload header: {}+{}, payload: {})",
+            self.0.file_len(),
+            self.0.metadata_offset(), self.0.metadata_len(),
+            self.0.payload_header_offset(), self.0.payload_header_len(),
+            self.0.payload_offset(),
+        )
+    }
+}
+
+impl Display for PayloadHeader {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        if f.alternate() {
+            writeln!(f, "{:<16}{}", "Match ID:", self.id())?;
+            writeln!(f, "{:<16}{}", "Duration:", format_game_time(self.duration() as f32 / 1000.))?;
+            writeln!(f, "{:<16}{} (loading ends with chunk {}, game starts with chunk {})", "Chunks:",
+                self.chunk_count(), self.load_end_chunk(), self.game_start_chunk())?;
+            writeln!(f, "{:<16}{} (every {} ms)", "Keyframes:", self.keyframe_count(), self.keyframe_interval())?;
+            write!(f, "{:<16}{:?}", "Encryption key:", self.encryption_key())
+        } else {
+            write!(
+                f,
+                concat!(
+                    "Match ID: {0}\n",
+                    "Match Length: {1} ms\n",
+                    "Keyframe count: {2}\n",
+                    "Last loading Chunk: {3}\n",
+                    "First game chunk: {4}\n",
+                    "Total chunk count: {5}\n",
+                    "Keyframe interval: {6}\n",
+                    "Encryption key ({7} chars): {8:?}",
+                ),
+                self.id(),
+                self.duration(),
+                self.keyframe_count(),
+                self.load_end_chunk(),
+                self.game_start_chunk(),
+                self.chunk_count(),
+                self.keyframe_interval(),
+                self.encryption_key().len(),
+                self.encryption_key(),
+            )
+        }
+    }
+}
+
+impl Display for Compact<'_, PayloadHeader> {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(
+            f, "Game {} ({}, {} chunks, {} keyframes)",
+            self.0.id(),
+            format_game_time(self.0.duration() as f32 / 1000.),
+            self.0.chunk_count(),
+            self.0.keyframe_count(),
+        )
+    }
+}
+
+impl Display for Segment {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        if f.alternate() {
+            writeln!(f, "{} {}", segment_kind(self), self.id())?;
+            writeln!(f, "  {:<12}{} bytes", "Length:", self.len())?;
+            writeln!(f, "  {:<12}{}", "Offset:", self.offset())?;
+            writeln!(f, "  {:<12}{}", "Next chunk:", self.next_chunk_id())?;
+            write!(f, "  {:<12}{}", "Loaded:", if self.is_loaded() { format!("{} bytes", self.data().len()) } else { "no".to_string() })
+        } else {
+            write!(f,
+                "{} {} (len: {}, next: {}, offset: {}, loaded: {})",
+                segment_kind(self),
+                self.id(), self.len(), self.next_chunk_id(), self.offset(), self.is_loaded(),
+            )
+        }
+    }
+}
+
+impl Display for Compact<'_, Segment> {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "{} {} ({} bytes at {})", segment_kind(self.0), self.0.id(), self.0.len(), self.0.offset())
+    }
+}
+
+impl Display for Table<'_, Segment> {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "{}", ColorTable(self.0, Colors::default()))
+    }
+}
+
+impl Display for ColorTable<'_, Segment> {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        let timed = self.0.iter().any(Segment::is_loaded);
+        let mut title = format!("{:>8} {:<8} {:>10} {:>10} {:>10}", "ID", "Type", "Length", "NextChunk", "Offset");
+        if timed {
+            title.push_str(&format!(" {:>10} {:>10}", "Start", "End"));
+        }
+        writeln!(f, "{}", self.1.paint(title, Style::Title))?;
+        for segment in self.0 {
+            write!(
+                f, "{:>8} {:<8} {:>10} {:>10} {:>10}",
+                segment.id(), self.1.segment_kind(segment), segment.len(), segment.next_chunk_id(), segment.offset(),
+            )?;
+            if timed {
+                match segment.time_bounds() {
+                    Ok((start, end)) => write!(f, " {:>10} {:>10}", format_game_time(start), format_game_time(end))?,
+                    Err(_) => write!(f, " {:>10} {:>10}", "-", "-")?,
+                }
+            }
+            writeln!(f)?;
+        }
+        Ok(())
+    }
+}
+
+impl Display for GenericSection<'_> {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        if f.alternate() {
+            writeln!(f, "Section {}", self.data_type())?;
+            writeln!(f, "  {:<12}{:#04x}", "Marker:", self.kind())?;
+            writeln!(f, "  {:<12}{}", "Time:", self.time())?;
+            writeln!(f, "  {:<12}{:?}", "Parameters:", self.params())?;
+            write!(f, "  {:<12}{:?}", "Data:", self.raw_data().unwrap_or(&[]))
+        } else {
+            write!(
+                f, "Section {} (marker: {:#04x}, time: {}, params: {:?}, len: {})",
+                self.data_type(), self.kind(), self.time(), self.params(), self.data_len(),
+            )
+        }
+    }
+}
+
+impl Display for Compact<'_, GenericSection<'_>> {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "{:#04}#{:#03} {} {:?} ({} bytes)", self.0.data_type(), self.0.kind(), self.0.time(), self.0.params(), self.0.data_len())
+    }
+}
+
+impl Display for Table<'_, GenericSection<'_>> {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        write!(f, "{}", ColorTable(self.0, Colors::default()))
+    }
+}
+
+impl Display for ColorTable<'_, GenericSection<'_>> {
+    fn fmt(&self, f: &mut Formatter) -> Result {
+        let title = format!("{:>6} {:>6} {:>12} {:<16} {:>8}", "Type", "Marker", "Time", "Params", "Length");
+        writeln!(f, "{}", self.1.paint(title, Style::Title))?;
+        for section in self.0 {
+            writeln!(
+                f, "{:>6} {:>#6x} {:>12} {:<16} {:>8}",
+                self.1.section_type(section.data_type()), section.kind(), section.time().to_string(),
+                format!("{:?}", section.params()), section.data_len(),
+            )?;
+        }
+        Ok(())
+    }
+}
@@ -1,8 +1,12 @@
-/*!
- * Scope for all the iterators that may be used to process ROFL data
- */
-
-mod payload;
-mod segment;
-pub use payload::*;
+/*!
+ * Scope for all the iterators that may be used to process ROFL data
+ */
+
+mod cancel;
+mod page;
+mod payload;
+mod segment;
+pub use cancel::*;
+pub use page::*;
+pub use payload::*;
 pub use segment::*;
\ No newline at end of file
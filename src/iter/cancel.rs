@@ -0,0 +1,46 @@
+use std::sync::Arc;
+use std::sync::atomic::{AtomicBool, Ordering};
+
+/// A flag shared between threads to request that long-running iterations stop early
+///
+/// Iterators that were given a token check it before processing each item and stop
+/// with `Errors::Cancelled` once it is cancelled.
+///
+/// # Examples
+///
+/// ```rust
+/// use lolrofl::iter::{CancellationToken, PayloadIterOptions};
+///
+/// # let content = lolrofl::test::sample_full_file_0();
+/// // let content = std::fs::read("game.rofl").unwrap();
+/// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+///
+/// let token = CancellationToken::new();
+/// let mut segments = game.segment_iter(&PayloadIterOptions::new().cancel_token(&token)).unwrap();
+/// assert_eq!(segments.next().is_some(), true);
+///
+/// // e.g. from another thread, when the client that requested the data disconnects
+/// token.cancel();
+/// assert_eq!(segments.next().is_none(), true);
+/// assert_eq!(matches!(segments.to_error(), lolrofl::Errors::Cancelled), true);
+/// ```
+#[derive(Clone, Debug, Default)]
+pub struct CancellationToken {
+    /// Whether cancellation was requested
+    cancelled: Arc<AtomicBool>,
+}
+
+impl CancellationToken {
+    /// Build a new token that is not cancelled
+    pub fn new() -> CancellationToken { CancellationToken::default() }
+    /// Request that iterations that hold the token stop
+    pub fn cancel(&self) { self.cancelled.store(true, Ordering::Relaxed); }
+    /// Whether cancellation was requested
+    pub fn is_cancelled(&self) -> bool { self.cancelled.load(Ordering::Relaxed) }
+}
+
+impl PartialEq for CancellationToken {
+    fn eq(&self, other: &Self) -> bool { Arc::ptr_eq(&self.cancelled, &other.cancelled) }
+}
+
+impl Eq for CancellationToken {}
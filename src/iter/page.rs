@@ -0,0 +1,90 @@
+/// A page of items: the items to skip first and the maximum number of items to keep
+///
+/// A pagination counts the items it was shown across all the iterators it is applied to, so a
+/// page may span the sections of several segments.
+///
+/// # Examples
+///
+/// ```rust
+/// use lolrofl::iter::{Pagination, PayloadIterOptions};
+///
+/// # let content = lolrofl::test::sample_full_file_0();
+/// // let content = std::fs::read("game.rofl").unwrap();
+/// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+///
+/// # #[cfg(feature = "payload")] {
+/// // The 2nd to 4th sections of the payload
+/// let mut page = Pagination::new(1, Some(3));
+/// let mut types = Vec::new();
+/// for segment in game.segment_iter(&PayloadIterOptions::with_data()).unwrap() {
+///     types.extend(page.apply(segment.section_iter().unwrap()).map(|section| section.data_type()));
+///     if page.is_done() {
+///         break;
+///     }
+/// }
+/// # assert_eq!(types.len(), 3);
+/// # assert_eq!(page.seen(), 4);
+/// # }
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub struct Pagination {
+    /// The number of items to skip before the page starts
+    skip: usize,
+    /// The maximum number of items in the page, `None` to keep all the items that are not skipped
+    limit: Option<usize>,
+    /// The number of items shown to the pagination so far
+    seen: usize,
+}
+
+impl Pagination {
+    /// Build a pagination that skips `skip` items then keeps at most `limit` items
+    pub fn new(skip: usize, limit: Option<usize>) -> Pagination {
+        Pagination { skip, limit, seen: 0 }
+    }
+    /// The number of items skipped before the page starts
+    pub fn skip(&self) -> usize { self.skip }
+    /// The maximum number of items in the page
+    pub fn limit(&self) -> Option<usize> { self.limit }
+    /// The number of items shown to the pagination so far
+    pub fn seen(&self) -> usize { self.seen }
+    /// Count an item and tell whether it is in the page
+    pub fn admit(&mut self) -> bool {
+        if self.is_done() {
+            return false;
+        }
+        self.seen += 1;
+        self.seen > self.skip
+    }
+    /// Whether the page is full and no more items will be admitted
+    pub fn is_done(&self) -> bool {
+        self.limit.is_some_and(|limit| self.seen >= self.skip.saturating_add(limit))
+    }
+    /// Keep the items of an iterator that are in the page
+    ///
+    /// The returned iterator stops as soon as the page is full, without reading more items.
+    pub fn apply<I: Iterator>(&mut self, iter: I) -> Paged<'_, I> {
+        Paged { pagination: self, iter }
+    }
+}
+
+/// An iterator that only yields the items of another iterator that are in a page, see [`Pagination::apply`]
+#[derive(Debug)]
+pub struct Paged<'a, I> {
+    /// The page's state, shared with the iterators the pagination was applied to before
+    pagination: &'a mut Pagination,
+    /// The paginated iterator
+    iter: I,
+}
+
+impl<I: Iterator> Iterator for Paged<'_, I> {
+    type Item = I::Item;
+    fn next(&mut self) -> Option<Self::Item> {
+        while !self.pagination.is_done() {
+            let item = self.iter.next()?;
+            if self.pagination.admit() {
+                return Some(item);
+            }
+        }
+        None
+    }
+}
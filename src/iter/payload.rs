@@ -1,119 +1,372 @@
-#[cfg(feature="payload")]
-use blowfish::{
-    Blowfish,
-    cipher::{
-        BlockDecryptMut, KeyInit,
-        generic_array::GenericArray,
-    },
-};
-use crate::{Errors, PayloadHeader, Segment};
-use crate::SEGMENT_HEADER_LEN;
-
-/// An iterator for lightweight scanning of data segments in a payload
-pub struct PayloadIterator<'a> {
-    /// The segment's data
-    data: &'a[u8],
-    /// The iterator's mosition in the segment
-    index: usize,
-    /// The number of payload segments to go through
-    segment_count: usize,
-    /// Code of the last error that occured during an iteration
-    last_error: Option<Errors>,
-    /// The decryption cipher key
-    #[cfg(feature="payload")]
-    key: Blowfish::<byteorder::BigEndian>,
-    /// Whether to parse segment data or only iterate over headers
-    parse_data: bool,
-}
-
-impl<'a> PayloadIterator<'a> {
-    /// Build a new iterator from a raw decrypted segment's slice
-    pub fn new(data: &'a[u8], head: &'_ PayloadHeader, parse_data: bool) -> Result<PayloadIterator<'a>, crate::error::Errors> {
-        let segment_count = (head.chunk_count()+head.keyframe_count()) as usize;
-        if data.len() < segment_count*SEGMENT_HEADER_LEN {
-            return Err(Errors::BufferTooSmall);
-        }
-        Ok(PayloadIterator {
-            data,
-            segment_count,
-            parse_data,
-            index: 0,
-            last_error: None,
-            #[cfg(feature="payload")]
-            key: Blowfish::<byteorder::BigEndian>::new_from_slice(&head.segment_encryption_key()[..]).unwrap(),
-        })
-    }
-
-    /// Whether the iterator is valid
-    pub fn is_valid(&self) -> bool { self.last_error.is_none() }
-    /// Get the last error that occured
-    /// 
-    /// Panics if no error occured
-    pub fn to_error(self) -> Errors {self.last_error.unwrap()}
-    /// The index in the data slice the iterator is at
-    /// 
-    /// This should only be used for debugging purposes when
-    /// is_valid returns false after an iteration
-    pub fn internal_index(&self) -> usize { self.index }
-    /// The data slice the iterator is moving through
-    /// 
-    /// This should only be used for debugging purposes when
-    /// is_valid returns false after an iteration
-    pub fn internal_slice(&self) -> &[u8] { self.data }
-}
-
-impl<'a> std::iter::Iterator for PayloadIterator<'a> {
-    type Item = Segment;
-
-    fn next(&mut self) -> Option<Self::Item> {
-        if self.index >= self.segment_count {
-            return None;
-        }
-        #[allow(unused_mut)]
-        Segment::from_slice(&self.data[self.index*SEGMENT_HEADER_LEN..])
-        .and_then(|mut f| {
-            #[cfg(feature="payload")]
-            {
-                if self.parse_data {
-                    let segment_data_start = SEGMENT_HEADER_LEN * self.segment_count + f.offset();
-                    if self.data.len() < segment_data_start + f.len() {
-                        return Err(Errors::BufferTooSmall);
-                    } else {
-                        decrypt_segment(&self.data[segment_data_start..segment_data_start+f.len()], f.data_mut(), &mut self.key)?;
-                    }
-                }
-            }
-            self.index += 1;
-            Ok(f)
-        }).or_else(|e|{
-            self.last_error = Some(e);
-            Err(Errors::NoData)
-        }).ok()
-    }
-}
-
-/// Decrypt a payload segment.
-/// The provided slice must match the exact extent of the encrypted data
-#[cfg(feature="payload")]
-fn decrypt_segment(cipher: &[u8], out: &mut Vec<u8>, key: &mut Blowfish::<byteorder::BigEndian>) -> Result<(), crate::error::Errors> {
-    use std::io::Read;
-
-    let mut data_store = cipher.to_vec();
-
-    for i in (0..data_store.len()).step_by(8) {
-        key.decrypt_block_mut(
-            GenericArray::from_mut_slice(&mut data_store[i..i+8])
-        );
-    }
-
-    let depad_size = data_store[data_store.len()-1] as usize;
-    assert_eq!(data_store.len() >= depad_size, true);
-    data_store.resize(data_store.len()-depad_size, 0);
-
-    let mut decoder = flate2::read::GzDecoder::new(&data_store[..]);
-    let decoder_result = decoder.read_to_end(out);
-    if decoder_result.is_err() {
-        return Err(Errors::InvalidBuffer);
-    }
-    Ok(())
-}
\ No newline at end of file
+#[cfg(feature="payload")]
+use blowfish::{
+    Blowfish,
+    cipher::{
+        BlockDecryptMut, BlockEncryptMut, KeyInit,
+        generic_array::GenericArray,
+    },
+};
+use crate::{Errors, PayloadHeader, Segment, SegmentKind};
+use crate::SEGMENT_HEADER_LEN;
+use super::CancellationToken;
+
+/// Options that control how a payload's segments are iterated over
+///
+/// # Examples
+///
+/// ```rust
+/// use lolrofl::iter::PayloadIterOptions;
+///
+/// # let content = lolrofl::test::sample_full_file_0();
+/// // let content = std::fs::read("game.rofl").unwrap();
+/// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+///
+/// let options = PayloadIterOptions::new().verify_lengths(true);
+/// assert_eq!(game.segment_iter(&options).unwrap().count(), 3);
+///
+/// # #[cfg(feature = "payload")] {
+/// let options = PayloadIterOptions::with_data().parallel(true);
+/// assert_eq!(game.segment_iter(&options).unwrap().all(|s| s.is_loaded()), true);
+/// # }
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[non_exhaustive]
+pub struct PayloadIterOptions {
+    /// Whether segments' data is decrypted and loaded, requires the `payload` feature
+    pub load_data: bool,
+    /// Whether segments' data is checked to be within the payload even if it is not loaded
+    pub verify_lengths: bool,
+    /// Whether segments' data is decrypted on all available CPUs before iterating
+    pub parallel: bool,
+    /// The decrypted segment encryption key to use instead of the payload header's
+    pub key_override: Option<Vec<u8>>,
+    /// A token checked before each segment to stop the iteration early
+    pub cancel_token: Option<CancellationToken>,
+}
+
+impl PayloadIterOptions {
+    /// Options to iterate over segment headers only
+    pub fn new() -> PayloadIterOptions { PayloadIterOptions::default() }
+    /// Options to iterate over segments with their data loaded
+    pub fn with_data() -> PayloadIterOptions { PayloadIterOptions::new().load_data(true) }
+    /// Set whether segments' data is decrypted and loaded
+    pub fn load_data(mut self, load_data: bool) -> PayloadIterOptions {
+        self.load_data = load_data;
+        self
+    }
+    /// Set whether segments' data is checked to be within the payload
+    pub fn verify_lengths(mut self, verify_lengths: bool) -> PayloadIterOptions {
+        self.verify_lengths = verify_lengths;
+        self
+    }
+    /// Set whether segments' data is decrypted in parallel
+    pub fn parallel(mut self, parallel: bool) -> PayloadIterOptions {
+        self.parallel = parallel;
+        self
+    }
+    /// Set the decrypted segment encryption key to use instead of the payload header's
+    pub fn key_override(mut self, key: &[u8]) -> PayloadIterOptions {
+        self.key_override = Some(key.to_vec());
+        self
+    }
+    /// Set a token that stops the iteration once cancelled
+    pub fn cancel_token(mut self, token: &CancellationToken) -> PayloadIterOptions {
+        self.cancel_token = Some(token.clone());
+        self
+    }
+}
+
+/// An iterator for lightweight scanning of data segments in a payload
+pub struct PayloadIterator<'a> {
+    /// The segment's data
+    data: &'a[u8],
+    /// The iterator's mosition in the segment
+    index: usize,
+    /// The number of payload segments to go through
+    segment_count: usize,
+    /// Whether segments' data is loaded
+    load_data: bool,
+    /// Whether segments' data is checked to be within the payload
+    verify_lengths: bool,
+    /// A token checked before each segment to stop the iteration early
+    cancel_token: Option<CancellationToken>,
+    /// Code of the last error that occured during an iteration
+    last_error: Option<Errors>,
+    /// Decrypted data served instead of the payload's for some segments
+    overrides: Option<&'a std::collections::HashMap<(SegmentKind, u32), Vec<u8>>>,
+    /// The decryption cipher key, only set when segment data is parsed
+    #[cfg(feature="payload")]
+    key: Option<Blowfish::<byteorder::BigEndian>>,
+    /// Segments' data decrypted ahead of the iteration, only set for parallel iterations
+    #[cfg(feature="payload")]
+    decrypted: Vec<Option<Result<Vec<u8>, Errors>>>,
+}
+
+impl<'a> PayloadIterator<'a> {
+    /// Build a new iterator from a raw decrypted segment's slice
+    ///
+    /// # Errors
+    ///
+    /// If the slice is too small to hold all segment headers, or if data loading is requested
+    /// and the lib was compiled without the `payload` feature
+    pub fn new(data: &'a[u8], head: &'_ PayloadHeader, options: &PayloadIterOptions) -> Result<PayloadIterator<'a>, crate::error::Errors> {
+        let segment_count = (head.chunk_count()+head.keyframe_count()) as usize;
+        if data.len() < segment_count*SEGMENT_HEADER_LEN {
+            return Err(Errors::too_small(segment_count*SEGMENT_HEADER_LEN, data.len()));
+        }
+        #[cfg(feature="payload")]
+        let key = if options.load_data {
+            Some(match &options.key_override {
+                Some(key) => Blowfish::<byteorder::BigEndian>::new_from_slice(&key[..]).map_err(|_| Errors::InvalidBuffer)?,
+                None => segment_cipher(head)?,
+            })
+        } else {
+            None
+        };
+        #[cfg(feature="payload")]
+        let decrypted = match &key {
+            Some(key) if options.parallel => decrypt_all(data, segment_count, key, options.cancel_token.as_ref()),
+            _ => Vec::new(),
+        };
+        #[cfg(not(feature="payload"))]
+        if options.load_data {
+            return Err(Errors::FeatureDisabled { feature: "payload" });
+        }
+        Ok(PayloadIterator {
+            data,
+            segment_count,
+            index: 0,
+            load_data: options.load_data,
+            verify_lengths: options.verify_lengths,
+            cancel_token: options.cancel_token.clone(),
+            last_error: None,
+            overrides: None,
+            #[cfg(feature="payload")]
+            key,
+            #[cfg(feature="payload")]
+            decrypted,
+        })
+    }
+
+    /// Serve overridden data instead of decrypting the payload's for some segments
+    pub(crate) fn set_overrides(&mut self, overrides: &'a std::collections::HashMap<(SegmentKind, u32), Vec<u8>>) {
+        self.overrides = Some(overrides);
+    }
+    /// Whether the iterator is valid
+    pub fn is_valid(&self) -> bool { self.last_error.is_none() }
+    /// Move past the segment the iteration failed on so that it can continue with the next ones
+    ///
+    /// Returns the failed segment's header if it could be read, without its data, and the error
+    /// it failed with. Returns `None` if the iteration did not fail or was cancelled.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use lolrofl::iter::PayloadIterOptions;
+    ///
+    /// # let mut content = lolrofl::test::sample_full_file_0();
+    /// # let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+    /// # let start = {
+    /// #     let payload = game.payload().unwrap();
+    /// #     let segments = (payload.chunk_count() + payload.keyframe_count()) as usize;
+    /// #     let segment = game.segment_iter(&PayloadIterOptions::new()).unwrap().nth(1).unwrap();
+    /// #     game.head().payload_offset() + segments * 17 + segment.offset()
+    /// # };
+    /// # // Corrupt the second segment's data
+    /// # content[start..start+8].fill(0);
+    /// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+    /// # #[cfg(feature = "payload")] {
+    /// let mut iterator = game.segment_iter(&PayloadIterOptions::with_data()).unwrap();
+    /// let mut decrypted = 0;
+    /// loop {
+    ///     decrypted += iterator.by_ref().count();
+    ///     match iterator.skip_failed() {
+    ///         Some((segment, error)) => println!("Skipped {:?}: {}", segment, error),
+    ///         None => break,
+    ///     }
+    /// }
+    /// # assert_eq!(iterator.is_valid(), true);
+    /// # assert_eq!(decrypted, game.payload().unwrap().chunk_count() as usize + game.payload().unwrap().keyframe_count() as usize - 1);
+    /// # }
+    /// ```
+    pub fn skip_failed(&mut self) -> Option<(Option<Segment>, Errors)> {
+        if matches!(self.last_error, None | Some(Errors::Cancelled)) {
+            return None;
+        }
+        let error = self.last_error.take()?;
+        let segment = Segment::from_slice(&self.data[self.index*SEGMENT_HEADER_LEN..]).ok();
+        self.index += 1;
+        Some((segment, error))
+    }
+    /// Get the last error that occured
+    /// 
+    /// Panics if no error occured
+    pub fn to_error(self) -> Errors {self.last_error.unwrap()}
+    /// The index in the data slice the iterator is at
+    /// 
+    /// This should only be used for debugging purposes when
+    /// is_valid returns false after an iteration
+    pub fn internal_index(&self) -> usize { self.index }
+    /// The data slice the iterator is moving through
+    /// 
+    /// This should only be used for debugging purposes when
+    /// is_valid returns false after an iteration
+    pub fn internal_slice(&self) -> &[u8] { self.data }
+}
+
+impl PayloadIterator<'_> {
+    /// Check the length of a segment whose header was just read and load its data
+    fn load(&mut self, f: &mut Segment) -> Result<(), Errors> {
+        let segment_data_start = SEGMENT_HEADER_LEN * self.segment_count + f.offset();
+        if self.verify_lengths && self.data.len() < segment_data_start + f.len() {
+            return Err(Errors::too_small(segment_data_start + f.len(), self.data.len()));
+        }
+        let overridden = self.overrides
+            .and_then(|o| o.get(&(f.kind()?, f.id())))
+            .filter(|_| self.load_data);
+        if let Some(data) = overridden {
+            f.set_data(data.clone());
+            return Ok(());
+        }
+        #[cfg(feature="payload")]
+        {
+            if let Some(decrypted) = self.decrypted.get_mut(self.index) {
+                f.set_data(decrypted.take().ok_or(Errors::NoData)??);
+            } else if let Some(key) = self.key.as_mut() {
+                if self.data.len() < segment_data_start + f.len() {
+                    return Err(Errors::too_small(segment_data_start + f.len(), self.data.len()));
+                } else {
+                    decrypt_segment(&self.data[segment_data_start..segment_data_start+f.len()], f.data_mut(), key)?;
+                }
+            }
+        }
+        Ok(())
+    }
+}
+
+impl<'a> std::iter::Iterator for PayloadIterator<'a> {
+    type Item = Segment;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        if self.index >= self.segment_count {
+            return None;
+        }
+        if self.cancel_token.as_ref().is_some_and(CancellationToken::is_cancelled) {
+            self.last_error = Some(Errors::Cancelled);
+            return None;
+        }
+        Segment::from_slice(&self.data[self.index*SEGMENT_HEADER_LEN..])
+        .and_then(|mut f| {
+            let (kind, id) = (f.kind(), f.id());
+            self.load(&mut f).map_err(|e| Errors::Segment { kind, id, source: Box::new(e) })?;
+            self.index += 1;
+            Ok(f)
+        }).map_err(|e|{
+            self.last_error = Some(e);
+            Errors::NoData
+        }).ok()
+    }
+}
+
+/// Build the cipher used to decrypt a payload's segments
+#[cfg(feature="payload")]
+pub(crate) fn segment_cipher(head: &PayloadHeader) -> Result<Blowfish::<byteorder::BigEndian>, Errors> {
+    Blowfish::<byteorder::BigEndian>::new_from_slice(&head.segment_encryption_key()?[..])
+        .map_err(|_| Errors::InvalidBuffer)
+}
+
+/// Decrypt the data of all segments of a payload on all available CPUs
+///
+/// Segments whose header could not be read, or that were not reached before cancellation, are left empty.
+/// Threads are not available on `wasm32` targets, segments are decrypted sequentially there.
+#[cfg(feature="payload")]
+fn decrypt_all(data: &[u8], segment_count: usize, key: &Blowfish::<byteorder::BigEndian>, cancel_token: Option<&CancellationToken>) -> Vec<Option<Result<Vec<u8>, Errors>>> {
+    let mut decrypted: Vec<Option<Result<Vec<u8>, Errors>>> = (0..segment_count).map(|_| None).collect();
+    #[cfg(target_arch="wasm32")]
+    decrypt_range(data, segment_count, 0, &mut decrypted, &mut key.clone(), cancel_token);
+    #[cfg(not(target_arch="wasm32"))]
+    {
+        let jobs = std::thread::available_parallelism().map(|n| n.get()).unwrap_or(1);
+        let per_job = std::cmp::max(1, segment_count.div_ceil(jobs));
+        std::thread::scope(|scope| {
+            for (job, results) in decrypted.chunks_mut(per_job).enumerate() {
+                let mut key = key.clone();
+                scope.spawn(move || decrypt_range(data, segment_count, job * per_job, results, &mut key, cancel_token));
+            }
+        });
+    }
+    decrypted
+}
+
+/// Decrypt the data of consecutive segments of a payload, starting with the segment at index `first`
+#[cfg(feature="payload")]
+fn decrypt_range(data: &[u8], segment_count: usize, first: usize, results: &mut [Option<Result<Vec<u8>, Errors>>], key: &mut Blowfish::<byteorder::BigEndian>, cancel_token: Option<&CancellationToken>) {
+    for (i, result) in results.iter_mut().enumerate() {
+        if cancel_token.is_some_and(CancellationToken::is_cancelled) {
+            break;
+        }
+        let index = first + i;
+        if let Ok(segment) = Segment::from_slice(&data[index*SEGMENT_HEADER_LEN..]) {
+            let start = SEGMENT_HEADER_LEN * segment_count + segment.offset();
+            *result = Some(if data.len() < start + segment.len() {
+                Err(Errors::too_small(start + segment.len(), data.len()))
+            } else {
+                let mut out = Vec::new();
+                decrypt_segment(&data[start..start+segment.len()], &mut out, key).map(|_| out)
+            });
+        }
+    }
+}
+
+/// Decrypt a payload segment.
+/// The provided slice must match the exact extent of the encrypted data
+#[cfg(feature="payload")]
+pub(crate) fn decrypt_segment(cipher: &[u8], out: &mut Vec<u8>, key: &mut Blowfish::<byteorder::BigEndian>) -> Result<(), crate::error::Errors> {
+    use std::io::Read;
+
+    if cipher.is_empty() || !cipher.len().is_multiple_of(8) {
+        return Err(Errors::InvalidBuffer);
+    }
+    let mut data_store = cipher.to_vec();
+
+    for i in (0..data_store.len()).step_by(8) {
+        key.decrypt_block_mut(
+            GenericArray::from_mut_slice(&mut data_store[i..i+8])
+        );
+    }
+
+    let depad_size = data_store[data_store.len()-1] as usize;
+    if data_store.len() < depad_size {
+        return Err(Errors::InvalidBuffer);
+    }
+    data_store.resize(data_store.len()-depad_size, 0);
+
+    let mut decoder = flate2::read::GzDecoder::new(&data_store[..]);
+    let decoder_result = decoder.read_to_end(out);
+    if decoder_result.is_err() {
+        return Err(Errors::InvalidBuffer);
+    }
+    Ok(())
+}
+
+/// Encrypt a segment's decrypted data, the reverse of `decrypt_segment`
+///
+/// The data is gzipped, padded to a multiple of 8 bytes then encrypted
+#[cfg(feature="payload")]
+pub(crate) fn encrypt_segment(data: &[u8], key: &mut Blowfish::<byteorder::BigEndian>) -> Result<Vec<u8>, crate::error::Errors> {
+    use std::io::Write;
+
+    let mut encoder = flate2::write::GzEncoder::new(Vec::new(), flate2::Compression::default());
+    encoder.write_all(data)?;
+    let mut data_store = encoder.finish()?;
+
+    let pad_size = 8 - data_store.len() % 8;
+    data_store.resize(data_store.len() + pad_size, pad_size as u8);
+
+    for i in (0..data_store.len()).step_by(8) {
+        key.encrypt_block_mut(
+            GenericArray::from_mut_slice(&mut data_store[i..i+8])
+        );
+    }
+    Ok(data_store)
+}
@@ -0,0 +1,132 @@
+/*!
+Fast detection of ROFL files from their first bytes
+
+ROFL files start with a 6-byte signature: the [`Rofl::MAGIC`] "RIOT" string followed by 2 bytes
+that identify the version of the file format. Files with the version bytes `0, 0` follow the format
+documented in `ROFLFormat.adoc` and parsed by this crate, files written by more recent clients use
+`2, 0`.
+
+Detection only reads the signature, which is enough for file managers and indexers to sniff
+files without parsing them.
+
+[`is_probably_rofl`] and [`declared_len`] only depend on the first bytes of a file and are `const`,
+they are meant to pre-filter streams, such as uploads, before buffering them. Their behavior is
+stable: they only rely on the magic string and, for [`declared_len`], on the file length field
+of the header of files whose version bytes are `0, 0`. Other versions may store their length
+elsewhere, so their length is not read.
+
+# Examples
+
+```rust
+use lolrofl::kind::{detect, FileKind};
+
+# let content = lolrofl::test::sample_base_file_0();
+// let content = std::fs::read("game.rofl").unwrap();
+match detect(&content[..]) {
+    Some(FileKind::RoflV1) => println!("A ROFL file this crate can parse"),
+    Some(kind) => println!("A ROFL file in an unsupported format ({:?})", kind),
+    None => println!("Not a ROFL file"),
+}
+# assert_eq!(detect(&content[..]), Some(FileKind::RoflV1));
+# assert_eq!(detect(b"RIOT\x02\x00..."), Some(FileKind::RoflV2));
+# assert_eq!(detect(b"RIOT\x07\x01"), Some(FileKind::Unknown([7, 1])));
+# assert_eq!(detect(b"RIOT"), None);
+# assert_eq!(detect(b"PK\x03\x04\x00\x00"), None);
+```
+*/
+
+use crate::Rofl;
+
+/// Length of a ROFL file's signature
+pub const SIGNATURE_LEN: usize = 6;
+/// Offset of the file length field in a ROFL file's header
+const FILE_LEN_OFFSET: usize = 264;
+
+/// The format of a ROFL file, as identified by its signature
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum FileKind {
+    /// The format parsed by this crate, whose version bytes are `0, 0`
+    RoflV1,
+    /// The format written by more recent clients, whose version bytes are `2, 0`
+    RoflV2,
+    /// A ROFL file with unknown version bytes
+    Unknown([u8; 2]),
+}
+
+impl FileKind {
+    /// Whether files of this kind can be parsed by [`Rofl::from_slice`]
+    pub fn is_supported(&self) -> bool { matches!(self, FileKind::RoflV1) }
+}
+
+/// Identify a ROFL file's format from its first bytes
+///
+/// Returns `None` if the slice does not start with a ROFL file's signature
+pub fn detect(slice: &[u8]) -> Option<FileKind> {
+    if slice.len() < SIGNATURE_LEN || !is_probably_rofl(slice) {
+        return None;
+    }
+    Some(match [slice[4], slice[5]] {
+        [0, 0] => FileKind::RoflV1,
+        [2, 0] => FileKind::RoflV2,
+        version => FileKind::Unknown(version),
+    })
+}
+
+/// Whether a stream that starts with some bytes is probably a ROFL file, at least 4 bytes are required
+///
+/// # Examples
+///
+/// ```rust
+/// use lolrofl::kind::is_probably_rofl;
+///
+/// const SNIFFED: bool = is_probably_rofl(b"RIOT\x00\x00");
+/// assert_eq!(SNIFFED, true);
+/// assert_eq!(is_probably_rofl(b"RIO"), false);
+/// assert_eq!(is_probably_rofl(b"GIF89a"), false);
+/// ```
+pub const fn is_probably_rofl(first_bytes: &[u8]) -> bool {
+    if first_bytes.len() < Rofl::MAGIC.len() {
+        return false;
+    }
+    let mut i = 0;
+    while i < Rofl::MAGIC.len() {
+        if first_bytes[i] != Rofl::MAGIC[i] {
+            return false;
+        }
+        i += 1;
+    }
+    true
+}
+
+/// The length of a ROFL file as declared in its header, at least 268 bytes are required
+///
+/// Returns `None` if the bytes are not the start of a ROFL file whose version bytes are `0, 0`,
+/// see [`FileKind::RoflV1`], or are too short to hold the length.
+/// The declared length is not checked against the stream and may be wrong for corrupted files.
+///
+/// # Examples
+///
+/// ```rust
+/// use lolrofl::kind::declared_len;
+///
+/// # let content = lolrofl::test::sample_full_file_0();
+/// // let content = std::fs::read("game.rofl").unwrap();
+/// assert_eq!(declared_len(&content[..512]), Some(content.len() as u64));
+/// assert_eq!(declared_len(&content[..100]), None);
+/// # let mut v2 = content[..512].to_vec();
+/// # v2[4] = 2;
+/// # assert_eq!(declared_len(&v2), None);
+/// ```
+pub const fn declared_len(first_bytes: &[u8]) -> Option<u64> {
+    if !is_probably_rofl(first_bytes) || first_bytes.len() < FILE_LEN_OFFSET + 4
+        || first_bytes[4] != 0 || first_bytes[5] != 0 {
+        return None;
+    }
+    Some(u32::from_le_bytes([
+        first_bytes[FILE_LEN_OFFSET],
+        first_bytes[FILE_LEN_OFFSET + 1],
+        first_bytes[FILE_LEN_OFFSET + 2],
+        first_bytes[FILE_LEN_OFFSET + 3],
+    ]) as u64)
+}
@@ -1,210 +1,954 @@
-/*!
-Rust library and tool to parse and inspect ROFL replay files generated from League of Legends games.
-
-Backward-compatibility for replay files is NOT to be expected as of now.
-
-# Usage as a command-line tool
-
-After building with `cargo install --bin lolrofl --features "clap json payload"`, a new `lolrofl` executable become available.
-
-Said executable allows the inspection of ROFL files to extract game information, metadata, or development intel with the following commands:
-
-* `get`: Get high-level information on the file
-  * `get info`: Print simple/high-level info on the file and the game
-  * `get metadata`: Print the game's metadata
-  * `get payload`: Print technical information on the file
-* `analyze`: Get low-level information on the file - usually for debug and development purpose
-* `export`: Export chunk or keyframe data to a file or directory
-
-# Usage as a library
-
-Use `lolrofl` to parse a loaded file's content :
-
-```rust
-// Parse the file's content with Rofl
-# let content = lolrofl::test::sample_base_file_0();
-// let content = std::fs::read(source_file).unwrap();
-let data = lolrofl::Rofl::from_slice(&content[..]).unwrap();
-
-// Print the file's length as specified within the file (this may not match the actual file size if it is incomplete or corrupted)
-println!("Expected file length: {:?} bytes", data.head().file_len());
-// Print the file's metadata (a JSON string)
-println!("{}", data.metadata().unwrap());
-// Print information on the game without depending on the metadata
-let payload = data.payload().unwrap();
-println!("The game {} lasted {} seconds", payload.id(), payload.duration()/1000);
-# assert_eq!(payload.duration(), 91722);
-```
-*/
-
-mod error;
-pub use error::*;
-pub mod iter;
-pub mod model;
-// FIXME: the test feature is only required because doctest context is not passed by cargo at compile-time
-#[cfg(any(doctest, test, feature = "test"))]
-pub mod test;
-use model::*;
-
-/// Base ROFL file parser
-/// 
-/// # Usage
-/// 
-/// ```rust
-/// // Parse a file's content
-/// # let content = lolrofl::test::sample_base_file_0();
-/// // let content = std::fs::read("game.rofl").unwrap();
-/// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
-///
-/// let header = game.head(); // File header
-/// let data = game.metadata(); // Game metadata JSON string
-/// # assert_eq!(data.is_ok(), true);
-/// let payload = game.payload(); // Game payload
-/// # assert_eq!(payload.is_ok(), true);
-/// ```
-pub struct Rofl<'a> {
-    /// ROFL file's Start Header
-    head: BinHeader,
-    /// ROFL File's data
-    data: &'a[u8],
-}
-
-impl Rofl<'_> {
-    /// Starting bytes of a ROFL file
-    /// 
-    /// This is public for ease of file recognition but should generally NOT be relied upon
-    pub const MAGIC: [u8; 4] = [82,73,79,84]; // TODO: check if 6 bytes instead of 0
-    /// Get the ROFL header
-    /// 
-    /// # Examples
-    /// 
-    /// ```rust
-    /// # let content = lolrofl::test::sample_base_file_0();
-    /// // let content = std::fs::read("game.rofl").unwrap();
-    /// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
-    /// println!("Expected file length: {} bytes", game.head().file_len());
-    /// # assert_eq!(game.head().file_len(), 0x01cd);
-    /// ```
-    pub fn head(&self) -> &BinHeader { &self.head }
-    /// Get the loaded JSON Metadata string
-    /// 
-    /// # Warning
-    /// 
-    /// The returned string is not guaranteed to be valid if the file is malformed
-    /// 
-    /// # Examples
-    /// 
-    /// ```rust
-    /// # let content = lolrofl::test::sample_base_file_0();
-    /// // let content = std::fs::read("game.rofl").unwrap();
-    /// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
-    /// let meta = json::parse(game.metadata().unwrap()).unwrap();
-    /// println!("Duration: {} ms", meta["gameLength"]);
-    /// println!("Patch: {}", meta["gameVersion"]);
-    /// # assert_eq!(meta["gameVersion"], "12.10.444.2068");
-    /// ```
-    pub fn metadata(&self) -> Result<&str, Errors> {
-        if self.data.len() < self.head.metadata_offset() + self.head.metadata_len() {
-            return Err(error::Errors::BufferTooSmall);
-        }
-        std::str::from_utf8(
-                &self.data[self.head.metadata_offset()..self.head.metadata_offset() + self.head.metadata_len()]
-        )
-        .or_else(|_| Err(error::Errors::InvalidBuffer))
-    }
-    /// Get the loaded payload header
-    /// 
-    /// # Examples
-    /// 
-    /// ```rust
-    /// # let content = lolrofl::test::sample_base_file_0();
-    /// // let content = std::fs::read("game.rofl").unwrap();
-    /// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
-    ///
-    /// let payload = game.payload().unwrap();
-    /// println!("Game ID: {}", payload.id());
-    /// println!("Duration: {} ms", payload.duration());
-    /// # assert_eq!(payload.duration(), 0x01664a);
-    /// # assert_eq!(payload.chunk_count(), 6);
-    /// ```
-    pub fn payload(&self) -> Result<PayloadHeader, Errors> {
-        if self.data.len() < self.head.payload_header_offset() + self.head.payload_header_len() {
-            Err(Errors::BufferTooSmall)
-        } else {
-            let payload = PayloadHeader::from_raw_section(
-                &self.data[self.head.payload_header_offset()..self.head.payload_header_offset() + self.head.payload_header_len()]
-            );
-            Ok(payload)
-        }
-    }
-    /// Get an iterator over the payload's segments
-    /// 
-    /// `with_data` is implicitly `false` if the lib was compiled without the `payload` feature
-    /// 
-    /// # Examples
-    /// 
-    /// ```ignore
-    /// # let content = lolrofl::test::sample_base_file_0();
-    /// // let content = std::fs::read("game.rofl").unwrap();
-    /// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
-    ///
-    /// for segment in game.segment_iter(false).unwrap() {
-    ///     println("{} {}", if segment.is_chunk() { "Chunk" } else { "Keyframe" }, segment.id())
-    /// }
-    /// ```
-    /// 
-    /// ```rust
-    /// // Truncated file
-    /// # let content = lolrofl::test::sample_base_file_0();
-    /// // let content = std::fs::read("game.rofl").unwrap();
-    /// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
-    ///
-    /// let mut data = game.segment_iter(false);
-    /// assert_eq!(data.is_err(), true)
-    /// ```
-    pub fn segment_iter<'a>(&'a self, with_data: bool) -> Result<crate::iter::PayloadIterator<'a>, error::Errors> {
-        // FIXME: the doctest should be runnable
-        if self.data.len() < self.head.file_len() {
-            Err(error::Errors::BufferTooSmall)
-        } else {
-            self.payload().and_then(|p|
-                crate::iter::PayloadIterator::new(
-                    &self.data[self.head.payload_offset()..self.head.file_len()],
-                    &p,
-                    with_data,
-                )
-            )
-        }
-    }
-    /// Create a new Rofl instance from a ROFL file's slice
-    /// 
-    /// # Panics
-    /// 
-    /// If the buffer contains less than 288 bytes - in the future, this will be an error
-    /// 
-    /// # Errors
-    /// 
-    /// If the slice does not start with [`MAGIC`]
-    /// 
-    /// [`MAGIC`]: Rofl::MAGIC
-    /// 
-    /// # Examples
-    /// 
-    /// ```rust
-    /// # let content = lolrofl::test::sample_base_file_0();
-    /// // let content = std::fs::read("game.rofl").unwrap();
-    /// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
-    /// ```
-    pub fn from_slice<'a>(slice: &'a[u8]) -> Result<Rofl<'a>, Errors> {
-        if slice.len() < Rofl::MAGIC.len() || Rofl::MAGIC != slice[..Rofl::MAGIC.len()] {
-            return Err(Errors::InvalidBuffer);
-        }
-        // FIXME: return Result<> in BinHeader initializers and control slice size
-        let header = BinHeader::from_raw_source(slice);
-
-        Ok(Rofl {
-            head: header,
-            data: slice,
-        })
-    }
-}
+/*!
+Rust library and tool to parse and inspect ROFL replay files generated from League of Legends games.
+
+Backward-compatibility for replay files is NOT to be expected as of now.
+
+# Usage as a command-line tool
+
+After building with `cargo install --bin lolrofl --features "clap json payload"`, a new `lolrofl` executable become available.
+
+Said executable allows the inspection of ROFL files to extract game information, metadata, or development intel with the following commands:
+
+* `get`: Get high-level information on the file
+  * `get info`: Print a summary of the game (version, duration, teams, players and winner), as JSON with `--json`, or specific info such as when it was created with `--date`
+  * `get metadata`: Print the game's metadata, in a canonical form that can be diffed with `--canonical`
+  * `get payload`: Print technical information on the file, as JSON or CSV with `--format json` or `--format csv`
+  * `get header`: Print the file's header and format version and flag inconsistencies with the actual file
+  * `get segments`: Print the payload's segment headers as a table or JSON, with the time range each segment covers with `--times`
+  * `get accolades`: Print awards computed from the players' stats (most damage, best KDA, most vision, largest gold lead)
+  * `get share`: Print a short summary of the game to paste in Discord (`--style discord`) or elsewhere (`--style plain`)
+* `analyze`: Get low-level information on the file - usually for debug and development purpose, `--mode stats --format json` prints the sections of each segment by type as JSON lines, `--mode coverage` reports the share of sections decoded by typed decoders by type and `--mode metrics -v --window 30 --step 10` prints the metrics over sliding windows, `--mode chapters --event-type 1024` splits the game in laning, mid-game and late-game phases, `--mode detail --skip 100 --limit 20` pages through the sections and `--mode layout --offset 0x1A3` maps the file's areas and locates an offset in them
+* `export`: Export chunk or keyframe data to a file or directory, exports may be encrypted with a password (`--encrypt-with PASSWORD`) and the `encrypt` feature. `export all` lists the exported files in a manifest (TSV, or JSON lines or CSV with `--format`), may skip unreadable segments with `--keep-going` and describe the game in a `summary.json` file with `--sidecar`, `export sections --sample 1/100 --type N` writes a sample of the game's sections as text and `export tensor --type N --every 60` writes the sections sent by each entity over time as a NumPy tensor with a JSON manifest, with the `npy` feature
+* `events`: Print the file's sections as JSON lines, `--where "type = 256 && time < 600"` only prints the sections that match an expression (see the `filter` module for its grammar) and `--pov 0x40000001` only prints the sections caused by or targeting an entity, `--chapter late-game --event-type 1024` only prints the sections of a phase of the game
+* `timeline`: Print the game's interaction pings, the only events the lib decodes, in chronological order with their sections' raw parameters
+* `align`: Compare the file with another recording of the same game (`lolrofl FILE align OTHER`), such as an observer's and a player's, and list the chunks and sections that differ
+* `minimize`: Build the smallest file that reproduces a parsing failure, to share it in bug reports
+* `repair`: Rebuild a truncated or partially corrupted file (`lolrofl FILE repair --output FIXED`) from its segments whose data is whole, with its length fixed
+* `merge`: Combine partial copies of the same game, such as a truncated file and separately downloaded segments, into its most complete file (`lolrofl FILE merge --part OTHER --output MERGED`)
+* `normalize`: Rewrite the file with its chunks then its keyframes in ascending order and compact data offsets, to simplify diffs and deduplication
+* `list`: List the ROFL files of a directory, scan results are cached in `~/.cache/lolrofl/index.json`, `--min-version 14.10` only lists the games played on a patch or after it
+* `rename`: Rename the ROFL files of a directory from a template such as `{date}_{champion}_{result}_{matchid}.rofl`, `{date}` is the game's creation date when the metadata holds it and the file's modification date otherwise
+* `dedupe`: Find the ROFL files of a directory that hold the same game and optionally delete or hard-link duplicates
+* `stats`: Print statistics aggregated over the ROFL files of a directory, processed in parallel (`--jobs N`) and memory-mapped with the `mmap` feature, parsing metrics may be exported with `--metrics FILE` and the `metrics` feature
+* `schema dump`: Print the reference of the section types known to the lib and of their decoders, as Markdown (`--format md`) or JSON (`--format json`)
+* `schema drift OLD_DIR NEW_DIR`: Compare the section types of the ROFL files of two directories, such as two patches, and report the types that appeared, disappeared or changed length, exits with an error if any changed
+* `selftest`: Run every parsing step on a file, or on the built-in sample of builds with the `test` feature, and report which ones succeed
+* `privacy`: List the personally identifying information held by the file (names, Riot IDs, PUUIDs) and where it is found in the metadata and payload
+* `play`: Launch the local League of Legends client in replay mode on the file, the install directory may be set with `--install` or `LOLROFL_LEAGUE_DIR`
+* `notify`: Post a summary of the game to a Discord webhook (`--webhook URL`), requires the `http` feature
+* `fetch`: Download a game from a spectator server (`--region euw1 --game ID --key KEY`), downloads resume from cached segments, requires the `http` feature
+
+When a command fails on a file, run it again with `--explain` to print hints on the cause, such as a truncated download.
+
+The text output of `get`, `analyze` and `list` is colored when it is written to a terminal, `--color always` or `--color never` overrides this and the `NO_COLOR` environment variable disables colors.
+
+# Usage as a library
+
+Use `lolrofl` to parse a loaded file's content :
+
+```rust
+// Parse the file's content with Rofl
+# let content = lolrofl::test::sample_base_file_0();
+// let content = std::fs::read(source_file).unwrap();
+let data = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+
+// Print the file's length as specified within the file (this may not match the actual file size if it is incomplete or corrupted)
+println!("Expected file length: {:?} bytes", data.head().file_len());
+// Print the file's metadata (a JSON string)
+println!("{}", data.metadata().unwrap());
+// Print information on the game without depending on the metadata
+let payload = data.payload().unwrap();
+println!("The game {} lasted {} seconds", payload.id(), payload.duration()/1000);
+# assert_eq!(payload.duration(), 91722);
+```
+
+The library never writes to the standard output or error streams, so it may be embedded in
+terminal interfaces and services: failures are returned as [`Errors`] and tolerated
+inconsistencies are listed by [`Rofl::warnings`].
+*/
+
+// Keep the library silent, output is left to the applications that embed it
+#![deny(clippy::print_stdout, clippy::print_stderr, clippy::dbg_macro)]
+
+mod capabilities;
+mod error;
+pub use capabilities::*;
+pub use error::*;
+pub mod analysis;
+pub mod dataset;
+#[cfg(feature="encrypt")]
+pub mod encrypt;
+#[cfg(feature="ffi")]
+pub mod ffi;
+pub mod filter;
+pub mod fingerprint;
+pub mod fmt;
+pub mod iter;
+pub mod kind;
+#[cfg(feature="metrics")]
+pub mod metrics;
+pub mod minimize;
+pub mod model;
+#[cfg(feature="http")]
+pub mod notify;
+#[cfg(feature="json")]
+pub mod privacy;
+pub mod repair;
+#[cfg(feature="http")]
+pub mod spectator;
+#[cfg(feature="npy")]
+pub mod tensor;
+pub mod timeline;
+pub mod verify;
+#[cfg(all(feature="wasm", target_arch="wasm32"))]
+pub mod wasm;
+pub mod warnings;
+pub mod write;
+// FIXME: the test feature is only required because doctest context is not passed by cargo at compile-time
+#[cfg(any(doctest, test, feature = "test"))]
+pub mod test;
+use model::*;
+
+/// Base ROFL file parser
+/// 
+/// # Usage
+/// 
+/// ```rust
+/// // Parse a file's content
+/// # let content = lolrofl::test::sample_base_file_0();
+/// // let content = std::fs::read("game.rofl").unwrap();
+/// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+///
+/// let header = game.head(); // File header
+/// let data = game.metadata(); // Game metadata JSON string
+/// # assert_eq!(data.is_ok(), true);
+/// let payload = game.payload(); // Game payload
+/// # assert_eq!(payload.is_ok(), true);
+/// ```
+pub struct Rofl<'a> {
+    /// ROFL file's Start Header
+    head: BinHeader,
+    /// ROFL File's data, only the headers and metadata are held when read from a reader
+    data: Source<'a>,
+    /// The file's actual length, which may exceed the held data's
+    actual_len: usize,
+    /// Decrypted data served instead of the file's for some segments
+    overrides: std::collections::HashMap<(SegmentKind, u32), Vec<u8>>,
+    /// SHA-256 digest of the file computed while it was read, if requested
+    digest: Option<[u8; 32]>,
+}
+
+/// The data a [`Rofl`] instance reads from
+enum Source<'a> {
+    /// A slice provided by the caller
+    Borrowed(&'a [u8]),
+    /// Data read by the instance
+    Owned(Vec<u8>),
+    /// A memory-mapped file
+    #[cfg(feature="mmap")]
+    Mapped(memmap2::Mmap),
+}
+
+impl std::ops::Deref for Source<'_> {
+    type Target = [u8];
+    fn deref(&self) -> &[u8] {
+        match self {
+            Source::Borrowed(data) => data,
+            Source::Owned(data) => data,
+            #[cfg(feature="mmap")]
+            Source::Mapped(data) => data,
+        }
+    }
+}
+
+/// Check that a file starts with the signature of a supported ROFL format
+fn check_signature(data: &[u8]) -> Result<(), Errors> {
+    match kind::detect(data) {
+        Some(kind::FileKind::RoflV1) => Ok(()),
+        Some(kind) => Err(Errors::UnsupportedFormat { kind }),
+        None => Err(Errors::InvalidBuffer),
+    }
+}
+
+impl Rofl<'_> {
+    /// Starting bytes of a ROFL file
+    /// 
+    /// This is public for ease of file recognition but should generally NOT be relied upon:
+    /// the version bytes that follow it tell which format the file uses, use [`kind::detect`]
+    /// to check them. The constructors reject files whose format is not supported with
+    /// [`Errors::UnsupportedFormat`].
+    pub const MAGIC: [u8; 4] = [82,73,79,84];
+    /// Get the ROFL header
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust
+    /// # let content = lolrofl::test::sample_base_file_0();
+    /// // let content = std::fs::read("game.rofl").unwrap();
+    /// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+    /// println!("Expected file length: {} bytes", game.head().file_len());
+    /// # assert_eq!(game.head().file_len(), 0x01cd);
+    /// ```
+    pub fn head(&self) -> &BinHeader { &self.head }
+    /// Get the loaded JSON Metadata string
+    /// 
+    /// # Warning
+    /// 
+    /// The returned string is not guaranteed to be valid if the file is malformed
+    /// 
+    /// The metadata is located with the file's header only, it is available even if the
+    /// payload header is malformed.
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust
+    /// # let content = lolrofl::test::sample_base_file_0();
+    /// // let content = std::fs::read("game.rofl").unwrap();
+    /// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+    /// let meta = json::parse(game.metadata().unwrap()).unwrap();
+    /// println!("Duration: {} ms", meta["gameLength"]);
+    /// println!("Patch: {}", meta["gameVersion"]);
+    /// # assert_eq!(meta["gameVersion"], "12.10.444.2068");
+    /// # let mut content = content;
+    /// # content[428] = 0xFF; // The encryption key's length
+    /// # let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+    /// # assert_eq!(game.payload().is_err(), true);
+    /// # assert_eq!(game.metadata().is_ok(), true);
+    /// ```
+    pub fn metadata(&self) -> Result<&str, Errors> {
+        if self.data.len() < self.head.metadata_offset() + self.head.metadata_len() {
+            return Err(Errors::too_small(self.head.metadata_offset() + self.head.metadata_len(), self.data.len()));
+        }
+        Ok(std::str::from_utf8(
+                &self.data[self.head.metadata_offset()..self.head.metadata_offset() + self.head.metadata_len()]
+        )?)
+    }
+    /// Get the loaded payload header
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust
+    /// # let content = lolrofl::test::sample_base_file_0();
+    /// // let content = std::fs::read("game.rofl").unwrap();
+    /// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+    ///
+    /// let payload = game.payload().unwrap();
+    /// println!("Game ID: {}", payload.id());
+    /// println!("Duration: {} ms", payload.duration());
+    /// # assert_eq!(payload.duration(), 0x01664a);
+    /// # assert_eq!(payload.chunk_count(), 6);
+    /// ```
+    /// 
+    /// ```rust
+    /// // Encryption key length larger than the payload header
+    /// # let mut content = lolrofl::test::sample_base_file_0();
+    /// # content[427] = 0xff;
+    /// // let content = std::fs::read("game.rofl").unwrap();
+    /// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+    ///
+    /// let payload = game.payload();
+    /// assert_eq!(matches!(payload, Err(lolrofl::Errors::MalformedPayloadHeader { field: "encryption_key_length" })), true);
+    /// ```
+    pub fn payload(&self) -> Result<PayloadHeader, Errors> {
+        if self.data.len() < self.head.payload_header_offset() + self.head.payload_header_len() {
+            Err(Errors::too_small(self.head.payload_header_offset() + self.head.payload_header_len(), self.data.len()))
+        } else {
+            PayloadHeader::from_raw_section(
+                &self.data[self.head.payload_header_offset()..self.head.payload_header_offset() + self.head.payload_header_len()]
+            )
+        }
+    }
+    /// Get an iterator over the payload's segments
+    /// 
+    /// If `options` require loading segment data and the lib was compiled without the `payload` feature,
+    /// `Errors::FeatureDisabled` is returned, use `capabilities` to check beforehand
+    /// 
+    /// # Examples
+    /// 
+    /// ```ignore
+    /// # let content = lolrofl::test::sample_base_file_0();
+    /// // let content = std::fs::read("game.rofl").unwrap();
+    /// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+    ///
+    /// for segment in game.segment_iter(&lolrofl::iter::PayloadIterOptions::new()).unwrap() {
+    ///     println("{} {}", if segment.is_chunk() { "Chunk" } else { "Keyframe" }, segment.id())
+    /// }
+    /// ```
+    /// 
+    /// ```rust
+    /// // Truncated file
+    /// # let content = lolrofl::test::sample_base_file_0();
+    /// // let content = std::fs::read("game.rofl").unwrap();
+    /// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+    ///
+    /// let mut data = game.segment_iter(&lolrofl::iter::PayloadIterOptions::new());
+    /// assert_eq!(data.is_err(), true)
+    /// ```
+    ///
+    /// ```rust
+    /// // Segment data requested without the payload feature
+    /// # let content = lolrofl::test::sample_full_file_0();
+    /// // let content = std::fs::read("game.rofl").unwrap();
+    /// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+    ///
+    /// let data = game.segment_iter(&lolrofl::iter::PayloadIterOptions::with_data());
+    /// assert_eq!(data.is_ok(), lolrofl::capabilities().payload);
+    /// ```
+    pub fn segment_iter<'a>(&'a self, options: &iter::PayloadIterOptions) -> Result<crate::iter::PayloadIterator<'a>, error::Errors> {
+        // FIXME: the doctest should be runnable
+        if self.data.len() < self.actual_len {
+            // Segments were not read by headers_from_reader
+            Err(error::Errors::NoData)
+        } else if self.data.len() < self.head.file_len() {
+            Err(Errors::too_small(self.head.file_len(), self.data.len()))
+        } else {
+            let mut iterator = crate::iter::PayloadIterator::new(
+                &self.data[self.head.payload_offset()..self.head.file_len()],
+                &self.payload()?,
+                options,
+            )?;
+            if !self.overrides.is_empty() {
+                iterator.set_overrides(&self.overrides);
+            }
+            Ok(iterator)
+        }
+    }
+    /// Decrypt and load the data of a single segment
+    ///
+    /// This avoids decrypting all of the file's segments when only one of them is needed
+    ///
+    /// # Errors
+    ///
+    /// If the segment could not be decrypted, or if the lib was compiled without the `payload`
+    /// feature and the segment's data is not overridden
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # let content = lolrofl::test::sample_full_file_0();
+    /// // let content = std::fs::read("game.rofl").unwrap();
+    /// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+    ///
+    /// let mut keyframe = game.segment_iter(&lolrofl::iter::PayloadIterOptions::new()).unwrap().find(|s| s.is_keyframe()).unwrap();
+    /// # if lolrofl::capabilities().payload {
+    /// game.load_segment(&mut keyframe).unwrap();
+    /// assert_eq!(keyframe.is_loaded(), true);
+    /// # } else {
+    /// # assert_eq!(game.load_segment(&mut keyframe).is_err(), true);
+    /// # }
+    /// ```
+    pub fn load_segment(&self, segment: &mut Segment) -> Result<(), Errors> {
+        if let Some(data) = segment.kind().and_then(|kind| self.overrides.get(&(kind, segment.id()))) {
+            segment.set_data(data.clone());
+            return Ok(());
+        }
+        #[cfg(feature="payload")]
+        {
+            let mut key = iter::segment_cipher(&self.payload()?)?;
+            let mut data = Vec::new();
+            iter::decrypt_segment(self.segment_raw_data(segment)?, &mut data, &mut key)?;
+            segment.set_data(data);
+            Ok(())
+        }
+        #[cfg(not(feature="payload"))]
+        Err(Errors::FeatureDisabled { feature: "payload" })
+    }
+    /// Group the payload's segments in windows made of a keyframe and the chunks that follow it
+    ///
+    /// A keyframe's window holds the chunks from the keyframe's next chunk to the next keyframe's,
+    /// chunks that precede the first keyframe are not part of any window
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use lolrofl::iter::PayloadIterOptions;
+    ///
+    /// # let content = lolrofl::test::sample_full_file_0();
+    /// // let content = std::fs::read("game.rofl").unwrap();
+    /// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+    ///
+    /// for window in game.keyframe_windows(&PayloadIterOptions::new()).unwrap() {
+    ///     let ids: Vec<u32> = window.chunks().iter().map(|c| c.id()).collect();
+    ///     println!("Keyframe {}: chunks {:?}", window.keyframe().id(), ids);
+    /// }
+    /// # let windows = game.keyframe_windows(&PayloadIterOptions::new()).unwrap();
+    /// # assert_eq!(windows.len(), 1);
+    /// # assert_eq!(windows[0].chunks().iter().map(|c| c.id()).collect::<Vec<u32>>(), vec![2]);
+    /// ```
+    pub fn keyframe_windows(&self, options: &iter::PayloadIterOptions) -> Result<Vec<KeyframeWindow>, Errors> {
+        let mut iterator = self.segment_iter(options)?;
+        let (mut keyframes, mut chunks): (Vec<Segment>, Vec<Segment>) = iterator.by_ref().partition(|s| s.is_keyframe());
+        if !iterator.is_valid() {
+            return Err(iterator.to_error());
+        }
+        keyframes.sort_by_key(|k| k.next_chunk_id());
+        chunks.sort_by_key(|c| c.id());
+        let ends: Vec<Option<u32>> = keyframes.iter().skip(1).map(|k| Some(k.next_chunk_id())).chain(std::iter::once(None)).collect();
+        let mut chunks = chunks.into_iter().peekable();
+        let mut windows = Vec::with_capacity(keyframes.len());
+        for (keyframe, end) in keyframes.into_iter().zip(ends) {
+            while chunks.next_if(|c| c.id() < keyframe.next_chunk_id()).is_some() {}
+            let mut window = Vec::new();
+            while let Some(chunk) = chunks.next_if(|c| end.is_none_or(|e| c.id() < e)) {
+                window.push(chunk);
+            }
+            windows.push(KeyframeWindow::new(keyframe, window));
+        }
+        Ok(windows)
+    }
+    /// Get the header of the keyframe to load before a chunk to rebuild the game's state at that chunk
+    ///
+    /// This is the keyframe with the highest next chunk ID that does not exceed `chunk_id`,
+    /// `None` if the chunk precedes the first keyframe. Only segment headers are read.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # let content = lolrofl::test::sample_full_file_0();
+    /// // let content = std::fs::read("game.rofl").unwrap();
+    /// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+    ///
+    /// let keyframe = game.keyframe_for_chunk(2).unwrap().unwrap();
+    /// assert_eq!((keyframe.id(), keyframe.next_chunk_id()), (1, 2));
+    /// // The first chunk precedes the first keyframe
+    /// assert_eq!(game.keyframe_for_chunk(1).unwrap().is_none(), true);
+    /// ```
+    pub fn keyframe_for_chunk(&self, chunk_id: u32) -> Result<Option<Segment>, Errors> {
+        let mut iterator = self.segment_iter(&iter::PayloadIterOptions::new())?;
+        let keyframe = iterator.by_ref()
+            .filter(|s| s.is_keyframe() && s.next_chunk_id() <= chunk_id)
+            .max_by_key(|k| k.next_chunk_id());
+        if !iterator.is_valid() {
+            return Err(iterator.to_error());
+        }
+        Ok(keyframe)
+    }
+    /// Get the fingerprint of the file's game
+    ///
+    /// Files with the same fingerprint hold the same game data even if their metadata or signature differ
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # let content = lolrofl::test::sample_full_file_0();
+    /// // let content = std::fs::read("game.rofl").unwrap();
+    /// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+    ///
+    /// // Changing the metadata does not change the fingerprint
+    /// let mut builder = lolrofl::write::RoflBuilder::from_rofl(&game).unwrap();
+    /// builder.set_metadata("{}");
+    /// let copy = builder.build();
+    ///
+    /// let fingerprint = game.fingerprint().unwrap();
+    /// assert_eq!(fingerprint, lolrofl::Rofl::from_slice(&copy[..]).unwrap().fingerprint().unwrap());
+    /// assert_eq!(fingerprint.match_id(), game.payload().unwrap().id());
+    /// ```
+    pub fn fingerprint(&self) -> Result<fingerprint::Fingerprint, Errors> {
+        fingerprint::Fingerprint::from_rofl(self)
+    }
+    /// Get the inconsistencies of the file that do not prevent it from being parsed
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # let content = lolrofl::test::sample_full_file_0();
+    /// // let content = std::fs::read("game.rofl").unwrap();
+    /// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+    /// assert_eq!(game.warnings().is_empty(), true);
+    ///
+    /// // A truncated file
+    /// let game = lolrofl::Rofl::from_slice(&content[..600]).unwrap();
+    /// for warning in game.warnings().iter() {
+    ///     println!("WARNING: {}", warning);
+    /// }
+    /// # assert_eq!(game.warnings().len(), 1);
+    /// ```
+    pub fn warnings(&self) -> warnings::Warnings {
+        use warnings::Warning;
+        let mut warnings = warnings::Warnings::new();
+        let head = &self.head;
+        if head.file_len() != self.actual_len {
+            warnings.push(Warning::FileLength { declared: head.file_len(), actual: self.actual_len });
+        }
+        for (section, start, len) in [
+            ("Metadata", head.metadata_offset(), head.metadata_len()),
+            ("Payload header", head.payload_header_offset(), head.payload_header_len()),
+            ("Payload", head.payload_offset(), 0),
+        ] {
+            if start + len > self.actual_len {
+                warnings.push(Warning::SectionOutOfBounds { section, start, end: start + len, actual: self.actual_len });
+            }
+        }
+        if let (Ok(metadata), Ok(payload)) = (self.metadata(), self.payload()) {
+            let ids = model::find_keys(metadata, &["lastGameChunkId", "lastKeyFrameId"]).unwrap_or_default();
+            for (kind, id, count) in [
+                ("chunk", ids.first(), payload.chunk_count()),
+                ("keyframe", ids.get(1), payload.keyframe_count()),
+            ] {
+                if let Some(id) = id.copied().flatten().and_then(|id| id.parse().ok()) {
+                    if id != count {
+                        warnings.push(Warning::SegmentCount { kind, metadata: id, header: count });
+                    }
+                }
+            }
+        }
+        warnings
+    }
+    /// Check the integrity of the whole file
+    ///
+    /// The file's declared length, its metadata's UTF-8 and JSON validity, the bounds of its
+    /// segments and whether their data can be decrypted and decompressed are checked. Checks go
+    /// on after a failure so that the report lists every problem found, only the segments that
+    /// follow an unreadable segment header are not checked.
+    ///
+    /// Segment data is not checked if the lib was compiled without the `payload` feature, nor
+    /// for files read with [`Rofl::headers_from_reader`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # let content = lolrofl::test::sample_full_file_0();
+    /// // let content = std::fs::read("game.rofl").unwrap();
+    /// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+    ///
+    /// let report = game.verify();
+    /// if !report.is_valid() {
+    ///     for segment in report.segments().iter().filter(|s| !s.is_valid()) {
+    ///         println!("{} is corrupted", segment);
+    ///     }
+    /// }
+    /// # assert_eq!(report.is_valid(), true);
+    /// # assert_eq!(report.segments().len(), 3);
+    /// # assert_eq!(report.segments().iter().all(|s| s.data().is_some() == lolrofl::capabilities().payload), true);
+    /// # // A truncated file
+    /// # let report = lolrofl::Rofl::from_slice(&content[..600]).unwrap().verify();
+    /// # assert_eq!(report.is_valid(), false);
+    /// # assert_eq!(report.is_length_valid(), false);
+    /// # assert_eq!(report.segments().iter().filter(|s| !s.in_bounds()).count() > 0, true);
+    /// # // Corrupted metadata and segment data
+    /// # let mut content = content;
+    /// # content[288] = b'x';
+    /// # content[0x250] ^= 0xFF;
+    /// # let report = lolrofl::Rofl::from_slice(&content[..]).unwrap().verify();
+    /// # assert_eq!(report.metadata().is_err(), true);
+    /// # assert_eq!(report.is_length_valid(), true);
+    /// # assert_eq!(report.segments().iter().any(|s| matches!(s.data(), Some(Err(_)))), lolrofl::capabilities().payload);
+    /// ```
+    pub fn verify(&self) -> verify::VerificationReport {
+        verify::VerificationReport::from_rofl(self)
+    }
+    /// Detect the version of the file's format, see [`FormatVersion`](model::FormatVersion)
+    pub fn format_version(&self) -> model::FormatVersion {
+        model::FormatVersion::from_rofl(self)
+    }
+    /// Map the file's bytes to the areas that hold them, sorted by offset
+    ///
+    /// The header, metadata and payload header are located with the file's header and the
+    /// segments with the payload's segment headers. The bytes no area covers, including the ones
+    /// past the last area, are reported as [`Area::Unused`](model::Area::Unused). Areas may overlap
+    /// or extend past the end of corrupted files, which are mapped as long as their segment headers
+    /// are whole.
+    ///
+    /// # Errors
+    ///
+    /// If the payload header or the segment headers could not be read, or if the file was read
+    /// with [`Rofl::headers_from_reader`]
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use lolrofl::model::Area;
+    ///
+    /// # let content = lolrofl::test::sample_full_file_0();
+    /// // let content = std::fs::read("game.rofl").unwrap();
+    /// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+    ///
+    /// let corruption = 0x250;
+    /// let layout = game.layout().unwrap();
+    /// for extent in layout.iter() {
+    ///     println!("{:#010x} {:>8} {}", extent.offset(), extent.len(), extent.area());
+    /// }
+    /// let hit = layout.iter().find(|e| e.contains(corruption)).unwrap();
+    /// # assert_eq!(layout.first().unwrap().area(), Area::Header);
+    /// # assert_eq!(layout.windows(2).all(|w| w[0].end() <= w[1].offset()), true);
+    /// # assert_eq!(layout.last().unwrap().end(), content.len());
+    /// # assert_eq!(hit.area(), Area::Segment { kind: Some(lolrofl::model::SegmentKind::Keyframe), id: 1 });
+    /// # let truncated = lolrofl::Rofl::from_slice(&content[..600]).unwrap().layout().unwrap();
+    /// # assert_eq!(truncated.len(), layout.len());
+    /// # assert_eq!(lolrofl::Rofl::from_slice(&content[..480]).unwrap().layout().is_err(), true);
+    /// ```
+    pub fn layout(&self) -> Result<Vec<model::Extent>, Errors> {
+        use model::{Area, Extent};
+        let head = &self.head;
+        let payload = self.payload()?;
+        let segment_count = (payload.chunk_count() + payload.keyframe_count()) as usize;
+        let segments_start = head.payload_offset() + segment_count * SEGMENT_HEADER_LEN;
+        let mut extents = vec![
+            Extent::new(Area::Header, 0, head.header_len()),
+            Extent::new(Area::Metadata, head.metadata_offset(), head.metadata_len()),
+            Extent::new(Area::PayloadHeader, head.payload_header_offset(), head.payload_header_len()),
+            Extent::new(Area::SegmentHeaders, head.payload_offset(), segment_count * SEGMENT_HEADER_LEN),
+        ];
+        if self.data.len() < self.actual_len {
+            // Segment headers were not read by headers_from_reader
+            return Err(Errors::NoData);
+        }
+        // Segment headers are read even if the file was truncated after them
+        let directory = self.data.get(head.payload_offset()..).ok_or(Errors::too_small(head.payload_offset(), self.data.len()))?;
+        let mut iterator = iter::PayloadIterator::new(directory, &payload, &iter::PayloadIterOptions::new())?;
+        for segment in iterator.by_ref() {
+            let area = Area::Segment { kind: segment.kind(), id: segment.id() };
+            extents.push(Extent::new(area, segments_start + segment.offset(), segment.len()));
+        }
+        if !iterator.is_valid() {
+            return Err(iterator.to_error());
+        }
+        extents.sort_by_key(|e| (e.offset(), e.len()));
+
+        let mut layout = Vec::with_capacity(extents.len());
+        let mut covered = 0;
+        for extent in extents {
+            if extent.offset() > covered {
+                layout.push(Extent::new(Area::Unused, covered, extent.offset() - covered));
+            }
+            covered = covered.max(extent.end());
+            layout.push(extent);
+        }
+        if self.actual_len > covered {
+            layout.push(Extent::new(Area::Unused, covered, self.actual_len - covered));
+        }
+        Ok(layout)
+    }
+    /// Check that each keyframe's first section is timed at the keyframe's ID times the payload's
+    /// keyframe interval, within `tolerance` milliseconds
+    ///
+    /// Drifted or mislabeled keyframes have valid lengths and are not caught by [`Rofl::warnings`].
+    /// Keyframes whose first section is timed relatively to a previous section are not checked,
+    /// nor are the keyframes of payloads without a keyframe interval.
+    ///
+    /// # Errors
+    ///
+    /// If the file's keyframes could not be decrypted or split into sections, or if the lib was
+    /// compiled without the `payload` feature
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use lolrofl::{model::PayloadHeader, warnings::Warning, write::RoflBuilder};
+    ///
+    /// # let content = lolrofl::test::sample_full_file_0();
+    /// // let content = std::fs::read("game.rofl").unwrap();
+    /// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+    /// # #[cfg(feature = "payload")] {
+    /// for warning in game.keyframe_warnings(500).unwrap() {
+    ///     println!("WARNING: {}", warning);
+    /// }
+    /// # assert_eq!(
+    /// #     game.keyframe_warnings(500).unwrap().iter().next(),
+    /// #     Some(&Warning::KeyframeTime { id: 1, expected: 60000000, actual: 1000 }),
+    /// # );
+    /// # // The sample's only keyframe starts at 1s
+    /// # let p = game.payload().unwrap();
+    /// # let mut builder = RoflBuilder::new(PayloadHeader::new(p.id(), p.duration(), p.load_end_chunk(), p.game_start_chunk(), 1000, p.encryption_key()));
+    /// # for (segment, data) in RoflBuilder::from_rofl(&game).unwrap().segments() {
+    /// #     builder.push_segment(segment.clone(), data.clone());
+    /// # }
+    /// # let rebuilt = builder.build();
+    /// # assert_eq!(lolrofl::Rofl::from_slice(&rebuilt[..]).unwrap().keyframe_warnings(0).unwrap().is_empty(), true);
+    /// # // Files without keyframes or keyframe interval are not flagged
+    /// # let short = lolrofl::test::sample_no_keyframe_file_0();
+    /// # let short = lolrofl::Rofl::from_slice(&short[..]).unwrap();
+    /// # assert_eq!(short.warnings().is_empty(), true);
+    /// # assert_eq!(short.keyframe_warnings(0).unwrap().is_empty(), true);
+    /// # assert_eq!(short.segment_iter(&lolrofl::iter::PayloadIterOptions::with_data()).unwrap().count(), 2);
+    /// # let mut builder = RoflBuilder::new(PayloadHeader::new(p.id(), p.duration(), p.load_end_chunk(), p.game_start_chunk(), 0, p.encryption_key()));
+    /// # for (segment, data) in RoflBuilder::from_rofl(&game).unwrap().segments() {
+    /// #     builder.push_segment(segment.clone(), data.clone());
+    /// # }
+    /// # let rebuilt = builder.build();
+    /// # assert_eq!(lolrofl::Rofl::from_slice(&rebuilt[..]).unwrap().keyframe_warnings(0).unwrap().is_empty(), true);
+    /// # }
+    /// ```
+    pub fn keyframe_warnings(&self, tolerance: u64) -> Result<warnings::Warnings, Errors> {
+        use model::section::PacketTime;
+
+        let interval = self.payload()?.keyframe_interval() as u64;
+        let mut warnings = warnings::Warnings::new();
+        if interval == 0 {
+            return Ok(warnings);
+        }
+        let mut iterator = self.segment_iter(&iter::PayloadIterOptions::with_data())?;
+        for segment in iterator.by_ref().filter(|s| s.is_keyframe()) {
+            let first = segment.section_iter()?.next();
+            if let Some(PacketTime::Absolute(time)) = first.as_ref().map(|s| s.time()) {
+                let (expected, actual) = (segment.id() as u64 * interval, (time.max(0.) * 1000.).round() as u64);
+                if expected.abs_diff(actual) > tolerance {
+                    warnings.push(warnings::Warning::KeyframeTime { id: segment.id(), expected, actual });
+                }
+            }
+        }
+        if !iterator.is_valid() {
+            return Err(iterator.to_error());
+        }
+        Ok(warnings)
+    }
+    /// Serve decrypted segment data from memory instead of the file for some segments
+    ///
+    /// Overridden segments keep the headers of the file, only their data is replaced when
+    /// it is loaded, which allows editing a segment and checking the result without
+    /// rebuilding the whole file. [`RoflBuilder::from_rofl`](write::RoflBuilder::from_rofl)
+    /// only re-encrypts overridden segments when writing the file back.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use lolrofl::{iter::PayloadIterOptions, model::SegmentKind};
+    ///
+    /// # let content = lolrofl::test::sample_full_file_0();
+    /// // let content = std::fs::read("game.rofl").unwrap();
+    /// let overrides = [((SegmentKind::Chunk, 2), vec![0x01, 0x02])].into_iter().collect();
+    /// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap().with_overrides(overrides);
+    ///
+    /// # #[cfg(feature = "payload")] {
+    /// let chunk = game.segment_iter(&PayloadIterOptions::with_data()).unwrap().find(|s| s.is_chunk() && s.id() == 2).unwrap();
+    /// assert_eq!(chunk.data(), &vec![0x01, 0x02]);
+    ///
+    /// let mut keyframe = game.segment_iter(&PayloadIterOptions::new()).unwrap().find(|s| s.is_keyframe()).unwrap();
+    /// game.load_segment(&mut keyframe).unwrap();
+    /// assert_ne!(keyframe.data(), &vec![0x01, 0x02]);
+    /// # }
+    /// ```
+    pub fn with_overrides(mut self, overrides: std::collections::HashMap<(SegmentKind, u32), Vec<u8>>) -> Self {
+        self.overrides = overrides;
+        self
+    }
+    /// Get the decrypted data served instead of the file's for some segments
+    pub fn overrides(&self) -> &std::collections::HashMap<(SegmentKind, u32), Vec<u8>> { &self.overrides }
+    /// The approximate number of bytes held in memory by the parser, including overridden segments
+    ///
+    /// The file's content is borrowed and is not counted, nor are segments loaded by iterators
+    /// which are counted by [`Segment::approx_size`]
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use lolrofl::model::SegmentKind;
+    ///
+    /// # let content = lolrofl::test::sample_full_file_0();
+    /// // let content = std::fs::read("game.rofl").unwrap();
+    /// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+    /// let base = game.memory_footprint();
+    ///
+    /// let overrides = [((SegmentKind::Chunk, 1), vec![0; 4096])].into_iter().collect();
+    /// let game = game.with_overrides(overrides);
+    /// assert_eq!(game.memory_footprint() >= base + 4096, true);
+    /// ```
+    pub fn memory_footprint(&self) -> usize {
+        std::mem::size_of::<Self>()
+            + if let Source::Owned(data) = &self.data { data.capacity() } else { 0 }
+            + self.overrides.capacity() * std::mem::size_of::<((SegmentKind, u32), Vec<u8>)>()
+            + self.overrides.values().map(Vec::capacity).sum::<usize>()
+    }
+    /// Get the raw encrypted data of one of the file's segments
+    pub(crate) fn segment_raw_data(&self, segment: &Segment) -> Result<&[u8], Errors> {
+        let payload = self.payload()?;
+        let start = self.head.payload_offset()
+            + (payload.chunk_count() + payload.keyframe_count()) as usize * SEGMENT_HEADER_LEN
+            + segment.offset();
+        if self.data.len() < start + segment.len() {
+            Err(Errors::too_small(start + segment.len(), self.data.len()))
+        } else {
+            Ok(&self.data[start..start + segment.len()])
+        }
+    }
+    /// Create a new Rofl instance from a ROFL file's slice
+    /// 
+    /// # Errors
+    /// 
+    /// If the slice does not start with [`MAGIC`] ([`Errors::InvalidBuffer`]), if its format is not
+    /// supported ([`Errors::UnsupportedFormat`]), if it is too small to hold a header or if the
+    /// header is malformed, see [`BinHeader::from_raw_source`]
+    /// 
+    /// [`MAGIC`]: Rofl::MAGIC
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust
+    /// # let content = lolrofl::test::sample_base_file_0();
+    /// // let content = std::fs::read("game.rofl").unwrap();
+    /// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+    /// # let mut v2 = content.clone();
+    /// # v2[4] = 2;
+    /// # assert_eq!(matches!(lolrofl::Rofl::from_slice(&v2[..]), Err(lolrofl::Errors::UnsupportedFormat { kind: lolrofl::kind::FileKind::RoflV2 })), true);
+    /// # assert_eq!(matches!(lolrofl::Rofl::from_slice(b"RIOT"), Err(lolrofl::Errors::InvalidBuffer)), true);
+    /// ```
+    pub fn from_slice<'a>(slice: &'a[u8]) -> Result<Rofl<'a>, Errors> {
+        check_signature(slice)?;
+        let header = BinHeader::from_raw_source(slice)?;
+
+        Ok(Rofl {
+            head: header,
+            data: Source::Borrowed(slice),
+            actual_len: slice.len(),
+            overrides: std::collections::HashMap::new(),
+            digest: None,
+        })
+    }
+    /// Open a ROFL file by memory-mapping it
+    ///
+    /// The file is not read up front, the operating system loads the parts of the file that are
+    /// accessed on demand and may evict them under memory pressure. This is much lighter than
+    /// reading whole files when scanning directories of large replays.
+    ///
+    /// This requires the `mmap` feature. The file must not be modified while it is open, which
+    /// is undefined behavior.
+    ///
+    /// # Errors
+    ///
+    /// If the file can't be opened or mapped, or under the same conditions as [`Rofl::from_slice`]
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # let content = lolrofl::test::sample_full_file_0();
+    /// # let path = std::env::temp_dir().join(format!("lolrofl-open-{}.rofl", std::process::id()));
+    /// # std::fs::write(&path, &content).unwrap();
+    /// // let path = "game.rofl";
+    /// let game = lolrofl::Rofl::open(&path).unwrap();
+    /// println!("Game {}", game.payload().unwrap().id());
+    /// # assert_eq!(game.segment_iter(&lolrofl::iter::PayloadIterOptions::new()).unwrap().count(), 3);
+    /// # drop(game);
+    /// # std::fs::remove_file(&path).unwrap();
+    /// ```
+    #[cfg(feature="mmap")]
+    pub fn open<P: AsRef<std::path::Path>>(path: P) -> Result<Rofl<'static>, Errors> {
+        let file = std::fs::File::open(path)?;
+        // SAFETY: callers are told not to modify files while they are open
+        let data = unsafe { memmap2::Mmap::map(&file)? };
+        check_signature(&data)?;
+        let header = BinHeader::from_raw_source(&data)?;
+        Ok(Rofl {
+            head: header,
+            actual_len: data.len(),
+            data: Source::Mapped(data),
+            overrides: std::collections::HashMap::new(),
+            digest: None,
+        })
+    }
+    /// Create a new Rofl instance by reading only a ROFL file's headers and metadata from a reader
+    ///
+    /// Only the start of the file is read, up to the end of the metadata and payload header, which
+    /// is much faster than reading whole files when indexing many of them. The reader is not kept,
+    /// so segments are never read: [`segment_iter`](Rofl::segment_iter) returns [`Errors::NoData`]
+    /// on the returned instance. Use [`Rofl::from_slice`], or `Rofl::open` with the `mmap` feature, to access segments.
+    ///
+    /// # Errors
+    ///
+    /// If the reader fails, if the file is too small to hold a header, if it does not start with [`MAGIC`]
+    /// or if its format is not supported
+    ///
+    /// [`MAGIC`]: Rofl::MAGIC
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # let content = lolrofl::test::sample_full_file_0();
+    /// # let mut file = std::io::Cursor::new(&content);
+    /// // let mut file = std::fs::File::open("game.rofl").unwrap();
+    /// let game = lolrofl::Rofl::headers_from_reader(&mut file).unwrap();
+    /// println!("Game {} on patch {}", game.payload().unwrap().id(), json::parse(game.metadata().unwrap()).unwrap()["gameVersion"]);
+    ///
+    /// # assert_eq!(game.payload().unwrap().id(), lolrofl::Rofl::from_slice(&content[..]).unwrap().payload().unwrap().id());
+    /// # assert_eq!(game.warnings().is_empty(), true);
+    /// # assert_eq!(game.memory_footprint() < content.len() + std::mem::size_of::<lolrofl::Rofl>(), true);
+    /// assert_eq!(matches!(game.segment_iter(&lolrofl::iter::PayloadIterOptions::new()), Err(lolrofl::Errors::NoData)), true);
+    /// # assert_eq!(lolrofl::Rofl::headers_from_reader(std::io::Cursor::new(&content[..100])).is_err(), true);
+    /// # let mut v2 = content.clone();
+    /// # v2[4] = 2;
+    /// # assert_eq!(lolrofl::Rofl::headers_from_reader(std::io::Cursor::new(&v2)).err().unwrap().kind(), "unsupported_format");
+    /// ```
+    pub fn headers_from_reader<R: std::io::Read + std::io::Seek>(mut reader: R) -> Result<Rofl<'static>, Errors> {
+        use std::io::SeekFrom;
+
+        let actual_len = reader.seek(SeekFrom::End(0))? as usize;
+        if actual_len < BinHeader::LENGTH {
+            return Err(Errors::too_small(BinHeader::LENGTH, actual_len));
+        }
+        let mut data = vec![0; BinHeader::LENGTH];
+        reader.seek(SeekFrom::Start(0))?;
+        reader.read_exact(&mut data)?;
+        check_signature(&data)?;
+        let header = BinHeader::from_raw_source(&data)?;
+        let end = (header.metadata_offset() + header.metadata_len())
+            .max(header.payload_header_offset() + header.payload_header_len())
+            .min(actual_len);
+        if end > data.len() {
+            let start = data.len();
+            data.resize(end, 0);
+            reader.read_exact(&mut data[start..])?;
+        }
+        Ok(Rofl {
+            head: header,
+            data: Source::Owned(data),
+            actual_len,
+            overrides: std::collections::HashMap::new(),
+            digest: None,
+        })
+    }
+    /// Create a new Rofl instance by reading a whole ROFL file from a reader while computing its SHA-256 digest
+    ///
+    /// The file is read once, so that it may be checksummed and parsed without being read twice,
+    /// and its digest is then available with [`source_digest`](Rofl::source_digest).
+    ///
+    /// This requires the `digest` feature.
+    ///
+    /// # Errors
+    ///
+    /// If the reader fails, or under the same conditions as [`Rofl::from_slice`]
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # let content = lolrofl::test::sample_full_file_0();
+    /// # let file = std::io::Cursor::new(&content);
+    /// // let file = std::fs::File::open("game.rofl").unwrap();
+    /// let game = lolrofl::Rofl::from_reader_hashed(file).unwrap();
+    /// let digest: String = game.source_digest().unwrap().iter().map(|b| format!("{:02x}", b)).collect();
+    /// println!("Game {} has the checksum {}", game.payload().unwrap().id(), digest);
+    /// # use sha2::Digest;
+    /// # assert_eq!(game.source_digest().unwrap(), &sha2::Sha256::digest(&content)[..]);
+    /// # assert_eq!(game.segment_iter(&lolrofl::iter::PayloadIterOptions::new()).unwrap().count(), 3);
+    /// # assert_eq!(lolrofl::Rofl::from_slice(&content[..]).unwrap().source_digest(), None);
+    /// ```
+    #[cfg(feature="digest")]
+    pub fn from_reader_hashed<R: std::io::Read>(mut reader: R) -> Result<Rofl<'static>, Errors> {
+        use sha2::Digest;
+
+        let mut hasher = sha2::Sha256::new();
+        let mut data = Vec::new();
+        let mut block = vec![0; 1 << 16];
+        loop {
+            let read = match reader.read(&mut block) {
+                Ok(0) => break,
+                Ok(read) => read,
+                Err(e) if e.kind() == std::io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(e.into()),
+            };
+            hasher.update(&block[..read]);
+            data.extend_from_slice(&block[..read]);
+        }
+        check_signature(&data)?;
+        let header = BinHeader::from_raw_source(&data)?;
+        Ok(Rofl {
+            head: header,
+            actual_len: data.len(),
+            data: Source::Owned(data),
+            overrides: std::collections::HashMap::new(),
+            digest: Some(hasher.finalize().into()),
+        })
+    }
+    /// The SHA-256 digest of the file, if it was computed while reading it with [`Rofl::from_reader_hashed`]
+    pub fn source_digest(&self) -> Option<&[u8; 32]> {
+        self.digest.as_ref()
+    }
+}
@@ -0,0 +1,159 @@
+/*!
+Counters and histograms to monitor the parsing of many files
+
+Metrics are rendered in the OpenMetrics text format, which Prometheus can scrape
+or read through the node exporter's textfile collector.
+
+This module requires the `metrics` feature.
+
+# Examples
+
+```rust
+use lolrofl::metrics::ParseMetrics;
+
+let mut metrics = ParseMetrics::new();
+# let content = lolrofl::test::sample_full_file_0();
+// let content = std::fs::read("game.rofl").unwrap();
+let start = std::time::Instant::now();
+let result = lolrofl::Rofl::from_slice(&content[..]).and_then(|game| game.payload());
+metrics.record(result.as_ref().map(|_| ()), start.elapsed(), content.len());
+metrics.record(Err(&lolrofl::Errors::BufferTooSmall { expected: 288, actual: 12 }), std::time::Duration::from_millis(2), 0);
+
+let text = metrics.render();
+assert_eq!(text.contains("lolrofl_files_parsed_total 1\n"), true);
+assert_eq!(text.contains("lolrofl_parse_failures_total{kind=\"buffer_too_small\"} 1\n"), true);
+assert_eq!(text.ends_with("# EOF\n"), true);
+```
+*/
+
+use std::collections::BTreeMap;
+use std::fmt::Write;
+use crate::Errors;
+
+/// A histogram of observed values with cumulative buckets
+#[derive(Clone, Debug, PartialEq)]
+pub struct Histogram {
+    /// The buckets' upper bounds, sorted
+    bounds: Vec<f64>,
+    /// The number of observed values that fall in each bucket, non-cumulative
+    counts: Vec<u64>,
+    /// The sum of observed values
+    sum: f64,
+    /// The number of observed values
+    count: u64,
+}
+
+impl Histogram {
+    /// Build an empty histogram with buckets' upper bounds
+    pub fn new(bounds: &[f64]) -> Histogram {
+        let mut bounds = bounds.to_vec();
+        bounds.sort_by(f64::total_cmp);
+        Histogram { counts: vec![0; bounds.len()], bounds, sum: 0., count: 0 }
+    }
+    /// Add a value to the histogram
+    pub fn observe(&mut self, value: f64) {
+        if let Some(index) = self.bounds.iter().position(|b| value <= *b) {
+            self.counts[index] += 1;
+        }
+        self.sum += value;
+        self.count += 1;
+    }
+    /// The sum of observed values
+    pub fn sum(&self) -> f64 { self.sum }
+    /// The number of observed values
+    pub fn count(&self) -> u64 { self.count }
+    /// Add another histogram's values, both histograms must have the same buckets
+    pub fn merge(&mut self, other: &Histogram) {
+        for (count, other) in self.counts.iter_mut().zip(&other.counts) {
+            *count += other;
+        }
+        self.sum += other.sum;
+        self.count += other.count;
+    }
+    /// Write the histogram in the OpenMetrics text format
+    fn render(&self, name: &str, out: &mut String) {
+        let _ = writeln!(out, "# TYPE {} histogram", name);
+        let mut cumulative = 0;
+        for (bound, count) in self.bounds.iter().zip(&self.counts) {
+            cumulative += count;
+            let _ = writeln!(out, "{}_bucket{{le=\"{}\"}} {}", name, bound, cumulative);
+        }
+        let _ = writeln!(out, "{}_bucket{{le=\"+Inf\"}} {}", name, self.count);
+        let _ = writeln!(out, "{}_sum {}", name, self.sum);
+        let _ = writeln!(out, "{}_count {}", name, self.count);
+    }
+}
+
+/// Metrics of the parsing of a set of files
+///
+/// Metrics collected by different threads may be merged
+#[derive(Clone, Debug, PartialEq)]
+pub struct ParseMetrics {
+    /// The number of successfully parsed files
+    files: u64,
+    /// The number of parsed bytes
+    bytes: u64,
+    /// The number of failures by error kind
+    failures: BTreeMap<&'static str, u64>,
+    /// The time spent decoding each file, in seconds
+    durations: Histogram,
+}
+
+impl Default for ParseMetrics {
+    fn default() -> Self {
+        ParseMetrics {
+            files: 0,
+            bytes: 0,
+            failures: BTreeMap::new(),
+            durations: Histogram::new(&ParseMetrics::DURATION_BUCKETS),
+        }
+    }
+}
+
+impl ParseMetrics {
+    /// The upper bounds in seconds of the decode duration histogram's buckets
+    pub const DURATION_BUCKETS: [f64; 8] = [0.001, 0.005, 0.01, 0.05, 0.1, 0.5, 1., 5.];
+    /// Build empty metrics
+    pub fn new() -> ParseMetrics { ParseMetrics::default() }
+    /// Record the outcome of a file's parsing, how long it took and the file's size
+    pub fn record(&mut self, result: Result<(), &Errors>, duration: std::time::Duration, bytes: usize) {
+        match result {
+            Ok(()) => {
+                self.files += 1;
+                self.bytes += bytes as u64;
+            },
+            Err(e) => *self.failures.entry(e.kind()).or_default() += 1,
+        }
+        self.durations.observe(duration.as_secs_f64());
+    }
+    /// The number of successfully parsed files
+    pub fn files(&self) -> u64 { self.files }
+    /// The number of failures by error kind
+    pub fn failures(&self) -> &BTreeMap<&'static str, u64> { &self.failures }
+    /// The time spent decoding each file
+    pub fn durations(&self) -> &Histogram { &self.durations }
+    /// Add the metrics collected by another thread
+    pub fn merge(&mut self, other: &ParseMetrics) {
+        self.files += other.files;
+        self.bytes += other.bytes;
+        for (kind, count) in &other.failures {
+            *self.failures.entry(kind).or_default() += count;
+        }
+        self.durations.merge(&other.durations);
+    }
+    /// Render the metrics in the OpenMetrics text format
+    pub fn render(&self) -> String {
+        let mut out = String::new();
+        let _ = writeln!(out, "# TYPE lolrofl_files_parsed counter");
+        let _ = writeln!(out, "lolrofl_files_parsed_total {}", self.files);
+        let _ = writeln!(out, "# TYPE lolrofl_bytes_parsed counter");
+        let _ = writeln!(out, "lolrofl_bytes_parsed_total {}", self.bytes);
+        let _ = writeln!(out, "# TYPE lolrofl_parse_failures counter");
+        for (kind, count) in &self.failures {
+            let _ = writeln!(out, "lolrofl_parse_failures_total{{kind=\"{}\"}} {}", kind, count);
+        }
+        self.durations.render("lolrofl_decode_duration_seconds", &mut out);
+        out.push_str("# EOF\n");
+        out
+    }
+}
@@ -0,0 +1,188 @@
+/*!
+Reduce ROFL files that fail to parse to small synthetic samples that reproduce the failure
+
+Replay files are often hundreds of MB large, this makes them impractical to share in bug reports.
+Minimized samples keep the data required to reproduce a failure and drop everything else.
+*/
+
+use crate::{Errors, Rofl, iter::PayloadIterOptions, model::BinHeader, write::RoflBuilder};
+
+/// The successive parsing stages of a ROFL file
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Stage {
+    /// Parsing of the file's start header
+    Header,
+    /// Loading of the JSON metadata
+    Metadata,
+    /// Parsing of the payload header
+    PayloadHeader,
+    /// Parsing of the payload's segment headers
+    SegmentHeaders,
+    /// Decryption and decompression of a segment's data
+    SegmentData,
+    /// Iteration over the sections of a segment's data
+    Sections,
+}
+
+impl std::fmt::Display for Stage {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Stage::Header => write!(f, "header"),
+            Stage::Metadata => write!(f, "metadata"),
+            Stage::PayloadHeader => write!(f, "payload header"),
+            Stage::SegmentHeaders => write!(f, "segment headers"),
+            Stage::SegmentData => write!(f, "segment data"),
+            Stage::Sections => write!(f, "sections"),
+        }
+    }
+}
+
+/// A parsing failure and the stage at which it occured
+#[derive(Debug)]
+pub struct Failure {
+    /// The stage that failed
+    stage: Stage,
+    /// The index of the failing segment in the payload, if any
+    segment: Option<usize>,
+    /// The error raised by the failing stage
+    error: Errors,
+}
+
+impl Failure {
+    /// The stage that failed
+    pub fn stage(&self) -> Stage { self.stage }
+    /// The index in the payload of the segment that failed, if the failure is segment-specific
+    pub fn segment_index(&self) -> Option<usize> { self.segment }
+    /// The error raised by the failing stage
+    pub fn error(&self) -> &Errors { &self.error }
+}
+
+impl std::fmt::Display for Failure {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.segment {
+            Some(index) => write!(f, "{} of segment #{} failed: {}", self.stage, index, self.error),
+            None => write!(f, "{} failed: {}", self.stage, self.error),
+        }
+    }
+}
+
+/// Run all parsing stages on a file's content and return the first failure, if any
+///
+/// Segment data is only decrypted and iterated over if the lib was compiled with the `payload` feature
+///
+/// # Examples
+///
+/// ```rust
+/// # let content = lolrofl::test::sample_base_file_0();
+/// // let content = std::fs::read("game.rofl").unwrap();
+/// let failure = lolrofl::minimize::diagnose(&content[..]).unwrap();
+/// assert_eq!(failure.stage(), lolrofl::minimize::Stage::SegmentHeaders);
+/// ```
+pub fn diagnose(data: &[u8]) -> Option<Failure> {
+    let fail = |stage, segment, error| Some(Failure { stage, segment, error });
+    if data.len() < BinHeader::LENGTH {
+        return fail(Stage::Header, None, Errors::too_small(BinHeader::LENGTH, data.len()));
+    }
+    let rofl = match Rofl::from_slice(data) {
+        Ok(rofl) => rofl,
+        Err(e) => return fail(Stage::Header, None, e),
+    };
+    if let Err(e) = rofl.metadata() {
+        return fail(Stage::Metadata, None, e);
+    }
+    if let Err(e) = rofl.payload() {
+        return fail(Stage::PayloadHeader, None, e);
+    }
+    let mut iterator = match rofl.segment_iter(&PayloadIterOptions::new()) {
+        Ok(iterator) => iterator,
+        Err(e) => return fail(Stage::SegmentHeaders, None, e),
+    };
+    for _ in iterator.by_ref() {}
+    if !iterator.is_valid() {
+        let index = iterator.internal_index();
+        return fail(Stage::SegmentHeaders, Some(index), iterator.to_error());
+    }
+    #[cfg(feature="payload")]
+    {
+        let mut iterator = match rofl.segment_iter(&PayloadIterOptions::with_data()) {
+            Ok(iterator) => iterator,
+            // Headers were already validated, only the encryption key may be invalid
+            Err(e) => return fail(Stage::PayloadHeader, None, e),
+        };
+        for (index, segment) in iterator.by_ref().enumerate() {
+            if !segment.is_loaded() {
+                continue;
+            }
+            let mut sections = match segment.section_iter() {
+                Ok(sections) => sections,
+                Err(e) => return fail(Stage::Sections, Some(index), e),
+            };
+            for _ in sections.by_ref() {}
+            if !sections.is_valid() {
+                return fail(Stage::Sections, Some(index), sections.to_error());
+            }
+        }
+        if !iterator.is_valid() {
+            let index = iterator.internal_index();
+            return fail(Stage::SegmentData, Some(index), iterator.to_error());
+        }
+    }
+    None
+}
+
+/// Build the smallest file that fails at the same stage as the provided file
+///
+/// Returns `None` if the provided file does not fail to parse
+///
+/// The minimized file only keeps the failing segment if the failure is segment-specific
+/// and its metadata is replaced with an empty JSON object unless the metadata is what fails.
+///
+/// # Examples
+///
+/// ```rust
+/// # let content = lolrofl::test::sample_full_file_0();
+/// // let content = std::fs::read("game.rofl").unwrap();
+/// // Corrupt the file's metadata
+/// let mut broken = content.clone();
+/// broken[300] = 0xff;
+///
+/// let minimized = lolrofl::minimize::minimize(&broken[..]).unwrap();
+/// assert_eq!(minimized.len() < broken.len(), true);
+/// assert_eq!(
+///     lolrofl::minimize::diagnose(&minimized[..]).unwrap().stage(),
+///     lolrofl::minimize::diagnose(&broken[..]).unwrap().stage(),
+/// );
+/// # assert_eq!(lolrofl::minimize::diagnose(&broken[..]).unwrap().stage(), lolrofl::minimize::Stage::Metadata);
+/// ```
+pub fn minimize(data: &[u8]) -> Option<Vec<u8>> {
+    let failure = diagnose(data)?;
+    let mut candidates = vec![data[..std::cmp::min(data.len(), BinHeader::LENGTH)].to_vec()];
+    if let Some(rebuilt) = rebuild(data, &failure) {
+        candidates.push(rebuilt);
+    }
+    candidates.sort_by_key(|c| c.len());
+    candidates.into_iter()
+        .find(|c| diagnose(&c[..]).map(|f| f.stage()) == Some(failure.stage()))
+        .or_else(|| Some(data.to_vec()))
+}
+
+/// Rebuild a file with only the content required to reproduce a failure
+fn rebuild(data: &[u8], failure: &Failure) -> Option<Vec<u8>> {
+    let rofl = Rofl::from_slice(data).ok()?;
+    let mut builder = RoflBuilder::new(rofl.payload().ok()?);
+    builder.set_signature(rofl.head().signature());
+    match failure.stage() {
+        Stage::Metadata => {
+            let head = rofl.head();
+            let end = std::cmp::min(data.len(), head.metadata_offset() + head.metadata_len());
+            builder.set_raw_metadata(data[std::cmp::min(end, head.metadata_offset())..end].to_vec());
+        },
+        _ => { builder.set_metadata("{}"); },
+    }
+    if let Some(index) = failure.segment_index() {
+        let segment = rofl.segment_iter(&PayloadIterOptions::new()).ok()?.nth(index)?;
+        let segment_data = rofl.segment_raw_data(&segment).ok()?.to_vec();
+        builder.push_segment(segment, segment_data);
+    }
+    Some(builder.build())
+}
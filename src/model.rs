@@ -1,11 +1,23 @@
-/*!
-Structs that map to parts of the data model of a ROFL file.
-*/
-
-mod binheader;
-mod payload;
-mod segment;
-pub mod section;
-pub use binheader::*;
-pub use payload::*;
-pub use segment::*;
+/*!
+Structs that map to parts of the data model of a ROFL file.
+*/
+
+mod binheader;
+mod layout;
+mod metadata;
+mod payload;
+mod region;
+mod segment;
+pub mod section;
+#[cfg(feature="json")]
+mod summary;
+mod version;
+pub use binheader::*;
+pub use layout::*;
+pub use metadata::*;
+pub use payload::*;
+pub use region::*;
+pub use segment::*;
+#[cfg(feature="json")]
+pub use summary::*;
+pub use version::*;
@@ -1,116 +1,203 @@
-use byteorder::{ByteOrder, LittleEndian};
-
-/// ROFL file's header information
-#[derive(Debug)]
-pub struct BinHeader {
-    /// The file's signature
-    signature: Vec<u8>, // Fixed-size: 256 bits (or 0 if ignored)
-    /// The size of the header (constant in all known examples)
-    header_length: u16,
-    /// Total file length
-    file_length: u32,
-    /// Offset in bytes from the start of the file of the metadata section
-    metadata_offset: u32,
-    /// Length in bytes of the metadata section
-    metadata_length: u32,
-    /// Offset in bytes from the start of the file of the payload header section
-    payload_header_offset: u32,
-    /// Length in bytes of the payload header section
-    payload_header_length: u32,
-    /// Offset in bytes from the start of the file of the payload section
-    payload_offset: u32,
-}
-
-impl std::fmt::Display for BinHeader {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(
-            f,
-            concat!(
-                "Header size: {0}\n",
-                "File size: {1}\n",
-                "Metadata offset: {2}\n",
-                "Metadata length: {3}\n",
-                "Payload Header offset: {4}\n",
-                "Payload Header Length: {5}\n",
-                "Payload offset: {6}",
-            ),
-            self.header_length,
-            self.file_length,
-            self.metadata_offset,
-            self.metadata_length,
-            self.payload_header_offset,
-            self.payload_header_length,
-            self.payload_offset,
-            )
-    }
-}
-
-impl BinHeader {
-    /// Get the file's signature
-    pub fn signature(&self) -> &Vec<u8> {
-        &self.signature
-    }
-    /// Get the file's header length
-    pub fn header_len(&self) -> usize {
-        self.header_length as usize
-    }
-    /// Get the file's length in bytes as per its binary data
-    /// 
-    /// This may not match the actual file's length if an error occured
-    pub fn file_len(&self) -> usize {
-        self.file_length as usize
-    }
-    /// Length of the file's metadata section
-    /// 
-    /// This should not be required in normal use
-    pub fn metadata_len(&self) -> usize {
-        self.metadata_length as usize
-    }
-    /// Offset of the file's metadata section
-    /// 
-    /// This should not be required in normal use
-    pub fn metadata_offset(&self) -> usize {
-        self.metadata_offset as usize
-    }
-    /// Length of the file's payload header section
-    /// 
-    /// This should not be required in normal use
-    pub fn payload_header_len(&self) -> usize {
-        self.payload_header_length as usize
-    }
-    /// Offset of the file's payload header section
-    /// 
-    /// This should not be required in normal use
-    pub fn payload_header_offset(&self) -> usize {
-        self.payload_header_offset as usize
-    }
-    /// Offset of the file's payload section
-    /// 
-    /// This should not be required in normal use
-    pub fn payload_offset(&self) -> usize {
-        self.payload_offset as usize
-    }
-    
-    /// Create a new header from a manually-loaded file start section
-    /// 
-    /// Use from_raw_source instead
-    #[warn(deprecated)]
-    fn from_raw_section(data: &[u8]) -> BinHeader {
-        BinHeader {
-            signature: Vec::from(&data[6..262]),
-            header_length: LittleEndian::read_u16(&data[262..]),
-            file_length: LittleEndian::read_u32(&data[264..]),
-            metadata_offset: LittleEndian::read_u32(&data[268..]),
-            metadata_length: LittleEndian::read_u32(&data[272..]),
-            payload_header_offset: LittleEndian::read_u32(&data[276..]),
-            payload_header_length: LittleEndian::read_u32(&data[280..]),
-            payload_offset: LittleEndian::read_u32(&data[284..]),
-        }
-    }
-    /// Create a new header from a manually-loaded file start section
-    /// 
-    /// This will be replaced by a from_raw function in the future
-    pub fn from_raw_source(data: &[u8]) -> BinHeader {
-        BinHeader::from_raw_section(&data[0..])
-    }
-}
+use byteorder::{ByteOrder, LittleEndian};
+use crate::Errors;
+
+/// ROFL file's header information
+///
+/// With the `serde` feature, the header may be serialized with any serde format, as may the
+/// payload header, segment headers and section summaries to describe a whole file's structure:
+///
+/// ```rust
+/// # #[cfg(feature = "serde")] {
+/// use lolrofl::iter::PayloadIterOptions;
+///
+/// # let content = lolrofl::test::sample_full_file_0();
+/// // let content = std::fs::read("game.rofl").unwrap();
+/// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+/// let segments: Vec<_> = game.segment_iter(&PayloadIterOptions::new()).unwrap().collect();
+/// let description = serde_json::json!({
+///     "head": game.head(),
+///     "payload": game.payload().unwrap(),
+///     "segments": segments,
+/// });
+/// println!("{}", description);
+/// # assert_eq!(description["head"]["file_length"], content.len());
+/// # assert_eq!(description["payload"]["chunk_count"], 2);
+/// # assert_eq!(description["segments"][1]["chunk_id"], 2);
+/// # assert_eq!(description["segments"][1].get("data"), None);
+/// # }
+/// ```
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature="serde", derive(serde::Serialize))]
+pub struct BinHeader {
+    /// The file's signature
+    signature: Vec<u8>, // Fixed-size: 256 bits (or 0 if ignored)
+    /// The size of the header (constant in all known examples)
+    header_length: u16,
+    /// Total file length
+    file_length: u32,
+    /// Offset in bytes from the start of the file of the metadata section
+    metadata_offset: u32,
+    /// Length in bytes of the metadata section
+    metadata_length: u32,
+    /// Offset in bytes from the start of the file of the payload header section
+    payload_header_offset: u32,
+    /// Length in bytes of the payload header section
+    payload_header_length: u32,
+    /// Offset in bytes from the start of the file of the payload section
+    payload_offset: u32,
+}
+
+impl BinHeader {
+    /// Length in bytes of the header, including the file's magic
+    pub const LENGTH: usize = 288;
+    /// Length in bytes of the file's signature
+    pub(crate) const SIGNATURE_LEN: usize = 256;
+    /// Get the file's signature
+    pub fn signature(&self) -> &Vec<u8> {
+        &self.signature
+    }
+    /// Get the file's header length
+    pub fn header_len(&self) -> usize {
+        self.header_length as usize
+    }
+    /// Get the file's length in bytes as per its binary data
+    /// 
+    /// This may not match the actual file's length if an error occured
+    pub fn file_len(&self) -> usize {
+        self.file_length as usize
+    }
+    /// Length of the file's metadata section
+    /// 
+    /// This should not be required in normal use
+    pub fn metadata_len(&self) -> usize {
+        self.metadata_length as usize
+    }
+    /// Offset of the file's metadata section
+    /// 
+    /// This should not be required in normal use
+    pub fn metadata_offset(&self) -> usize {
+        self.metadata_offset as usize
+    }
+    /// Length of the file's payload header section
+    /// 
+    /// This should not be required in normal use
+    pub fn payload_header_len(&self) -> usize {
+        self.payload_header_length as usize
+    }
+    /// Offset of the file's payload header section
+    /// 
+    /// This should not be required in normal use
+    pub fn payload_header_offset(&self) -> usize {
+        self.payload_header_offset as usize
+    }
+    /// Offset of the file's payload section
+    /// 
+    /// This should not be required in normal use
+    pub fn payload_offset(&self) -> usize {
+        self.payload_offset as usize
+    }
+    
+    /// Create a new header describing a file with the provided sections' lengths
+    pub(crate) fn from_layout(signature: &[u8], metadata_len: usize, payload_header_len: usize, payload_len: usize) -> BinHeader {
+        let mut signature = signature.to_vec();
+        signature.resize(BinHeader::SIGNATURE_LEN, 0);
+        let header_length = BinHeader::LENGTH as u32;
+        BinHeader {
+            signature,
+            header_length: BinHeader::LENGTH as u16,
+            file_length: header_length + (metadata_len + payload_header_len + payload_len) as u32,
+            metadata_offset: header_length,
+            metadata_length: metadata_len as u32,
+            payload_header_offset: header_length + metadata_len as u32,
+            payload_header_length: payload_header_len as u32,
+            payload_offset: header_length + (metadata_len + payload_header_len) as u32,
+        }
+    }
+    /// Serialize the header, including the file's magic
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use lolrofl::model::BinHeader;
+    ///
+    /// # let content = lolrofl::test::sample_full_file_0();
+    /// // let content = std::fs::read("game.rofl").unwrap();
+    /// let head = BinHeader::from_raw_source(&content[..]).unwrap();
+    /// let bytes = head.to_bytes();
+    /// assert_eq!(&bytes[..], &content[..BinHeader::LENGTH]);
+    /// assert_eq!(BinHeader::from_raw_source(&bytes[..]).unwrap(), head);
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let mut data = vec![0; BinHeader::LENGTH];
+        data[..crate::Rofl::MAGIC.len()].copy_from_slice(&crate::Rofl::MAGIC);
+        let signature_len = std::cmp::min(self.signature.len(), BinHeader::SIGNATURE_LEN);
+        data[6..6+signature_len].copy_from_slice(&self.signature[..signature_len]);
+        LittleEndian::write_u16(&mut data[262..], self.header_length);
+        LittleEndian::write_u32(&mut data[264..], self.file_length);
+        LittleEndian::write_u32(&mut data[268..], self.metadata_offset);
+        LittleEndian::write_u32(&mut data[272..], self.metadata_length);
+        LittleEndian::write_u32(&mut data[276..], self.payload_header_offset);
+        LittleEndian::write_u32(&mut data[280..], self.payload_header_length);
+        LittleEndian::write_u32(&mut data[284..], self.payload_offset);
+        data
+    }
+    /// Create a new header from a manually-loaded file start section
+    /// 
+    /// Use from_raw_source instead
+    #[warn(deprecated)]
+    fn from_raw_section(data: &[u8]) -> Result<BinHeader, Errors> {
+        if data.len() < BinHeader::LENGTH {
+            return Err(Errors::too_small(BinHeader::LENGTH, data.len()));
+        }
+        let header = BinHeader {
+            signature: Vec::from(&data[6..262]),
+            header_length: LittleEndian::read_u16(&data[262..]),
+            file_length: LittleEndian::read_u32(&data[264..]),
+            metadata_offset: LittleEndian::read_u32(&data[268..]),
+            metadata_length: LittleEndian::read_u32(&data[272..]),
+            payload_header_offset: LittleEndian::read_u32(&data[276..]),
+            payload_header_length: LittleEndian::read_u32(&data[280..]),
+            payload_offset: LittleEndian::read_u32(&data[284..]),
+        };
+        // Sections must be found between the end of the header and the declared end of the file
+        let file_len = header.file_length as u64;
+        for (offset_field, offset, length_field, length) in [
+            ("metadata_offset", header.metadata_offset, "metadata_length", header.metadata_length),
+            ("payload_header_offset", header.payload_header_offset, "payload_header_length", header.payload_header_length),
+            ("payload_offset", header.payload_offset, "file_length", 0),
+        ] {
+            if (offset as usize) < BinHeader::LENGTH {
+                return Err(Errors::MalformedHeader { field: offset_field });
+            }
+            if offset as u64 + length as u64 > file_len {
+                return Err(Errors::MalformedHeader { field: length_field });
+            }
+        }
+        Ok(header)
+    }
+    /// Create a new header from a manually-loaded file start section
+    ///
+    /// # Errors
+    ///
+    /// If the data is too small to hold a header or if the header's sections are not found
+    /// between the end of the header and the declared end of the file
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use lolrofl::{Errors, model::BinHeader};
+    ///
+    /// # let content = lolrofl::test::sample_full_file_0();
+    /// // let content = std::fs::read("game.rofl").unwrap();
+    /// let head = BinHeader::from_raw_source(&content[..]).unwrap();
+    /// assert_eq!(head.file_len(), content.len());
+    ///
+    /// assert_eq!(matches!(BinHeader::from_raw_source(&content[..200]), Err(Errors::BufferTooSmall { expected: 288, actual: 200 })), true);
+    /// let mut broken = content.clone();
+    /// broken[275] = 0x01; // Metadata length
+    /// assert_eq!(matches!(BinHeader::from_raw_source(&broken[..]), Err(Errors::MalformedHeader { field: "metadata_length" })), true);
+    /// ```
+    pub fn from_raw_source(data: &[u8]) -> Result<BinHeader, Errors> {
+        BinHeader::from_raw_section(data)
+    }
+}
@@ -0,0 +1,69 @@
+use super::SegmentKind;
+
+/// What a range of a file's bytes holds
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Area {
+    /// The file's header
+    Header,
+    /// The game's JSON metadata
+    Metadata,
+    /// The payload's header
+    PayloadHeader,
+    /// The headers of the payload's segments
+    SegmentHeaders,
+    /// A segment's encrypted data
+    Segment {
+        /// Whether the segment is a chunk or a keyframe, `None` if its type is unknown
+        kind: Option<SegmentKind>,
+        /// The segment's ID
+        id: u32,
+    },
+    /// Bytes no other area covers
+    Unused,
+}
+
+impl std::fmt::Display for Area {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Area::Header => write!(f, "Header"),
+            Area::Metadata => write!(f, "Metadata"),
+            Area::PayloadHeader => write!(f, "Payload header"),
+            Area::SegmentHeaders => write!(f, "Segment headers"),
+            Area::Segment { kind: Some(SegmentKind::Chunk), id } => write!(f, "Chunk {}", id),
+            Area::Segment { kind: Some(SegmentKind::Keyframe), id } => write!(f, "Keyframe {}", id),
+            Area::Segment { kind: None, id } => write!(f, "Segment {}", id),
+            Area::Unused => write!(f, "Unused"),
+        }
+    }
+}
+
+/// A range of a file's bytes and what it holds
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct Extent {
+    /// What the range holds
+    area: Area,
+    /// The offset of the range's first byte in the file
+    offset: usize,
+    /// The range's length
+    len: usize,
+}
+
+impl Extent {
+    /// Build an extent from what it holds, its offset and its length
+    pub fn new(area: Area, offset: usize, len: usize) -> Extent {
+        Extent { area, offset, len }
+    }
+    /// What the range holds
+    pub fn area(&self) -> Area { self.area }
+    /// The offset of the range's first byte in the file
+    pub fn offset(&self) -> usize { self.offset }
+    /// The range's length
+    pub fn len(&self) -> usize { self.len }
+    /// Whether the range is empty
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+    /// The offset that follows the range's last byte
+    pub fn end(&self) -> usize { self.offset + self.len }
+    /// Whether the range holds a file offset
+    pub fn contains(&self, offset: usize) -> bool { (self.offset..self.end()).contains(&offset) }
+}
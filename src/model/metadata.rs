@@ -0,0 +1,916 @@
+/*!
+Access to the JSON metadata of a ROFL file
+*/
+
+use crate::Errors;
+
+/// Extract the raw JSON values of some of the top-level keys of a JSON object
+///
+/// The document is scanned without being parsed nor copied and the scan stops as
+/// soon as all requested keys are found. This avoids materializing a whole metadata
+/// document (and its large `statsJson` string) when only a few values are needed.
+///
+/// The returned values are in the same order as the requested keys and are raw JSON
+/// slices (strings are quoted and escaped, see [`unescape_json_string`]).
+/// Keys are compared without unescaping them.
+///
+/// # Errors
+///
+/// If the document is not a JSON object or is malformed before all keys are found
+///
+/// # Examples
+///
+/// ```rust
+/// use lolrofl::model::extract_keys;
+///
+/// # let content = lolrofl::test::sample_base_file_0();
+/// // let content = std::fs::read("game.rofl").unwrap();
+/// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+///
+/// let values = extract_keys(game.metadata().unwrap(), &["gameVersion", "gameLength", "unknown"]).unwrap();
+/// assert_eq!(values, vec![Some("\"12.10.444.2068\""), Some("91722"), None]);
+/// # let deep = format!("{{\"a\":{}{},\"b\":1}}", "[".repeat(1_000_000), "]".repeat(1_000_000));
+/// # assert_eq!(extract_keys(&deep, &["b"]).unwrap(), vec![Some("1")]);
+/// # assert_eq!(extract_keys(r#"{"a":[1,{"b":[]},"]"],"c":{"d":{}}}"#, &["a", "c"]).unwrap(), vec![Some(r#"[1,{"b":[]},"]"]"#), Some(r#"{"d":{}}"#)]);
+/// # assert_eq!(extract_keys(r#"{"a":[1}"#, &["b"]).is_err(), true);
+/// ```
+#[cfg(feature="json-stream")]
+pub fn extract_keys<'a>(json: &'a str, keys: &[&str]) -> Result<Vec<Option<&'a str>>, Errors> {
+    find_keys(json, keys)
+}
+
+/// Scan a JSON object for the raw values of some of its top-level keys
+pub(crate) fn find_keys<'a>(json: &'a str, keys: &[&str]) -> Result<Vec<Option<&'a str>>, Errors> {
+    let mut values = vec![None; keys.len()];
+    let mut remaining = keys.len();
+    let mut scanner = Scanner { data: json.as_bytes(), pos: 0 };
+
+    scanner.expect(b'{')?;
+    if scanner.peek()? == b'}' {
+        return Ok(values);
+    }
+    while remaining > 0 {
+        let (key_start, key_end) = scanner.string()?;
+        scanner.expect(b':')?;
+        scanner.skip_whitespace();
+        let value_start = scanner.pos;
+        scanner.value()?;
+        let key = &json[key_start+1..key_end-1];
+        for (i, k) in keys.iter().enumerate() {
+            if values[i].is_none() && *k == key {
+                values[i] = Some(&json[value_start..scanner.pos]);
+                remaining -= 1;
+            }
+        }
+        match scanner.next()? {
+            b',' => continue,
+            b'}' => break,
+            _ => return Err(Errors::InvalidBuffer),
+        }
+    }
+    Ok(values)
+}
+
+/// Check that a string is a single JSON object, without parsing its values
+pub(crate) fn check_json(json: &str) -> Result<(), Errors> {
+    let mut scanner = Scanner { data: json.as_bytes(), pos: 0 };
+    if scanner.peek()? != b'{' {
+        return Err(Errors::InvalidBuffer);
+    }
+    scanner.value()?;
+    scanner.skip_whitespace();
+    if scanner.pos == json.len() { Ok(()) } else { Err(Errors::InvalidBuffer) }
+}
+
+/// Decode a raw quoted JSON string value
+///
+/// # Examples
+///
+/// ```rust
+/// use lolrofl::model::unescape_json_string;
+///
+/// assert_eq!(unescape_json_string(r#""[{\"NAME\":\"Séb\"}]""#).unwrap(), "[{\"NAME\":\"Séb\"}]");
+/// ```
+#[cfg(feature="json-stream")]
+pub fn unescape_json_string(raw: &str) -> Result<String, Errors> {
+    unescape(raw)
+}
+
+/// Decode a raw quoted JSON string value
+fn unescape(raw: &str) -> Result<String, Errors> {
+    let raw = raw.strip_prefix('"').and_then(|r| r.strip_suffix('"')).ok_or(Errors::InvalidBuffer)?;
+    let mut output = String::with_capacity(raw.len());
+    let mut chars = raw.chars();
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            output.push(c);
+            continue;
+        }
+        match chars.next().ok_or(Errors::InvalidBuffer)? {
+            '"' => output.push('"'),
+            '\\' => output.push('\\'),
+            '/' => output.push('/'),
+            'b' => output.push('\u{8}'),
+            'f' => output.push('\u{c}'),
+            'n' => output.push('\n'),
+            'r' => output.push('\r'),
+            't' => output.push('\t'),
+            'u' => {
+                let mut code = read_hex_code(&mut chars)?;
+                if (0xd800..0xdc00).contains(&code) {
+                    // Surrogate pair
+                    if chars.next() != Some('\\') || chars.next() != Some('u') {
+                        return Err(Errors::InvalidBuffer);
+                    }
+                    let low = read_hex_code(&mut chars)?;
+                    code = 0x10000 + ((code - 0xd800) << 10) + (low.wrapping_sub(0xdc00) & 0x3ff);
+                }
+                output.push(char::from_u32(code).ok_or(Errors::InvalidBuffer)?);
+            },
+            _ => return Err(Errors::InvalidBuffer),
+        }
+    }
+    Ok(output)
+}
+
+/// The typed content of a file's JSON metadata
+///
+/// Fields that may be missing from the metadata (e.g. in custom or bot games) are optional.
+///
+/// # Examples
+///
+/// ```rust
+/// use lolrofl::model::Metadata;
+///
+/// # let content = lolrofl::test::sample_base_file_0();
+/// // let content = std::fs::read("game.rofl").unwrap();
+/// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+///
+/// let metadata = Metadata::from_json(game.metadata().unwrap()).unwrap();
+/// assert_eq!(metadata.game_length(), Some(91722));
+/// assert_eq!(metadata.game_version(), Some("12.10.444.2068"));
+/// assert_eq!(metadata.players().len(), 0);
+/// ```
+///
+/// ```rust
+/// // Bot games have players without a name and most stats are missing
+/// # let metadata = lolrofl::test::sample_metadata_bot_game_0();
+/// let metadata = lolrofl::model::Metadata::from_json(metadata).unwrap();
+/// assert_eq!(metadata.last_game_chunk_id(), None);
+///
+/// let bot = &metadata.players()[1];
+/// assert_eq!(bot.name(), None);
+/// assert_eq!(bot.champion(), Some("Ashe"));
+/// assert_eq!(bot.win(), None);
+/// assert_eq!(metadata.players()[2].team(), None);
+/// ```
+///
+/// With the `serde` feature, the typed metadata may be serialized with any serde format:
+///
+/// ```rust
+/// # #[cfg(feature = "serde")] {
+/// # let metadata = lolrofl::test::sample_metadata_ranked_0();
+/// let metadata = lolrofl::model::Metadata::from_json(metadata).unwrap();
+/// let serialized = serde_json::to_string(&metadata).unwrap();
+/// println!("{}", serialized);
+/// # assert_eq!(serialized.contains(r#""NAME":"Faker""#), true);
+/// # assert_eq!(serde_json::from_str::<lolrofl::model::Metadata>(&serialized).unwrap(), metadata);
+/// # }
+/// ```
+#[cfg(feature="json")]
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature="serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Metadata {
+    /// The game's duration in milliseconds
+    game_length: Option<u64>,
+    /// The game's version
+    game_version: Option<String>,
+    /// The game's creation time in milliseconds since the epoch
+    game_creation: Option<u64>,
+    /// The ID of the last chunk of the game
+    last_game_chunk_id: Option<u32>,
+    /// The ID of the last keyframe of the game
+    last_key_frame_id: Option<u32>,
+    /// The ID of the server platform the game was played on
+    platform_id: Option<String>,
+    /// The stats of the game's players
+    players: Vec<PlayerStats>,
+}
+
+#[cfg(feature="json")]
+impl Metadata {
+    /// The game's duration in milliseconds
+    pub fn game_length(&self) -> Option<u64> { self.game_length }
+    /// The game's version
+    pub fn game_version(&self) -> Option<&str> { self.game_version.as_deref() }
+    /// The game's version, parsed, `None` if it is missing or malformed
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # let metadata = lolrofl::test::sample_metadata_ranked_0();
+    /// let metadata = lolrofl::model::Metadata::from_json(metadata).unwrap();
+    /// assert_eq!(metadata.version().unwrap().is_at_least("13.1"), true);
+    /// ```
+    pub fn version(&self) -> Option<super::GameVersion> { self.game_version.as_deref()?.parse().ok() }
+    /// The game's creation time, as found in the `gameCreation` key in milliseconds since the epoch
+    ///
+    /// The files written by the game client do not hold it, it is only available when the
+    /// metadata was completed by another tool, such as with the match data of Riot's API.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use std::time::{Duration, UNIX_EPOCH};
+    ///
+    /// let metadata = lolrofl::model::Metadata::from_json(r#"{"gameCreation":1700000000000,"statsJson":"[]"}"#).unwrap();
+    /// assert_eq!(metadata.game_creation(), Some(UNIX_EPOCH + Duration::from_secs(1700000000)));
+    /// # let metadata = lolrofl::test::sample_metadata_ranked_0();
+    /// # assert_eq!(lolrofl::model::Metadata::from_json(metadata).unwrap().game_creation(), None);
+    /// ```
+    pub fn game_creation(&self) -> Option<std::time::SystemTime> {
+        self.game_creation.map(|ms| std::time::UNIX_EPOCH + std::time::Duration::from_millis(ms))
+    }
+    /// The game's creation time as a UTC date-time, see [`Metadata::game_creation`]
+    ///
+    /// This requires the `time` feature, convert the result with
+    /// [`OffsetDateTime::to_offset`](time::OffsetDateTime::to_offset) to get a local time.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let metadata = lolrofl::model::Metadata::from_json(r#"{"gameCreation":1700000000000,"statsJson":"[]"}"#).unwrap();
+    /// let created = metadata.game_creation_time().unwrap();
+    /// assert_eq!(created.to_offset(time::UtcOffset::from_hms(1, 0, 0).unwrap()).hour(), 23);
+    /// ```
+    #[cfg(feature="time")]
+    pub fn game_creation_time(&self) -> Option<time::OffsetDateTime> {
+        time::OffsetDateTime::from_unix_timestamp_nanos(self.game_creation? as i128 * 1_000_000).ok()
+    }
+    /// The ID of the last chunk of the game
+    pub fn last_game_chunk_id(&self) -> Option<u32> { self.last_game_chunk_id }
+    /// The ID of the last keyframe of the game
+    pub fn last_key_frame_id(&self) -> Option<u32> { self.last_key_frame_id }
+    /// The stats of the game's players, as found in the `statsJson` key
+    pub fn players(&self) -> &[PlayerStats] { &self.players }
+    /// The ID of the server platform the game was played on, such as `EUW1`
+    ///
+    /// The platform is read from the `platformId` key, or from the `gameKey` object of metadata
+    /// downloaded from a spectator server. Files written by the game client do not hold it.
+    pub fn platform_id(&self) -> Option<&str> { self.platform_id.as_deref() }
+    /// The region the game was played in, `None` if the metadata does not hold a known platform
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use lolrofl::model::{Metadata, Region};
+    ///
+    /// let metadata = Metadata::from_json(r#"{"gameLength":1000,"platformId":"EUW1","statsJson":"[]"}"#).unwrap();
+    /// assert_eq!(metadata.region(), Some(Region::Euw));
+    /// # let metadata = Metadata::from_json(r#"{"gameKey":{"gameId":1,"platformId":"KR"}}"#).unwrap();
+    /// # assert_eq!(metadata.region(), Some(Region::Kr));
+    /// # let metadata = Metadata::from_json(lolrofl::test::sample_metadata_ranked_0()).unwrap();
+    /// # assert_eq!(metadata.region(), None);
+    /// ```
+    pub fn region(&self) -> Option<super::Region> { self.platform_id.as_deref()?.parse().ok() }
+    /// The players' stats aggregated by team, sorted by team ID
+    ///
+    /// Players without a team are ignored
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # let metadata = lolrofl::test::sample_metadata_ranked_0();
+    /// let metadata = lolrofl::model::Metadata::from_json(metadata).unwrap();
+    /// for team in metadata.team_stats() {
+    ///     println!("Team {}: {} kills, {} gold", team.team(), team.kills(), team.gold());
+    /// }
+    /// # let teams = metadata.team_stats();
+    /// # assert_eq!(teams.len(), 2);
+    /// # assert_eq!((teams[0].team(), teams[0].kills(), teams[0].win()), (100, 7, Some(true)));
+    /// # assert_eq!((teams[1].team(), teams[1].players(), teams[1].win()), (200, 1, Some(false)));
+    /// ```
+    pub fn team_stats(&self) -> Vec<TeamStats> {
+        let mut teams = std::collections::BTreeMap::<u32, TeamStats>::new();
+        for player in &self.players {
+            if let Some(team) = player.team() {
+                teams.entry(team).or_insert_with(|| TeamStats { team, ..TeamStats::default() }).add(player);
+            }
+        }
+        teams.into_values().collect()
+    }
+    /// How the game ended, as found in the players' stats flags
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use lolrofl::model::{EndReason, Metadata};
+    ///
+    /// let metadata = Metadata::from_json(r#"{"statsJson":"[{\"WIN\":\"Win\",\"GAME_ENDED_IN_EARLY_SURRENDER\":\"1\"}]"}"#).unwrap();
+    /// assert_eq!(metadata.end_reason(), EndReason::Remake);
+    ///
+    /// # let metadata = lolrofl::test::sample_metadata_ranked_0();
+    /// let metadata = Metadata::from_json(metadata).unwrap();
+    /// assert_eq!(metadata.end_reason(), EndReason::NexusDestroyed);
+    /// # assert_eq!(Metadata::from_json("{}").unwrap().end_reason(), EndReason::Unknown);
+    /// ```
+    pub fn end_reason(&self) -> EndReason {
+        let flagged = |key: &str| self.players.iter().any(|p| p.get_number(key).unwrap_or(0) != 0);
+        if flagged("GAME_ENDED_IN_EARLY_SURRENDER") {
+            EndReason::Remake
+        } else if flagged("GAME_ENDED_IN_SURRENDER") {
+            EndReason::Surrender
+        } else if self.players.iter().any(|p| p.win().is_some()) {
+            EndReason::NexusDestroyed
+        } else {
+            EndReason::Unknown
+        }
+    }
+    /// Re-serialize a file's JSON metadata in a canonical form
+    ///
+    /// Object keys are sorted, whitespace is removed and numbers are written in their
+    /// shortest form (`1.50` becomes `1.5`). The `statsJson` value is canonicalized too,
+    /// so that metadata of different replays or written by different tools can be diffed.
+    ///
+    /// # Errors
+    ///
+    /// If the metadata or its `statsJson` value are not valid JSON
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use lolrofl::model::Metadata;
+    ///
+    /// let raw = r#"{ "lastGameChunkId": 3, "gameLength": 1.50e3,
+    ///     "statsJson": "[{\"WIN\":\"Win\", \"NAME\":\"Faker\"}]" }"#;
+    /// assert_eq!(
+    ///     Metadata::canonicalize(raw).unwrap(),
+    ///     r#"{"gameLength":1500,"lastGameChunkId":3,"statsJson":"[{\"NAME\":\"Faker\",\"WIN\":\"Win\"}]"}"#,
+    /// );
+    /// # let metadata = lolrofl::test::sample_metadata_ranked_0();
+    /// # let canonical = Metadata::canonicalize(metadata).unwrap();
+    /// # assert_eq!(Metadata::canonicalize(&canonical).unwrap(), canonical);
+    /// # assert_eq!(Metadata::from_json(&canonical).unwrap(), Metadata::from_json(metadata).unwrap());
+    /// ```
+    pub fn canonicalize(metadata: &str) -> Result<String, Errors> {
+        let mut root = canonical_value(&json::parse(metadata)?);
+        if let Some(stats) = root["statsJson"].as_str().filter(|s| !s.trim().is_empty()) {
+            root["statsJson"] = canonical_value(&json::parse(stats)?).dump().into();
+        }
+        Ok(root.dump())
+    }
+    /// Parse a file's JSON metadata
+    ///
+    /// # Errors
+    ///
+    /// If the metadata or its `statsJson` value are not valid JSON
+    pub fn from_json(metadata: &str) -> Result<Metadata, Errors> {
+        let root = json::parse(metadata)?;
+        let stats = match root["statsJson"].as_str() {
+            Some(stats) if !stats.trim().is_empty() => json::parse(stats)?,
+            _ => json::JsonValue::new_array(),
+        };
+        Ok(Metadata {
+            game_length: root["gameLength"].as_u64(),
+            game_version: root["gameVersion"].as_str().filter(|v| !v.is_empty()).map(str::to_string),
+            game_creation: root["gameCreation"].as_u64(),
+            last_game_chunk_id: root["lastGameChunkId"].as_u32(),
+            last_key_frame_id: root["lastKeyFrameId"].as_u32(),
+            platform_id: root["platformId"].as_str().or_else(|| root["gameKey"]["platformId"].as_str())
+                .filter(|p| !p.is_empty()).map(str::to_string),
+            players: stats.members().map(PlayerStats::from_json).collect(),
+        })
+    }
+}
+
+/// Copy a JSON value with its objects' keys sorted and its numbers in their shortest form
+#[cfg(feature="json")]
+fn canonical_value(value: &json::JsonValue) -> json::JsonValue {
+    match value {
+        json::JsonValue::Object(object) => {
+            let mut entries: Vec<(&str, &json::JsonValue)> = object.iter().collect();
+            entries.sort_by(|a, b| a.0.cmp(b.0));
+            let mut canonical = json::JsonValue::new_object();
+            for (key, value) in entries {
+                canonical[key] = canonical_value(value);
+            }
+            canonical
+        },
+        json::JsonValue::Array(values) => json::JsonValue::Array(values.iter().map(canonical_value).collect()),
+        json::JsonValue::Number(number) => {
+            let (positive, mut mantissa, mut exponent) = number.as_parts();
+            while mantissa != 0 && mantissa % 10 == 0 {
+                mantissa /= 10;
+                exponent += 1;
+            }
+            if mantissa == 0 {
+                exponent = 0;
+            }
+            json::JsonValue::Number(json::number::Number::from_parts(positive || mantissa == 0, mantissa, exponent))
+        },
+        value => value.clone(),
+    }
+}
+
+/// The stats of a team's players, summed
+#[cfg(feature="json")]
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+#[cfg_attr(feature="serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct TeamStats {
+    /// The team's ID (`100` for blue side, `200` for red side)
+    team: u32,
+    /// The number of players in the team
+    players: usize,
+    /// The number of champions killed by the team
+    kills: i64,
+    /// The number of deaths of the team's players
+    deaths: i64,
+    /// The gold earned by the team
+    gold: i64,
+    /// The number of turrets destroyed by the team
+    towers: i64,
+    /// The number of dragons killed by the team
+    dragons: i64,
+    /// The number of barons killed by the team
+    barons: i64,
+    /// Whether the team won the game
+    win: Option<bool>,
+}
+
+#[cfg(feature="json")]
+impl TeamStats {
+    /// The team's ID (`100` for blue side, `200` for red side)
+    pub fn team(&self) -> u32 { self.team }
+    /// The number of players in the team
+    pub fn players(&self) -> usize { self.players }
+    /// The number of champions killed by the team
+    pub fn kills(&self) -> i64 { self.kills }
+    /// The number of deaths of the team's players
+    pub fn deaths(&self) -> i64 { self.deaths }
+    /// The gold earned by the team
+    pub fn gold(&self) -> i64 { self.gold }
+    /// The number of turrets destroyed by the team
+    pub fn towers(&self) -> i64 { self.towers }
+    /// The number of dragons killed by the team
+    pub fn dragons(&self) -> i64 { self.dragons }
+    /// The number of barons killed by the team
+    pub fn barons(&self) -> i64 { self.barons }
+    /// Whether the team won the game, `None` if none of its players has a result
+    pub fn win(&self) -> Option<bool> { self.win }
+    /// Add a player's stats to the team's
+    fn add(&mut self, player: &PlayerStats) {
+        let stat = |key: &str| player.get_number(key).unwrap_or(0);
+        self.players += 1;
+        self.kills += stat("CHAMPIONS_KILLED");
+        self.deaths += stat("NUM_DEATHS");
+        self.gold += stat("GOLD_EARNED");
+        self.towers += stat("TURRETS_KILLED");
+        self.dragons += stat("DRAGON_KILLS");
+        self.barons += stat("BARON_KILLS");
+        self.win = self.win.or(player.win());
+    }
+}
+
+/// A player's identity, unified across the metadata schemas of different game versions
+///
+/// Older replays only hold a summoner name, newer ones hold a Riot ID (game name and tag line)
+/// and a PUUID that never changes for a given account.
+///
+/// # Examples
+///
+/// ```rust
+/// use lolrofl::model::PlayerIdentity;
+///
+/// # let metadata = lolrofl::test::sample_metadata_ranked_0();
+/// let metadata = lolrofl::model::Metadata::from_json(metadata).unwrap();
+/// let faker = metadata.players()[0].identity();
+/// assert_eq!(faker.matches("faker"), true);
+///
+/// let riot_id = PlayerIdentity::from_riot_id("Hide on bush#KR1").unwrap();
+/// assert_eq!(riot_id.riot_id(), Some("Hide on bush#KR1".to_string()));
+/// assert_eq!(riot_id.matches("hideonbush#kr1"), true);
+/// # assert_eq!(faker.puuid(), Some("a1b2c3"));
+/// # assert_eq!(faker.display_name(), Some("Faker".to_string()));
+/// # assert_eq!(faker.same_player(&metadata.players()[1].identity()), false);
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq, Hash)]
+pub struct PlayerIdentity {
+    /// The account's permanent ID
+    puuid: Option<String>,
+    /// The game name part of the player's Riot ID
+    game_name: Option<String>,
+    /// The tag line part of the player's Riot ID
+    tag_line: Option<String>,
+    /// The player's summoner name, used before Riot IDs
+    summoner_name: Option<String>,
+}
+
+impl PlayerIdentity {
+    /// Build an identity from a Riot ID such as `Name#TAG`
+    pub fn from_riot_id(riot_id: &str) -> Option<PlayerIdentity> {
+        let (game_name, tag_line) = riot_id.rsplit_once('#')?;
+        if game_name.is_empty() || tag_line.is_empty() {
+            return None;
+        }
+        Some(PlayerIdentity {
+            game_name: Some(game_name.to_string()),
+            tag_line: Some(tag_line.to_string()),
+            ..PlayerIdentity::default()
+        })
+    }
+    /// The account's permanent ID
+    pub fn puuid(&self) -> Option<&str> { self.puuid.as_deref() }
+    /// The game name part of the player's Riot ID
+    pub fn game_name(&self) -> Option<&str> { self.game_name.as_deref() }
+    /// The tag line part of the player's Riot ID
+    pub fn tag_line(&self) -> Option<&str> { self.tag_line.as_deref() }
+    /// The player's summoner name
+    pub fn summoner_name(&self) -> Option<&str> { self.summoner_name.as_deref() }
+    /// The player's full Riot ID (`Name#TAG`)
+    pub fn riot_id(&self) -> Option<String> {
+        match (&self.game_name, &self.tag_line) {
+            (Some(name), Some(tag)) => Some(format!("{}#{}", name, tag)),
+            _ => None,
+        }
+    }
+    /// The name to show for the player: the Riot ID if available, else the summoner name or game name
+    pub fn display_name(&self) -> Option<String> {
+        self.riot_id().or_else(|| self.summoner_name.clone()).or_else(|| self.game_name.clone())
+    }
+    /// Whether a name, Riot ID or PUUID designates the player
+    ///
+    /// Names are compared ignoring case and whitespace, as the game does
+    pub fn matches(&self, query: &str) -> bool {
+        let query = normalize_name(query);
+        [self.riot_id(), self.game_name.clone(), self.summoner_name.clone(), self.puuid.clone()]
+            .iter().flatten()
+            .any(|v| normalize_name(v) == query)
+    }
+    /// Whether two identities designate the same player
+    ///
+    /// PUUIDs are compared if both identities have one, else names are
+    pub fn same_player(&self, other: &PlayerIdentity) -> bool {
+        if let (Some(a), Some(b)) = (&self.puuid, &other.puuid) {
+            return a == b;
+        }
+        if let (Some(a), Some(b)) = (self.riot_id(), other.riot_id()) {
+            return normalize_name(&a) == normalize_name(&b);
+        }
+        match (self.summoner_name.as_ref().or(self.game_name.as_ref()), other.summoner_name.as_ref().or(other.game_name.as_ref())) {
+            (Some(a), Some(b)) => normalize_name(a) == normalize_name(b),
+            _ => false,
+        }
+    }
+}
+
+impl std::fmt::Display for PlayerIdentity {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.display_name().as_deref().unwrap_or("?"))
+    }
+}
+
+/// Normalize a player name for comparisons
+fn normalize_name(name: &str) -> String {
+    name.chars().filter(|c| !c.is_whitespace()).flat_map(char::to_lowercase).collect()
+}
+
+/// How a game ended
+///
+/// Remakes are early surrenders, they should usually be excluded from aggregate stats
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[cfg_attr(feature="serde", derive(serde::Serialize, serde::Deserialize))]
+#[non_exhaustive]
+pub enum EndReason {
+    /// A team surrendered
+    Surrender,
+    /// A team surrendered early, usually because a player never connected
+    Remake,
+    /// A team's nexus was destroyed
+    NexusDestroyed,
+    /// The metadata holds no result
+    Unknown,
+}
+
+impl std::fmt::Display for EndReason {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            EndReason::Surrender => write!(f, "Surrender"),
+            EndReason::Remake => write!(f, "Remake"),
+            EndReason::NexusDestroyed => write!(f, "Nexus destroyed"),
+            EndReason::Unknown => write!(f, "Unknown"),
+        }
+    }
+}
+
+/// The stats of a player, as found in the metadata's `statsJson` key
+///
+/// Stats are stored as strings by the game, missing or empty values are `None`
+///
+/// # Examples
+///
+/// ```rust
+/// # let metadata = lolrofl::test::sample_metadata_ranked_0();
+/// let metadata = lolrofl::model::Metadata::from_json(metadata).unwrap();
+/// for player in metadata.players() {
+///     println!("{} played {}", player.name().unwrap_or("?"), player.champion().unwrap_or("?"));
+/// }
+/// # let player = &metadata.players()[1];
+/// # assert_eq!(player.name(), Some("케인"));
+/// # assert_eq!(player.team(), Some(200));
+/// # assert_eq!(player.win(), Some(false));
+/// # assert_eq!(player.get_number("CHAMPIONS_KILLED"), Some(2));
+/// ```
+#[cfg(feature="json")]
+#[derive(Clone, Debug, Default, PartialEq)]
+#[cfg_attr(feature="serde", derive(serde::Serialize, serde::Deserialize), serde(transparent))]
+pub struct PlayerStats {
+    /// All of the player's stats by key
+    stats: std::collections::BTreeMap<String, String>,
+}
+
+#[cfg(feature="json")]
+impl PlayerStats {
+    /// Get a stat by its key (e.g. `CHAMPIONS_KILLED`)
+    pub fn get(&self, key: &str) -> Option<&str> {
+        self.stats.get(key).map(String::as_str).filter(|v| !v.is_empty())
+    }
+    /// Get a numeric stat by its key
+    pub fn get_number(&self, key: &str) -> Option<i64> {
+        self.get(key).and_then(|v| v.parse().ok())
+    }
+    /// All of the player's stats, by key
+    pub fn stats(&self) -> &std::collections::BTreeMap<String, String> { &self.stats }
+    /// The player's name
+    pub fn name(&self) -> Option<&str> { self.get("NAME") }
+    /// The player's identity, whichever fields the replay's game version provides
+    pub fn identity(&self) -> PlayerIdentity {
+        let field = |key: &str| self.get(key).map(str::to_string);
+        PlayerIdentity {
+            puuid: field("PUUID"),
+            game_name: field("RIOT_ID_GAME_NAME"),
+            tag_line: field("RIOT_ID_TAG_LINE").or_else(|| field("RIOT_ID_TAGLINE")),
+            summoner_name: field("NAME"),
+        }
+    }
+    /// The player's KDA ratio: kills and assists per death, or kills and assists if the player never died
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let metadata = lolrofl::model::Metadata::from_json(
+    ///     r#"{"statsJson":"[{\"CHAMPIONS_KILLED\":\"6\",\"NUM_DEATHS\":\"2\",\"ASSISTS\":\"4\"},{\"CHAMPIONS_KILLED\":\"3\"}]"}"#
+    /// ).unwrap();
+    /// assert_eq!(metadata.players()[0].kda(), Some(5.));
+    /// assert_eq!(metadata.players()[1].kda(), None);
+    /// ```
+    pub fn kda(&self) -> Option<f64> {
+        let kills = self.get_number("CHAMPIONS_KILLED")?;
+        let deaths = self.get_number("NUM_DEATHS")?;
+        let assists = self.get_number("ASSISTS")?;
+        Some((kills + assists) as f64 / std::cmp::max(deaths, 1) as f64)
+    }
+    /// The name of the player's champion
+    pub fn champion(&self) -> Option<&str> { self.get("SKIN") }
+    /// The player's team (`100` for blue side, `200` for red side)
+    ///
+    /// Older replays that use `1` and `2` are mapped to `100` and `200`
+    pub fn team(&self) -> Option<u32> {
+        match self.get("TEAM").and_then(|v| v.parse().ok()) {
+            Some(1) => Some(100),
+            Some(2) => Some(200),
+            team => team,
+        }
+    }
+    /// Whether the player won the game
+    pub fn win(&self) -> Option<bool> {
+        match self.get("WIN") {
+            Some("Win") => Some(true),
+            Some("Fail") => Some(false),
+            _ => None,
+        }
+    }
+    /// Parse a player's stats from a JSON object
+    fn from_json(value: &json::JsonValue) -> PlayerStats {
+        let stats = value.entries()
+            .filter_map(|(k, v)| {
+                let value = if v.is_string() { v.as_str().map(str::to_string) } else if v.is_null() { None } else { Some(v.dump()) };
+                value.map(|v| (k.to_string(), v))
+            })
+            .collect();
+        PlayerStats { stats }
+    }
+}
+
+/// The mode of a game, which determines the map and the objectives that are available
+///
+/// The mode is read from the metadata's `gameMode`, `mapId` or `queueId` keys when present.
+/// Most replays do not hold these keys, the mode is then [`GameMode::Unknown`].
+///
+/// # Examples
+///
+/// ```rust
+/// use lolrofl::model::GameMode;
+///
+/// assert_eq!(GameMode::from_metadata(r#"{"gameLength":1000,"mapId":12}"#), GameMode::Aram);
+/// assert_eq!(GameMode::from_metadata(r#"{"gameMode":"CLASSIC"}"#), GameMode::Classic);
+/// assert_eq!(GameMode::from_queue_id(1700), GameMode::Arena);
+/// assert_eq!(GameMode::Aram.has_jungle(), false);
+///
+/// # let content = lolrofl::test::sample_base_file_0();
+/// // let content = std::fs::read("game.rofl").unwrap();
+/// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+/// assert_eq!(GameMode::from_metadata(game.metadata().unwrap()), GameMode::Unknown);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum GameMode {
+    /// Summoner's Rift games (normal, ranked, bots, custom)
+    Classic,
+    /// All Random All Mid, played on the Howling Abyss
+    Aram,
+    /// Twisted Treeline games
+    TwistedTreeline,
+    /// Nexus Blitz
+    NexusBlitz,
+    /// Arena (2v2v2v2), played on the Rings of Wrath
+    Arena,
+    /// Ultra Rapid Fire and One For All, played on Summoner's Rift
+    Rotating,
+    /// The mode could not be determined
+    Unknown,
+}
+
+impl GameMode {
+    /// Get a game mode from the ID of the map it is played on
+    pub fn from_map_id(map_id: u64) -> GameMode {
+        match map_id {
+            1 | 2 | 11 => GameMode::Classic,
+            10 => GameMode::TwistedTreeline,
+            12 | 14 => GameMode::Aram,
+            21 => GameMode::NexusBlitz,
+            30 => GameMode::Arena,
+            _ => GameMode::Unknown,
+        }
+    }
+    /// Get a game mode from a matchmaking queue ID
+    pub fn from_queue_id(queue_id: u64) -> GameMode {
+        match queue_id {
+            0 | 400 | 420 | 430 | 440 | 490 | 700 | 830 | 840 | 850 => GameMode::Classic,
+            450 | 100 => GameMode::Aram,
+            460 | 470 => GameMode::TwistedTreeline,
+            1200 | 1300 => GameMode::NexusBlitz,
+            1700 | 1710 => GameMode::Arena,
+            900 | 1010 | 1020 | 1900 => GameMode::Rotating,
+            _ => GameMode::Unknown,
+        }
+    }
+    /// Get a game mode from the name used by the game client
+    pub fn from_name(name: &str) -> GameMode {
+        match name.to_ascii_uppercase().as_str() {
+            "CLASSIC" | "PRACTICETOOL" => GameMode::Classic,
+            "ARAM" => GameMode::Aram,
+            "NEXUSBLITZ" => GameMode::NexusBlitz,
+            "CHERRY" => GameMode::Arena,
+            "URF" | "ARURF" | "ONEFORALL" => GameMode::Rotating,
+            _ => GameMode::Unknown,
+        }
+    }
+    /// Detect a game's mode from its JSON metadata
+    pub fn from_metadata(metadata: &str) -> GameMode {
+        let values = match find_keys(metadata, &["gameMode", "mapId", "queueId"]) {
+            Ok(values) => values,
+            Err(_) => return GameMode::Unknown,
+        };
+        let name = values[0].and_then(|v| unescape(v).ok()).map(|v| GameMode::from_name(&v));
+        let map = values[1].and_then(|v| v.trim_matches('"').parse().ok()).map(GameMode::from_map_id);
+        let queue = values[2].and_then(|v| v.trim_matches('"').parse().ok()).map(GameMode::from_queue_id);
+        [name, map, queue].into_iter().flatten()
+            .find(|m| *m != GameMode::Unknown)
+            .unwrap_or(GameMode::Unknown)
+    }
+    /// Whether the game's map has a jungle with neutral objectives (dragons, barons, ...)
+    ///
+    /// Analysis of jungle and objective events is meaningless when this is `false`
+    pub fn has_jungle(&self) -> bool {
+        matches!(self, GameMode::Classic | GameMode::TwistedTreeline | GameMode::NexusBlitz | GameMode::Rotating | GameMode::Unknown)
+    }
+}
+
+impl std::fmt::Display for GameMode {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            GameMode::Classic => write!(f, "Classic"),
+            GameMode::Aram => write!(f, "ARAM"),
+            GameMode::TwistedTreeline => write!(f, "Twisted Treeline"),
+            GameMode::NexusBlitz => write!(f, "Nexus Blitz"),
+            GameMode::Arena => write!(f, "Arena"),
+            GameMode::Rotating => write!(f, "Rotating game mode"),
+            GameMode::Unknown => write!(f, "Unknown"),
+        }
+    }
+}
+
+/// Read the 4 hexadecimal digits of a JSON unicode escape sequence
+fn read_hex_code(chars: &mut std::str::Chars) -> Result<u32, Errors> {
+    let digits: String = chars.take(4).collect();
+    if digits.len() != 4 {
+        return Err(Errors::InvalidBuffer);
+    }
+    u32::from_str_radix(&digits, 16).map_err(|_| Errors::InvalidBuffer)
+}
+
+/// Minimal JSON scanner that validates the document's structure without parsing values
+struct Scanner<'a> {
+    /// The scanned document
+    data: &'a [u8],
+    /// The scanner's position in the document
+    pos: usize,
+}
+
+impl Scanner<'_> {
+    fn skip_whitespace(&mut self) {
+        while self.pos < self.data.len() && self.data[self.pos].is_ascii_whitespace() {
+            self.pos += 1;
+        }
+    }
+    /// Get the next non-whitespace byte without consuming it
+    fn peek(&mut self) -> Result<u8, Errors> {
+        self.skip_whitespace();
+        self.data.get(self.pos).copied().ok_or(Errors::too_small(self.pos + 1, self.data.len()))
+    }
+    /// Consume the next non-whitespace byte
+    fn next(&mut self) -> Result<u8, Errors> {
+        let b = self.peek()?;
+        self.pos += 1;
+        Ok(b)
+    }
+    fn expect(&mut self, expected: u8) -> Result<(), Errors> {
+        if self.next()? == expected { Ok(()) } else { Err(Errors::InvalidBuffer) }
+    }
+    /// Skip a string and return its bounds, quotes included
+    fn string(&mut self) -> Result<(usize, usize), Errors> {
+        self.expect(b'"')?;
+        let start = self.pos - 1;
+        while self.pos < self.data.len() {
+            match self.data[self.pos] {
+                b'\\' => self.pos += 2,
+                b'"' => {
+                    self.pos += 1;
+                    return Ok((start, self.pos));
+                },
+                _ => self.pos += 1,
+            }
+        }
+        Err(Errors::too_small(self.pos + 1, self.data.len()))
+    }
+    /// Skip any JSON value
+    ///
+    /// Nested arrays and objects are tracked with a stack rather than recursion, so that the
+    /// documents of untrusted files can't exhaust the call stack however deep they are
+    fn value(&mut self) -> Result<(), Errors> {
+        let mut closing = Vec::new();
+        loop {
+            match self.peek()? {
+                b'"' => { self.string()?; },
+                open @ (b'{' | b'[') => {
+                    let close = if open == b'{' {b'}'} else {b']'};
+                    self.pos += 1;
+                    if self.peek()? == close {
+                        self.pos += 1;
+                    } else {
+                        if open == b'{' {
+                            self.string()?;
+                            self.expect(b':')?;
+                        }
+                        closing.push(close);
+                        continue;
+                    }
+                },
+                _ => {
+                    let start = self.pos;
+                    while self.pos < self.data.len() && matches!(self.data[self.pos], b'-' | b'+' | b'.' | b'0'..=b'9' | b'a'..=b'z' | b'E') {
+                        self.pos += 1;
+                    }
+                    if start == self.pos {
+                        return Err(Errors::InvalidBuffer);
+                    }
+                },
+            }
+            // Close the containers that end after the value, up to the one that holds a next value
+            loop {
+                let Some(&close) = closing.last() else { return Ok(()) };
+                match self.next()? {
+                    b',' => {
+                        if close == b'}' {
+                            self.string()?;
+                            self.expect(b':')?;
+                        }
+                        break;
+                    },
+                    c if c == close => { closing.pop(); },
+                    _ => return Err(Errors::InvalidBuffer),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,130 @@
+/// The server platform a game was played on
+///
+/// ROFL files written by the game client do not hold their platform, however the client names
+/// replays after their platform ID and game ID (e.g. `EUW1-5900755914.rofl`). Files downloaded
+/// from a spectator server hold it in their metadata, see [`Metadata::region`](super::Metadata::region).
+///
+/// Game IDs are assigned by each platform and their ranges overlap, so the platform can't be
+/// decoded from a game's ID.
+///
+/// # Examples
+///
+/// ```rust
+/// use lolrofl::model::Region;
+///
+/// assert_eq!(Region::from_file_name("EUW1-5900755914.rofl"), Some(Region::Euw));
+/// assert_eq!(Region::from_file_name("my_game.rofl"), None);
+/// assert_eq!("kr".parse::<Region>().unwrap().to_string(), "KR");
+/// assert_eq!(Region::Na.platform_id(), "NA1");
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+#[non_exhaustive]
+pub enum Region {
+    /// Brazil
+    Br,
+    /// Europe Nordic & East
+    Eune,
+    /// Europe West
+    Euw,
+    /// Japan
+    Jp,
+    /// Korea
+    Kr,
+    /// Latin America North
+    Lan,
+    /// Latin America South
+    Las,
+    /// North America
+    Na,
+    /// Oceania
+    Oce,
+    /// Turkey
+    Tr,
+    /// Russia
+    Ru,
+    /// Philippines
+    Ph,
+    /// Singapore
+    Sg,
+    /// Thailand
+    Th,
+    /// Taiwan
+    Tw,
+    /// Vietnam
+    Vn,
+    /// Public Beta Environment
+    Pbe,
+}
+
+impl Region {
+    /// All known regions
+    pub const ALL: [Region; 17] = [
+        Region::Br, Region::Eune, Region::Euw, Region::Jp, Region::Kr, Region::Lan, Region::Las, Region::Na, Region::Oce,
+        Region::Tr, Region::Ru, Region::Ph, Region::Sg, Region::Th, Region::Tw, Region::Vn, Region::Pbe,
+    ];
+    /// The region's platform ID, as used by the game client and the Riot API
+    pub fn platform_id(&self) -> &'static str {
+        match self {
+            Region::Br => "BR1",
+            Region::Eune => "EUN1",
+            Region::Euw => "EUW1",
+            Region::Jp => "JP1",
+            Region::Kr => "KR",
+            Region::Lan => "LA1",
+            Region::Las => "LA2",
+            Region::Na => "NA1",
+            Region::Oce => "OC1",
+            Region::Tr => "TR1",
+            Region::Ru => "RU",
+            Region::Ph => "PH2",
+            Region::Sg => "SG2",
+            Region::Th => "TH2",
+            Region::Tw => "TW2",
+            Region::Vn => "VN2",
+            Region::Pbe => "PBE1",
+        }
+    }
+    /// The region's short name
+    pub fn name(&self) -> &'static str {
+        match self {
+            Region::Br => "BR",
+            Region::Eune => "EUNE",
+            Region::Euw => "EUW",
+            Region::Jp => "JP",
+            Region::Kr => "KR",
+            Region::Lan => "LAN",
+            Region::Las => "LAS",
+            Region::Na => "NA",
+            Region::Oce => "OCE",
+            Region::Tr => "TR",
+            Region::Ru => "RU",
+            Region::Ph => "PH",
+            Region::Sg => "SG",
+            Region::Th => "TH",
+            Region::Tw => "TW",
+            Region::Vn => "VN",
+            Region::Pbe => "PBE",
+        }
+    }
+    /// Get the region of a replay from its file name, if it was named by the game client
+    pub fn from_file_name(name: &str) -> Option<Region> {
+        let prefix = name.split(['-', '_']).next()?;
+        Region::ALL.into_iter().find(|r| r.platform_id().eq_ignore_ascii_case(prefix))
+    }
+}
+
+impl std::fmt::Display for Region {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.name())
+    }
+}
+
+impl std::str::FromStr for Region {
+    type Err = crate::Errors;
+    /// Parse a region from its short name or platform ID
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Region::ALL.into_iter()
+            .find(|r| r.name().eq_ignore_ascii_case(s) || r.platform_id().eq_ignore_ascii_case(s))
+            .ok_or(crate::Errors::InvalidArgument { name: "region", reason: "expected a region's short name or platform ID" })
+    }
+}
@@ -0,0 +1,142 @@
+use crate::{Errors, Segment};
+use super::{GenericSection, SectionCore};
+
+/// The position of a section within its segment's decrypted data
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum SectionPosition {
+    /// The offset in bytes of the section's start
+    Offset(usize),
+    /// The index of the section among the segment's sections
+    Index(usize),
+}
+
+/// The canonical address of a section in a file
+///
+/// Addresses are formatted as `chunk:42/offset:0x1A3` or `kf:7/idx:15`
+///
+/// # Examples
+///
+/// ```rust
+/// use lolrofl::model::section::{SectionAddress, SectionPosition};
+///
+/// let address: SectionAddress = "chunk:42/offset:0x1A3".parse().unwrap();
+/// assert_eq!(address, SectionAddress::chunk(42, SectionPosition::Offset(0x1a3)));
+/// assert_eq!(address.to_string(), "chunk:42/offset:0x1A3");
+///
+/// let address: SectionAddress = "kf:7/idx:15".parse().unwrap();
+/// assert_eq!(address.is_keyframe(), true);
+/// assert_eq!(address.position(), SectionPosition::Index(15));
+///
+/// assert_eq!("chunk:42".parse::<SectionAddress>().is_err(), true);
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct SectionAddress {
+    /// Whether the section is in a keyframe
+    keyframe: bool,
+    /// The ID of the section's segment
+    segment: u32,
+    /// The position of the section in the segment
+    position: SectionPosition,
+}
+
+impl SectionAddress {
+    /// The address of a section in a chunk
+    pub fn chunk(id: u32, position: SectionPosition) -> SectionAddress {
+        SectionAddress { keyframe: false, segment: id, position }
+    }
+    /// The address of a section in a keyframe
+    pub fn keyframe(id: u32, position: SectionPosition) -> SectionAddress {
+        SectionAddress { keyframe: true, segment: id, position }
+    }
+    /// Whether the section is in a chunk
+    pub fn is_chunk(&self) -> bool { !self.keyframe }
+    /// Whether the section is in a keyframe
+    pub fn is_keyframe(&self) -> bool { self.keyframe }
+    /// The ID of the section's segment
+    pub fn segment_id(&self) -> u32 { self.segment }
+    /// The position of the section in its segment
+    pub fn position(&self) -> SectionPosition { self.position }
+    /// Whether the addressed section is in a segment
+    pub fn is_in(&self, segment: &Segment) -> bool {
+        segment.id() == self.segment && segment.is_keyframe() == self.keyframe && segment.is_chunk() != self.keyframe
+    }
+    /// Whether a section's index and offset in a segment match the address
+    pub fn matches(&self, index: usize, offset: usize) -> bool {
+        match self.position {
+            SectionPosition::Offset(o) => o == offset,
+            SectionPosition::Index(i) => i == index,
+        }
+    }
+    /// Find the addressed section in a loaded segment
+    ///
+    /// Returns the section's index and offset in the segment along with the section
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use lolrofl::iter::PayloadIterOptions;
+    /// use lolrofl::model::section::{SectionAddress, SectionPosition};
+    ///
+    /// # let content = lolrofl::test::sample_full_file_0();
+    /// // let content = std::fs::read("game.rofl").unwrap();
+    /// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+    /// let address = SectionAddress::chunk(1, SectionPosition::Index(1));
+    ///
+    /// # #[cfg(feature = "payload")] {
+    /// let segment = game.segment_iter(&PayloadIterOptions::with_data()).unwrap().find(|s| address.is_in(s)).unwrap();
+    /// let (index, offset, section) = address.resolve(&segment).unwrap();
+    /// assert_eq!(section.data_type(), 256);
+    /// # assert_eq!((index, offset), (1, 12));
+    /// # }
+    /// ```
+    pub fn resolve<'a>(&self, segment: &'a Segment) -> Result<(usize, usize, GenericSection<'a>), Errors> {
+        if !self.is_in(segment) {
+            return Err(Errors::NoData);
+        }
+        let mut offset = 0;
+        let mut sections = segment.section_iter()?;
+        for (index, section) in sections.by_ref().enumerate() {
+            if self.matches(index, offset) {
+                return Ok((index, offset, section));
+            }
+            offset += section.len();
+        }
+        if sections.is_valid() { Err(Errors::NoData) } else { Err(sections.to_error()) }
+    }
+}
+
+impl std::fmt::Display for SectionAddress {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}:{}/", if self.keyframe {"kf"} else {"chunk"}, self.segment)?;
+        match self.position {
+            SectionPosition::Offset(offset) => write!(f, "offset:{:#X}", offset),
+            SectionPosition::Index(index) => write!(f, "idx:{}", index),
+        }
+    }
+}
+
+impl std::str::FromStr for SectionAddress {
+    type Err = Errors;
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let invalid = |reason| Errors::InvalidArgument { name: "address", reason };
+        let (segment, position) = s.split_once('/').ok_or(invalid("expected SEGMENT/POSITION"))?;
+        let (kind, id) = segment.split_once(':').ok_or(invalid("expected a segment such as chunk:ID or kf:ID"))?;
+        let (position_kind, value) = position.split_once(':').ok_or(invalid("expected a position such as offset:OFFSET or idx:INDEX"))?;
+        let keyframe = match kind {
+            "chunk" | "c" => false,
+            "kf" | "keyframe" | "k" => true,
+            _ => return Err(invalid("the segment kind is not chunk or kf")),
+        };
+        let segment = id.parse().map_err(|_| invalid("the segment ID is not a number"))?;
+        let value = match value.strip_prefix("0x").or_else(|| value.strip_prefix("0X")) {
+            Some(hex) => usize::from_str_radix(hex, 16),
+            None => value.parse(),
+        }.map_err(|_| invalid("the position is not a number"))?;
+        let position = match position_kind {
+            "offset" | "off" => SectionPosition::Offset(value),
+            "idx" | "index" => SectionPosition::Index(value),
+            _ => return Err(invalid("the position kind is not offset or idx")),
+        };
+        Ok(SectionAddress { keyframe, segment, position })
+    }
+}
@@ -0,0 +1,197 @@
+/*!
+Typed decoding of the sections whose data layout is known
+
+A [`Registry`] maps section data types to the [`SectionDecoder`] that reads their data.
+[`Registry::default`] holds the decoders of the lib, documented in `ROFLFormat.adoc`, and
+custom decoders may be added with [`Registry::register`] to decode more types or to replace
+the lib's decoders for other game versions.
+
+Interaction pings are the only sections decoded by the lib. The section types of movements,
+spawns, damage and chat messages have not been identified, they are listed in the unidentified
+sections of `ROFLFormat.adoc` and may be decoded with custom decoders.
+
+# Examples
+
+```rust
+use lolrofl::model::section::GenericSection;
+use lolrofl::model::section::decode::{Decoded, Registry};
+
+// A relative time, 1-byte length and 1-byte parameters section of type 397
+let mut bytes = vec![0xB0, 0x00, 16, 0x8D, 0x01, 0x00];
+bytes.extend_from_slice(&[0, 0]);
+bytes.extend_from_slice(&1200f32.to_le_bytes());
+bytes.extend_from_slice(&800f32.to_le_bytes());
+bytes.push(3);
+bytes.extend_from_slice(&[246, 176, 176, 176, 112]);
+let section = GenericSection::from_slice(&bytes, None).unwrap();
+
+let registry = Registry::default();
+match registry.decode(&section) {
+    Some(Ok(Decoded::Ping(ping))) => println!("Ping {:?} at {:?}", ping.ping_type(), ping.position()),
+    Some(Ok(decoded)) => println!("{:?}", decoded),
+    Some(Err(e)) => println!("Malformed section: {}", e),
+    None => println!("No decoder for type {}", section.data_type()),
+}
+# assert_eq!(registry.decode(&section).unwrap().unwrap(), Decoded::Ping(lolrofl::model::section::decode::Ping::new((1200., 800.), Some(3), None)));
+# assert_eq!(Registry::new().decode(&section).is_none(), true);
+```
+*/
+
+use byteorder::{ByteOrder, LittleEndian};
+use crate::Errors;
+use super::{GenericSection, SectionCore};
+
+/// The data of a section decoded by a [`SectionDecoder`]
+#[derive(Clone, Debug, PartialEq)]
+#[non_exhaustive]
+pub enum Decoded {
+    /// An interaction ping
+    Ping(Ping),
+    /// The data of a section decoded by a custom decoder, as named values
+    Fields(Vec<(String, String)>),
+}
+
+impl std::fmt::Display for Decoded {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Decoded::Ping(ping) => write!(f, "{}", ping),
+            Decoded::Fields(fields) => {
+                let fields: Vec<String> = fields.iter().map(|(name, value)| format!("{}={}", name, value)).collect();
+                write!(f, "{}", fields.join(" "))
+            },
+        }
+    }
+}
+
+/// Reads the data of the sections of a data type
+pub trait SectionDecoder {
+    /// The data type of the sections the decoder reads, see [`GenericSection::data_type`]
+    fn data_type(&self) -> u32;
+    /// The decoder's human-readable name
+    fn name(&self) -> &'static str;
+    /// Decode a section's data
+    ///
+    /// # Errors
+    ///
+    /// If the section's data does not match the decoder's layout
+    fn decode(&self, section: &GenericSection) -> Result<Decoded, Errors>;
+}
+
+/// The decoders of section data types, by data type
+pub struct Registry {
+    /// The registered decoders, by data type
+    decoders: std::collections::BTreeMap<u32, Box<dyn SectionDecoder + Send + Sync>>,
+}
+
+impl std::fmt::Debug for Registry {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.debug_map().entries(self.decoders.iter().map(|(data_type, decoder)| (data_type, decoder.name()))).finish()
+    }
+}
+
+impl Default for Registry {
+    /// Build a registry that holds the lib's decoders
+    fn default() -> Self {
+        let mut registry = Registry::new();
+        registry.register(PingDecoder);
+        registry
+    }
+}
+
+impl Registry {
+    /// Build a registry without decoders
+    pub fn new() -> Registry {
+        Registry { decoders: std::collections::BTreeMap::new() }
+    }
+    /// Add a decoder, replacing the decoder previously registered for its data type if any
+    pub fn register<D: SectionDecoder + Send + Sync + 'static>(&mut self, decoder: D) {
+        self.decoders.insert(decoder.data_type(), Box::new(decoder));
+    }
+    /// Get the decoder of a data type
+    pub fn get(&self, data_type: u32) -> Option<&(dyn SectionDecoder + Send + Sync)> {
+        self.decoders.get(&data_type).map(|d| d.as_ref())
+    }
+    /// Whether a data type has a decoder
+    pub fn contains(&self, data_type: u32) -> bool {
+        self.decoders.contains_key(&data_type)
+    }
+    /// The data types that have a decoder, sorted
+    pub fn data_types(&self) -> impl Iterator<Item = u32> + '_ {
+        self.decoders.keys().copied()
+    }
+    /// Only keep the decoders of the data types for which `keep` returns `true`
+    pub fn retain<F: FnMut(u32) -> bool>(&mut self, mut keep: F) {
+        self.decoders.retain(|data_type, _| keep(*data_type));
+    }
+    /// Decode a section with the decoder of its data type, `None` if it has none
+    pub fn decode(&self, section: &GenericSection) -> Option<Result<Decoded, Errors>> {
+        self.get(section.data_type()).map(|decoder| decoder.decode(section))
+    }
+}
+
+/// An interaction ping, see `ROFLFormat.adoc`
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub struct Ping {
+    /// The ping's position on the map
+    position: (f32, f32),
+    /// The ping's type ID, `None` for generic pings
+    ping_type: Option<u8>,
+    /// The ID of the pinged unit, for unit pings
+    target: Option<u32>,
+}
+
+impl Ping {
+    /// Build a ping from its position, type ID and target
+    pub fn new(position: (f32, f32), ping_type: Option<u8>, target: Option<u32>) -> Ping {
+        Ping { position, ping_type, target }
+    }
+    /// The ping's position on the map
+    pub fn position(&self) -> (f32, f32) { self.position }
+    /// The ping's type ID, `None` for generic pings
+    pub fn ping_type(&self) -> Option<u8> { self.ping_type }
+    /// The ID of the pinged unit, for unit pings
+    pub fn target(&self) -> Option<u32> { self.target }
+}
+
+impl std::fmt::Display for Ping {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Ping at ({:.1}, {:.1})", self.position.0, self.position.1)?;
+        if let Some(ping_type) = self.ping_type {
+            write!(f, " type={}", ping_type)?;
+        }
+        if let Some(target) = self.target {
+            write!(f, " target={}", target)?;
+        }
+        Ok(())
+    }
+}
+
+/// Decodes interaction pings
+#[derive(Clone, Copy, Debug, Default)]
+pub struct PingDecoder;
+
+impl PingDecoder {
+    /// The length of the data that precedes a ping's specific bytes
+    const HEAD_LEN: usize = 10;
+    /// The length of the data that follows a ping's specific bytes
+    const TAIL_LEN: usize = 5;
+}
+
+impl SectionDecoder for PingDecoder {
+    fn data_type(&self) -> u32 { 397 }
+    fn name(&self) -> &'static str { "Interaction ping" }
+    fn decode(&self, section: &GenericSection) -> Result<Decoded, Errors> {
+        let data = section.raw_data().ok_or(Errors::NoData)?;
+        if data.len() < PingDecoder::HEAD_LEN + PingDecoder::TAIL_LEN {
+            return Err(Errors::too_small(PingDecoder::HEAD_LEN + PingDecoder::TAIL_LEN, data.len()));
+        }
+        let position = (LittleEndian::read_f32(&data[2..6]), LittleEndian::read_f32(&data[6..10]));
+        let (ping_type, target) = match &data[PingDecoder::HEAD_LEN..data.len() - PingDecoder::TAIL_LEN] {
+            [] => (None, None),
+            [ping_type] => (Some(*ping_type), None),
+            [ping_type, target @ ..] if target.len() == 4 => (Some(*ping_type), Some(LittleEndian::read_u32(target))),
+            _ => return Err(Errors::InvalidBuffer),
+        };
+        Ok(Decoded::Ping(Ping { position, ping_type, target }))
+    }
+}
@@ -1,119 +1,281 @@
-use byteorder::{ByteOrder, LittleEndian};
-use crate::{Errors, section::SectionCore};
-
-/// How time may be expressed within a section packet
-pub enum PacketTime {
-    /// Absolute time from the game's start
-    Absolute(f32),
-    /// Relative time in milliseconds from the last section
-    Relative(u8),
-}
-
-/// Generic data container used for quick scans and iteration over a ROFL segment's data
-#[derive(Clone, Debug)]
-pub struct GenericSection<'a> {
-    /// Length of the constant part of the section
-    core_len: usize,
-    /// Type of the data within the packet
-    data_type: u32,
-    /// Data of the section
-    data: &'a[u8],
-}
-
-impl GenericSection<'_> {
-    /// Whether the time is encoded on 1 or 4 bytes
-    const TIME_BYTE: u8 = 0x80;
-    /// Whether a byte type is in the packet or the last packet's type
-    /// should be used
-    ///
-    /// Backward compatibility would require to handle dynamically the
-    /// associated size as it seems that the type bytes used to be only
-    /// 1-byte long
-    const TYPE_BYTE: u8 = 0x40;
-    /// Whether the block's parameters are encoded on 1 or 4 bytes
-    const BPARAM_BYTE: u8 = 0x20;
-    /// Whether the block's content's length is encoded on 1 or 4 bytes
-    const LENGTH_BYTE: u8 = 0x10;
-    /// Get full raw internal section
-    #[warn(deprecated)]
-    pub fn bytes(&self) -> &[u8] { &self.data }
-    /// Get the section's time
-    pub fn time(&self) -> PacketTime {
-        if self.data[0] & GenericSection::TIME_BYTE != 0 {
-            PacketTime::Relative(self.data[1])
-        } else {
-            PacketTime::Absolute(f32::from_bits(LittleEndian::read_u32(&self.data[1..5])))
-        }
-    }
-    /// The parameters for this packet. The slice may be either 1 or 4 bytes long
-    /// 
-    pub fn params(&self) -> &[u8] {
-        let marker = self.data[0];
-
-        let params_offset = 1
-            + if marker & GenericSection::TIME_BYTE != 0 {1} else {4}
-            + if marker & GenericSection::LENGTH_BYTE != 0 {1} else {4}
-            + if marker & GenericSection::TYPE_BYTE != 0 {0} else {2};
-        let params_len = if marker & GenericSection::BPARAM_BYTE != 0 {1} else {4};
-
-        &self.data[params_offset..params_offset+params_len]
-    }
-    /// The type of the data within the packet
-    ///
-    /// Types should be within u16's space, however a larger type
-    /// is used for future-proofing
-    pub fn data_type(&self) -> u32 {
-        self.data_type
-    }
-    /// Create a new GenericDataSegment from a slice.
-    ///
-    /// Providing a slice with the data of multiple GenericDataSegment returns
-    /// the segment that starts at the first byte of the slice
-    pub fn from_slice(slice: &[u8], last_datatype: Option<u32>) -> Result<GenericSection, crate::error::Errors> {
-        if slice.len() == 0 {
-            return Err(crate::error::Errors::NoData);
-        }
-        let marker = slice[0];
-
-        let length_offset = 1 +
-            if marker & GenericSection::TIME_BYTE != 0 {1} else {4};
-        let type_offset = length_offset
-            + if marker & GenericSection::LENGTH_BYTE != 0 {1} else {4};
-        let core_len = type_offset
-            + if marker & GenericSection::TYPE_BYTE != 0 {0} else {2}
-            + if marker & GenericSection::BPARAM_BYTE != 0 {1} else {4};
-
-        if slice.len() < core_len { return Err(Errors::BufferTooSmall); }
-        let data_len =
-            if marker & GenericSection::LENGTH_BYTE != 0 {
-                slice[length_offset] as usize
-            } else {
-                LittleEndian::read_u32(&slice[length_offset..]) as usize
-            };
-
-        if slice.len() < core_len + data_len { return Err(Errors::BufferTooSmall); }
-
-        let data_type =
-            if marker & GenericSection::TYPE_BYTE != 0 {
-                if last_datatype.is_none() { return Err(Errors::NoData); }
-                last_datatype.unwrap()
-            } else {
-                LittleEndian::read_u16(&slice[type_offset..]) as u32
-            };
-        Ok(GenericSection {
-            core_len,
-            data: &slice[..core_len+data_len],
-            data_type,
-        })
-    }
-}
-
-impl SectionCore for GenericSection<'_> {
-    const KIND: u8 = 0;
-    fn kind(&self) -> u8 {self.data[0]}
-    fn core_len(&self) -> usize { self.core_len }
-    fn data_len(&self) -> usize { self.data.len()-self.core_len }
-    fn raw_data(&self) -> std::option::Option<&[u8]> {
-        if self.data_len() == 0 { None } else { Some(&self.data[self.core_len..]) }
-    }
-}
+use byteorder::{ByteOrder, LittleEndian};
+use crate::{Errors, section::SectionCore};
+
+/// How time may be expressed within a section packet
+#[derive(Clone, Copy, Debug, PartialEq)]
+#[cfg_attr(feature="serde", derive(serde::Serialize))]
+pub enum PacketTime {
+    /// Absolute time from the game's start
+    Absolute(f32),
+    /// Relative time in milliseconds from the last section
+    Relative(u8),
+}
+
+impl PacketTime {
+    /// Get the absolute time in seconds of the packet
+    /// 
+    /// `prev` is the absolute time in seconds of the previous packet,
+    /// it is only used if the packet's time is relative
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust
+    /// use lolrofl::model::section::PacketTime;
+    /// 
+    /// assert_eq!(PacketTime::Absolute(12.5).to_absolute(10.), 12.5);
+    /// assert_eq!(PacketTime::Relative(250).to_absolute(10.), 10.25);
+    /// ```
+    pub fn to_absolute(&self, prev: f32) -> f32 {
+        match self {
+            PacketTime::Absolute(t) => *t,
+            PacketTime::Relative(t) => prev + (*t as f32) / 1000.,
+        }
+    }
+    /// Get the packet's time as a duration
+    /// 
+    /// This is a duration from the game's start if the time is absolute,
+    /// else from the previous packet.
+    /// Returns `None` if an absolute time is negative or not finite
+    pub fn as_duration(&self) -> Option<std::time::Duration> {
+        match self {
+            PacketTime::Absolute(t) => std::time::Duration::try_from_secs_f32(*t).ok(),
+            PacketTime::Relative(t) => Some(std::time::Duration::from_millis(*t as u64)),
+        }
+    }
+}
+
+impl std::fmt::Display for PacketTime {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            PacketTime::Absolute(t) => write!(f, "{}", format_game_time(*t)),
+            PacketTime::Relative(t) => write!(f, "+{}ms", t),
+        }
+    }
+}
+
+/// Format an in-game time in seconds as `mm:ss.mmm`
+/// 
+/// Minutes are not wrapped into hours and negative times are prefixed with `-`
+/// 
+/// # Examples
+/// 
+/// ```rust
+/// use lolrofl::model::section::format_game_time;
+/// 
+/// assert_eq!(format_game_time(75.5), "01:15.500");
+/// assert_eq!(format_game_time(3725.), "62:05.000");
+/// assert_eq!(format_game_time(-1.25), "-00:01.250");
+/// ```
+pub fn format_game_time(seconds: f32) -> String {
+    let millis = (seconds.abs() * 1000.).round() as u64;
+    format!(
+        "{}{:02}:{:02}.{:03}",
+        if seconds < 0. {"-"} else {""},
+        millis / 60_000,
+        millis / 1000 % 60,
+        millis % 1000,
+    )
+}
+
+/// Generic data container used for quick scans and iteration over a ROFL segment's data
+#[derive(Clone, Debug)]
+pub struct GenericSection<'a> {
+    /// Length of the constant part of the section
+    core_len: usize,
+    /// Type of the data within the packet
+    data_type: u32,
+    /// Data of the section
+    data: &'a[u8],
+}
+
+impl GenericSection<'_> {
+    /// Whether the time is encoded on 1 or 4 bytes
+    const TIME_BYTE: u8 = 0x80;
+    /// Whether a byte type is in the packet or the last packet's type
+    /// should be used
+    ///
+    /// Backward compatibility would require to handle dynamically the
+    /// associated size as it seems that the type bytes used to be only
+    /// 1-byte long
+    const TYPE_BYTE: u8 = 0x40;
+    /// Whether the block's parameters are encoded on 1 or 4 bytes
+    const BPARAM_BYTE: u8 = 0x20;
+    /// Whether the block's content's length is encoded on 1 or 4 bytes
+    const LENGTH_BYTE: u8 = 0x10;
+    /// Read a string in the section's variable data, see [`read_string`](super::read_string)
+    ///
+    /// Returns the decoded string and the number of bytes it used
+    ///
+    /// # Errors
+    ///
+    /// If the section has no variable data, if `offset` is out of it or if the string could not be read
+    pub fn string_at(&self, offset: usize, length: super::StringLength, encoding: super::StringEncoding, mode: super::DecodeMode) -> Result<(String, usize), Errors> {
+        let data = self.raw_data().ok_or(Errors::NoData)?;
+        super::read_string(data.get(offset..).ok_or(Errors::too_small(offset, data.len()))?, length, encoding, mode)
+    }
+    /// The approximate number of bytes held in memory by the section
+    ///
+    /// The section's data is borrowed from its segment and is not counted
+    pub fn approx_size(&self) -> usize { std::mem::size_of::<Self>() }
+    /// Get full raw internal section
+    #[warn(deprecated)]
+    pub fn bytes(&self) -> &[u8] { self.data }
+    /// Serialize the section from its time, data type, parameters and data
+    ///
+    /// The section's marker is kept, so fields are encoded on as many bytes as in the
+    /// original section and the data type is omitted if it was.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use lolrofl::iter::PayloadIterOptions;
+    /// use lolrofl::model::section::{GenericSection, SectionCore};
+    ///
+    /// # let content = lolrofl::test::sample_full_file_0();
+    /// // let content = std::fs::read("game.rofl").unwrap();
+    /// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+    /// # #[cfg(feature = "payload")] {
+    /// for segment in game.segment_iter(&PayloadIterOptions::with_data()).unwrap() {
+    ///     let mut offset = 0;
+    ///     let mut last_type = None;
+    ///     for section in segment.section_iter().unwrap() {
+    ///         let bytes = section.to_bytes();
+    ///         assert_eq!(&bytes[..], &segment.data()[offset..offset + section.len()]);
+    ///         let parsed = GenericSection::from_slice(&bytes[..], last_type).unwrap();
+    ///         assert_eq!((parsed.time(), parsed.data_type(), parsed.params()), (section.time(), section.data_type(), section.params()));
+    ///         offset += section.len();
+    ///         last_type = Some(section.data_type());
+    ///     }
+    /// }
+    /// # }
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let marker = self.kind();
+        let mut bytes = Vec::with_capacity(self.data.len());
+        bytes.push(marker);
+        match self.time() {
+            PacketTime::Relative(time) => bytes.push(time),
+            PacketTime::Absolute(time) => bytes.extend_from_slice(&time.to_bits().to_le_bytes()),
+        }
+        if marker & GenericSection::LENGTH_BYTE != 0 {
+            bytes.push(self.data_len() as u8);
+        } else {
+            bytes.extend_from_slice(&(self.data_len() as u32).to_le_bytes());
+        }
+        if marker & GenericSection::TYPE_BYTE == 0 {
+            bytes.extend_from_slice(&(self.data_type as u16).to_le_bytes());
+        }
+        bytes.extend_from_slice(self.params());
+        bytes.extend_from_slice(self.raw_data().unwrap_or(&[]));
+        bytes
+    }
+    /// Get the section's time
+    pub fn time(&self) -> PacketTime {
+        if self.data[0] & GenericSection::TIME_BYTE != 0 {
+            PacketTime::Relative(self.data[1])
+        } else {
+            PacketTime::Absolute(f32::from_bits(LittleEndian::read_u32(&self.data[1..5])))
+        }
+    }
+    /// The parameters for this packet. The slice may be either 1 or 4 bytes long
+    /// 
+    pub fn params(&self) -> &[u8] {
+        let marker = self.data[0];
+
+        let params_offset = 1
+            + if marker & GenericSection::TIME_BYTE != 0 {1} else {4}
+            + if marker & GenericSection::LENGTH_BYTE != 0 {1} else {4}
+            + if marker & GenericSection::TYPE_BYTE != 0 {0} else {2};
+        let params_len = if marker & GenericSection::BPARAM_BYTE != 0 {1} else {4};
+
+        &self.data[params_offset..params_offset+params_len]
+    }
+    /// The type of the data within the packet
+    ///
+    /// Types should be within u16's space, however a larger type
+    /// is used for future-proofing
+    pub fn data_type(&self) -> u32 {
+        self.data_type
+    }
+    /// Create a new GenericDataSegment from a slice.
+    ///
+    /// Providing a slice with the data of multiple GenericDataSegment returns
+    /// the segment that starts at the first byte of the slice
+    pub fn from_slice(slice: &[u8], last_datatype: Option<u32>) -> Result<GenericSection<'_>, crate::error::Errors> {
+        if slice.is_empty() {
+            return Err(crate::error::Errors::NoData);
+        }
+        let marker = slice[0];
+
+        let length_offset = 1 +
+            if marker & GenericSection::TIME_BYTE != 0 {1} else {4};
+        let type_offset = length_offset
+            + if marker & GenericSection::LENGTH_BYTE != 0 {1} else {4};
+        let core_len = type_offset
+            + if marker & GenericSection::TYPE_BYTE != 0 {0} else {2}
+            + if marker & GenericSection::BPARAM_BYTE != 0 {1} else {4};
+
+        if slice.len() < core_len { return Err(Errors::too_small(core_len, slice.len())); }
+        let data_len =
+            if marker & GenericSection::LENGTH_BYTE != 0 {
+                slice[length_offset] as usize
+            } else {
+                LittleEndian::read_u32(&slice[length_offset..]) as usize
+            };
+
+        if slice.len() < core_len + data_len { return Err(Errors::too_small(core_len + data_len, slice.len())); }
+
+        let data_type =
+            if marker & GenericSection::TYPE_BYTE != 0 {
+                if last_datatype.is_none() { return Err(Errors::NoData); }
+                last_datatype.unwrap()
+            } else {
+                LittleEndian::read_u16(&slice[type_offset..]) as u32
+            };
+        Ok(GenericSection {
+            core_len,
+            data: &slice[..core_len+data_len],
+            data_type,
+        })
+    }
+}
+
+/// Serialize a summary of the section: its time, type, parameters and data length
+///
+/// # Examples
+///
+/// ```rust
+/// # #[cfg(all(feature = "serde", feature = "payload"))] {
+/// use lolrofl::iter::PayloadIterOptions;
+///
+/// # let content = lolrofl::test::sample_full_file_0();
+/// // let content = std::fs::read("game.rofl").unwrap();
+/// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+/// let segment = game.segment_iter(&PayloadIterOptions::with_data()).unwrap().next().unwrap();
+/// let sections: Vec<_> = segment.section_iter().unwrap().collect();
+/// println!("{}", serde_json::to_string(&sections).unwrap());
+/// # assert_eq!(serde_json::to_string(&sections[0]).unwrap(), r#"{"time":{"Absolute":0.5},"type":256,"params":[1],"len":3}"#);
+/// # }
+/// ```
+#[cfg(feature="serde")]
+impl serde::Serialize for GenericSection<'_> {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        use serde::ser::SerializeStruct;
+        let mut state = serializer.serialize_struct("GenericSection", 4)?;
+        state.serialize_field("time", &self.time())?;
+        state.serialize_field("type", &self.data_type())?;
+        state.serialize_field("params", self.params())?;
+        state.serialize_field("len", &self.data_len())?;
+        state.end()
+    }
+}
+
+impl SectionCore for GenericSection<'_> {
+    const KIND: u8 = 0;
+    fn kind(&self) -> u8 {self.data[0]}
+    fn core_len(&self) -> usize { self.core_len }
+    fn data_len(&self) -> usize { self.data.len()-self.core_len }
+    fn raw_data(&self) -> std::option::Option<&[u8]> {
+        if self.data_len() == 0 { None } else { Some(&self.data[self.core_len..]) }
+    }
+}
@@ -0,0 +1,65 @@
+/// Information on a kind of section whose purpose is known
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub struct SectionKindInfo {
+    /// The section's data type, see [`GenericSection::data_type`](super::GenericSection::data_type)
+    id: u32,
+    /// The section's human-readable name
+    name: &'static str,
+    /// The first game version the data type is known to be used in, if known
+    min_version: Option<&'static str>,
+    /// Whether the lib decodes the section's data
+    decoder: bool,
+}
+
+impl SectionKindInfo {
+    /// The section's data type, see [`GenericSection::data_type`](super::GenericSection::data_type)
+    pub fn id(&self) -> u32 { self.id }
+    /// The section's human-readable name
+    pub fn name(&self) -> &'static str { self.name }
+    /// The first game version the data type is known to be used in, if known
+    pub fn min_version(&self) -> Option<&'static str> { self.min_version }
+    /// Whether the data type may be used in a game version, `true` if its first version is unknown
+    pub fn is_used_in(&self, version: &crate::model::GameVersion) -> bool {
+        self.min_version.is_none_or(|min| version.is_at_least(min))
+    }
+    /// Whether the lib decodes the section's data with a decoder of the default
+    /// [`Registry`](super::decode::Registry), sections without a decoder are only available as
+    /// [`GenericSection`](super::GenericSection)
+    pub fn has_decoder(&self) -> bool { self.decoder }
+}
+
+/// The kinds of sections whose purpose is known, sorted by data type
+const KNOWN_KINDS: &[SectionKindInfo] = &[
+    SectionKindInfo { id: 397, name: "Interaction ping", min_version: None, decoder: true },
+];
+
+/// The kinds of sections whose purpose is known, sorted by data type
+///
+/// Data types change between game versions, a kind is only listed once it is documented
+/// in `ROFLFormat.adoc`.
+///
+/// # Examples
+///
+/// ```rust
+/// use lolrofl::model::section::known_kinds;
+///
+/// for kind in known_kinds() {
+///     println!("{}: {} (decoded: {})", kind.id(), kind.name(), kind.has_decoder());
+/// }
+/// # assert_eq!(known_kinds()[0].name(), "Interaction ping");
+/// ```
+pub fn known_kinds() -> &'static [SectionKindInfo] { KNOWN_KINDS }
+
+/// Get the information on a section's data type if it is known
+///
+/// # Examples
+///
+/// ```rust
+/// use lolrofl::model::section::kind_info;
+///
+/// assert_eq!(kind_info(397).map(|k| k.name()), Some("Interaction ping"));
+/// assert_eq!(kind_info(0), None);
+/// ```
+pub fn kind_info(id: u32) -> Option<&'static SectionKindInfo> {
+    KNOWN_KINDS.binary_search_by_key(&id, |k| k.id).ok().map(|i| &KNOWN_KINDS[i])
+}
@@ -0,0 +1,187 @@
+use byteorder::{ByteOrder, LittleEndian};
+use crate::Errors;
+
+/// How a string's characters are encoded
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StringEncoding {
+    /// UTF-8, lengths are counted in bytes
+    Utf8,
+    /// UTF-16 little endian, lengths are counted in 2-byte code units
+    Utf16Le,
+    /// UTF-16 little endian if the data looks like it, else UTF-8
+    Auto,
+}
+
+/// How a string's end is found
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum StringLength {
+    /// The string is preceded by its length on 1 byte
+    U8,
+    /// The string is preceded by its length on 2 bytes
+    U16,
+    /// The string is preceded by its length on 4 bytes
+    U32,
+    /// The string ends with a NUL character or at the end of the data
+    NulTerminated,
+}
+
+/// How invalid characters are handled
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum DecodeMode {
+    /// Fail on invalid characters
+    Strict,
+    /// Replace invalid characters with `U+FFFD`
+    Lossy,
+}
+
+/// Whether decoded text has no control characters other than whitespace
+fn is_clean(text: &str) -> bool {
+    !text.chars().any(|c| c.is_control() && !matches!(c, '\t' | '\n' | '\r'))
+}
+
+/// Decode UTF-16 little endian data if it is valid
+fn utf16(data: &[u8]) -> Option<String> {
+    if !data.len().is_multiple_of(2) {
+        return None;
+    }
+    char::decode_utf16(data.chunks_exact(2).map(LittleEndian::read_u16)).collect::<Result<String, _>>().ok()
+}
+
+/// Guess whether string data is UTF-16 little endian
+///
+/// Data is considered UTF-8 if it is valid UTF-8 without control characters, else UTF-16
+/// if it is valid UTF-16 without control characters. This is a heuristic meant for short
+/// strings such as names and chat messages.
+///
+/// # Examples
+///
+/// ```rust
+/// use lolrofl::model::section::{detect_encoding, StringEncoding};
+///
+/// assert_eq!(detect_encoding("Faker".as_bytes()), StringEncoding::Utf8);
+/// assert_eq!(detect_encoding("Щука".as_bytes()), StringEncoding::Utf8);
+/// for name in ["Faker", "페이커", "Щука"] {
+///     let utf16: Vec<u8> = name.encode_utf16().flat_map(u16::to_le_bytes).collect();
+///     assert_eq!(detect_encoding(&utf16), StringEncoding::Utf16Le);
+/// }
+/// ```
+pub fn detect_encoding(data: &[u8]) -> StringEncoding {
+    if std::str::from_utf8(data).is_ok_and(is_clean) {
+        StringEncoding::Utf8
+    } else if utf16(data).is_some_and(|s| is_clean(&s)) {
+        StringEncoding::Utf16Le
+    } else {
+        StringEncoding::Utf8
+    }
+}
+
+/// Decode raw string data
+///
+/// # Errors
+///
+/// If the data holds invalid characters in strict mode
+///
+/// # Examples
+///
+/// ```rust
+/// use lolrofl::model::section::{decode_string, DecodeMode, StringEncoding};
+///
+/// assert_eq!(decode_string("Щука".as_bytes(), StringEncoding::Utf8, DecodeMode::Strict).unwrap(), "Щука");
+/// assert_eq!(decode_string(&[0x41, 0xff], StringEncoding::Utf8, DecodeMode::Lossy).unwrap(), "A\u{fffd}");
+/// assert_eq!(decode_string(&[0x41, 0xff], StringEncoding::Utf8, DecodeMode::Strict).is_err(), true);
+/// ```
+pub fn decode_string(data: &[u8], encoding: StringEncoding, mode: DecodeMode) -> Result<String, Errors> {
+    let encoding = match encoding {
+        StringEncoding::Auto => detect_encoding(data),
+        encoding => encoding,
+    };
+    match (encoding, mode) {
+        (StringEncoding::Utf16Le, _) => {
+            if !data.len().is_multiple_of(2) && mode == DecodeMode::Strict {
+                return Err(Errors::InvalidBuffer);
+            }
+            let units: Vec<u16> = data.chunks_exact(2).map(LittleEndian::read_u16).collect();
+            let mut out = String::with_capacity(units.len());
+            for c in char::decode_utf16(units) {
+                match (c, mode) {
+                    (Ok(c), _) => out.push(c),
+                    (Err(_), DecodeMode::Lossy) => out.push(char::REPLACEMENT_CHARACTER),
+                    (Err(_), DecodeMode::Strict) => return Err(Errors::InvalidBuffer),
+                }
+            }
+            if !data.len().is_multiple_of(2) {
+                out.push(char::REPLACEMENT_CHARACTER);
+            }
+            Ok(out)
+        },
+        (_, DecodeMode::Strict) => Ok(std::str::from_utf8(data)?.to_string()),
+        (_, DecodeMode::Lossy) => Ok(String::from_utf8_lossy(data).into_owned()),
+    }
+}
+
+/// Read a string at the start of a section's data
+///
+/// Returns the decoded string and the number of bytes it used, including its length prefix and
+/// NUL terminator. With [`StringEncoding::Auto`], the encoding of length-prefixed strings is
+/// detected from their data and their length is counted in bytes.
+///
+/// # Errors
+///
+/// If the data is shorter than the string's length or holds invalid characters in strict mode
+///
+/// # Examples
+///
+/// ```rust
+/// use lolrofl::model::section::{read_string, DecodeMode, StringEncoding, StringLength};
+///
+/// let data = lolrofl::test::sample_strings_0();
+/// let (name, used) = read_string(&data, StringLength::U8, StringEncoding::Utf8, DecodeMode::Strict).unwrap();
+/// assert_eq!(name, "페이커");
+/// let (name, used2) = read_string(&data[used..], StringLength::U16, StringEncoding::Utf16Le, DecodeMode::Strict).unwrap();
+/// assert_eq!(name, "Щука");
+/// let (chat, _) = read_string(&data[used+used2..], StringLength::NulTerminated, StringEncoding::Auto, DecodeMode::Lossy).unwrap();
+/// assert_eq!(chat, "gg wp 잘했어요");
+///
+/// // UTF-16 is detected in NUL-terminated strings, lone surrogates are replaced in lossy mode
+/// let utf16 = [0x46, 0x00, 0x61, 0x00, 0x00, 0xd8, 0x00, 0x00, 0xff];
+/// assert_eq!(read_string(&utf16, StringLength::NulTerminated, StringEncoding::Auto, DecodeMode::Lossy).unwrap(), ("Fa\u{fffd}".to_string(), 8));
+/// assert_eq!(read_string(&utf16, StringLength::NulTerminated, StringEncoding::Utf16Le, DecodeMode::Strict).is_err(), true);
+///
+/// assert_eq!(read_string(&[5, b'a'], StringLength::U8, StringEncoding::Utf8, DecodeMode::Lossy).is_err(), true);
+/// ```
+pub fn read_string(data: &[u8], length: StringLength, encoding: StringEncoding, mode: DecodeMode) -> Result<(String, usize), Errors> {
+    let unit = if encoding == StringEncoding::Utf16Le { 2 } else { 1 };
+    let (start, len) = match length {
+        StringLength::U8 => (1, *data.first().ok_or(Errors::too_small(1, 0))? as usize * unit),
+        StringLength::U16 if data.len() >= 2 => (2, LittleEndian::read_u16(data) as usize * unit),
+        StringLength::U32 if data.len() >= 4 => (4, LittleEndian::read_u32(data) as usize * unit),
+        StringLength::U16 => return Err(Errors::too_small(2, data.len())),
+        StringLength::U32 => return Err(Errors::too_small(4, data.len())),
+        StringLength::NulTerminated => {
+            let (end, terminator) = match encoding {
+                StringEncoding::Utf8 => (data.iter().position(|b| *b == 0), 1),
+                StringEncoding::Utf16Le => (data.chunks_exact(2).position(|u| u == [0, 0]).map(|i| i * 2), 2),
+                StringEncoding::Auto => {
+                    // A UTF-16 string that starts with a latin character has a NUL byte at index 1
+                    let utf8_end = data.iter().position(|b| *b == 0).unwrap_or(data.len());
+                    let utf16_end = data.chunks_exact(2).position(|u| u == [0, 0]).map_or(data.len() & !1, |i| i * 2);
+                    let utf8_clean = std::str::from_utf8(&data[..utf8_end]).is_ok_and(is_clean);
+                    let encoding = if utf8_end == 1 || (!utf8_clean && utf16(&data[..utf16_end]).is_some_and(|s| is_clean(&s))) {
+                        StringEncoding::Utf16Le
+                    } else {
+                        StringEncoding::Utf8
+                    };
+                    return read_string(data, length, encoding, mode);
+                },
+            };
+            return match end {
+                Some(end) => Ok((decode_string(&data[..end], encoding, mode)?, end + terminator)),
+                None => Ok((decode_string(data, encoding, mode)?, data.len())),
+            };
+        },
+    };
+    if data.len() < start + len {
+        return Err(Errors::too_small(start + len, data.len()));
+    }
+    Ok((decode_string(&data[start..start + len], encoding, mode)?, start + len))
+}
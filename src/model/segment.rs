@@ -1,109 +1,341 @@
-/*!
-Data segments that make up a payload as well as their section components
-*/
-
-use byteorder::{ByteOrder, LittleEndian};
-use crate::iter::SegmentIterator;
-
-/// Length in bytes of a segment header
-pub(crate) const SEGMENT_HEADER_LEN: usize = 17;
-
-/// Container for Chunk and Keyframe data
-#[derive(Debug)]
-pub struct Segment {
-    /// The segment's ID
-    id: u32,
-    /// Whether the segment is a chunk or a keyframe
-    segment_type: u8,
-    /// Length of the segment's data
-    length: u32,
-    /// ID of the first associated Chunk (if this segment is a keyframe), else 0
-    chunk_id: u32,
-    /// Internal offset of the segment's data
-    offset: u32,
-    /// Segment's data (if it is loaded)
-    data: Vec<u8>,
-}
-
-/// Internal enum that maps segment type high-level names to their numerical value
-#[derive(Debug)] #[repr(u8)]
-enum SegmentType {
-    Chunk = 1,
-    Keyframe = 2,
-}
-
-impl Segment {
-    /// The segment's ID
-    pub fn id(&self) -> u32 { self.id }
-    /// The length in bytes of the segment's data
-    pub fn len(&self) -> usize { self.length as usize }
-    /// The offset in bytes from the segment headers' end at which the segment's data starts
-    pub fn offset(&self) -> usize { self.offset as usize }
-    /// Whether the segment's data section is loaded
-    pub fn is_loaded(&self) -> bool { !self.data.is_empty() }
-    /// Get the raw segment's data
-    pub fn data(&self) -> &Vec<u8> { &self.data }
-    /// Get the raw segment's data as a mutable Vec
-    /// 
-    /// __WARNING:__This should only be used if you decrypt/load segment data yourself
-    pub fn data_mut(&mut self) -> &mut Vec<u8> { &mut self.data }
-    /// Build a new segment headet from a payload's data
-    /// 
-    /// This does not load the segment's data section
-    /// 
-    /// Use from_slice instead
-    #[warn(deprecated)]
-    pub fn from_raw_section(data: &[u8]) -> Segment {
-        Segment {
-            id: LittleEndian::read_u32(&data[0..]),
-            segment_type: data[4],
-            length: LittleEndian::read_u32(&data[5..]),
-            chunk_id: LittleEndian::read_u32(&data[9..]),
-            offset: LittleEndian::read_u32(&data[13..]),
-            data: Vec::new(),
-        }
-    }
-    /// Build a new segment headet from a payload's data
-    /// 
-    /// This does not load the segment's data section
-    pub fn from_slice(data: &[u8]) -> Result<Segment, crate::error::Errors> {
-        if data.len()<SEGMENT_HEADER_LEN {
-            Err(crate::error::Errors::BufferTooSmall)
-        } else {
-            Ok(Segment::from_raw_section(data))
-        }
-    }
-    /// Attach data to the Segment
-    /// 
-    /// CAUTION: no validation is performed on the provided data,
-    /// ensure that the provided vec is the segment's vec
-    pub fn set_data(&mut self, data: Vec<u8>) {
-        self.data = data;
-    }
-    /// Whether this segment is a chunk
-    pub fn is_chunk(&self) -> bool {
-        self.segment_type == SegmentType::Chunk as u8
-    }
-    /// Whether this segment is a keyframe
-    pub fn is_keyframe(&self) -> bool {
-        self.segment_type == SegmentType::Keyframe as u8
-    }
-    /// Get a section iterator over the data of the segment
-    pub fn section_iter<'a>(&'a self) -> Result<SegmentIterator<'a>, crate::Errors> {
-        if self.data.len() == 0 {
-            Err(crate::Errors::NoData)
-        } else {
-            Ok(SegmentIterator::new(&self.data[..]))
-        }
-    }
-}
-
-impl std::fmt::Display for Segment {
-    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
-        write!(f,
-            "{} {} (len: {}, next: {}, offset: {}, loaded: {})",
-            if self.is_chunk() {"Chunk"} else { if self.is_keyframe() {"Keyframe"} else {"Segment"} },
-            self.id, self.length, self.chunk_id, self.offset, self.data.len() > 0,
-        )
-    }
-}
+/*!
+Data segments that make up a payload as well as their section components
+*/
+
+use byteorder::{ByteOrder, LittleEndian};
+use crate::iter::SegmentIterator;
+
+/// Length in bytes of a segment header
+pub(crate) const SEGMENT_HEADER_LEN: usize = 17;
+
+/// A segment header's fields in the order and with the sizes they have in a file
+///
+/// The struct is `#[repr(C, packed)]` so that it has the header's exact size, 17 bytes, and
+/// layout. Its integers are stored in native byte order though, while they are little endian in
+/// files: use [`RawSegmentHeader::from_bytes`] and [`RawSegmentHeader::to_bytes`] to convert
+/// headers rather than transmuting them.
+///
+/// | Offset | Size | Field           |
+/// |--------|------|-----------------|
+/// | 0      | 4    | `id`            |
+/// | 4      | 1    | `kind`          |
+/// | 5      | 4    | `length`        |
+/// | 9      | 4    | `next_chunk_id` |
+/// | 13     | 4    | `offset`        |
+///
+/// # Examples
+///
+/// ```rust
+/// use lolrofl::model::RawSegmentHeader;
+///
+/// # let content = lolrofl::test::sample_full_file_0();
+/// // let content = std::fs::read("game.rofl").unwrap();
+/// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+/// let start = game.head().payload_offset();
+/// let bytes: [u8; RawSegmentHeader::LEN] = content[start..start + RawSegmentHeader::LEN].try_into().unwrap();
+/// let header = RawSegmentHeader::from_bytes(&bytes);
+/// let (id, kind) = (header.id, header.kind);
+/// assert_eq!((id, kind), (1, 1));
+/// assert_eq!(header.to_bytes(), bytes);
+/// assert_eq!(std::mem::size_of::<RawSegmentHeader>(), RawSegmentHeader::LEN);
+/// ```
+#[repr(C, packed)]
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, Hash)]
+pub struct RawSegmentHeader {
+    /// The segment's ID
+    pub id: u32,
+    /// The segment's kind, see [`SegmentKind`]
+    pub kind: u8,
+    /// Length of the segment's data
+    pub length: u32,
+    /// ID of the chunk that follows the segment if it is a keyframe, else 0
+    pub next_chunk_id: u32,
+    /// Offset of the segment's data from the end of the segment headers
+    pub offset: u32,
+}
+
+impl RawSegmentHeader {
+    /// Length in bytes of a segment header
+    pub const LEN: usize = SEGMENT_HEADER_LEN;
+    /// Read a segment header from its little endian representation
+    pub fn from_bytes(data: &[u8; SEGMENT_HEADER_LEN]) -> RawSegmentHeader {
+        RawSegmentHeader {
+            id: LittleEndian::read_u32(&data[0..]),
+            kind: data[4],
+            length: LittleEndian::read_u32(&data[5..]),
+            next_chunk_id: LittleEndian::read_u32(&data[9..]),
+            offset: LittleEndian::read_u32(&data[13..]),
+        }
+    }
+    /// Write the segment header in its little endian representation
+    pub fn to_bytes(&self) -> [u8; SEGMENT_HEADER_LEN] {
+        let mut data = [0; SEGMENT_HEADER_LEN];
+        LittleEndian::write_u32(&mut data[0..], self.id);
+        data[4] = self.kind;
+        LittleEndian::write_u32(&mut data[5..], self.length);
+        LittleEndian::write_u32(&mut data[9..], self.next_chunk_id);
+        LittleEndian::write_u32(&mut data[13..], self.offset);
+        data
+    }
+}
+
+impl From<&Segment> for RawSegmentHeader {
+    fn from(segment: &Segment) -> Self {
+        RawSegmentHeader {
+            id: segment.id,
+            kind: segment.segment_type,
+            length: segment.length,
+            next_chunk_id: segment.chunk_id,
+            offset: segment.offset,
+        }
+    }
+}
+
+impl From<RawSegmentHeader> for Segment {
+    fn from(header: RawSegmentHeader) -> Self {
+        Segment {
+            id: header.id,
+            segment_type: header.kind,
+            length: header.length,
+            chunk_id: header.next_chunk_id,
+            offset: header.offset,
+            data: Vec::new(),
+        }
+    }
+}
+
+/// Container for Chunk and Keyframe data
+///
+/// With the `serde` feature, only the segment's header is serialized, not its data
+#[derive(Clone, Debug)]
+#[cfg_attr(feature="serde", derive(serde::Serialize))]
+pub struct Segment {
+    /// The segment's ID
+    id: u32,
+    /// Whether the segment is a chunk or a keyframe
+    segment_type: u8,
+    /// Length of the segment's data
+    length: u32,
+    /// ID of the first associated Chunk (if this segment is a keyframe), else 0
+    chunk_id: u32,
+    /// Internal offset of the segment's data
+    offset: u32,
+    /// Segment's data (if it is loaded)
+    #[cfg_attr(feature="serde", serde(skip))]
+    data: Vec<u8>,
+}
+
+/// A keyframe along with the chunks that follow it until the next keyframe
+///
+/// The keyframe holds a snapshot of the game's state that the chunks update
+#[derive(Clone, Debug)]
+pub struct KeyframeWindow {
+    /// The window's keyframe
+    keyframe: Segment,
+    /// The chunks that follow the keyframe, sorted by ID
+    chunks: Vec<Segment>,
+}
+
+impl KeyframeWindow {
+    /// Build a new window
+    pub(crate) fn new(keyframe: Segment, chunks: Vec<Segment>) -> KeyframeWindow {
+        KeyframeWindow { keyframe, chunks }
+    }
+    /// The window's keyframe
+    pub fn keyframe(&self) -> &Segment { &self.keyframe }
+    /// The chunks that follow the keyframe, sorted by ID
+    pub fn chunks(&self) -> &[Segment] { &self.chunks }
+    /// Split the window in its keyframe and chunks
+    pub fn into_parts(self) -> (Segment, Vec<Segment>) { (self.keyframe, self.chunks) }
+    /// The approximate number of bytes held in memory by the window's segments
+    pub fn approx_size(&self) -> usize {
+        std::mem::size_of::<KeyframeWindow>()
+            + self.keyframe.approx_size()
+            + self.chunks.iter().map(Segment::approx_size).sum::<usize>()
+            + (self.chunks.capacity() - self.chunks.len()) * std::mem::size_of::<Segment>()
+    }
+}
+
+/// The kind of a segment, mapped to its numerical value in segment headers
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)] #[repr(u8)]
+pub enum SegmentKind {
+    /// A chunk, which holds the game's updates
+    Chunk = 1,
+    /// A keyframe, which holds a snapshot of the game's state
+    Keyframe = 2,
+}
+
+impl Segment {
+    /// The segment's ID
+    pub fn id(&self) -> u32 { self.id }
+    /// The length in bytes of the segment's data
+    pub fn len(&self) -> usize { self.length as usize }
+    /// Whether the segment's data is empty
+    pub fn is_empty(&self) -> bool { self.length == 0 }
+    /// ID of the first chunk associated to the segment if it is a keyframe, else 0
+    pub fn next_chunk_id(&self) -> u32 { self.chunk_id }
+    /// The offset in bytes from the segment headers' end at which the segment's data starts
+    pub fn offset(&self) -> usize { self.offset as usize }
+    /// Whether the segment's data section is loaded
+    pub fn is_loaded(&self) -> bool { !self.data.is_empty() }
+    /// Get the raw segment's data
+    pub fn data(&self) -> &Vec<u8> { &self.data }
+    /// Get the raw segment's data as a mutable Vec
+    /// 
+    /// __WARNING:__This should only be used if you decrypt/load segment data yourself
+    pub fn data_mut(&mut self) -> &mut Vec<u8> { &mut self.data }
+    /// Build a new chunk header, its length and offset are set when building a file
+    pub fn chunk(id: u32) -> Segment {
+        Segment { id, segment_type: SegmentKind::Chunk as u8, length: 0, chunk_id: 0, offset: 0, data: Vec::new() }
+    }
+    /// Build a new keyframe header, its length and offset are set when building a file
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use lolrofl::model::{PayloadHeader, Segment};
+    ///
+    /// let mut builder = lolrofl::write::RoflBuilder::new(PayloadHeader::new(42, 60000, 1, 2, 60000, "a2V5"));
+    /// builder.push_segment(Segment::chunk(1), vec![0; 8]).push_segment(Segment::keyframe(1, 2), vec![0; 16]);
+    /// let content = builder.build();
+    ///
+    /// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+    /// let keyframe = game.segment_iter(&lolrofl::iter::PayloadIterOptions::new()).unwrap().last().unwrap();
+    /// assert_eq!((keyframe.is_keyframe(), keyframe.next_chunk_id(), keyframe.len()), (true, 2, 16));
+    /// ```
+    pub fn keyframe(id: u32, next_chunk_id: u32) -> Segment {
+        Segment { id, segment_type: SegmentKind::Keyframe as u8, length: 0, chunk_id: next_chunk_id, offset: 0, data: Vec::new() }
+    }
+    /// Build a new segment headet from a payload's data
+    /// 
+    /// This does not load the segment's data section
+    /// 
+    /// Use from_slice instead
+    #[warn(deprecated)]
+    pub fn from_raw_section(data: &[u8]) -> Segment {
+        let mut header = [0; SEGMENT_HEADER_LEN];
+        header.copy_from_slice(&data[..SEGMENT_HEADER_LEN]);
+        RawSegmentHeader::from_bytes(&header).into()
+    }
+    /// Build a new segment headet from a payload's data
+    /// 
+    /// This does not load the segment's data section
+    pub fn from_slice(data: &[u8]) -> Result<Segment, crate::error::Errors> {
+        if data.len()<SEGMENT_HEADER_LEN {
+            Err(crate::error::Errors::too_small(SEGMENT_HEADER_LEN, data.len()))
+        } else {
+            Ok(Segment::from_raw_section(data))
+        }
+    }
+    /// Copy the segment's header with a different data layout
+    pub(crate) fn relocated(&self, offset: usize, length: usize) -> Segment {
+        Segment {
+            id: self.id,
+            segment_type: self.segment_type,
+            length: length as u32,
+            chunk_id: self.chunk_id,
+            offset: offset as u32,
+            data: Vec::new(),
+        }
+    }
+    /// Serialize the segment's header
+    ///
+    /// The segment's data is not included as it is stored encrypted after all segment headers
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use lolrofl::iter::PayloadIterOptions;
+    /// use lolrofl::model::Segment;
+    ///
+    /// # let content = lolrofl::test::sample_full_file_0();
+    /// // let content = std::fs::read("game.rofl").unwrap();
+    /// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+    /// let start = game.head().payload_offset();
+    /// for (index, segment) in game.segment_iter(&PayloadIterOptions::new()).unwrap().enumerate() {
+    ///     let bytes = segment.to_bytes();
+    ///     assert_eq!(&bytes[..], &content[start+index*bytes.len()..start+(index+1)*bytes.len()]);
+    ///     assert_eq!(Segment::from_slice(&bytes[..]).unwrap().to_bytes(), bytes);
+    /// }
+    /// ```
+    pub fn to_bytes(&self) -> Vec<u8> {
+        RawSegmentHeader::from(self).to_bytes().to_vec()
+    }
+    /// Attach data to the Segment
+    /// 
+    /// CAUTION: no validation is performed on the provided data,
+    /// ensure that the provided vec is the segment's vec
+    pub fn set_data(&mut self, data: Vec<u8>) {
+        self.data = data;
+    }
+    /// The segment's kind, if its type is known
+    pub fn kind(&self) -> Option<SegmentKind> {
+        match self.segment_type {
+            1 => Some(SegmentKind::Chunk),
+            2 => Some(SegmentKind::Keyframe),
+            _ => None,
+        }
+    }
+    /// The approximate number of bytes held in memory by the segment, including its loaded data
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// let mut segment = lolrofl::model::Segment::chunk(1);
+    /// let empty = segment.approx_size();
+    /// segment.set_data(vec![0; 1024]);
+    /// assert_eq!(segment.approx_size(), empty + 1024);
+    /// ```
+    pub fn approx_size(&self) -> usize {
+        std::mem::size_of::<Segment>() + self.data.capacity()
+    }
+    /// Whether this segment is a chunk
+    pub fn is_chunk(&self) -> bool {
+        self.segment_type == SegmentKind::Chunk as u8
+    }
+    /// Whether this segment is a keyframe
+    pub fn is_keyframe(&self) -> bool {
+        self.segment_type == SegmentKind::Keyframe as u8
+    }
+    /// Get the in-game times in seconds of the first and last complete sections of the segment
+    ///
+    /// Sections' headers are scanned to chain relative times but their data is not decoded.
+    /// A first section with a relative time is considered relative to the game's start.
+    ///
+    /// # Errors
+    ///
+    /// If the segment's data is not loaded or holds no complete section
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use lolrofl::iter::PayloadIterOptions;
+    ///
+    /// # let content = lolrofl::test::sample_full_file_0();
+    /// // let content = std::fs::read("game.rofl").unwrap();
+    /// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+    /// # #[cfg(feature = "payload")] {
+    /// for segment in game.segment_iter(&PayloadIterOptions::with_data()).unwrap() {
+    ///     let (start, end) = segment.time_bounds().unwrap();
+    ///     println!("Segment {} covers {:.3}s to {:.3}s", segment.id(), start, end);
+    /// }
+    /// # let chunk = game.segment_iter(&PayloadIterOptions::with_data()).unwrap().next().unwrap();
+    /// # assert_eq!(chunk.time_bounds().unwrap(), (0.5, 0.515));
+    /// # }
+    /// assert_eq!(lolrofl::model::Segment::chunk(1).time_bounds().is_err(), true);
+    /// ```
+    pub fn time_bounds(&self) -> Result<(f32, f32), crate::Errors> {
+        let mut sections = self.section_iter()?;
+        let first = sections.next().ok_or(crate::Errors::NoData)?.time().to_absolute(0.);
+        let last = sections.fold(first, |time, section| section.time().to_absolute(time));
+        Ok((first, last))
+    }
+    /// Get a section iterator over the data of the segment
+    pub fn section_iter<'a>(&'a self) -> Result<SegmentIterator<'a>, crate::Errors> {
+        if self.data.is_empty() {
+            Err(crate::Errors::NoData)
+        } else {
+            Ok(SegmentIterator::new(&self.data[..]))
+        }
+    }
+}
@@ -0,0 +1,76 @@
+use crate::{Errors, Rofl};
+use super::{EndReason, Metadata, PayloadHeader, TeamStats};
+
+/// A game's high-level summary: its ID, duration and version, how it ended and its teams' results
+///
+/// # Examples
+///
+/// ```rust
+/// # let content = lolrofl::test::sample_full_file_0();
+/// // let content = std::fs::read("game.rofl").unwrap();
+/// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+///
+/// let summary = lolrofl::model::Summary::from_rofl(&game).unwrap();
+/// println!("Game {} ({}) lasted {} seconds", summary.match_id(), summary.game_version().unwrap_or("?"), summary.duration() / 1000);
+/// # assert_eq!(summary.game_version(), Some("12.10.444.2068"));
+/// # assert_eq!(summary.teams().len(), 0);
+/// ```
+#[derive(Clone, Debug, PartialEq)]
+pub struct Summary {
+    /// The game's ID
+    match_id: u64,
+    /// The game's duration in milliseconds
+    duration: u32,
+    /// The game's parsed metadata
+    metadata: Metadata,
+}
+
+impl Summary {
+    /// Build a summary from a file's payload header and parsed metadata
+    pub fn new(payload: &PayloadHeader, metadata: Metadata) -> Summary {
+        Summary { match_id: payload.id(), duration: payload.duration(), metadata }
+    }
+    /// Build the summary of a file
+    ///
+    /// # Errors
+    ///
+    /// If the file's payload header or metadata could not be parsed
+    pub fn from_rofl(rofl: &Rofl) -> Result<Summary, Errors> {
+        Ok(Summary::new(&rofl.payload()?, Metadata::from_json(rofl.metadata()?)?))
+    }
+    /// The game's ID
+    pub fn match_id(&self) -> u64 { self.match_id }
+    /// The game's duration in milliseconds, as found in the payload header
+    pub fn duration(&self) -> u32 { self.duration }
+    /// The game's version
+    pub fn game_version(&self) -> Option<&str> { self.metadata.game_version() }
+    /// How the game ended
+    pub fn end_reason(&self) -> EndReason { self.metadata.end_reason() }
+    /// The teams' aggregated stats, sorted by team ID
+    pub fn teams(&self) -> Vec<TeamStats> { self.metadata.team_stats() }
+    /// The champions played by a team's players
+    pub fn champions(&self, team: u32) -> Vec<&str> {
+        self.metadata.players().iter()
+            .filter(|p| p.team() == Some(team))
+            .map(|p| p.champion().unwrap_or("?"))
+            .collect()
+    }
+    /// The team that won the game, if any
+    pub fn winner(&self) -> Option<u32> {
+        self.teams().iter().find(|t| t.win() == Some(true)).map(TeamStats::team)
+    }
+    /// The game's parsed metadata
+    pub fn metadata(&self) -> &Metadata { &self.metadata }
+    /// The display name of a team (`Blue` or `Red`)
+    pub fn team_name(team: u32) -> String {
+        match team {
+            100 => "Blue".to_string(),
+            200 => "Red".to_string(),
+            _ => format!("Team {}", team),
+        }
+    }
+    /// The game's duration formatted as minutes and seconds
+    pub fn format_duration(&self) -> String {
+        format!("{}:{:02}", self.duration / 60_000, self.duration / 1000 % 60)
+    }
+}
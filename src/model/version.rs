@@ -0,0 +1,154 @@
+use crate::kind::FileKind;
+use super::section::{decode::Registry, kind_info};
+
+/// A game version, such as `12.10.444.2068`
+///
+/// Versions are ordered by their numbers, parts that are missing from a parsed version are 0
+/// so that a patch such as `14.10` precedes all of its builds.
+///
+/// # Examples
+///
+/// ```rust
+/// use lolrofl::model::GameVersion;
+///
+/// let version: GameVersion = "12.10.444.2068".parse().unwrap();
+/// assert_eq!((version.major(), version.minor()), (12, 10));
+/// assert_eq!(version.is_at_least("12.9"), true);
+/// assert_eq!(version.is_at_least("12.10.445"), false);
+/// assert_eq!(version < "13.1".parse().unwrap(), true);
+/// assert_eq!("14.10".parse::<GameVersion>().unwrap().to_string(), "14.10.0.0");
+/// # assert_eq!("9.24".parse::<GameVersion>().unwrap() < "12.1".parse().unwrap(), true);
+/// # assert_eq!("".parse::<GameVersion>().is_err(), true);
+/// # assert_eq!("14.x".parse::<GameVersion>().unwrap_err().kind(), "invalid_argument");
+/// # assert_eq!("1.2.3.4.5".parse::<GameVersion>().is_err(), true);
+/// ```
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct GameVersion {
+    /// The season
+    major: u32,
+    /// The patch in the season
+    minor: u32,
+    /// The client build
+    build: u32,
+    /// The build's revision
+    revision: u32,
+}
+
+impl GameVersion {
+    /// Build a version from its parts
+    pub fn new(major: u32, minor: u32, build: u32, revision: u32) -> GameVersion {
+        GameVersion { major, minor, build, revision }
+    }
+    /// The season
+    pub fn major(&self) -> u32 { self.major }
+    /// The patch in the season
+    pub fn minor(&self) -> u32 { self.minor }
+    /// The client build
+    pub fn build(&self) -> u32 { self.build }
+    /// The build's revision
+    pub fn revision(&self) -> u32 { self.revision }
+    /// Whether the version is the same as or more recent than a version such as `14.10`,
+    /// `false` if `version` can't be parsed
+    pub fn is_at_least(&self, version: &str) -> bool {
+        version.parse().is_ok_and(|version: GameVersion| *self >= version)
+    }
+}
+
+impl std::fmt::Display for GameVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}.{}.{}.{}", self.major, self.minor, self.build, self.revision)
+    }
+}
+
+impl std::str::FromStr for GameVersion {
+    type Err = crate::Errors;
+    /// Parse a version made of 1 to 4 dot-separated numbers
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut parts = [0; 4];
+        let invalid = || crate::Errors::InvalidArgument { name: "version", reason: "expected 1 to 4 dot-separated numbers" };
+        for (index, part) in s.trim().split('.').enumerate() {
+            *parts.get_mut(index).ok_or_else(invalid)? = part.parse().map_err(|_| invalid())?;
+        }
+        Ok(GameVersion::new(parts[0], parts[1], parts[2], parts[3]))
+    }
+}
+
+/// The version of a file's format, as detected from its signature and metadata
+///
+/// The version is detected from the file's signature, see [`FileKind`], and from the game
+/// version held by its metadata. Only the format whose signature ends with `0, 0` is parsed,
+/// it was used by all game versions until the format written by more recent clients, and the
+/// [`Rofl`](crate::Rofl) constructors refuse other formats with
+/// [`Errors::UnsupportedFormat`](crate::Errors::UnsupportedFormat). Section data types change
+/// between game versions though, so section decoders must match the game version: the
+/// [`timeline`](crate::timeline) decodes sections with [`FormatVersion::registry`].
+///
+/// # Examples
+///
+/// ```rust
+/// # let content = lolrofl::test::sample_base_file_0();
+/// // let content = std::fs::read("game.rofl").unwrap();
+/// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+///
+/// let format = game.format_version();
+/// println!("{}: {} section decoders apply", format, format.registry().data_types().count());
+/// # assert_eq!(format.to_string(), "ROFL v1 (game 12.10.444.2068)");
+/// # assert_eq!(format.game_version(), Some(lolrofl::model::GameVersion::new(12, 10, 444, 2068)));
+/// # assert_eq!(format.registry().contains(397), true);
+/// # let other = lolrofl::model::FormatVersion::new(lolrofl::kind::FileKind::RoflV2, None);
+/// # assert_eq!((other.is_supported(), other.registry().data_types().count()), (false, 0));
+/// ```
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub struct FormatVersion {
+    /// The format identified by the file's signature
+    kind: FileKind,
+    /// The game version held by the file's metadata, if it could be read
+    game: Option<GameVersion>,
+}
+
+impl FormatVersion {
+    /// Build a format version from a file's signature kind and game version
+    pub fn new(kind: FileKind, game: Option<GameVersion>) -> FormatVersion {
+        FormatVersion { kind, game }
+    }
+    /// Detect a file's format version from its signature and metadata
+    pub(crate) fn from_rofl(rofl: &crate::Rofl) -> FormatVersion {
+        let kind = crate::kind::detect(&rofl.data).unwrap_or(FileKind::Unknown([0; 2]));
+        let game = rofl.metadata().ok()
+            .and_then(|metadata| super::find_keys(metadata, &["gameVersion"]).ok())
+            .and_then(|values| values[0]?.trim_matches('"').parse().ok());
+        FormatVersion { kind, game }
+    }
+    /// The format identified by the file's signature
+    pub fn file_kind(&self) -> FileKind { self.kind }
+    /// The game version held by the file's metadata, if it could be read
+    pub fn game_version(&self) -> Option<GameVersion> { self.game }
+    /// Whether the file's format can be parsed
+    pub fn is_supported(&self) -> bool { self.kind.is_supported() }
+    /// The section decoders that apply to the format and game version
+    ///
+    /// The lib's decoders are kept if their data type is known to be used in the game version,
+    /// or if the game version is unknown. The registry is empty if the format is not supported.
+    pub fn registry(&self) -> Registry {
+        let mut registry = Registry::default();
+        registry.retain(|data_type| self.is_supported() && match (kind_info(data_type), self.game) {
+            (Some(info), Some(game)) => info.is_used_in(&game),
+            _ => true,
+        });
+        registry
+    }
+}
+
+impl std::fmt::Display for FormatVersion {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self.kind {
+            FileKind::RoflV1 => write!(f, "ROFL v1")?,
+            FileKind::RoflV2 => write!(f, "ROFL v2")?,
+            FileKind::Unknown([a, b]) => write!(f, "ROFL with unknown version bytes {}, {}", a, b)?,
+        }
+        if let Some(game) = self.game {
+            write!(f, " (game {})", game)?;
+        }
+        Ok(())
+    }
+}
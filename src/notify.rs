@@ -0,0 +1,68 @@
+/*!
+Post game summaries to chat applications
+
+This module requires the `http` feature.
+*/
+
+use crate::{Errors, model::Summary};
+
+/// The color of an embed's side bar for each winning team
+fn team_color(team: Option<u32>) -> u32 {
+    match team {
+        Some(100) => 0x3b82f6,
+        Some(200) => 0xef4444,
+        _ => 0x9ca3af,
+    }
+}
+
+/// Build the Discord webhook message that holds a game's summary as an embed
+///
+/// # Examples
+///
+/// ```rust
+/// # let metadata = lolrofl::model::Metadata::from_json(lolrofl::test::sample_metadata_ranked_0()).unwrap();
+/// # let content = lolrofl::test::sample_full_file_0();
+/// # let payload = lolrofl::Rofl::from_slice(&content[..]).unwrap().payload().unwrap();
+/// let summary = lolrofl::model::Summary::new(&payload, metadata);
+/// let message = lolrofl::notify::discord_message(&summary);
+/// assert_eq!(message["embeds"][0]["title"], "Blue victory");
+/// assert_eq!(message["embeds"][0]["fields"][1]["value"], "Garen");
+/// ```
+pub fn discord_message(summary: &Summary) -> json::JsonValue {
+    let title = match summary.winner() {
+        Some(team) => format!("{} victory", Summary::team_name(team)),
+        None => "No result".to_string(),
+    };
+    let description = format!(
+        "{} · {} · {}",
+        summary.end_reason(),
+        summary.format_duration(),
+        summary.game_version().unwrap_or("unknown patch"),
+    );
+    let fields: Vec<json::JsonValue> = summary.teams().iter().map(|team| json::object! {
+        name: format!("{} ({} kills)", Summary::team_name(team.team()), team.kills()),
+        value: summary.champions(team.team()).join(", "),
+        inline: true,
+    }).collect();
+    let embed = json::object! {
+        title: title,
+        description: description,
+        color: team_color(summary.winner()),
+        fields: fields,
+        footer: { text: format!("Match {}", summary.match_id()) },
+    };
+    json::object! { embeds: [embed] }
+}
+
+/// Post a game's summary to a Discord webhook
+///
+/// # Errors
+///
+/// If the webhook could not be reached or rejected the message
+pub fn discord(webhook: &str, summary: &Summary) -> Result<(), Errors> {
+    ureq::post(webhook)
+        .set("Content-Type", "application/json")
+        .send_string(&discord_message(summary).dump())
+        .map_err(|e| Errors::Io(std::io::Error::other(e.to_string())))?;
+    Ok(())
+}
@@ -0,0 +1,202 @@
+/*!
+Find the personally identifying information held by a file
+
+Players' names, Riot IDs and PUUIDs are read from the metadata, then searched for in the
+decrypted sections of the payload in UTF-8 and UTF-16. Chat messages can't be told apart
+from other packets yet, only those that hold a player's name are found.
+
+This module requires the `json` feature.
+
+# Examples
+
+```rust
+use lolrofl::privacy::{scan, FieldKind};
+
+# let content = lolrofl::test::sample_full_file_0();
+# let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+# let mut builder = lolrofl::write::RoflBuilder::from_rofl(&game).unwrap();
+# builder.set_metadata(lolrofl::test::sample_metadata_ranked_0());
+# #[cfg(feature = "payload")]
+# builder.push_decrypted_segment(lolrofl::model::Segment::chunk(3), &[0xb0, 0x00, 0x06, 0x2a, 0x00, 0x00, 0xec, 0xbc, 0x80, 0xec, 0x9d, 0xb8]).unwrap();
+# let content = builder.build();
+// let content = std::fs::read("game.rofl").unwrap();
+let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+
+for finding in scan(&game).unwrap() {
+    println!("{}", finding);
+}
+# let findings = scan(&game).unwrap();
+# assert_eq!(findings.iter().filter(|f| f.kind() == FieldKind::Puuid).count(), 2);
+# assert_eq!(findings[0].to_string(), "metadata:player:0/NAME\tname\tFaker");
+# #[cfg(feature = "payload")]
+# assert_eq!(findings.last().unwrap().to_string(), "chunk:3/offset:0x0+0\tname\t케인");
+```
+*/
+
+use crate::{Errors, Rofl, model::{Metadata, section::SectionAddress}};
+
+/// The kind of a personally identifying field
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum FieldKind {
+    /// A summoner name or the game name of a Riot ID
+    Name,
+    /// The tag line of a Riot ID
+    TagLine,
+    /// A player's unique and permanent ID
+    Puuid,
+    /// Another account ID
+    AccountId,
+}
+
+impl std::fmt::Display for FieldKind {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        f.write_str(match self {
+            FieldKind::Name => "name",
+            FieldKind::TagLine => "tag line",
+            FieldKind::Puuid => "puuid",
+            FieldKind::AccountId => "account id",
+        })
+    }
+}
+
+/// The metadata keys of players' stats that identify them
+pub const IDENTIFYING_KEYS: [(&str, FieldKind); 7] = [
+    ("NAME", FieldKind::Name),
+    ("RIOT_ID_GAME_NAME", FieldKind::Name),
+    ("RIOT_ID_TAG_LINE", FieldKind::TagLine),
+    ("RIOT_ID_TAGLINE", FieldKind::TagLine),
+    ("PUUID", FieldKind::Puuid),
+    ("SUMMONER_ID", FieldKind::AccountId),
+    ("ACCOUNT_ID", FieldKind::AccountId),
+];
+
+/// Where a personally identifying value was found
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum Location {
+    /// A key of a player's stats in the metadata
+    Metadata {
+        /// The player's index in the metadata
+        player: usize,
+        /// The stats key
+        key: &'static str,
+    },
+    /// The data of a section
+    Section {
+        /// The section's address
+        address: SectionAddress,
+        /// The value's offset in the section's variable data
+        offset: usize,
+    },
+}
+
+impl std::fmt::Display for Location {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        match self {
+            Location::Metadata { player, key } => write!(f, "metadata:player:{}/{}", player, key),
+            Location::Section { address, offset } => write!(f, "{}+{}", address, offset),
+        }
+    }
+}
+
+/// A personally identifying value and where it was found
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Finding {
+    /// The value's kind
+    kind: FieldKind,
+    /// The value
+    value: String,
+    /// Where the value was found
+    location: Location,
+}
+
+impl Finding {
+    /// The value's kind
+    pub fn kind(&self) -> FieldKind { self.kind }
+    /// The value
+    pub fn value(&self) -> &str { &self.value }
+    /// Where the value was found
+    pub fn location(&self) -> &Location { &self.location }
+}
+
+impl std::fmt::Display for Finding {
+    fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        write!(f, "{}\t{}\t{}", self.location, self.kind, self.value)
+    }
+}
+
+/// Find the offsets of a needle in a haystack
+#[cfg(feature = "payload")]
+fn find_all<'a>(haystack: &'a [u8], needle: &'a [u8]) -> impl Iterator<Item = usize> + 'a {
+    haystack.windows(needle.len()).enumerate().filter(move |(_, w)| *w == needle).map(|(i, _)| i)
+}
+
+/// Search the decrypted sections of a file for identifying values in UTF-8 and UTF-16
+#[cfg(feature = "payload")]
+fn scan_sections(rofl: &Rofl, values: &[(FieldKind, String)], findings: &mut Vec<Finding>) -> Result<(), Errors> {
+    use crate::{iter::PayloadIterOptions, model::section::{SectionCore, SectionPosition}};
+
+    let needles: Vec<(FieldKind, &str, Vec<Vec<u8>>)> = values.iter()
+        .map(|(kind, value)| (*kind, value.as_str(), vec![
+            value.as_bytes().to_vec(),
+            value.encode_utf16().flat_map(u16::to_le_bytes).collect(),
+        ]))
+        .collect();
+    let mut iterator = rofl.segment_iter(&PayloadIterOptions::with_data())?;
+    for segment in iterator.by_ref() {
+        let mut offset = 0;
+        for section in segment.section_iter()? {
+            let address = if segment.is_chunk() {
+                SectionAddress::chunk(segment.id(), SectionPosition::Offset(offset))
+            } else {
+                SectionAddress::keyframe(segment.id(), SectionPosition::Offset(offset))
+            };
+            offset += section.len();
+            let data = match section.raw_data() {
+                Some(data) => data,
+                None => continue,
+            };
+            for (kind, value, encodings) in &needles {
+                for needle in encodings {
+                    for position in find_all(data, needle) {
+                        findings.push(Finding {
+                            kind: *kind,
+                            value: value.to_string(),
+                            location: Location::Section { address, offset: position },
+                        });
+                    }
+                }
+            }
+        }
+    }
+    if !iterator.is_valid() {
+        return Err(iterator.to_error());
+    }
+    Ok(())
+}
+
+/// Find the personally identifying values of a file's metadata and where they appear in its payload
+///
+/// Sections are only searched if the lib was compiled with the `payload` feature
+///
+/// # Errors
+///
+/// If the file's metadata could not be parsed or its segments could not be decrypted
+pub fn scan(rofl: &Rofl) -> Result<Vec<Finding>, Errors> {
+    let metadata = Metadata::from_json(rofl.metadata()?)?;
+    let mut findings = Vec::new();
+    let mut values: Vec<(FieldKind, String)> = Vec::new();
+    for (player, stats) in metadata.players().iter().enumerate() {
+        for (key, kind) in IDENTIFYING_KEYS {
+            if let Some(value) = stats.get(key).filter(|v| !v.is_empty()) {
+                findings.push(Finding { kind, value: value.to_string(), location: Location::Metadata { player, key } });
+                // Short values such as tag lines would match unrelated data
+                if value.len() >= 3 && !values.iter().any(|(_, v)| v == value) {
+                    values.push((kind, value.to_string()));
+                }
+            }
+        }
+    }
+    #[cfg(feature = "payload")]
+    scan_sections(rofl, &values, &mut findings)?;
+    Ok(findings)
+}
@@ -0,0 +1,206 @@
+/*!
+Salvage truncated or partially corrupted files
+
+Clients that crash while recording a game leave files whose declared length does not match
+their actual length and whose last segments are missing, [`Rofl::segment_iter`] refuses such
+files. [`repair`] rebuilds them from the segments whose data is whole.
+
+# Examples
+
+```rust
+# let content = lolrofl::test::sample_full_file_0();
+// let content = std::fs::read("game.rofl").unwrap();
+// A download that stopped before the end of the file
+let truncated = &content[..600];
+assert_eq!(lolrofl::Rofl::from_slice(truncated).unwrap().segment_iter(&lolrofl::iter::PayloadIterOptions::new()).is_err(), true);
+
+let repaired = lolrofl::repair::repair(truncated).unwrap();
+for dropped in repaired.report().dropped() {
+    println!("Dropped {}", dropped);
+}
+let game = lolrofl::Rofl::from_slice(repaired.data()).unwrap();
+assert_eq!(game.head().file_len(), repaired.data().len());
+# assert_eq!(game.segment_iter(&lolrofl::iter::PayloadIterOptions::new()).unwrap().count(), 2);
+# assert_eq!(repaired.report().dropped().len(), 1);
+# assert_eq!(repaired.report().kept(), 2);
+# assert_eq!(repaired.report().is_metadata_replaced(), false);
+# // Whole files are rebuilt as they are
+# let whole = lolrofl::repair::repair(&content[..]).unwrap();
+# assert_eq!(whole.report().is_empty(), true);
+# assert_eq!(whole.data(), &content[..]);
+# // Corrupted segments are only detected with the payload feature
+# let mut corrupted = content.clone();
+# corrupted[0x250] ^= 0xFF;
+# let repaired = lolrofl::repair::repair(&corrupted[..]).unwrap();
+# assert_eq!(repaired.report().kept(), if lolrofl::capabilities().payload { 2 } else { 3 });
+```
+*/
+
+use crate::{Errors, Rofl, iter, model::{Area, PayloadHeader, Segment, SegmentKind}, write::RoflBuilder};
+
+/// Why a segment was dropped from a repaired file
+#[derive(Debug)]
+#[non_exhaustive]
+pub enum DropReason {
+    /// The segment's data ends after the end of the file
+    Missing,
+    /// The segment's data could not be decrypted or decompressed
+    Corrupted(Errors),
+}
+
+/// A segment dropped from a repaired file
+#[derive(Debug)]
+pub struct Dropped {
+    /// Whether the segment is a chunk or a keyframe, `None` if its type is unknown
+    kind: Option<SegmentKind>,
+    /// The segment's ID
+    id: u32,
+    /// Why the segment was dropped
+    reason: DropReason,
+}
+
+impl Dropped {
+    /// Whether the segment is a chunk or a keyframe, `None` if its type is unknown
+    pub fn kind(&self) -> Option<SegmentKind> { self.kind }
+    /// The segment's ID
+    pub fn id(&self) -> u32 { self.id }
+    /// Why the segment was dropped
+    pub fn reason(&self) -> &DropReason { &self.reason }
+}
+
+impl std::fmt::Display for Dropped {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        let area = Area::Segment { kind: self.kind, id: self.id };
+        match &self.reason {
+            DropReason::Missing => write!(f, "{} (its data is missing)", area),
+            DropReason::Corrupted(e) => write!(f, "{} (its data is corrupted: {})", area, e),
+        }
+    }
+}
+
+/// What was changed to repair a file
+#[derive(Debug, Default)]
+pub struct RepairReport {
+    /// The file's length declared in its original header
+    declared_len: usize,
+    /// The original file's actual length
+    actual_len: usize,
+    /// The number of segments kept in the repaired file
+    kept: usize,
+    /// The segments dropped from the repaired file
+    dropped: Vec<Dropped>,
+    /// Why the segment headers could not all be read, the segments that follow are lost
+    segment_headers: Option<Errors>,
+    /// Whether the metadata could not be read and was replaced with an empty JSON object
+    metadata_replaced: bool,
+}
+
+impl RepairReport {
+    /// The file's length declared in its original header
+    pub fn declared_len(&self) -> usize { self.declared_len }
+    /// The original file's actual length
+    pub fn actual_len(&self) -> usize { self.actual_len }
+    /// The number of segments kept in the repaired file
+    pub fn kept(&self) -> usize { self.kept }
+    /// The segments dropped from the repaired file
+    pub fn dropped(&self) -> &[Dropped] { &self.dropped }
+    /// Why the segment headers could not all be read, if they could not
+    ///
+    /// The segments whose header follows the first unreadable one are lost and not listed in [`RepairReport::dropped`]
+    pub fn segment_headers(&self) -> Option<&Errors> { self.segment_headers.as_ref() }
+    /// Whether the metadata could not be read and was replaced with an empty JSON object
+    pub fn is_metadata_replaced(&self) -> bool { self.metadata_replaced }
+    /// Whether the file was whole and nothing had to be changed
+    pub fn is_empty(&self) -> bool {
+        self.declared_len == self.actual_len && self.dropped.is_empty() && self.segment_headers.is_none() && !self.metadata_replaced
+    }
+}
+
+/// A repaired file's content along with what was changed to repair it
+#[derive(Debug)]
+pub struct Repaired {
+    /// The repaired file's content
+    data: Vec<u8>,
+    /// What was changed
+    report: RepairReport,
+}
+
+impl Repaired {
+    /// The repaired file's content
+    pub fn data(&self) -> &[u8] { &self.data }
+    /// What was changed to repair the file
+    pub fn report(&self) -> &RepairReport { &self.report }
+    /// Get the repaired file's content and the report of the changes
+    pub fn into_parts(self) -> (Vec<u8>, RepairReport) { (self.data, self.report) }
+}
+
+/// Rebuild a file from its segments whose data is whole
+///
+/// The segments whose data is missing are dropped, as are the segments whose data cannot be
+/// decrypted and decompressed when the lib was compiled with the `payload` feature. Metadata
+/// that is not valid UTF-8 is replaced with an empty JSON object. The file's header, including
+/// its length, and the payload header's segment counts are rebuilt to match the kept segments.
+///
+/// # Errors
+///
+/// If the file's header or payload header could not be read, as the file cannot be rebuilt
+/// without the payload's encryption key
+pub fn repair(data: &[u8]) -> Result<Repaired, Errors> {
+    let rofl = Rofl::from_slice(data)?;
+    let head = rofl.head();
+    let payload = rofl.payload()?;
+    let mut report = RepairReport { declared_len: head.file_len(), actual_len: data.len(), ..RepairReport::default() };
+    let mut builder = RoflBuilder::new(payload.clone());
+    builder.set_signature(head.signature());
+    match rofl.metadata() {
+        Ok(metadata) => { builder.set_metadata(metadata); },
+        Err(_) => {
+            builder.set_metadata("{}");
+            report.metadata_replaced = true;
+        },
+    }
+    let mut iterator = match segment_directory(&rofl, &payload) {
+        Ok(iterator) => iterator,
+        Err(e) => {
+            report.segment_headers = Some(e);
+            return Ok(Repaired { data: builder.build(), report });
+        },
+    };
+    for segment in iterator.by_ref() {
+        match salvage_segment(&rofl, segment) {
+            Ok((mut segment, raw)) => {
+                // Only the encrypted data is written, the decrypted data was only loaded to check it
+                segment.set_data(Vec::new());
+                builder.push_segment(segment, raw);
+                report.kept += 1;
+            },
+            Err(dropped) => report.dropped.push(dropped),
+        }
+    }
+    if !iterator.is_valid() {
+        report.segment_headers = Some(iterator.to_error());
+    }
+    Ok(Repaired { data: builder.build(), report })
+}
+
+/// Iterate over a file's segment headers, even if the file was truncated after them
+pub(crate) fn segment_directory<'a>(rofl: &'a Rofl, payload: &PayloadHeader) -> Result<iter::PayloadIterator<'a>, Errors> {
+    let offset = rofl.head().payload_offset();
+    let directory = rofl.data.get(offset..).ok_or(Errors::too_small(offset, rofl.data.len()))?;
+    iter::PayloadIterator::new(directory, payload, &iter::PayloadIterOptions::new())
+}
+
+/// Get a segment's encrypted data if it is whole
+///
+/// The segment's decrypted data is loaded to check it when the lib was compiled with the
+/// `payload` feature, the segment is dropped if its data is missing or corrupted.
+pub(crate) fn salvage_segment(rofl: &Rofl, mut segment: Segment) -> Result<(Segment, Vec<u8>), Dropped> {
+    let raw = match rofl.segment_raw_data(&segment) {
+        Ok(raw) => raw.to_vec(),
+        Err(_) => return Err(Dropped { kind: segment.kind(), id: segment.id(), reason: DropReason::Missing }),
+    };
+    match rofl.load_segment(&mut segment) {
+        Ok(()) | Err(Errors::FeatureDisabled { .. }) => Ok((segment, raw)),
+        Err(e) => Err(Dropped { kind: segment.kind(), id: segment.id(), reason: DropReason::Corrupted(e) }),
+    }
+}
@@ -0,0 +1,215 @@
+/*!
+Download games from a spectator (observer) server
+
+Spectator servers serve a game's metadata as well as its chunks and keyframes, which are
+encrypted and compressed exactly like the segments of a ROFL file. Downloaded segments are
+cached on disk so an interrupted download resumes where it stopped, then assembled with a
+[`RoflBuilder`].
+
+This module requires the `http` feature.
+
+# Examples
+
+```no_run
+use lolrofl::spectator::{Download, SpectatorClient};
+
+let mut client = SpectatorClient::for_platform("EUW1");
+let download = Download::new("EUW1", 1234567890, std::path::Path::new("cache"));
+let builder = download.run(&mut client, Some("encryption-key")).unwrap();
+std::fs::write("game.rofl", builder.build()).unwrap();
+```
+*/
+
+use std::path::{Path, PathBuf};
+use std::time::{Duration, Instant};
+use crate::{Errors, model::{PayloadHeader, Segment}, write::RoflBuilder};
+
+/// A rate-limited client of a spectator server's REST API that retries failed requests
+#[derive(Clone, Debug)]
+pub struct SpectatorClient {
+    /// The server's base URL, without a trailing slash
+    base_url: String,
+    /// The minimum delay between two requests
+    min_interval: Duration,
+    /// The number of times a failed request is retried
+    retries: u32,
+    /// The delay before the first retry, doubled after each retry
+    backoff: Duration,
+    /// When the last request was sent
+    last_request: Option<Instant>,
+}
+
+impl SpectatorClient {
+    /// Build a client of the server at a base URL such as `http://spectator.example.com:8080`
+    pub fn new(base_url: &str) -> SpectatorClient {
+        SpectatorClient {
+            base_url: base_url.trim_end_matches('/').to_string(),
+            min_interval: Duration::from_millis(250),
+            retries: 3,
+            backoff: Duration::from_secs(1),
+            last_request: None,
+        }
+    }
+    /// Build a client of a platform's public spectator server (e.g. `EUW1`)
+    pub fn for_platform(platform_id: &str) -> SpectatorClient {
+        SpectatorClient::new(&format!("http://spectator.{}.lol.pvp.net:8080", platform_id.to_ascii_lowercase()))
+    }
+    /// Set the minimum delay between two requests
+    pub fn min_interval(mut self, min_interval: Duration) -> SpectatorClient {
+        self.min_interval = min_interval;
+        self
+    }
+    /// Set how many times a failed request is retried and the delay before the first retry
+    pub fn retries(mut self, retries: u32, backoff: Duration) -> SpectatorClient {
+        self.retries = retries;
+        self.backoff = backoff;
+        self
+    }
+    /// Get a resource, retrying on network errors, rate limits and server errors
+    fn get(&mut self, path: &str) -> Result<Vec<u8>, Errors> {
+        use std::io::Read;
+
+        let url = format!("{}/observer-mode/rest/consumer/{}", self.base_url, path);
+        let mut backoff = self.backoff;
+        let mut attempt = 0;
+        loop {
+            if let Some(elapsed) = self.last_request.map(|t| t.elapsed()) {
+                if elapsed < self.min_interval {
+                    std::thread::sleep(self.min_interval - elapsed);
+                }
+            }
+            self.last_request = Some(Instant::now());
+            let error = match ureq::get(&url).call() {
+                Ok(response) => {
+                    let mut data = Vec::new();
+                    response.into_reader().read_to_end(&mut data)?;
+                    return Ok(data);
+                },
+                Err(ureq::Error::Status(code, _)) if code != 429 && code < 500 =>
+                    return Err(Errors::Io(std::io::Error::other(format!("{}: status code {}", url, code)))),
+                Err(e) => e,
+            };
+            if attempt >= self.retries {
+                return Err(Errors::Io(std::io::Error::other(error.to_string())));
+            }
+            attempt += 1;
+            std::thread::sleep(backoff);
+            backoff *= 2;
+        }
+    }
+    /// Get a game's metadata
+    pub fn game_metadata(&mut self, platform_id: &str, game_id: u64) -> Result<json::JsonValue, Errors> {
+        let data = self.get(&format!("getGameMetaData/{}/{}/0/token", platform_id, game_id))?;
+        Ok(json::parse(std::str::from_utf8(&data)?)?)
+    }
+    /// Get information on the last available chunk of a game
+    pub fn last_chunk_info(&mut self, platform_id: &str, game_id: u64) -> Result<json::JsonValue, Errors> {
+        let data = self.get(&format!("getLastChunkInfo/{}/{}/0/token", platform_id, game_id))?;
+        Ok(json::parse(std::str::from_utf8(&data)?)?)
+    }
+    /// Get a chunk's encrypted data
+    pub fn chunk(&mut self, platform_id: &str, game_id: u64, id: u32) -> Result<Vec<u8>, Errors> {
+        self.get(&format!("getGameDataChunk/{}/{}/{}/token", platform_id, game_id, id))
+    }
+    /// Get a keyframe's encrypted data
+    pub fn keyframe(&mut self, platform_id: &str, game_id: u64, id: u32) -> Result<Vec<u8>, Errors> {
+        self.get(&format!("getKeyFrame/{}/{}/{}/token", platform_id, game_id, id))
+    }
+}
+
+/// The download of a game's segments to a cache directory
+#[derive(Clone, Debug)]
+pub struct Download {
+    /// The game's platform ID (e.g. `EUW1`)
+    platform_id: String,
+    /// The game's ID
+    game_id: u64,
+    /// Where downloaded segments are cached
+    cache_dir: PathBuf,
+}
+
+impl Download {
+    /// Prepare the download of a game, segments are cached in a sub-directory of `cache_dir`
+    pub fn new(platform_id: &str, game_id: u64, cache_dir: &Path) -> Download {
+        Download {
+            platform_id: platform_id.to_string(),
+            game_id,
+            cache_dir: cache_dir.join(format!("{}-{}", platform_id, game_id)),
+        }
+    }
+    /// The directory downloaded segments are cached in
+    pub fn cache_dir(&self) -> &Path { &self.cache_dir }
+    /// Get a segment from the cache or download it
+    fn segment<F>(&self, name: &str, fetch: F) -> Result<Vec<u8>, Errors>
+    where F: FnOnce() -> Result<Vec<u8>, Errors> {
+        let path = self.cache_dir.join(name);
+        if let Ok(data) = std::fs::read(&path) {
+            return Ok(data);
+        }
+        let data = fetch()?;
+        let partial = path.with_extension("part");
+        std::fs::write(&partial, &data)?;
+        std::fs::rename(&partial, &path)?;
+        Ok(data)
+    }
+    /// Download the game's segments that are not cached yet and assemble them
+    ///
+    /// `key` is the game's base64 encryption key, it is read from the game's metadata if not set.
+    /// Keyframes that are not listed in the metadata are assumed to precede the chunk that
+    /// starts at the same time.
+    ///
+    /// # Errors
+    ///
+    /// If the game's metadata does not hold the expected fields or holds inconsistent chunk IDs,
+    /// if no encryption key is available
+    /// or if a segment could not be downloaded
+    pub fn run(&self, client: &mut SpectatorClient, key: Option<&str>) -> Result<RoflBuilder, Errors> {
+        std::fs::create_dir_all(&self.cache_dir)?;
+        let metadata = client.game_metadata(&self.platform_id, self.game_id)?;
+        let last_chunk = client.last_chunk_info(&self.platform_id, self.game_id)?;
+        let field = |value: &json::JsonValue| value.as_u32().ok_or(Errors::InvalidBuffer);
+        let end_startup_chunk_id = field(&metadata["endStartupChunkId"])?;
+        let start_game_chunk_id = field(&metadata["startGameChunkId"])?;
+        let last_chunk_id = last_chunk["chunkId"].as_u32().or_else(|| metadata["lastChunkId"].as_u32()).ok_or(Errors::InvalidBuffer)?;
+        let last_keyframe_id = last_chunk["keyFrameId"].as_u32().or_else(|| metadata["lastKeyFrameId"].as_u32()).unwrap_or(0);
+        let chunk_interval = metadata["chunkTimeInterval"].as_u32().unwrap_or(30000).max(1);
+        let keyframe_interval = metadata["keyFrameTimeInterval"].as_u32().unwrap_or(60000);
+        // The game's length is estimated from its chunks if the server does not provide it
+        let game_length = metadata["gameLength"].as_u32().map(Ok).unwrap_or_else(|| {
+            last_chunk_id.checked_sub(start_game_chunk_id)
+                .and_then(|count| count.checked_add(1))
+                .and_then(|count| count.checked_mul(chunk_interval))
+                .ok_or(Errors::InvalidBuffer)
+        })?;
+        let key = key.map(str::to_string)
+            .or_else(|| metadata["encryptionKey"].as_str().filter(|k| !k.is_empty()).map(str::to_string))
+            .ok_or(Errors::NoData)?;
+
+        let mut builder = RoflBuilder::new(PayloadHeader::new(
+            self.game_id, game_length, end_startup_chunk_id, start_game_chunk_id, keyframe_interval, &key,
+        ));
+        builder.set_metadata(&json::object! {
+            gameLength: game_length,
+            lastGameChunkId: last_chunk_id,
+            lastKeyFrameId: last_keyframe_id,
+            platformId: self.platform_id.as_str(),
+            statsJson: "[]",
+        }.dump());
+        let chunk_ids = (1..=end_startup_chunk_id).chain(start_game_chunk_id..=last_chunk_id);
+        for id in chunk_ids {
+            let data = self.segment(&format!("chunk-{}.bin", id), || client.chunk(&self.platform_id, self.game_id, id))?;
+            builder.push_segment(Segment::chunk(id), data);
+        }
+        let listed: Vec<(u32, u32)> = metadata["pendingAvailableKeyFrameInfo"].members()
+            .filter_map(|k| Some((k["keyFrameId"].as_u32()?, k["nextChunkId"].as_u32()?)))
+            .collect();
+        let chunks_per_keyframe = (keyframe_interval / chunk_interval).max(1);
+        for id in 1..=last_keyframe_id {
+            let next_chunk_id = listed.iter().find(|(k, _)| *k == id).map(|(_, c)| *c)
+                .unwrap_or(start_game_chunk_id + (id - 1) * chunks_per_keyframe);
+            let data = self.segment(&format!("keyframe-{}.bin", id), || client.keyframe(&self.platform_id, self.game_id, id))?;
+            builder.push_segment(Segment::keyframe(id, next_chunk_id), data);
+        }
+        Ok(builder)
+    }
+}
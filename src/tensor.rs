@@ -0,0 +1,148 @@
+/*!
+Dense tensors of a game's activity for machine-learning pipelines
+
+No game state is decoded yet, so games are sampled as the number of sections of a set of types
+sent by each entity during consecutive time steps. The resulting tensor has the shape
+`(entities, features, steps)`, its features being the counted section types. Tensors are written
+in NumPy's `.npy` format along with a JSON manifest that describes their axes, so they may be
+loaded with `numpy.load` without any Rust.
+
+This module requires the `npy` feature.
+
+# Examples
+
+```rust
+# let content = lolrofl::test::sample_full_file_0();
+// let content = std::fs::read("game.rofl").unwrap();
+let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+
+// The number of sections of types 256 and 512 sent by each entity every 10 seconds
+let tensor = lolrofl::tensor::activity(&game, &[256, 512], 10.).unwrap();
+let mut npy = Vec::new();
+tensor.write_npy(&mut npy).unwrap();
+// std::fs::write("activity.npy", &npy).unwrap();
+// std::fs::write("activity.json", tensor.manifest()).unwrap();
+# assert_eq!(&npy[..6], b"\x93NUMPY");
+# assert_eq!(npy.len() % 64, (tensor.values().len() * 4) % 64);
+# #[cfg(feature = "payload")] {
+# assert_eq!(tensor.shape(), [4, 2, 10]);
+# assert_eq!(tensor.entities(), [0, 1, 2, 7]);
+# assert_eq!(tensor.get(1, 0, 0), 1.);
+# assert_eq!(tensor.values().iter().sum::<f32>(), 5.);
+# assert_eq!(tensor.manifest().contains(r#""times": [0, 10, 20, 30, 40, 50, 60, 70, 80, 90]"#), true);
+# }
+```
+*/
+
+use crate::{Errors, Rofl, analysis};
+
+/// Section counts by entity, section type and time step
+#[derive(Clone, Debug, Default, PartialEq)]
+pub struct Tensor {
+    /// The IDs of the entities that sent sections, sorted
+    entities: Vec<u32>,
+    /// The counted section types, in the order of the features axis
+    types: Vec<u32>,
+    /// The duration of a time step in seconds
+    step: f32,
+    /// The number of time steps
+    steps: usize,
+    /// The counts in row-major order
+    values: Vec<f32>,
+}
+
+impl Tensor {
+    /// The IDs of the entities that sent sections, in the order of the entities axis
+    pub fn entities(&self) -> &[u32] { &self.entities }
+    /// The counted section types, in the order of the features axis
+    pub fn types(&self) -> &[u32] { &self.types }
+    /// The duration of a time step in seconds
+    pub fn step(&self) -> f32 { self.step }
+    /// The tensor's shape: its number of entities, features and time steps
+    pub fn shape(&self) -> [usize; 3] { [self.entities.len(), self.types.len(), self.steps] }
+    /// The tensor's values in row-major order
+    pub fn values(&self) -> &[f32] { &self.values }
+    /// The number of sections of a type sent by an entity during a time step, by index on each axis
+    ///
+    /// Panics if an index is out of the tensor's shape
+    pub fn get(&self, entity: usize, feature: usize, step: usize) -> f32 {
+        assert!(entity < self.entities.len() && feature < self.types.len() && step < self.steps);
+        self.values[(entity * self.types.len() + feature) * self.steps + step]
+    }
+    /// Write the tensor in NumPy's `.npy` format, as little endian 32-bit floats
+    pub fn write_npy<W: std::io::Write>(&self, mut writer: W) -> std::io::Result<()> {
+        let [entities, features, steps] = self.shape();
+        let mut header = format!("{{'descr': '<f4', 'fortran_order': False, 'shape': ({}, {}, {}), }}", entities, features, steps);
+        // The magic, version and header length take 10 bytes and the data must be aligned on 64 bytes
+        let padding = 63 - (10 + header.len()) % 64;
+        header.extend(std::iter::repeat_n(' ', padding));
+        header.push('\n');
+        writer.write_all(b"\x93NUMPY\x01\x00")?;
+        writer.write_all(&(header.len() as u16).to_le_bytes())?;
+        writer.write_all(header.as_bytes())?;
+        for value in &self.values {
+            writer.write_all(&value.to_le_bytes())?;
+        }
+        Ok(())
+    }
+    /// Describe the tensor's axes as a JSON object
+    ///
+    /// The manifest holds the tensor's `shape` and `dtype`, the names of its `axes`, the IDs of
+    /// its `entities`, the section `types` of its features along with their known name and the
+    /// start `times` in seconds of its steps
+    pub fn manifest(&self) -> String {
+        let list = |items: Vec<String>| items.join(", ");
+        let features = self.types.iter().map(|t| match crate::model::section::kind_info(*t) {
+            Some(info) => format!("{{\"type\": {}, \"name\": {:?}}}", t, info.name()),
+            None => format!("{{\"type\": {}, \"name\": null}}", t),
+        }).collect();
+        format!(
+            "{{\"shape\": [{}], \"dtype\": \"<f4\", \"axes\": [\"entity\", \"feature\", \"time\"], \"entities\": [{}], \"features\": [{}], \"step\": {}, \"times\": [{}]}}",
+            list(self.shape().iter().map(usize::to_string).collect()),
+            list(self.entities.iter().map(u32::to_string).collect()),
+            list(features),
+            self.step,
+            list((0..self.steps).map(|i| (i as f32 * self.step).to_string()).collect()),
+        )
+    }
+}
+
+/// Count the sections of the given types sent by each entity during consecutive time steps of `step` seconds
+///
+/// Sections are attributed to the entity ID found in their parameters. The game lasts until its
+/// payload's duration or its last section, whichever comes last.
+///
+/// # Errors
+///
+/// If `step` is not strictly positive or if the file's chunks could not be read
+pub fn activity(rofl: &Rofl, types: &[u32], step: f32) -> Result<Tensor, Errors> {
+    if step <= 0. {
+        return Err(Errors::InvalidArgument { name: "step", reason: "must be strictly positive" });
+    }
+    let mut duration = rofl.payload()?.duration() as f32 / 1000.;
+    let mut samples = Vec::new();
+    analysis::walk_timeline(rofl, |time, section| {
+        duration = duration.max(time);
+        if let Some(feature) = types.iter().position(|t| *t == section.data_type()) {
+            let entity = section.params().iter().rev().fold(0, |id, b| (id << 8) | *b as u32);
+            samples.push((entity, feature, time));
+        }
+    })?;
+    let mut entities: Vec<u32> = samples.iter().map(|(entity, _, _)| *entity).collect();
+    entities.sort_unstable();
+    entities.dedup();
+    let steps = (duration / step).floor() as usize + 1;
+    let mut tensor = Tensor {
+        values: vec![0.; entities.len() * types.len() * steps],
+        entities,
+        types: types.to_vec(),
+        step,
+        steps,
+    };
+    for (entity, feature, time) in samples {
+        let entity = tensor.entities.binary_search(&entity).unwrap_or_default();
+        let index = ((time.max(0.) / step) as usize).min(steps - 1);
+        tensor.values[(entity * types.len() + feature) * steps + index] += 1.;
+    }
+    Ok(tensor)
+}
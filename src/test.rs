@@ -1,35 +1,111 @@
-
-pub fn sample_base_file_0() -> Vec<u8> {
-    vec![ 82, 73, 79, 84, 0, 0, // magic
-        0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49, 50, 51, 52, 53, 54, 55, 56, 57, 58, 59, 60, 61, 62, 63, 64, 65, 66, 67, 68, 69, 70, 71, 72, 73, 74, 75, 76, 77, 78, 79, 80, 81, 82, 83, 84, 85, 86, 87, 88, 89, 90, 91, 92, 93, 94, 95, 96, 97, 98, 99, 100, 101, 102, 103, 104, 105, 106, 107, 108, 109, 110, 111, 112, 113, 114, 115, 116, 117, 118, 119, 120, 121, 122, 123, 124, 125, 126, 127, 128, 129, 130, 131, 132, 133, 134, 135, 136, 137, 138, 139, 140, 141, 142, 143, 144, 145, 146, 147, 148, 149, 150, 151, 152, 153, 154, 155, 156, 157, 158, 159, 160, 161, 162, 163, 164, 165, 166, 167, 168, 169, 170, 171, 172, 173, 174, 175, 176, 177, 178, 179, 180, 181, 182, 183, 184, 185, 186, 187, 188, 189, 190, 191, 192, 193, 194, 195, 196, 197, 198, 199, 200, 201, 202, 203, 204, 205, 206, 207, 208, 209, 210, 211, 212, 213, 214, 215, 216, 217, 218, 219, 220, 221, 222, 223, 224, 225, 226, 227, 228, 229, 230, 231, 232, 233, 234, 235, 236, 237, 238, 239, 240, 241, 242, 243, 244, 245, 246, 247, 248, 249, 250, 251, 252, 253, 254, 255, // signature
-        0x20, 1, // head size
-        0xcd, 1, 0, 0, // File size
-        0x20, 1, 0, 0, // Metadata offset
-        0x6b, 0, 0, 0, // Metadata length
-        0x8b, 1, 0, 0, // Payload header offset
-        0x42, 0, 0, 0, // Payload header length
-        0xcd, 1, 0, 0, // Payload offset
-        // Metadata string
-        0x7b, 0x22, 0x67, 0x61, 0x6d, 0x65, 0x4c, 0x65, 0x6e, 0x67, 0x74, 0x68, 0x22, 0x3a, 0x39, 0x31,
-        0x37, 0x32, 0x32, 0x2c, 0x22, 0x67, 0x61, 0x6d, 0x65, 0x56, 0x65, 0x72, 0x73, 0x69, 0x6f, 0x6e,
-        0x22, 0x3a, 0x22, 0x31, 0x32, 0x2e, 0x31, 0x30, 0x2e, 0x34, 0x34, 0x34, 0x2e, 0x32, 0x30, 0x36,
-        0x38, 0x22, 0x2c, 0x22, 0x6c, 0x61, 0x73, 0x74, 0x47, 0x61, 0x6d, 0x65, 0x43, 0x68, 0x75, 0x6e,
-        0x6b, 0x49, 0x64, 0x22, 0x3a, 0x36, 0x2c, 0x22, 0x6c, 0x61, 0x73, 0x74, 0x4b, 0x65, 0x79, 0x46,
-        0x72, 0x61, 0x6d, 0x65, 0x49, 0x64, 0x22, 0x3a, 0x32, 0x2c, 0x22, 0x73, 0x74, 0x61, 0x74, 0x73,
-        0x4a, 0x73, 0x6f, 0x6e, 0x22, 0x3a, 0x22, 0x5b, 0x5d, 0x22, 0x7d,
-        // Payload header
-        0xca, 0x63, 0xb6, 0x5f, 0x01, 0, 0, 0, // Match ID
-        0x4a, 0x66, 0x1 , 0x0, // Match duration
-        0x2, 0x0, 0x0, 0x0, // Number of keyframes
-        0x6, 0x0, 0x0, 0x0, // Number of chunks
-        0x1, 0x0, 0x0, 0x0, // Last data chunk
-        0x2, 0x0, 0x0, 0x0, // First game chunk
-        0x0, 0x87, 0x93, 0x3, // Keyframe interval
-        0x20, 0x0, // Encryption Key Length
-        // Encryption key
-        0x30, 0x4d, 0x35, 0x44, 0x67, 0x41, 0x32, 0x50, 0x73, 0x58, 0x4a, 0x59, 0x55, 0x36, 0x69, 0x30, 0x2f, 0x49, 0x58,
-        0x4f, 0x33, 0x35, 0x59, 0x6a, 0x53, 0x50, 0x66, 0x79, 0x4f, 0x63, 0x6a, 0x38,
-        // Arbitrary bytes, more would follow in an actual file
-        0x1,
-    ]
-}
\ No newline at end of file
+#[cfg(feature="test-corpus")]
+pub mod corpus;
+pub mod roundtrip;
+pub mod snapshot;
+
+pub fn sample_base_file_0() -> Vec<u8> {
+    vec![ 82, 73, 79, 84, 0, 0, // magic
+        0, 1, 2, 3, 4, 5, 6, 7, 8, 9, 10, 11, 12, 13, 14, 15, 16, 17, 18, 19, 20, 21, 22, 23, 24, 25, 26, 27, 28, 29, 30, 31, 32, 33, 34, 35, 36, 37, 38, 39, 40, 41, 42, 43, 44, 45, 46, 47, 48, 49, 50, 51, 52, 53, 54, 55, 56, 57, 58, 59, 60, 61, 62, 63, 64, 65, 66, 67, 68, 69, 70, 71, 72, 73, 74, 75, 76, 77, 78, 79, 80, 81, 82, 83, 84, 85, 86, 87, 88, 89, 90, 91, 92, 93, 94, 95, 96, 97, 98, 99, 100, 101, 102, 103, 104, 105, 106, 107, 108, 109, 110, 111, 112, 113, 114, 115, 116, 117, 118, 119, 120, 121, 122, 123, 124, 125, 126, 127, 128, 129, 130, 131, 132, 133, 134, 135, 136, 137, 138, 139, 140, 141, 142, 143, 144, 145, 146, 147, 148, 149, 150, 151, 152, 153, 154, 155, 156, 157, 158, 159, 160, 161, 162, 163, 164, 165, 166, 167, 168, 169, 170, 171, 172, 173, 174, 175, 176, 177, 178, 179, 180, 181, 182, 183, 184, 185, 186, 187, 188, 189, 190, 191, 192, 193, 194, 195, 196, 197, 198, 199, 200, 201, 202, 203, 204, 205, 206, 207, 208, 209, 210, 211, 212, 213, 214, 215, 216, 217, 218, 219, 220, 221, 222, 223, 224, 225, 226, 227, 228, 229, 230, 231, 232, 233, 234, 235, 236, 237, 238, 239, 240, 241, 242, 243, 244, 245, 246, 247, 248, 249, 250, 251, 252, 253, 254, 255, // signature
+        0x20, 1, // head size
+        0xcd, 1, 0, 0, // File size
+        0x20, 1, 0, 0, // Metadata offset
+        0x6b, 0, 0, 0, // Metadata length
+        0x8b, 1, 0, 0, // Payload header offset
+        0x42, 0, 0, 0, // Payload header length
+        0xcd, 1, 0, 0, // Payload offset
+        // Metadata string
+        0x7b, 0x22, 0x67, 0x61, 0x6d, 0x65, 0x4c, 0x65, 0x6e, 0x67, 0x74, 0x68, 0x22, 0x3a, 0x39, 0x31,
+        0x37, 0x32, 0x32, 0x2c, 0x22, 0x67, 0x61, 0x6d, 0x65, 0x56, 0x65, 0x72, 0x73, 0x69, 0x6f, 0x6e,
+        0x22, 0x3a, 0x22, 0x31, 0x32, 0x2e, 0x31, 0x30, 0x2e, 0x34, 0x34, 0x34, 0x2e, 0x32, 0x30, 0x36,
+        0x38, 0x22, 0x2c, 0x22, 0x6c, 0x61, 0x73, 0x74, 0x47, 0x61, 0x6d, 0x65, 0x43, 0x68, 0x75, 0x6e,
+        0x6b, 0x49, 0x64, 0x22, 0x3a, 0x36, 0x2c, 0x22, 0x6c, 0x61, 0x73, 0x74, 0x4b, 0x65, 0x79, 0x46,
+        0x72, 0x61, 0x6d, 0x65, 0x49, 0x64, 0x22, 0x3a, 0x32, 0x2c, 0x22, 0x73, 0x74, 0x61, 0x74, 0x73,
+        0x4a, 0x73, 0x6f, 0x6e, 0x22, 0x3a, 0x22, 0x5b, 0x5d, 0x22, 0x7d,
+        // Payload header
+        0xca, 0x63, 0xb6, 0x5f, 0x01, 0, 0, 0, // Match ID
+        0x4a, 0x66, 0x1 , 0x0, // Match duration
+        0x2, 0x0, 0x0, 0x0, // Number of keyframes
+        0x6, 0x0, 0x0, 0x0, // Number of chunks
+        0x1, 0x0, 0x0, 0x0, // Last data chunk
+        0x2, 0x0, 0x0, 0x0, // First game chunk
+        0x0, 0x87, 0x93, 0x3, // Keyframe interval
+        0x20, 0x0, // Encryption Key Length
+        // Encryption key
+        0x30, 0x4d, 0x35, 0x44, 0x67, 0x41, 0x32, 0x50, 0x73, 0x58, 0x4a, 0x59, 0x55, 0x36, 0x69, 0x30, 0x2f, 0x49, 0x58,
+        0x4f, 0x33, 0x35, 0x59, 0x6a, 0x53, 0x50, 0x66, 0x79, 0x4f, 0x63, 0x6a, 0x38,
+        // Arbitrary bytes, more would follow in an actual file
+        0x1,
+    ]
+}
+/// A complete file with 2 chunks and 1 keyframe whose sections may be decrypted and iterated over
+pub fn sample_full_file_0() -> Vec<u8> {
+    let mut data = sample_base_file_0();
+    data.truncate(461);
+    data[264..268].copy_from_slice(&[0x80, 0x2, 0, 0]); // File size
+    data[357] = 0x32; // Metadata lastGameChunkId
+    data[376] = 0x31; // Metadata lastKeyFrameId
+    data[407..411].copy_from_slice(&[0x1, 0x0, 0x0, 0x0]); // Number of keyframes
+    data[411..415].copy_from_slice(&[0x2, 0x0, 0x0, 0x0]); // Number of chunks
+    data.extend_from_slice(&[
+        // Chunk 1 header
+        0x01, 0x00, 0x00, 0x00, 0x01, 0x30, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00,
+        // Keyframe 1 header
+        0x01, 0x00, 0x00, 0x00, 0x02, 0x28, 0x00, 0x00, 0x00, 0x02, 0x00, 0x00, 0x00, 0x30, 0x00, 0x00, 0x00,
+        // Chunk 2 header
+        0x02, 0x00, 0x00, 0x00, 0x01, 0x28, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x00, 0x58, 0x00, 0x00, 0x00,
+        // Chunk 1 data (encrypted)
+        0x25, 0xac, 0x45, 0x42, 0x86, 0x76, 0x90, 0x78, 0x4e, 0x41, 0xc9, 0x4a, 0x29, 0xaf, 0x6d, 0x38,
+        0x91, 0x02, 0xb7, 0x76, 0x6e, 0x3c, 0xd6, 0xf7, 0xcb, 0xa5, 0x15, 0xc6, 0x51, 0x4f, 0x6a, 0x1c,
+        0xc7, 0x28, 0xbc, 0xe5, 0x21, 0x45, 0xdf, 0xf3, 0xad, 0x11, 0xae, 0x46, 0x45, 0x71, 0x6b, 0xcb,
+        // Keyframe 1 data (encrypted)
+        0x25, 0xac, 0x45, 0x42, 0x86, 0x76, 0x90, 0x78, 0x47, 0x62, 0x42, 0x83, 0x39, 0xd1, 0xd6, 0x01,
+        0xe0, 0xd7, 0x7b, 0xf6, 0xbd, 0xc5, 0x40, 0xe4, 0x64, 0x90, 0x2c, 0x1f, 0x2f, 0xde, 0x6c, 0xaa,
+        0x81, 0xa4, 0x15, 0xf9, 0x80, 0xbd, 0x4b, 0xfb,
+        // Chunk 2 data (encrypted)
+        0x25, 0xac, 0x45, 0x42, 0x86, 0x76, 0x90, 0x78, 0x64, 0x3d, 0xf2, 0xdd, 0x4b, 0x8f, 0x96, 0x91,
+        0xfc, 0x60, 0xa9, 0x8a, 0xdc, 0x0a, 0xff, 0x68, 0xa5, 0x7c, 0x03, 0x95, 0x10, 0xe0, 0x2f, 0x99,
+        0x6d, 0xa2, 0x51, 0x30, 0x4b, 0x1b, 0x5b, 0x9c,
+    ]);
+    data
+}
+/// A complete file with 2 chunks and no keyframe nor keyframe interval, as written for very short games
+pub fn sample_no_keyframe_file_0() -> Vec<u8> {
+    let mut data = sample_full_file_0();
+    data.drain(560..600); // Keyframe 1 data
+    data.drain(478..495); // Keyframe 1 header
+    data[264..268].copy_from_slice(&[0x47, 0x2, 0, 0]); // File size
+    data[376] = 0x30; // Metadata lastKeyFrameId
+    data[407..411].copy_from_slice(&[0x0, 0x0, 0x0, 0x0]); // Number of keyframes
+    data[423..427].copy_from_slice(&[0x0, 0x0, 0x0, 0x0]); // Keyframe interval
+    data[491..495].copy_from_slice(&[0x30, 0x0, 0x0, 0x0]); // Chunk 2 data offset
+    data
+}
+/// Strings as they may be found in sections: a Korean name in UTF-8 with a 1-byte length,
+/// a Cyrillic name in UTF-16 with a 2-byte length and a NUL-terminated UTF-8 chat message
+pub fn sample_strings_0() -> Vec<u8> {
+    vec![
+        // "페이커": 9 bytes of UTF-8
+        0x09, 0xed, 0x8e, 0x98, 0xec, 0x9d, 0xb4, 0xec, 0xbb, 0xa4,
+        // "Щука": 4 UTF-16 code units
+        0x04, 0x00, 0x29, 0x04, 0x43, 0x04, 0x3a, 0x04, 0x30, 0x04,
+        // "gg wp 잘했어요" followed by a NUL byte
+        0x67, 0x67, 0x20, 0x77, 0x70, 0x20, 0xec, 0x9e, 0x98, 0xed, 0x96, 0x88, 0xec, 0x96, 0xb4, 0xec,
+        0x9a, 0x94, 0x00,
+    ]
+}
+/// The metadata of a ranked game, with 2 of its 10 players
+pub fn sample_metadata_ranked_0() -> &'static str {
+    concat!(
+        r#"{"gameLength":1865432,"gameVersion":"13.1.486.9076","lastGameChunkId":64,"lastKeyFrameId":31,"#,
+        r#""statsJson":"[{\"NAME\":\"Faker\",\"SKIN\":\"Ahri\",\"TEAM\":\"100\",\"WIN\":\"Win\",\"PUUID\":\"a1b2c3\",\"CHAMPIONS_KILLED\":\"7\"},"#,
+        r#"{\"NAME\":\"케인\",\"SKIN\":\"Garen\",\"TEAM\":\"200\",\"WIN\":\"Fail\",\"PUUID\":\"d4e5f6\",\"CHAMPIONS_KILLED\":\"2\"}]"}"#,
+    )
+}
+/// The metadata of a custom game against bots, bots have empty names and few stats
+pub fn sample_metadata_bot_game_0() -> &'static str {
+    concat!(
+        r#"{"gameLength":602140,"gameVersion":"13.1.486.9076","#,
+        r#""statsJson":"[{\"NAME\":\"Player\",\"SKIN\":\"Annie\",\"TEAM\":\"100\",\"WIN\":\"Win\"},"#,
+        r#"{\"NAME\":\"\",\"SKIN\":\"Ashe\",\"TEAM\":\"200\"},{}]"}"#,
+    )
+}
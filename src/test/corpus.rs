@@ -0,0 +1,179 @@
+/*!
+Hash-pinned sample replays downloaded on demand for integration tests and benchmarks
+
+A corpus is described by a manifest where each non-empty line that does not start with `#`
+holds a file's SHA-256 digest, its name and the URL it may be downloaded from:
+
+```text
+# sha256 name url
+3f1b...e2a0 euw-ranked-12.14.rofl https://example.com/replays/euw-ranked-12.14.rofl
+```
+
+Files are only downloaded if they are missing from the cache directory or if their content
+does not match their pinned digest. A download whose content does not match is rejected.
+*/
+
+use std::path::{Path, PathBuf};
+use sha2::{Digest, Sha256};
+use crate::Errors;
+
+/// A sample replay of a corpus
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct CorpusEntry {
+    /// The name of the replay's file in the cache directory
+    name: String,
+    /// Where the replay may be downloaded from
+    url: String,
+    /// The lowercase hexadecimal SHA-256 digest of the replay
+    sha256: String,
+}
+
+impl CorpusEntry {
+    /// Build a new entry
+    ///
+    /// # Errors
+    ///
+    /// If the name is not a plain file name or if the digest is not a SHA-256 hexadecimal digest
+    pub fn new(name: &str, url: &str, sha256: &str) -> Result<CorpusEntry, Errors> {
+        if name.is_empty() || name.contains(['/', '\\']) || name == "." || name == ".." {
+            return Err(Errors::InvalidArgument { name: "name", reason: "expected a plain file name" });
+        }
+        if sha256.len() != 64 || !sha256.chars().all(|c| c.is_ascii_hexdigit()) {
+            return Err(Errors::InvalidArgument { name: "sha256", reason: "expected a hexadecimal SHA-256 digest" });
+        }
+        Ok(CorpusEntry {
+            name: name.to_string(),
+            url: url.to_string(),
+            sha256: sha256.to_ascii_lowercase(),
+        })
+    }
+    /// The name of the replay's file
+    pub fn name(&self) -> &str { &self.name }
+    /// Where the replay may be downloaded from
+    pub fn url(&self) -> &str { &self.url }
+    /// The replay's pinned SHA-256 digest
+    pub fn sha256(&self) -> &str { &self.sha256 }
+    /// Whether some data matches the replay's pinned digest
+    pub fn matches(&self, data: &[u8]) -> bool { sha256_hex(data) == self.sha256 }
+}
+
+/// A set of sample replays cached in a directory
+///
+/// # Examples
+///
+/// ```rust
+/// use lolrofl::test::corpus::{Corpus, sha256_hex};
+///
+/// let dir = std::env::temp_dir().join("lolrofl-corpus-doctest");
+/// let content = lolrofl::test::sample_full_file_0();
+/// let manifest = format!("# Sample files\n{} full.rofl https://example.com/full.rofl\n", sha256_hex(&content));
+/// let corpus = Corpus::parse_manifest(&dir, &manifest).unwrap();
+/// assert_eq!(corpus.entries().len(), 1);
+///
+/// // Files already present in the cache are not downloaded again
+/// std::fs::create_dir_all(&dir).unwrap();
+/// std::fs::write(dir.join("full.rofl"), &content).unwrap();
+/// let files = corpus.fetch().unwrap();
+/// assert_eq!(files, vec![dir.join("full.rofl")]);
+/// # std::fs::remove_dir_all(&dir).unwrap();
+/// ```
+#[derive(Clone, Debug)]
+pub struct Corpus {
+    /// The directory replays are cached in
+    dir: PathBuf,
+    /// The corpus' replays
+    entries: Vec<CorpusEntry>,
+}
+
+impl Corpus {
+    /// The environment variable that holds the path to the manifest used by `from_env`
+    pub const MANIFEST_VAR: &'static str = "LOLROFL_CORPUS";
+    /// Build an empty corpus cached in a directory
+    pub fn new(dir: &Path) -> Corpus {
+        Corpus { dir: dir.to_path_buf(), entries: Vec::new() }
+    }
+    /// The default cache directory: `$XDG_CACHE_HOME/lolrofl/corpus` or `~/.cache/lolrofl/corpus`
+    pub fn default_dir() -> PathBuf {
+        std::env::var_os("XDG_CACHE_HOME").map(PathBuf::from)
+            .or_else(|| std::env::var_os("HOME").map(|h| PathBuf::from(h).join(".cache")))
+            .unwrap_or_else(std::env::temp_dir)
+            .join("lolrofl").join("corpus")
+    }
+    /// Build a corpus from a manifest's content
+    ///
+    /// # Errors
+    ///
+    /// If a line does not hold a digest, a name and a URL or if an entry is invalid
+    pub fn parse_manifest(dir: &Path, manifest: &str) -> Result<Corpus, Errors> {
+        let mut corpus = Corpus::new(dir);
+        for line in manifest.lines().map(str::trim).filter(|l| !l.is_empty() && !l.starts_with('#')) {
+            let fields: Vec<&str> = line.split_whitespace().collect();
+            if fields.len() != 3 {
+                return Err(Errors::InvalidArgument { name: "manifest", reason: "expected a digest, a name and a URL on each line" });
+            }
+            corpus.push(CorpusEntry::new(fields[1], fields[2], fields[0])?);
+        }
+        Ok(corpus)
+    }
+    /// Load the corpus whose manifest is set in the `LOLROFL_CORPUS` environment variable
+    ///
+    /// Returns `None` if the variable is not set so tests that require the corpus may be skipped
+    pub fn from_env() -> Result<Option<Corpus>, Errors> {
+        match std::env::var_os(Corpus::MANIFEST_VAR) {
+            Some(path) => {
+                let manifest = std::fs::read_to_string(path)?;
+                Corpus::parse_manifest(&Corpus::default_dir(), &manifest).map(Some)
+            },
+            None => Ok(None),
+        }
+    }
+    /// Add a replay to the corpus
+    pub fn push(&mut self, entry: CorpusEntry) { self.entries.push(entry); }
+    /// The corpus' replays
+    pub fn entries(&self) -> &[CorpusEntry] { &self.entries }
+    /// The directory replays are cached in
+    pub fn dir(&self) -> &Path { &self.dir }
+    /// The path of a replay in the cache directory
+    pub fn path(&self, entry: &CorpusEntry) -> PathBuf { self.dir.join(&entry.name) }
+    /// Download the replays that are missing from the cache and return the path of all replays
+    ///
+    /// # Errors
+    ///
+    /// If a download fails or if a downloaded file does not match its pinned digest
+    pub fn fetch(&self) -> Result<Vec<PathBuf>, Errors> {
+        std::fs::create_dir_all(&self.dir)?;
+        let mut paths = Vec::with_capacity(self.entries.len());
+        for entry in &self.entries {
+            let path = self.path(entry);
+            let cached = std::fs::read(&path).map(|data| entry.matches(&data)).unwrap_or(false);
+            if !cached {
+                let data = download(entry.url())?;
+                if !entry.matches(&data) {
+                    return Err(Errors::Io(std::io::Error::new(
+                        std::io::ErrorKind::InvalidData,
+                        format!("{} does not match its pinned digest", entry.url()),
+                    )));
+                }
+                std::fs::write(&path, data)?;
+            }
+            paths.push(path);
+        }
+        Ok(paths)
+    }
+}
+
+/// Compute the lowercase hexadecimal SHA-256 digest of some data
+pub fn sha256_hex(data: &[u8]) -> String {
+    Sha256::digest(data).iter().map(|b| format!("{:02x}", b)).collect()
+}
+
+/// Download a file's content
+fn download(url: &str) -> Result<Vec<u8>, Errors> {
+    use std::io::Read;
+
+    let response = ureq::get(url).call()
+        .map_err(|e| Errors::Io(std::io::Error::other(e.to_string())))?;
+    let mut data = Vec::new();
+    response.into_reader().read_to_end(&mut data)?;
+    Ok(data)
+}
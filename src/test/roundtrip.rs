@@ -0,0 +1,103 @@
+/*!
+Round-trip assertions of the `to_bytes` serializers over sample files
+
+[`assert_round_trip`] parses a file, serializes its header, payload header, segment headers and
+sections with their `to_bytes` method and checks that the serialized bytes match the file's bytes
+and parse back to the same values. Running it over the bundled samples and over the replays of a
+[`Corpus`](super::corpus::Corpus) catches field order and width mistakes when the format's
+structures change.
+
+Sections are only checked if the lib was compiled with the `payload` feature.
+
+# Examples
+
+```rust
+use lolrofl::test::roundtrip::assert_round_trip;
+
+for sample in [lolrofl::test::sample_full_file_0(), lolrofl::test::sample_no_keyframe_file_0()] {
+    assert_round_trip(&sample).unwrap();
+}
+# #[cfg(feature = "payload")]
+# assert_eq!(assert_round_trip(&lolrofl::test::sample_full_file_0()).unwrap(), 11);
+
+// Replays listed in the manifest set in LOLROFL_CORPUS, if any
+# #[cfg(feature = "test-corpus")]
+if let Some(corpus) = lolrofl::test::corpus::Corpus::from_env().unwrap() {
+    for path in corpus.fetch().unwrap() {
+        let content = std::fs::read(&path).unwrap();
+        let checked = assert_round_trip(&content).unwrap();
+        println!("{}: {} structures", path.display(), checked);
+    }
+}
+```
+*/
+
+use crate::{Errors, Rofl, iter::PayloadIterOptions};
+use crate::model::{BinHeader, PayloadHeader, Segment, section::{GenericSection, SectionCore}};
+
+/// Check that the structures of a file serialize to the file's bytes and parse back to the same values
+///
+/// Returns the number of checked structures: the file header, the payload header, each segment
+/// header and each section of the segments that could be decrypted.
+///
+/// # Panics
+///
+/// If a structure does not serialize to its bytes in the file or does not parse back to the same values
+///
+/// # Errors
+///
+/// If the file could not be parsed
+pub fn assert_round_trip(content: &[u8]) -> Result<usize, Errors> {
+    let rofl = Rofl::from_slice(content)?;
+
+    let head = rofl.head();
+    let bytes = head.to_bytes();
+    assert_eq!(&bytes[..], &content[..BinHeader::LENGTH], "file header bytes");
+    assert_eq!(&BinHeader::from_raw_source(&bytes)?, head, "parsed file header");
+    let mut checked = 1;
+
+    let payload = rofl.payload()?;
+    let start = head.payload_header_offset();
+    let bytes = payload.to_bytes();
+    assert_eq!(&bytes[..], &content[start..start + head.payload_header_len()], "payload header bytes");
+    assert_eq!(PayloadHeader::from_raw_section(&bytes)?, payload, "parsed payload header");
+    checked += 1;
+
+    let mut segments = rofl.segment_iter(&PayloadIterOptions::new().load_data(cfg!(feature = "payload")))?;
+    for (index, segment) in segments.by_ref().enumerate() {
+        let bytes = segment.to_bytes();
+        let start = head.payload_offset() + index * bytes.len();
+        assert_eq!(&bytes[..], &content[start..start + bytes.len()], "header bytes of segment #{}", index);
+        assert_eq!(Segment::from_slice(&bytes)?.to_bytes(), bytes, "parsed header of segment #{}", index);
+        checked += 1;
+        if segment.is_loaded() {
+            checked += assert_sections_round_trip(&segment, index)?;
+        }
+    }
+    if !segments.is_valid() {
+        return Err(segments.to_error());
+    }
+    Ok(checked)
+}
+
+/// Check that the sections of a decrypted segment serialize to the segment's bytes and parse back to the same values
+fn assert_sections_round_trip(segment: &Segment, index: usize) -> Result<usize, Errors> {
+    let mut checked = 0;
+    let mut offset = 0;
+    let mut last_type = None;
+    let mut sections = segment.section_iter()?;
+    for section in sections.by_ref() {
+        let bytes = section.to_bytes();
+        assert_eq!(&bytes[..], &segment.data()[offset..offset + section.len()], "bytes of section 0x{:X} of segment #{}", offset, index);
+        let parsed = GenericSection::from_slice(&bytes, last_type)?;
+        assert_eq!(
+            (parsed.time(), parsed.data_type(), parsed.params(), parsed.raw_data(), parsed.len()),
+            (section.time(), section.data_type(), section.params(), section.raw_data(), section.len()),
+            "parsed section 0x{:X} of segment #{}", offset, index,
+        );
+        offset += section.len();
+        last_type = Some(section.data_type());
+        checked += 1;
+    }
+    if sections.is_valid() { Ok(checked) } else { Err(sections.to_error()) }
+}
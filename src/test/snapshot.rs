@@ -0,0 +1,109 @@
+/*!
+Golden-file snapshot assertions for rendered outputs
+
+Snapshots are stored as `<name>.snap` files in a directory. When a rendered output differs from
+its snapshot, the new output is written next to it as `<name>.snap.new` and the assertion fails
+with a line diff. Set `LOLROFL_UPDATE_SNAPSHOTS=1` to accept new outputs instead.
+
+# Examples
+
+```rust
+use lolrofl::test::snapshot::{assert_snapshot, render_analysis};
+
+let snapshots = concat!(env!("CARGO_MANIFEST_DIR"), "/src/test/snapshots");
+
+let content = lolrofl::test::sample_full_file_0();
+let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+let headers = format!("{:#}\n{:#}\n", game.head(), game.payload().unwrap());
+assert_snapshot(snapshots, "sample_full_file_0_headers", &headers);
+# #[cfg(feature = "payload")]
+assert_snapshot(snapshots, "sample_full_file_0_analysis", &render_analysis(&game).unwrap());
+```
+*/
+
+use std::path::Path;
+use crate::{Errors, Rofl, fmt::{Event, SegmentStats}, iter::PayloadIterOptions};
+use crate::model::section::{SectionAddress, SectionCore, SectionPosition};
+
+/// Render the stats of each segment followed by its decoded sections, as `analyze` does in
+/// verbose stats mode and human detail mode
+///
+/// # Errors
+///
+/// If the file's segments could not be decrypted
+pub fn render_analysis(rofl: &Rofl) -> Result<String, Errors> {
+    let mut out = String::new();
+    let mut iterator = rofl.segment_iter(&PayloadIterOptions::with_data())?;
+    let mut time = 0.;
+    for segment in iterator.by_ref() {
+        out.push_str(&format!("{:#}\n", SegmentStats::from_segment(&segment)));
+        let mut offset = 0;
+        for section in segment.section_iter()? {
+            time = section.time().to_absolute(time);
+            let address = if segment.is_chunk() {
+                SectionAddress::chunk(segment.id(), SectionPosition::Offset(offset))
+            } else {
+                SectionAddress::keyframe(segment.id(), SectionPosition::Offset(offset))
+            };
+            offset += section.len();
+            out.push_str(&format!("  {}\n", Event { address, time, section: &section }));
+        }
+    }
+    if !iterator.is_valid() {
+        return Err(iterator.to_error());
+    }
+    Ok(out)
+}
+
+/// Describe the lines that differ between an expected and an actual output
+fn diff(expected: &str, actual: &str) -> String {
+    let expected: Vec<&str> = expected.lines().collect();
+    let actual: Vec<&str> = actual.lines().collect();
+    let mut out = String::new();
+    for i in 0..std::cmp::max(expected.len(), actual.len()) {
+        match (expected.get(i), actual.get(i)) {
+            (Some(e), Some(a)) if e == a => {},
+            (e, a) => {
+                out.push_str(&format!("@@ line {} @@\n", i + 1));
+                if let Some(e) = e {
+                    out.push_str(&format!("-{}\n", e));
+                }
+                if let Some(a) = a {
+                    out.push_str(&format!("+{}\n", a));
+                }
+            },
+        }
+    }
+    out
+}
+
+/// Check that a rendered output matches its snapshot
+///
+/// # Panics
+///
+/// If the snapshot does not exist or differs from `actual`, unless `LOLROFL_UPDATE_SNAPSHOTS` is set
+pub fn assert_snapshot<P: AsRef<Path>>(dir: P, name: &str, actual: &str) {
+    let path = dir.as_ref().join(format!("{}.snap", name));
+    let pending = dir.as_ref().join(format!("{}.snap.new", name));
+    if std::env::var_os("LOLROFL_UPDATE_SNAPSHOTS").is_some() {
+        std::fs::write(&path, actual).unwrap();
+        let _ = std::fs::remove_file(&pending);
+        return;
+    }
+    // Snapshots may be checked out with CRLF line endings
+    let expected = match std::fs::read_to_string(&path) {
+        Ok(expected) => expected.replace("\r\n", "\n"),
+        Err(e) => {
+            let _ = std::fs::write(&pending, actual);
+            panic!("Could not read snapshot {}: {}, the new output was written to {}", path.display(), e, pending.display());
+        },
+    };
+    if expected != actual {
+        let _ = std::fs::write(&pending, actual);
+        panic!(
+            "Snapshot {} does not match, the new output was written to {}\n{}",
+            path.display(), pending.display(), diff(&expected, actual),
+        );
+    }
+    let _ = std::fs::remove_file(&pending);
+}
@@ -0,0 +1,129 @@
+/*!
+Ordered events of a game, decoded from its chunks
+
+[`events`] walks all decrypted chunks and decodes their sections with the decoders of a
+[`Registry`]. Only the sections whose data type has a decoder become events: the lib only
+decodes interaction pings (data type 397), so the timeline only holds pings unless custom
+decoders are registered and used with [`events_with`].
+
+The layout of the sections' parameters is not documented, they are provided as raw bytes.
+
+Events are only available if the lib was compiled with the `payload` feature.
+
+# Examples
+
+```rust
+use lolrofl::model::section::decode::Decoded;
+
+# let content = lolrofl::test::sample_full_file_0();
+// let content = std::fs::read("game.rofl").unwrap();
+let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+
+let timeline = lolrofl::timeline::events(&game).unwrap();
+for event in timeline.events() {
+    if let Decoded::Ping(ping) = event.data() {
+        println!("{:.1}s: ping at {:?} (params {:?})", event.time(), ping.position(), event.params());
+    }
+}
+# assert_eq!(timeline.events().len(), 0);
+# assert_eq!(timeline.malformed().len(), 0);
+```
+*/
+
+use crate::{Errors, Rofl, iter::PayloadIterOptions, model::section::{SectionAddress, SectionCore, SectionPosition, decode::{Decoded, Registry}, format_game_time}};
+
+/// An event of a game, decoded from a chunk's section
+#[derive(Clone, Debug, PartialEq)]
+pub struct GameEvent {
+    /// The event's in-game time in seconds
+    time: f32,
+    /// The address of the section the event was decoded from
+    address: SectionAddress,
+    /// The raw parameters of the section
+    params: Vec<u8>,
+    /// The event's decoded data
+    data: Decoded,
+}
+
+impl GameEvent {
+    /// The event's in-game time in seconds
+    pub fn time(&self) -> f32 { self.time }
+    /// The address of the section the event was decoded from
+    pub fn address(&self) -> SectionAddress { self.address }
+    /// The raw parameters of the section, 1 or 4 bytes whose meaning is not documented
+    pub fn params(&self) -> &[u8] { &self.params }
+    /// The event's decoded data
+    pub fn data(&self) -> &Decoded { &self.data }
+}
+
+impl std::fmt::Display for GameEvent {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{} {} params {:?}: {}", format_game_time(self.time), self.address, self.params, self.data)
+    }
+}
+
+/// The events of a game, see [`events`]
+#[derive(Debug, Default)]
+pub struct Timeline {
+    /// The decoded events, sorted by time
+    events: Vec<GameEvent>,
+    /// The sections that have a decoder but could not be decoded, and why
+    malformed: Vec<(SectionAddress, Errors)>,
+}
+
+impl Timeline {
+    /// The decoded events, sorted by time
+    pub fn events(&self) -> &[GameEvent] { &self.events }
+    /// The sections that have a decoder but could not be decoded, and why
+    pub fn malformed(&self) -> &[(SectionAddress, Errors)] { &self.malformed }
+    /// Get the decoded events, sorted by time
+    pub fn into_events(self) -> Vec<GameEvent> { self.events }
+}
+
+/// Decode the events of a game's chunks with the lib's decoders that apply to its game version
+///
+/// The decoders are selected with [`FormatVersion::registry`](crate::model::FormatVersion::registry).
+///
+/// # Errors
+///
+/// If the file's chunks could not be read or split into sections
+pub fn events(rofl: &Rofl) -> Result<Timeline, Errors> {
+    events_with(rofl, &rofl.format_version().registry())
+}
+
+/// Decode the events of a game's chunks with the decoders of a registry
+///
+/// Events that occur at the same time are kept in the order of the file. Sections that have a
+/// decoder but whose data does not match its layout are listed in [`Timeline::malformed`].
+///
+/// # Errors
+///
+/// If the file's chunks could not be read or split into sections
+pub fn events_with(rofl: &Rofl, registry: &Registry) -> Result<Timeline, Errors> {
+    let mut timeline = Timeline::default();
+    // Without decryption support, no chunk is loaded and the timeline is empty
+    let mut iterator = rofl.segment_iter(&PayloadIterOptions::new().load_data(cfg!(feature="payload")))?;
+    let mut time = 0.;
+    for segment in iterator.by_ref().filter(|s| s.is_chunk() && s.is_loaded()) {
+        let mut offset = 0;
+        let mut sections = segment.section_iter()?;
+        for section in sections.by_ref() {
+            time = section.time().to_absolute(time);
+            let address = SectionAddress::chunk(segment.id(), SectionPosition::Offset(offset));
+            offset += section.len();
+            match registry.decode(&section) {
+                None => {},
+                Some(Ok(data)) => timeline.events.push(GameEvent { time, address, params: section.params().to_vec(), data }),
+                Some(Err(e)) => timeline.malformed.push((address, e)),
+            }
+        }
+        if !sections.is_valid() {
+            return Err(sections.to_error());
+        }
+    }
+    if !iterator.is_valid() {
+        return Err(iterator.to_error());
+    }
+    timeline.events.sort_by(|a, b| a.time.total_cmp(&b.time));
+    Ok(timeline)
+}
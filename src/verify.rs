@@ -0,0 +1,378 @@
+/*!
+Check the integrity of a file
+
+Unlike [`Rofl::warnings`], which only reports the inconsistencies of a file's headers,
+a verification reads the whole file: it checks that the metadata is valid JSON, that the
+segments are within the file's bounds and that their data can be decrypted and decompressed.
+
+A [`Validator`] checks a file's structure as it is received instead, so that uploads of
+invalid files can be aborted without waiting for their end.
+*/
+
+use crate::{Errors, Rofl, iter, model::{self, BinHeader, PayloadHeader, RawSegmentHeader, SegmentKind, SEGMENT_HEADER_LEN}};
+
+/// The result of the checks on a segment, see [`VerificationReport::segments`]
+#[derive(Debug)]
+pub struct SegmentReport {
+    /// Whether the segment is a chunk or a keyframe, `None` if its type is unknown
+    kind: Option<SegmentKind>,
+    /// The segment's ID
+    id: u32,
+    /// The offset of the segment's data in the file
+    offset: usize,
+    /// The length of the segment's data
+    len: usize,
+    /// Whether the segment's data ends before the end of the file
+    in_bounds: bool,
+    /// Whether the segment's data could be decrypted and decompressed, `None` if it was not checked
+    data: Option<Result<(), Errors>>,
+}
+
+impl SegmentReport {
+    /// Whether the segment is a chunk or a keyframe, `None` if its type is unknown
+    pub fn kind(&self) -> Option<SegmentKind> { self.kind }
+    /// The segment's ID
+    pub fn id(&self) -> u32 { self.id }
+    /// The offset of the segment's data in the file
+    pub fn offset(&self) -> usize { self.offset }
+    /// The length of the segment's data
+    pub fn len(&self) -> usize { self.len }
+    /// Whether the segment's data is empty
+    pub fn is_empty(&self) -> bool { self.len == 0 }
+    /// Whether the segment's data ends before the end of the file
+    pub fn in_bounds(&self) -> bool { self.in_bounds }
+    /// Whether the segment's data could be decrypted and decompressed
+    ///
+    /// `None` if the data was not checked because it is out of the file's bounds or because the
+    /// lib was compiled without the `payload` feature
+    pub fn data(&self) -> Option<&Result<(), Errors>> { self.data.as_ref() }
+    /// Whether no check on the segment failed
+    pub fn is_valid(&self) -> bool { self.in_bounds && !matches!(self.data, Some(Err(_))) }
+}
+
+impl std::fmt::Display for SegmentReport {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", model::Area::Segment { kind: self.kind, id: self.id })
+    }
+}
+
+/// The result of the integrity checks of a file, see [`Rofl::verify`]
+#[derive(Debug)]
+pub struct VerificationReport {
+    /// The file's length declared in its header
+    declared_len: usize,
+    /// The file's actual length
+    actual_len: usize,
+    /// Whether the metadata is a valid UTF-8 JSON object
+    metadata: Result<(), Errors>,
+    /// Whether the payload header could be read
+    payload_header: Result<(), Errors>,
+    /// Whether all the segment headers could be read
+    segment_headers: Result<(), Errors>,
+    /// The checks of the segments whose header could be read
+    segments: Vec<SegmentReport>,
+}
+
+impl VerificationReport {
+    /// The file's length declared in its header
+    pub fn declared_len(&self) -> usize { self.declared_len }
+    /// The file's actual length
+    pub fn actual_len(&self) -> usize { self.actual_len }
+    /// Whether the file's declared length matches its actual length
+    pub fn is_length_valid(&self) -> bool { self.declared_len == self.actual_len }
+    /// Whether the metadata is a valid UTF-8 JSON object
+    pub fn metadata(&self) -> &Result<(), Errors> { &self.metadata }
+    /// Whether the payload header could be read
+    pub fn payload_header(&self) -> &Result<(), Errors> { &self.payload_header }
+    /// Whether all the segment headers could be read, segments are not checked past the first unreadable header
+    pub fn segment_headers(&self) -> &Result<(), Errors> { &self.segment_headers }
+    /// The checks of the segments whose header could be read, in the order of the file
+    pub fn segments(&self) -> &[SegmentReport] { &self.segments }
+    /// Whether no check failed
+    ///
+    /// The segments whose data was not checked because the lib was compiled without the
+    /// `payload` feature are not counted as failures
+    pub fn is_valid(&self) -> bool {
+        self.is_length_valid()
+            && self.metadata.is_ok()
+            && self.payload_header.is_ok()
+            && self.segment_headers.is_ok()
+            && self.segments.iter().all(SegmentReport::is_valid)
+    }
+    /// Verify the integrity of a file
+    pub(crate) fn from_rofl(rofl: &Rofl) -> VerificationReport {
+        let head = rofl.head();
+        let metadata = rofl.metadata().and_then(model::check_json);
+        let payload = rofl.payload();
+        let mut segments = Vec::new();
+        let segment_headers = match &payload {
+            Err(_) => Err(Errors::NoData),
+            // Segment headers were not read by headers_from_reader
+            Ok(_) if rofl.data.len() < rofl.actual_len => Err(Errors::NoData),
+            Ok(payload) => rofl.data.get(head.payload_offset()..).ok_or(Errors::too_small(head.payload_offset(), rofl.data.len())).and_then(|directory| {
+                let segments_start = head.payload_offset() + (payload.chunk_count() + payload.keyframe_count()) as usize * SEGMENT_HEADER_LEN;
+                let mut iterator = iter::PayloadIterator::new(directory, payload, &iter::PayloadIterOptions::new())?;
+                for mut segment in iterator.by_ref() {
+                    let offset = segments_start + segment.offset();
+                    let in_bounds = offset + segment.len() <= rofl.actual_len;
+                    let data = if in_bounds {
+                        match rofl.load_segment(&mut segment) {
+                            Err(Errors::FeatureDisabled { .. }) => None,
+                            result => Some(result),
+                        }
+                    } else {
+                        None
+                    };
+                    segments.push(SegmentReport { kind: segment.kind(), id: segment.id(), offset, len: segment.len(), in_bounds, data });
+                }
+                if iterator.is_valid() { Ok(()) } else { Err(iterator.to_error()) }
+            }),
+        };
+        VerificationReport {
+            declared_len: head.file_len(),
+            actual_len: rofl.actual_len,
+            metadata,
+            payload_header: payload.map(|_| ()),
+            segment_headers,
+            segments,
+        }
+    }
+}
+
+/// The state of a file fed to a [`Validator`]
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Verdict {
+    /// No check failed on the bytes received so far and more bytes are expected
+    Pending,
+    /// The file was received whole and no check failed
+    Complete,
+    /// The file is invalid from this byte offset on, see [`Validator::error`]
+    Invalid {
+        /// The offset of the earliest invalid byte
+        offset: usize,
+    },
+}
+
+/// The signature of the files a [`Validator`] accepts, see [`crate::kind::FileKind::RoflV1`]
+const SIGNATURE: [u8; crate::kind::SIGNATURE_LEN] = [Rofl::MAGIC[0], Rofl::MAGIC[1], Rofl::MAGIC[2], Rofl::MAGIC[3], 0, 0];
+
+/// The part of a file a [`Validator`] waits for to run its next check
+#[derive(Clone, Copy, Debug)]
+enum Stage {
+    /// The file's magic and header
+    Header,
+    /// The metadata and payload header, whose end is known from the header
+    Headers(usize),
+    /// The segment headers, whose end is known from the payload header
+    Directory(usize),
+    /// The segments' data, until the file's declared end
+    Data,
+}
+
+/// Checks a file's structure as its bytes are received, without holding its segments' data
+///
+/// Bytes may be pushed in chunks of any size. The header, metadata, payload header and segment
+/// headers are held until they can be checked, the segments' data is only counted: a file is
+/// invalid as soon as its start is not the header of a supported ROFL format, its metadata is
+/// not a JSON object, its payload header or a segment header is malformed, a segment ends after
+/// the file's declared length, or more bytes than this length are received.
+///
+/// At most [`DEFAULT_MAX_HELD`](Validator::DEFAULT_MAX_HELD) bytes are held by default, see
+/// [`with_max_held`](Validator::with_max_held): a file whose headers end after this limit is
+/// invalid, so that a forged header can't make the validator buffer the whole file. Call [`finish`](Validator::finish)
+/// once all bytes were pushed to detect truncated files.
+///
+/// The segments' data is not decrypted, use [`Rofl::verify`] on the received file to check it.
+///
+/// # Examples
+///
+/// ```rust
+/// use lolrofl::verify::{Validator, Verdict};
+///
+/// # let content = lolrofl::test::sample_full_file_0();
+/// let mut validator = Validator::new();
+/// for packet in content.chunks(100) {
+///     // let packet = stream.read(...);
+///     if let Verdict::Invalid { offset } = validator.push_bytes(packet) {
+///         println!("Aborting the upload, invalid at byte {}: {}", offset, validator.error().unwrap());
+///         break;
+///     }
+/// }
+/// assert_eq!(validator.finish(), Verdict::Complete);
+/// # let mut validator = Validator::new();
+/// # assert_eq!(validator.push_bytes(&content[..20]), Verdict::Pending);
+/// # assert_eq!(validator.finish(), Verdict::Invalid { offset: 20 });
+/// # let mut broken = content.clone();
+/// # broken[2] = b'X';
+/// # assert_eq!(Validator::new().push_bytes(&broken[..3]), Verdict::Invalid { offset: 2 });
+/// # let mut broken = content.clone();
+/// # broken[288] = b'x';
+/// # let mut validator = Validator::new();
+/// # assert_eq!(broken.chunks(7).map(|packet| validator.push_bytes(packet)).last(), Some(Verdict::Invalid { offset: 288 }));
+/// # let mut validator = Validator::new();
+/// # validator.push_bytes(&content);
+/// # assert_eq!(validator.push_bytes(&[0]), Verdict::Invalid { offset: content.len() });
+/// # let mut broken = content.clone();
+/// # broken[4] = 2;
+/// # assert_eq!(Validator::new().push_bytes(&broken[..5]), Verdict::Invalid { offset: 4 });
+/// # let mut validator = Validator::with_max_held(0);
+/// # assert_eq!(validator.push_bytes(&content), Verdict::Invalid { offset: lolrofl::model::BinHeader::LENGTH });
+/// # assert_eq!(validator.error().unwrap().kind(), "buffer_too_small");
+/// ```
+#[derive(Debug)]
+pub struct Validator {
+    /// The bytes received and held until the segments' data
+    buffer: Vec<u8>,
+    /// The number of bytes received
+    received: usize,
+    /// The maximum number of bytes held
+    max_held: usize,
+    /// The next check
+    stage: Stage,
+    /// The file's header, once it is checked
+    head: Option<BinHeader>,
+    /// The earliest invalid byte and why it is invalid
+    failure: Option<(usize, Errors)>,
+}
+
+impl Default for Validator {
+    fn default() -> Self {
+        Validator::new()
+    }
+}
+
+impl Validator {
+    /// The default maximum number of bytes held until the segments' data, which exceeds the
+    /// headers and metadata of real files by far
+    pub const DEFAULT_MAX_HELD: usize = 16 << 20;
+    /// Build a validator that expects the start of a file
+    pub fn new() -> Validator {
+        Validator::with_max_held(Validator::DEFAULT_MAX_HELD)
+    }
+    /// Build a validator that holds at most `max_held` bytes until the segments' data
+    ///
+    /// Files whose headers, metadata and segment headers end after `max_held` bytes are invalid.
+    /// The limit can't be lower than the length of a file's header.
+    pub fn with_max_held(max_held: usize) -> Validator {
+        Validator {
+            buffer: Vec::new(),
+            received: 0,
+            max_held: max_held.max(BinHeader::LENGTH),
+            stage: Stage::Header,
+            head: None,
+            failure: None,
+        }
+    }
+    /// The number of bytes received
+    pub fn received(&self) -> usize { self.received }
+    /// The file's length declared in its header, once it was received
+    pub fn declared_len(&self) -> Option<usize> { self.head.as_ref().map(BinHeader::file_len) }
+    /// Why the file is invalid, if it is
+    pub fn error(&self) -> Option<&Errors> { self.failure.as_ref().map(|(_, e)| e) }
+    /// The state of the file after the bytes received so far
+    pub fn verdict(&self) -> Verdict {
+        match (&self.failure, self.stage) {
+            (Some((offset, _)), _) => Verdict::Invalid { offset: *offset },
+            (None, Stage::Data) if Some(self.received) == self.declared_len() => Verdict::Complete,
+            (None, _) => Verdict::Pending,
+        }
+    }
+    /// Check the next bytes of the file
+    ///
+    /// Once the file is invalid, further bytes are ignored.
+    pub fn push_bytes(&mut self, data: &[u8]) -> Verdict {
+        if self.failure.is_some() {
+            return self.verdict();
+        }
+        let start = self.received;
+        self.received += data.len();
+        if let Some(declared_len) = self.declared_len().filter(|len| self.received > *len) {
+            self.failure = Some((declared_len, Errors::InvalidBuffer));
+        }
+        if let Stage::Data = self.stage {
+            return self.verdict();
+        }
+        // Bytes past the limit are never needed, as the checks fail before waiting for them
+        let kept = data.len().min(self.max_held.saturating_sub(self.buffer.len()));
+        self.buffer.extend_from_slice(&data[..kept]);
+        // The signature is checked byte by byte to reject other files early
+        let signature_len = SIGNATURE.len().min(self.buffer.len());
+        if let Some(index) = (start.min(signature_len)..signature_len).find(|i| self.buffer[*i] != SIGNATURE[*i]) {
+            let error = match crate::kind::detect(&self.buffer) {
+                Some(kind) => Errors::UnsupportedFormat { kind },
+                None => Errors::InvalidBuffer,
+            };
+            self.failure = Some((index, error));
+            return self.verdict();
+        }
+        if let Err((offset, error)) = self.advance() {
+            // A failure found in the bytes received earlier takes precedence, such as excess bytes
+            if self.failure.as_ref().is_none_or(|(earliest, _)| offset < *earliest) {
+                self.failure = Some((offset, error));
+            }
+        }
+        if let Stage::Data = self.stage {
+            self.buffer = Vec::new();
+        }
+        self.verdict()
+    }
+    /// Check the file once all its bytes were pushed, a file shorter than its declared length is invalid
+    pub fn finish(&mut self) -> Verdict {
+        if self.failure.is_none() && !matches!(self.verdict(), Verdict::Complete) {
+            self.failure = Some((self.received, Errors::too_small(self.declared_len().unwrap_or(BinHeader::LENGTH), self.received)));
+        }
+        self.verdict()
+    }
+    /// Run the checks whose bytes were all received
+    fn advance(&mut self) -> Result<(), (usize, Errors)> {
+        loop {
+            match self.stage {
+                Stage::Header if self.buffer.len() >= BinHeader::LENGTH => {
+                    let head = BinHeader::from_raw_source(&self.buffer).map_err(|e| (0, e))?;
+                    let end = (head.metadata_offset() + head.metadata_len()).max(head.payload_header_offset() + head.payload_header_len());
+                    if end > head.file_len() || head.payload_offset() > head.file_len() {
+                        return Err((head.file_len(), Errors::too_small(end.max(head.payload_offset()), head.file_len())));
+                    }
+                    if end > self.max_held {
+                        return Err((self.max_held, Errors::too_small(end, self.max_held)));
+                    }
+                    self.head = Some(head);
+                    self.stage = Stage::Headers(end);
+                },
+                Stage::Headers(end) if self.buffer.len() >= end => {
+                    let Some(head) = &self.head else { return Ok(()) };
+                    let metadata = &self.buffer[head.metadata_offset()..head.metadata_offset() + head.metadata_len()];
+                    std::str::from_utf8(metadata).map_err(Errors::from).and_then(model::check_json)
+                        .map_err(|e| (head.metadata_offset(), e))?;
+                    let payload = PayloadHeader::from_raw_section(&self.buffer[head.payload_header_offset()..head.payload_header_offset() + head.payload_header_len()])
+                        .map_err(|e| (head.payload_header_offset(), e))?;
+                    let end = (payload.chunk_count() as usize).checked_add(payload.keyframe_count() as usize)
+                        .and_then(|count| count.checked_mul(SEGMENT_HEADER_LEN))
+                        .and_then(|len| len.checked_add(head.payload_offset()))
+                        .ok_or((head.payload_header_offset(), Errors::MalformedPayloadHeader { field: "segment_count" }))?;
+                    if end > head.file_len() {
+                        return Err((head.file_len(), Errors::too_small(end, head.file_len())));
+                    }
+                    if end > self.max_held {
+                        return Err((self.max_held, Errors::too_small(end, self.max_held)));
+                    }
+                    self.stage = Stage::Directory(end);
+                },
+                Stage::Directory(end) if self.buffer.len() >= end => {
+                    let Some(head) = &self.head else { return Ok(()) };
+                    for offset in (head.payload_offset()..end).step_by(SEGMENT_HEADER_LEN) {
+                        let mut bytes = [0; SEGMENT_HEADER_LEN];
+                        bytes.copy_from_slice(&self.buffer[offset..offset + SEGMENT_HEADER_LEN]);
+                        let segment = RawSegmentHeader::from_bytes(&bytes);
+                        let segment_end = end + segment.offset as usize + segment.length as usize;
+                        if segment_end > head.file_len() {
+                            return Err((offset, Errors::too_small(segment_end, head.file_len())));
+                        }
+                    }
+                    self.stage = Stage::Data;
+                },
+                _ => return Ok(()),
+            }
+        }
+    }
+}
@@ -0,0 +1,104 @@
+/*!
+Inconsistencies that do not prevent a file from being parsed
+
+Parsing is lenient: a file whose header does not match its actual size or whose metadata
+disagrees with its payload header may still be read. These inconsistencies are reported
+as warnings, separately from errors, so they are not silently hidden.
+*/
+
+/// An inconsistency that was tolerated while parsing a file
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[non_exhaustive]
+pub enum Warning {
+    /// The file's length declared in its header does not match its actual length
+    FileLength {
+        /// The length declared in the header
+        declared: usize,
+        /// The file's actual length
+        actual: usize,
+    },
+    /// A section of the file ends after the end of the file
+    SectionOutOfBounds {
+        /// The name of the section
+        section: &'static str,
+        /// The offset of the section's start
+        start: usize,
+        /// The offset of the section's end
+        end: usize,
+        /// The file's actual length
+        actual: usize,
+    },
+    /// The number of segments in the metadata does not match the payload header
+    SegmentCount {
+        /// The type of segment
+        kind: &'static str,
+        /// The number of segments according to the metadata
+        metadata: u32,
+        /// The number of segments according to the payload header
+        header: u32,
+    },
+    /// A keyframe's first section is not timed at the keyframe's ID times the keyframe interval
+    KeyframeTime {
+        /// The keyframe's ID
+        id: u32,
+        /// The expected time in milliseconds
+        expected: u64,
+        /// The time of the keyframe's first section in milliseconds
+        actual: u64,
+    },
+}
+
+impl std::fmt::Display for Warning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Warning::FileLength { declared, actual } =>
+                write!(f, "Declared file size ({}) does not match the actual file size ({})", declared, actual),
+            Warning::SectionOutOfBounds { section, start, end, actual } =>
+                write!(f, "{} section ({}..{}) ends after the end of the file ({})", section, start, end, actual),
+            Warning::SegmentCount { kind, metadata, header } =>
+                write!(f, "The metadata declares {} {}s but the payload header declares {}", metadata, kind, header),
+            Warning::KeyframeTime { id, expected, actual } =>
+                write!(f, "Keyframe {} starts at {}ms but is expected at {}ms", id, actual, expected),
+        }
+    }
+}
+
+/// A collector of the warnings raised while parsing a file
+///
+/// # Examples
+///
+/// ```rust
+/// # let content = lolrofl::test::sample_base_file_0();
+/// // let content = std::fs::read("game.rofl").unwrap();
+/// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+///
+/// for warning in game.warnings().iter() {
+///     eprintln!("WARNING: {}", warning);
+/// }
+/// # assert_eq!(game.warnings().len(), 1);
+/// # assert_eq!(game.warnings().iter().next(), Some(&lolrofl::warnings::Warning::FileLength { declared: 461, actual: 462 }));
+/// ```
+#[derive(Clone, Debug, Default, PartialEq, Eq)]
+pub struct Warnings {
+    /// The collected warnings
+    warnings: Vec<Warning>,
+}
+
+impl Warnings {
+    /// Build an empty collector
+    pub fn new() -> Warnings { Warnings::default() }
+    /// Add a warning
+    pub fn push(&mut self, warning: Warning) { self.warnings.push(warning); }
+    /// The number of collected warnings
+    pub fn len(&self) -> usize { self.warnings.len() }
+    /// Whether no warning was collected
+    pub fn is_empty(&self) -> bool { self.warnings.is_empty() }
+    /// Iterate over the collected warnings
+    pub fn iter(&self) -> std::slice::Iter<'_, Warning> { self.warnings.iter() }
+}
+
+impl IntoIterator for Warnings {
+    type Item = Warning;
+    type IntoIter = std::vec::IntoIter<Warning>;
+    fn into_iter(self) -> Self::IntoIter { self.warnings.into_iter() }
+}
@@ -0,0 +1,78 @@
+/*!
+JavaScript bindings to inspect files in the browser
+
+Only the file's headers and metadata are kept in memory, so that a file's information may be
+shown before it is uploaded. Payload decryption and decompression are pure Rust and also
+available when built for `wasm32-unknown-unknown`, except for parallel decryption as browsers
+do not provide threads to WebAssembly modules.
+
+This module requires the `wasm` feature and a `wasm32` target.
+
+# Examples
+
+```js
+import init, { RoflInfo } from "./pkg/lolrofl.js";
+
+await init();
+const file = document.querySelector("input[type=file]").files[0];
+const info = new RoflInfo(new Uint8Array(await file.arrayBuffer()));
+console.log(`Game ${info.matchId} lasted ${info.duration / 1000} seconds`);
+const metadata = JSON.parse(info.metadata());
+```
+*/
+
+use wasm_bindgen::prelude::*;
+use crate::{Errors, Rofl, model::PayloadHeader};
+
+/// Convert an error to a JavaScript error, whose message holds the errors that caused it
+fn js_error(error: Errors) -> JsError {
+    let mut message = error.to_string();
+    let mut source = std::error::Error::source(&error);
+    while let Some(cause) = source {
+        message.push_str(&format!(": {}", cause));
+        source = cause.source();
+    }
+    JsError::new(&message)
+}
+
+/// The information held by a file's headers and metadata
+#[wasm_bindgen]
+pub struct RoflInfo {
+    /// The file, with its headers and metadata only
+    rofl: Rofl<'static>,
+    /// The file's payload header
+    payload: PayloadHeader,
+}
+
+#[wasm_bindgen]
+impl RoflInfo {
+    /// Read a file's headers and metadata
+    #[wasm_bindgen(constructor)]
+    pub fn new(data: &[u8]) -> Result<RoflInfo, JsError> {
+        let rofl = Rofl::headers_from_reader(std::io::Cursor::new(data)).map_err(js_error)?;
+        let payload = rofl.payload().map_err(js_error)?;
+        Ok(RoflInfo { rofl, payload })
+    }
+    /// The file's length in bytes declared in its header
+    #[wasm_bindgen(getter, js_name = fileLength)]
+    pub fn file_len(&self) -> usize { self.rofl.head().file_len() }
+    /// Whether the file's declared length matches the provided data's length
+    #[wasm_bindgen(getter, js_name = isComplete)]
+    pub fn is_complete(&self) -> bool { self.rofl.head().file_len() == self.rofl.actual_len }
+    /// The file's raw JSON metadata
+    pub fn metadata(&self) -> Result<String, JsError> {
+        self.rofl.metadata().map(str::to_string).map_err(js_error)
+    }
+    /// The game's ID
+    #[wasm_bindgen(getter, js_name = matchId)]
+    pub fn match_id(&self) -> u64 { self.payload.id() }
+    /// The game's duration in milliseconds
+    #[wasm_bindgen(getter)]
+    pub fn duration(&self) -> u32 { self.payload.duration() }
+    /// The number of chunks in the payload
+    #[wasm_bindgen(getter, js_name = chunkCount)]
+    pub fn chunk_count(&self) -> u32 { self.payload.chunk_count() }
+    /// The number of keyframes in the payload
+    #[wasm_bindgen(getter, js_name = keyframeCount)]
+    pub fn keyframe_count(&self) -> u32 { self.payload.keyframe_count() }
+}
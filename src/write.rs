@@ -0,0 +1,308 @@
+/*!
+Tools to assemble ROFL files from their components
+*/
+
+use crate::{Errors, Rofl, iter::PayloadIterOptions, model::{self, BinHeader, PayloadHeader, Segment, SEGMENT_HEADER_LEN}, repair};
+
+/// Builder that assembles a ROFL file from its metadata, payload header and segments
+/// 
+/// Segment data is written as-is and MUST already be encrypted.
+/// Segment counts, offsets and lengths as well as all file header offsets
+/// are computed when building the file.
+/// 
+/// # Examples
+/// 
+/// ```rust
+/// # let content = lolrofl::test::sample_full_file_0();
+/// // let content = std::fs::read("game.rofl").unwrap();
+/// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+///
+/// // Rebuild the file without its keyframes nor its metadata
+/// let mut builder = lolrofl::write::RoflBuilder::from_rofl(&game).unwrap();
+/// builder.set_metadata("{}").retain_segments(|s| s.is_chunk());
+/// let rebuilt = builder.build();
+///
+/// let rebuilt_game = lolrofl::Rofl::from_slice(&rebuilt[..]).unwrap();
+/// assert_eq!(rebuilt_game.metadata().unwrap(), "{}");
+/// assert_eq!(rebuilt_game.payload().unwrap().keyframe_count(), 0);
+/// # assert_eq!(rebuilt_game.payload().unwrap().chunk_count(), 2);
+/// # #[cfg(feature = "payload")]
+/// # assert_eq!(rebuilt_game.segment_iter(&lolrofl::iter::PayloadIterOptions::with_data()).unwrap().count(), 2);
+/// ```
+#[derive(Clone, Debug)]
+pub struct RoflBuilder {
+    /// The file's signature
+    signature: Vec<u8>,
+    /// The file's raw metadata
+    metadata: Vec<u8>,
+    /// The payload header, its segment counts are ignored
+    payload: PayloadHeader,
+    /// The segments' headers with their encrypted data
+    segments: Vec<(Segment, Vec<u8>)>,
+}
+
+impl RoflBuilder {
+    /// Create a new builder without metadata nor segments
+    pub fn new(payload: PayloadHeader) -> RoflBuilder {
+        RoflBuilder {
+            signature: Vec::new(),
+            metadata: Vec::new(),
+            payload,
+            segments: Vec::new(),
+        }
+    }
+    /// Create a new builder that holds a copy of a file's content
+    /// 
+    /// Segments overridden with [`Rofl::with_overrides`] are re-encrypted, the encrypted data
+    /// of all other segments is copied as-is so that only modified segments are processed
+    /// 
+    /// # Errors
+    /// 
+    /// If the file's metadata, payload header or segments could not be loaded, or if a segment
+    /// is overridden and the lib was compiled without the `payload` feature
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust
+    /// use lolrofl::{iter::PayloadIterOptions, model::SegmentKind, write::RoflBuilder};
+    /// 
+    /// # let content = lolrofl::test::sample_full_file_0();
+    /// // let content = std::fs::read("game.rofl").unwrap();
+    /// let overrides = [((SegmentKind::Keyframe, 1), b"edited".to_vec())].into_iter().collect();
+    /// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap().with_overrides(overrides);
+    /// # #[cfg(feature = "payload")] {
+    /// let rebuilt = RoflBuilder::from_rofl(&game).unwrap().build();
+    /// 
+    /// // Chunk 1 is copied as-is, keyframe 1 is re-encrypted
+    /// assert_eq!(rebuilt[512..560], content[512..560]);
+    /// let rebuilt_game = lolrofl::Rofl::from_slice(&rebuilt[..]).unwrap();
+    /// let keyframe = rebuilt_game.segment_iter(&PayloadIterOptions::with_data()).unwrap().find(|s| s.is_keyframe()).unwrap();
+    /// assert_eq!(keyframe.data(), b"edited");
+    /// # }
+    /// ```
+    pub fn from_rofl(rofl: &Rofl) -> Result<RoflBuilder, Errors> {
+        let mut builder = RoflBuilder::new(rofl.payload()?);
+        builder.set_signature(rofl.head().signature());
+        builder.set_raw_metadata(rofl.metadata()?.as_bytes().to_vec());
+        let mut iterator = rofl.segment_iter(&PayloadIterOptions::new())?;
+        for segment in iterator.by_ref() {
+            let overridden = segment.kind().and_then(|kind| rofl.overrides().get(&(kind, segment.id())));
+            match overridden {
+                Some(data) => builder.push_decrypted_segment(segment, data)?,
+                None => {
+                    let data = rofl.segment_raw_data(&segment)?.to_vec();
+                    builder.push_segment(segment, data)
+                },
+            };
+        }
+        if !iterator.is_valid() {
+            return Err(iterator.to_error());
+        }
+        Ok(builder)
+    }
+    /// Create a new builder that holds the most complete replay of a game from partial copies of it
+    ///
+    /// Copies may be truncated files, such as interrupted downloads, or files that only hold some
+    /// of the game's segments. Each segment is taken from the first copy, in the order of `parts`,
+    /// that holds its whole data, and when the lib was compiled with the `payload` feature, whose
+    /// data can be decrypted and decompressed. Segments of copies encrypted with another key are
+    /// re-encrypted with the first copy's key. The signature and payload header are those of the
+    /// first copy and the metadata is the first valid one, an empty JSON object if there is none.
+    /// Segments are sorted in canonical order, see [`normalize_order`](RoflBuilder::normalize_order).
+    ///
+    /// # Errors
+    ///
+    /// If there is no copy, if a copy's payload header could not be read, if a copy holds
+    /// another game ([`Errors::GameMismatch`]), if a segment of a copy encrypted with another key
+    /// could not be re-encrypted or if the merged file could not be verified
+    /// ([`Errors::VerificationFailed`])
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use lolrofl::{Rofl, iter::PayloadIterOptions, write::RoflBuilder};
+    ///
+    /// # let content = lolrofl::test::sample_full_file_0();
+    /// // let content = std::fs::read("game.rofl").unwrap();
+    /// // A download that stopped before the end of the file
+    /// let truncated = Rofl::from_slice(&content[..600]).unwrap();
+    /// // The missing segments, downloaded separately
+    /// # let game = Rofl::from_slice(&content[..]).unwrap();
+    /// # let downloaded = RoflBuilder::from_rofl(&game).unwrap().retain_segments(|s| !(s.is_chunk() && s.id() == 1)).build();
+    /// let downloaded = Rofl::from_slice(&downloaded[..]).unwrap();
+    ///
+    /// let merged = RoflBuilder::merge(&[truncated, downloaded]).unwrap().build();
+    /// let game = Rofl::from_slice(&merged[..]).unwrap();
+    /// assert_eq!(game.verify().is_valid(), true);
+    /// assert_eq!(game.segment_iter(&PayloadIterOptions::new()).unwrap().count(), 3);
+    /// # let mut builder = RoflBuilder::from_rofl(&Rofl::from_slice(&content[..]).unwrap()).unwrap();
+    /// # assert_eq!(merged, builder.normalize_order().build());
+    /// # assert_eq!(RoflBuilder::merge(&[]).is_err(), true);
+    /// # let other = RoflBuilder::new(lolrofl::model::PayloadHeader::new(43, 1000, 1, 2, 60000, "a2V5")).build();
+    /// # let mismatch = RoflBuilder::merge(&[Rofl::from_slice(&content[..]).unwrap(), Rofl::from_slice(&other[..]).unwrap()]).err().unwrap();
+    /// # assert_eq!(matches!(mismatch, lolrofl::Errors::GameMismatch { actual: 43, .. }), true);
+    /// ```
+    pub fn merge(parts: &[Rofl]) -> Result<RoflBuilder, Errors> {
+        let first = parts.first().ok_or(Errors::NoData)?;
+        let payload = first.payload()?;
+        let mut builder = RoflBuilder::new(payload.clone());
+        builder.set_signature(first.head().signature());
+        let metadata = parts.iter().find_map(|part| part.metadata().ok().filter(|m| model::check_json(m).is_ok()));
+        builder.set_metadata(metadata.unwrap_or("{}"));
+        let mut merged = std::collections::HashSet::new();
+        for part in parts {
+            let part_payload = part.payload()?;
+            if part_payload.id() != payload.id() {
+                return Err(Errors::GameMismatch { expected: payload.id(), actual: part_payload.id() });
+            }
+            let same_key = part_payload.encryption_key() == payload.encryption_key();
+            let Ok(mut segments) = repair::segment_directory(part, &part_payload) else { continue };
+            for segment in segments.by_ref() {
+                let key = (segment.kind(), segment.id());
+                if merged.contains(&key) {
+                    continue;
+                }
+                let Ok((mut segment, raw)) = repair::salvage_segment(part, segment) else { continue };
+                let overridden = segment.kind().is_some_and(|kind| part.overrides().contains_key(&(kind, segment.id())));
+                if same_key && !overridden {
+                    // Only the encrypted data is written, the decrypted data was only loaded to check it
+                    segment.set_data(Vec::new());
+                    builder.push_segment(segment, raw);
+                } else if segment.is_loaded() {
+                    let data = std::mem::take(segment.data_mut());
+                    builder.push_decrypted_segment(segment, &data)?;
+                } else {
+                    return Err(Errors::FeatureDisabled { feature: "payload" });
+                }
+                merged.insert(key);
+            }
+        }
+        builder.normalize_order();
+        let content = builder.build();
+        let report = Rofl::from_slice(&content[..])?.verify();
+        if !report.is_valid() {
+            return Err(Errors::VerificationFailed { report: Box::new(report) });
+        }
+        Ok(builder)
+    }
+    /// Set the file's signature
+    /// 
+    /// The signature is truncated or padded with zeros to fit in 256 bytes
+    pub fn set_signature(&mut self, signature: &[u8]) -> &mut Self {
+        self.signature = signature.to_vec();
+        self
+    }
+    /// Set the file's JSON metadata
+    pub fn set_metadata(&mut self, metadata: &str) -> &mut Self {
+        self.metadata = metadata.as_bytes().to_vec();
+        self
+    }
+    /// Set the file's metadata section without any validation
+    pub fn set_raw_metadata(&mut self, metadata: Vec<u8>) -> &mut Self {
+        self.metadata = metadata;
+        self
+    }
+    /// Add a segment at the end of the payload
+    /// 
+    /// `data` is the segment's encrypted data, the segment's own loaded data is ignored
+    pub fn push_segment(&mut self, segment: Segment, data: Vec<u8>) -> &mut Self {
+        self.segments.push((segment, data));
+        self
+    }
+    /// Encrypt a segment's data with the payload header's key and add it at the end of the payload
+    /// 
+    /// # Errors
+    /// 
+    /// If the payload header's key is invalid or the lib was compiled without the `payload` feature
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust
+    /// use lolrofl::{iter::PayloadIterOptions, model::Segment, write::RoflBuilder};
+    /// 
+    /// # let content = lolrofl::test::sample_full_file_0();
+    /// // let content = std::fs::read("game.rofl").unwrap();
+    /// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+    /// 
+    /// let mut builder = RoflBuilder::from_rofl(&game).unwrap();
+    /// let result = builder.push_decrypted_segment(Segment::chunk(3), b"new chunk");
+    /// # assert_eq!(result.is_ok(), lolrofl::capabilities().payload);
+    /// # #[cfg(feature = "payload")] {
+    /// let rebuilt = builder.build();
+    /// let rebuilt_game = lolrofl::Rofl::from_slice(&rebuilt[..]).unwrap();
+    /// let chunk = rebuilt_game.segment_iter(&PayloadIterOptions::with_data()).unwrap().last().unwrap();
+    /// assert_eq!(chunk.data(), b"new chunk");
+    /// # }
+    /// ```
+    pub fn push_decrypted_segment(&mut self, segment: Segment, data: &[u8]) -> Result<&mut Self, Errors> {
+        #[cfg(feature="payload")]
+        {
+            let mut key = crate::iter::segment_cipher(&self.payload)?;
+            let encrypted = crate::iter::encrypt_segment(data, &mut key)?;
+            Ok(self.push_segment(segment, encrypted))
+        }
+        #[cfg(not(feature="payload"))]
+        {
+            let _ = (segment, data);
+            Err(Errors::FeatureDisabled { feature: "payload" })
+        }
+    }
+    /// Only keep the segments for which the predicate returns `true`
+    pub fn retain_segments<F: FnMut(&Segment) -> bool>(&mut self, mut predicate: F) -> &mut Self {
+        self.segments.retain(|(segment, _)| predicate(segment));
+        self
+    }
+    /// Sort the segments in canonical order: chunks by ascending ID, then keyframes by ascending ID
+    /// 
+    /// Data offsets are always compacted when building the file, so files normalized this way
+    /// only differ if their content differs
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust
+    /// use lolrofl::write::RoflBuilder;
+    /// 
+    /// # let content = lolrofl::test::sample_full_file_0();
+    /// // let content = std::fs::read("game.rofl").unwrap();
+    /// let game = lolrofl::Rofl::from_slice(&content[..]).unwrap();
+    /// 
+    /// let mut builder = RoflBuilder::from_rofl(&game).unwrap();
+    /// builder.normalize_order();
+    /// let order: Vec<(bool, u32)> = builder.segments().iter().map(|(s, _)| (s.is_chunk(), s.id())).collect();
+    /// assert_eq!(order, [(true, 1), (true, 2), (false, 1)]);
+    /// 
+    /// let normalized = builder.build();
+    /// # assert_eq!(normalized.len(), content.len());
+    /// let mut builder = RoflBuilder::from_rofl(&lolrofl::Rofl::from_slice(&normalized[..]).unwrap()).unwrap();
+    /// assert_eq!(builder.normalize_order().build(), normalized);
+    /// ```
+    pub fn normalize_order(&mut self) -> &mut Self {
+        self.segments.sort_by_key(|(segment, _)| (!segment.is_chunk(), segment.id()));
+        self
+    }
+    /// The segments that will be written, along with their encrypted data
+    pub fn segments(&self) -> &[(Segment, Vec<u8>)] { &self.segments }
+    /// Build the file's content
+    pub fn build(&self) -> Vec<u8> {
+        let chunk_count = self.segments.iter().filter(|(s, _)| s.is_chunk()).count();
+        let payload = self.payload
+            .with_counts(chunk_count as u32, (self.segments.len() - chunk_count) as u32)
+            .to_bytes();
+        let segments_len = self.segments.iter().map(|(_, d)| SEGMENT_HEADER_LEN + d.len()).sum();
+        let head = BinHeader::from_layout(&self.signature, self.metadata.len(), payload.len(), segments_len);
+
+        let mut data = head.to_bytes();
+        data.reserve(head.file_len() - data.len());
+        data.extend_from_slice(&self.metadata);
+        data.extend_from_slice(&payload);
+        let mut offset = 0;
+        for (segment, segment_data) in &self.segments {
+            data.extend(segment.relocated(offset, segment_data.len()).to_bytes());
+            offset += segment_data.len();
+        }
+        for (_, segment_data) in &self.segments {
+            data.extend_from_slice(segment_data);
+        }
+        data
+    }
+}